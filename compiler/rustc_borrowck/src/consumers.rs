@@ -1,22 +1,31 @@
 //! This file provides API for compiler consumers.
 
+use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::LocalDefId;
+use rustc_index::bit_set::BitSet;
 use rustc_index::vec::IndexVec;
 use rustc_infer::infer::{DefiningAnchor, TyCtxtInferExt};
-use rustc_middle::mir::Body;
+use rustc_middle::mir::{Body, Location};
 use rustc_middle::ty::{self, TyCtxt};
+use rustc_mir_dataflow::Analysis;
 
 pub use super::{
+    borrow_set::{BorrowData, BorrowSet, TwoPhaseActivation},
+    dataflow::{calculate_borrows_out_of_scope_at_location, BorrowIndex, Borrows},
     facts::{AllFacts as PoloniusInput, RustcFacts},
     location::{LocationTable, RichLocation},
     nll::PoloniusOutput,
+    region_infer::RegionInferenceContext,
     BodyWithBorrowckFacts,
 };
 
-/// This function computes Polonius facts for the given body. It makes a copy of
-/// the body because it needs to regenerate the region identifiers. This function
-/// should never be invoked during a typical compilation session due to performance
-/// issues with Polonius.
+/// This function computes Polonius facts for the given body, together with its
+/// borrows and the region inference context the borrow checker computed for it.
+/// The `Borrows` dataflow analysis can be run on the returned body to know which
+/// borrows are in scope at each location. It makes a copy of the body because it
+/// needs to regenerate the region identifiers. This function should never be
+/// invoked during a typical compilation session due to performance issues with
+/// Polonius.
 ///
 /// Note:
 /// *   This function will panic if the required body was already stolen. This
@@ -37,3 +46,56 @@ pub fn get_body_with_borrowck_facts<'tcx>(
         *super::do_mir_borrowck(&infcx, input_body, promoted, true).1.unwrap()
     })
 }
+
+/// A body with the information the borrow checker computed for it, see
+/// [`get_body_with_borrowck_facts`], together with the results of the `Borrows` dataflow
+/// analysis on it.
+pub struct BodyWithBorrowsDataflow<'tcx> {
+    pub body_with_facts: BodyWithBorrowckFacts<'tcx>,
+    /// The borrows that go out of scope at each location, see
+    /// [`calculate_borrows_out_of_scope_at_location`].
+    pub borrows_out_of_scope_at_location: FxHashMap<Location, Vec<BorrowIndex>>,
+    /// The borrows that are in scope after the statement or terminator at each location.
+    pub borrows_in_scope_at_location: FxHashMap<Location, BitSet<BorrowIndex>>,
+}
+
+/// This function computes the same information as [`get_body_with_borrowck_facts`] for the
+/// body of `def`, and runs the `Borrows` dataflow analysis on it.
+///
+/// The MIR the borrow checker works on is stolen once the body has been borrow checked, which
+/// is why [`get_body_with_borrowck_facts`] is usually called from an override of the
+/// `mir_borrowck` query. Calling this function for every body before the analysis starts, e.g.
+/// from the `after_expansion` callback, doesn't need the override. Bodies that are borrow
+/// checked while type checking other bodies, e.g. `const fn`s used in array lengths, must be
+/// requested before those.
+///
+/// You can find a complete example at `src/test/run-make-fulldeps/obtain-borrowck-dataflow/`.
+pub fn get_body_with_borrows_dataflow<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    def: LocalDefId,
+) -> BodyWithBorrowsDataflow<'tcx> {
+    let body_with_facts = get_body_with_borrowck_facts(tcx, ty::WithOptConstParam::unknown(def));
+    let BodyWithBorrowckFacts { body, borrow_set, region_inference_context, .. } =
+        &body_with_facts;
+
+    let borrows_out_of_scope_at_location =
+        calculate_borrows_out_of_scope_at_location(body, region_inference_context, borrow_set);
+    let mut borrows_in_scope = Borrows::new(tcx, body, region_inference_context, borrow_set)
+        .into_engine(tcx, body)
+        .iterate_to_fixpoint()
+        .into_results_cursor(body);
+    let mut borrows_in_scope_at_location = FxHashMap::default();
+    for (block, data) in body.basic_blocks().iter_enumerated() {
+        for statement_index in 0..=data.statements.len() {
+            let location = Location { block, statement_index };
+            borrows_in_scope.seek_after_primary_effect(location);
+            borrows_in_scope_at_location.insert(location, borrows_in_scope.get().clone());
+        }
+    }
+
+    BodyWithBorrowsDataflow {
+        body_with_facts,
+        borrows_out_of_scope_at_location,
+        borrows_in_scope_at_location,
+    }
+}
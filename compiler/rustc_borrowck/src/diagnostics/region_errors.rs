@@ -14,6 +14,7 @@
     NllRegionVariableOrigin, RelateParamBound,
 };
 use rustc_middle::hir::place::PlaceBase;
+use rustc_middle::mir::pretty::create_dump_file;
 use rustc_middle::mir::{ConstraintCategory, ReturnConstraint};
 use rustc_middle::ty::subst::InternalSubsts;
 use rustc_middle::ty::Region;
@@ -22,6 +23,8 @@
 use rustc_span::symbol::{kw, sym, Ident};
 use rustc_span::Span;
 
+use std::io;
+
 use crate::borrowck_errors;
 use crate::session_diagnostics::GenericDoesNotLiveLongEnough;
 
@@ -360,7 +363,8 @@ pub(crate) fn report_region_error(
         // Check if we can use one of the "nice region errors".
         if let (Some(f), Some(o)) = (self.to_error_region(fr), self.to_error_region(outlived_fr)) {
             let nice = NiceRegionError::new_from_span(self.infcx, cause.span, o, f);
-            if let Some(diag) = nice.try_report_from_nll() {
+            if let Some(mut diag) = nice.try_report_from_nll() {
+                self.explain_region_error(fr, outlived_fr, &mut diag);
                 self.buffer_error(diag);
                 return;
             }
@@ -462,9 +466,70 @@ pub(crate) fn report_region_error(
             }
         }
 
+        self.explain_region_error(fr, outlived_fr, &mut diag);
         self.buffer_error(diag);
     }
 
+    /// With `-Zexplain-borrowck`, explains why `fr` must outlive `outlived_fr` by noting each
+    /// constraint of the chain between them, and writes all the constraints relevant to the
+    /// error to a graphviz file in the MIR dump directory.
+    fn explain_region_error(&self, fr: RegionVid, outlived_fr: RegionVid, diag: &mut Diagnostic) {
+        let tcx = self.infcx.tcx;
+        let Some(filter) = &tcx.sess.opts.unstable_opts.explain_borrowck else { return };
+        let source = self.body.source;
+        if filter != "all" && !tcx.def_path_str(source.def_id()).contains(filter.as_str()) {
+            return;
+        }
+
+        let target_test = |r| self.regioncx.provides_universal_region(r, fr, outlived_fr);
+        let Some((path, _)) =
+            self.regioncx.find_constraint_paths_between_regions(fr, target_test) else {
+            return;
+        };
+
+        let region_str = |r: RegionVid| match self.regioncx.region_definition(r).external_name {
+            Some(name) => format!("{:?} ({})", r, name),
+            None => format!("{:?}", r),
+        };
+        diag.note(&format!(
+            "`{}` must outlive `{}` because of the following constraints:",
+            region_str(fr),
+            region_str(outlived_fr),
+        ));
+        for (i, constraint) in path.iter().enumerate() {
+            let category = match constraint.category.description().trim_end() {
+                "" => format!("{:?}", constraint.category),
+                description => description.to_string(),
+            };
+            let msg = format!(
+                "{}. `{}: {}` ({})",
+                i + 1,
+                region_str(constraint.sup),
+                region_str(constraint.sub),
+                category,
+            );
+            if constraint.span.is_dummy() {
+                diag.note(&msg);
+            } else {
+                diag.span_note(constraint.span, &msg);
+            }
+        }
+
+        let disambiguator = format!("{}-{}", fr.index(), outlived_fr.index());
+        let result: io::Result<()> = try {
+            let mut file =
+                create_dump_file(tcx, "dot", None, "explain_borrowck", &disambiguator, source)?;
+            self.regioncx.dump_graphviz_constraint_paths(tcx, fr, target_test, &path, &mut file)?;
+        };
+        match result {
+            Ok(()) => diag.note(&format!(
+                "the constraints between these regions were written to the `{}` directory",
+                tcx.sess.opts.unstable_opts.dump_mir_dir,
+            )),
+            Err(err) => diag.note(&format!("failed to write the constraint graph: {}", err)),
+        };
+    }
+
     /// Report a specialized error when `FnMut` closures return a reference to a captured variable.
     /// This function expects `fr` to be local and `outlived_fr` to not be local.
     ///
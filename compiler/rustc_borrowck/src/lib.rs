@@ -450,6 +450,8 @@ fn do_mir_borrowck<'a, 'tcx>(
         let output_facts = mbcx.polonius_output.expect("Polonius output was not computed");
         Some(Box::new(BodyWithBorrowckFacts {
             body: body_owned,
+            borrow_set,
+            region_inference_context: regioncx,
             input_facts: *polonius_input.expect("Polonius input facts were not generated"),
             output_facts,
             location_table: location_table_owned,
@@ -471,6 +473,10 @@ fn do_mir_borrowck<'a, 'tcx>(
 pub struct BodyWithBorrowckFacts<'tcx> {
     /// A mir body that contains region identifiers.
     pub body: Body<'tcx>,
+    /// The borrows of the body, with their activation locations for two-phase borrows.
+    pub borrow_set: Rc<BorrowSet<'tcx>>,
+    /// The region inference context, holding the final value of each region of the body.
+    pub region_inference_context: Rc<RegionInferenceContext<'tcx>>,
     /// Polonius input facts.
     pub input_facts: AllFacts,
     /// Polonius output facts.
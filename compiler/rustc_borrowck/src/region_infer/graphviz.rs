@@ -27,6 +27,55 @@ pub(crate) fn dump_graphviz_scc_constraints(&self, mut w: &mut dyn Write) -> io:
 
         dot::render(&SccConstraints { regioncx: self, nodes_per_scc }, &mut w)
     }
+
+    /// Write out the constraints lying on a path from `from_region` to a region passing
+    /// `target_test`, i.e. every constraint that can explain why `from_region` must outlive
+    /// such a region. The constraints of `blame_path` are drawn in bold.
+    pub(crate) fn dump_graphviz_constraint_paths(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        from_region: RegionVid,
+        target_test: impl Fn(RegionVid) -> bool,
+        blame_path: &[OutlivesConstraint<'tcx>],
+        mut w: &mut dyn Write,
+    ) -> io::Result<()> {
+        // Collect the constraints reachable from `from_region`...
+        let fr_static = self.universal_regions.fr_static;
+        let mut reachable = FxHashSet::default();
+        reachable.insert(from_region);
+        let mut deque = VecDeque::new();
+        deque.push_back(from_region);
+        let mut constraints = vec![];
+        while let Some(r) = deque.pop_front() {
+            for constraint in self.constraint_graph.outgoing_edges(r, &self.constraints, fr_static)
+            {
+                if reachable.insert(constraint.sub) {
+                    deque.push_back(constraint.sub);
+                }
+                constraints.push(constraint);
+            }
+        }
+
+        // ...and only keep those from which a target region can be reached.
+        let mut regions: FxHashSet<_> = reachable.into_iter().filter(|&r| target_test(r)).collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for constraint in &constraints {
+                if regions.contains(&constraint.sub) && regions.insert(constraint.sup) {
+                    changed = true;
+                }
+            }
+        }
+        constraints.retain(|c| regions.contains(&c.sup) && regions.contains(&c.sub));
+        let mut regions: Vec<_> = regions.into_iter().collect();
+        regions.sort();
+
+        dot::render(
+            &ConstraintPaths { regioncx: self, tcx, from_region, regions, constraints, blame_path },
+            &mut w,
+        )
+    }
 }
 
 struct RawConstraints<'a, 'tcx> {
@@ -138,3 +187,70 @@ fn target(&'this self, edge: &(ConstraintSccIndex, ConstraintSccIndex)) -> Const
         edge.1
     }
 }
+
+struct ConstraintPaths<'a, 'tcx> {
+    regioncx: &'a RegionInferenceContext<'tcx>,
+    tcx: TyCtxt<'tcx>,
+    from_region: RegionVid,
+    regions: Vec<RegionVid>,
+    constraints: Vec<OutlivesConstraint<'tcx>>,
+    blame_path: &'a [OutlivesConstraint<'tcx>],
+}
+
+impl<'a, 'this, 'tcx> dot::Labeller<'this> for ConstraintPaths<'a, 'tcx> {
+    type Node = RegionVid;
+    type Edge = OutlivesConstraint<'tcx>;
+
+    fn graph_id(&'this self) -> dot::Id<'this> {
+        dot::Id::new("RegionInferenceContext").unwrap()
+    }
+    fn node_id(&'this self, n: &RegionVid) -> dot::Id<'this> {
+        dot::Id::new(format!("r{}", n.index())).unwrap()
+    }
+    fn node_shape(&'this self, _node: &RegionVid) -> Option<dot::LabelText<'this>> {
+        Some(dot::LabelText::LabelStr(Cow::Borrowed("box")))
+    }
+    fn node_label(&'this self, n: &RegionVid) -> dot::LabelText<'this> {
+        let label = match self.regioncx.region_definition(*n).external_name {
+            Some(name) => format!("{:?} ({})", n, name),
+            None => format!("{:?}", n),
+        };
+        dot::LabelText::LabelStr(label.into())
+    }
+    fn node_style(&'this self, n: &RegionVid) -> dot::Style {
+        if *n == self.from_region { dot::Style::Bold } else { dot::Style::None }
+    }
+    fn edge_label(&'this self, e: &OutlivesConstraint<'tcx>) -> dot::LabelText<'this> {
+        let mut label = format!("{:?}", e.category);
+        if !e.span.is_dummy() {
+            label.push('\n');
+            label.push_str(&self.tcx.sess.source_map().span_to_embeddable_string(e.span));
+        }
+        dot::LabelText::LabelStr(label.into())
+    }
+    fn edge_style(&'this self, e: &OutlivesConstraint<'tcx>) -> dot::Style {
+        if self.blame_path.contains(e) { dot::Style::Bold } else { dot::Style::None }
+    }
+}
+
+impl<'a, 'this, 'tcx> dot::GraphWalk<'this> for ConstraintPaths<'a, 'tcx> {
+    type Node = RegionVid;
+    type Edge = OutlivesConstraint<'tcx>;
+
+    fn nodes(&'this self) -> dot::Nodes<'this, RegionVid> {
+        (&self.regions[..]).into()
+    }
+    fn edges(&'this self) -> dot::Edges<'this, OutlivesConstraint<'tcx>> {
+        (&self.constraints[..]).into()
+    }
+
+    // Render `a: b` as `a -> b`, like the complete constraint graph.
+
+    fn source(&'this self, edge: &OutlivesConstraint<'tcx>) -> RegionVid {
+        edge.sup
+    }
+
+    fn target(&'this self, edge: &OutlivesConstraint<'tcx>) -> RegionVid {
+        edge.sub
+    }
+}
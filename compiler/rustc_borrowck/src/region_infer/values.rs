@@ -223,7 +223,7 @@ pub(crate) fn len(&self) -> usize {
 /// because (since it is returned) it must live for at least `'a`. But
 /// it would also contain various points from within the function.
 #[derive(Clone)]
-pub(crate) struct RegionValues<N: Idx> {
+pub struct RegionValues<N: Idx> {
     elements: Rc<RegionValueElements>,
     placeholder_indices: Rc<PlaceholderIndices>,
     points: SparseIntervalMatrix<N, PointIndex>,
@@ -354,7 +354,7 @@ pub(crate) fn region_value_str(&self, r: N) -> String {
     }
 }
 
-pub(crate) trait ToElementIndex: Debug + Copy {
+pub trait ToElementIndex: Debug + Copy {
     fn add_to_row<N: Idx>(self, values: &mut RegionValues<N>, row: N) -> bool;
 
     fn contained_in_row<N: Idx>(self, values: &RegionValues<N>, row: N) -> bool;
@@ -13,6 +13,7 @@ pub(crate) fn run(
     cg_clif_build_dir: &Path,
     host_triple: &str,
     target_triple: &str,
+    panic_unwind: bool,
 ) {
     if !config::get_bool("testsuite.abi-checker") {
         eprintln!("[SKIP] abi-checker");
@@ -32,6 +33,7 @@ pub(crate) fn run(
         cg_clif_build_dir,
         host_triple,
         target_triple,
+        panic_unwind,
     );
 
     eprintln!("Running abi-checker");
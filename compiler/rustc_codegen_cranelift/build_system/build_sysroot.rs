@@ -6,6 +6,20 @@
 use super::utils::{spawn_and_wait, try_hard_link};
 use super::SysrootKind;
 
+/// Whether the sysroot and the code compiled by the wrappers can use `-Cpanic=unwind`.
+/// cg_clif only supports unwinding on x86_64 Linux, where it needs the external assembler that is
+/// only used with the unstable features enabled. Cross-compiling always uses `-Cpanic=abort`.
+pub(crate) fn panic_unwind_supported(
+    use_unstable_features: bool,
+    host_triple: &str,
+    target_triple: &str,
+) -> bool {
+    use_unstable_features
+        && host_triple == target_triple
+        && target_triple.starts_with("x86_64-")
+        && target_triple.contains("-linux-")
+}
+
 pub(crate) fn build_sysroot(
     channel: &str,
     sysroot_kind: SysrootKind,
@@ -13,6 +27,7 @@ pub(crate) fn build_sysroot(
     cg_clif_build_dir: &Path,
     host_triple: &str,
     target_triple: &str,
+    panic_unwind: bool,
 ) {
     eprintln!("[BUILD] sysroot {:?}", sysroot_kind);
 
@@ -45,6 +60,10 @@ pub(crate) fn build_sysroot(
             .arg("-o")
             .arg(target_dir.join(wrapper_name))
             .arg("-g");
+        if panic_unwind {
+            // Makes the wrappers stop passing `-Cpanic=abort`.
+            build_cargo_wrapper_cmd.arg("--cfg").arg("panic_unwind");
+        }
         spawn_and_wait(build_cargo_wrapper_cmd);
     }
 
@@ -119,6 +138,7 @@ pub(crate) fn build_sysroot(
                 host_triple,
                 &cg_clif_dylib_path,
                 None,
+                panic_unwind,
             );
 
             if host_triple != target_triple {
@@ -134,6 +154,7 @@ pub(crate) fn build_sysroot(
                     target_triple,
                     &cg_clif_dylib_path,
                     linker,
+                    panic_unwind,
                 );
             }
 
@@ -156,6 +177,7 @@ fn build_clif_sysroot_for_triple(
     triple: &str,
     cg_clif_dylib_path: &Path,
     linker: Option<&str>,
+    panic_unwind: bool,
 ) {
     match fs::read_to_string(Path::new("build_sysroot").join("rustc_version")) {
         Err(e) => {
@@ -188,7 +210,10 @@ fn build_clif_sysroot_for_triple(
     // Build sysroot
     let mut build_cmd = Command::new("cargo");
     build_cmd.arg("build").arg("--target").arg(triple).current_dir("build_sysroot");
-    let mut rustflags = "-Zforce-unstable-if-unmarked -Cpanic=abort".to_string();
+    let mut rustflags = "-Zforce-unstable-if-unmarked".to_string();
+    if !panic_unwind {
+        rustflags.push_str(" -Cpanic=abort");
+    }
     rustflags.push_str(&format!(" -Zcodegen-backend={}", cg_clif_dylib_path.to_str().unwrap()));
     if channel == "release" {
         build_cmd.arg("--release");
@@ -132,6 +132,8 @@ pub fn main() {
 
     let cg_clif_build_dir =
         build_backend::build_backend(channel, &host_triple, use_unstable_features);
+    let panic_unwind =
+        build_sysroot::panic_unwind_supported(use_unstable_features, &host_triple, &target_triple);
     match command {
         Command::Test => {
             tests::run_tests(
@@ -141,6 +143,7 @@ pub fn main() {
                 &cg_clif_build_dir,
                 &host_triple,
                 &target_triple,
+                panic_unwind,
             );
 
             abi_checker::run(
@@ -150,6 +153,7 @@ pub fn main() {
                 &cg_clif_build_dir,
                 &host_triple,
                 &target_triple,
+                panic_unwind,
             );
         }
         Command::Build => {
@@ -160,6 +164,7 @@ pub fn main() {
                 &cg_clif_build_dir,
                 &host_triple,
                 &target_triple,
+                panic_unwind,
             );
         }
     }
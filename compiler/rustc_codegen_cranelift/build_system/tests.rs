@@ -7,7 +7,7 @@
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{self, Command};
 
 struct TestCase {
     config: &'static str,
@@ -123,6 +123,7 @@ const fn new(config: &'static str, func: &'static dyn Fn(&TestRunner)) -> Self {
             "example/alloc_example.rs",
             "--crate-type",
             "bin",
+            "-Cpanic=abort",
             "--target",
             &runner.target_triple,
         ]);
@@ -148,6 +149,19 @@ const fn new(config: &'static str, func: &'static dyn Fn(&TestRunner)) -> Self {
             &runner.host_triple,
         ]);
     }),
+    TestCase::new("jit.hot_reload", &|runner| {
+        // The test rewrites its own source file, so compile a copy of it.
+        let source = runner.out_dir.join("hot_reload.rs");
+        fs::copy("example/hot_reload.rs", &source).unwrap();
+        runner.run_rustc([
+            "-Zunstable-options",
+            "-Cllvm-args=mode=jit-hot-reload",
+            "-Cprefer-dynamic",
+            source.to_str().unwrap(),
+            "--target",
+            &runner.host_triple,
+        ]);
+    }),
     TestCase::new("aot.std_example", &|runner| {
         runner.run_rustc([
             "example/std_example.rs",
@@ -158,6 +172,21 @@ const fn new(config: &'static str, func: &'static dyn Fn(&TestRunner)) -> Self {
         ]);
         runner.run_out_command("std_example", ["arg"]);
     }),
+    TestCase::new("aot.unwinding", &|runner| {
+        if !runner.panic_unwind {
+            eprintln!("Skipping, the sysroot is built with -Cpanic=abort");
+            return;
+        }
+
+        runner.run_rustc([
+            "example/unwinding.rs",
+            "--crate-type",
+            "bin",
+            "--target",
+            &runner.target_triple,
+        ]);
+        runner.run_out_command("unwinding", []);
+    }),
     TestCase::new("aot.dst_field_align", &|runner| {
         runner.run_rustc([
             "example/dst-field-align.rs",
@@ -213,6 +242,47 @@ const fn new(config: &'static str, func: &'static dyn Fn(&TestRunner)) -> Self {
         ]);
         runner.run_out_command("mod_bench", []);
     }),
+    TestCase::new("debuginfo.gdb", &|runner| {
+        runner.run_rustc([
+            "example/debuginfo.rs",
+            "--crate-type",
+            "bin",
+            "--target",
+            &runner.target_triple,
+        ]);
+
+        // Variable locations are only emitted for x86_64.
+        let is_native_x86_64_linux = runner.host_triple == runner.target_triple
+            && runner.target_triple.starts_with("x86_64")
+            && runner.target_triple.contains("linux");
+        if !is_native_x86_64_linux || Command::new("gdb").arg("--version").output().is_err() {
+            eprintln!("Skipping, gdb on a native x86_64 Linux target is required");
+            return;
+        }
+
+        let output = Command::new("gdb")
+            .args(["-batch", "-nx"])
+            .args(["-ex", "break break_here", "-ex", "break break_under_pressure", "-ex", "run"])
+            .args(["-ex", "up", "-ex", "info locals", "-ex", "continue"])
+            .args(["-ex", "up", "-ex", "info locals"])
+            .arg(runner.out_dir.join("debuginfo"))
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        for expected in [
+            "int = 8",
+            "byte = 42",
+            "tuple = (1, true)",
+            "{x: 2, y: -3}",
+            "int = 7",
+            "marker = 1234",
+        ] {
+            if !stdout.contains(expected) {
+                eprintln!("gdb output doesn't contain `{expected}`:\n{stdout}");
+                process::exit(1);
+            }
+        }
+    }),
 ];
 
 const EXTENDED_SYSROOT_SUITE: &[TestCase] = &[
@@ -360,16 +430,12 @@ const fn new(config: &'static str, func: &'static dyn Fn(&TestRunner)) -> Self {
             let lint_rust_flags = format!("{} --cap-lints warn", runner.rust_flags);
 
             if runner.host_triple == runner.target_triple {
-                let mut run_cmd = runner.cargo_command([
-                    "test",
-                    "--tests",
-                    "--",
-                    "--exclude-should-panic",
-                    "--test-threads",
-                    "1",
-                    "-Zunstable-options",
-                    "-q",
-                ]);
+                let mut run_cmd = runner.cargo_command(["test", "--tests", "--"]);
+                // `#[should_panic]` tests are only run when panics can be caught.
+                if !runner.panic_unwind {
+                    run_cmd.arg("--exclude-should-panic");
+                }
+                run_cmd.args(["--test-threads", "1", "-Zunstable-options", "-q"]);
                 run_cmd.env("RUSTFLAGS", lint_rust_flags);
                 spawn_and_wait(run_cmd);
             } else {
@@ -391,6 +457,29 @@ const fn new(config: &'static str, func: &'static dyn Fn(&TestRunner)) -> Self {
             }
         });
     }),
+    TestCase::new("test.stdarch", &|runner| {
+        runner.in_dir(
+            ["build_sysroot", "sysroot_src", "library", "stdarch", "crates", "core_arch"],
+            |runner| {
+                runner.run_cargo(["clean"]);
+
+                if runner.host_triple == runner.target_triple
+                    && runner.target_triple.starts_with("x86_64")
+                {
+                    // `#[assert_instr]` checks the disassembly for the instructions LLVM would
+                    // emit, which doesn't apply to cg_clif. The intrinsics are still checked
+                    // against the expected results of the tests enabled by the emulated cpuid,
+                    // which only advertises the emulated features with `CG_CLIF_EMULATE_AVX2`.
+                    let mut test_cmd = runner.cargo_command(["test", "-q"]);
+                    test_cmd.env("STDARCH_DISABLE_ASSERT_INSTR", "1");
+                    test_cmd.env("CG_CLIF_EMULATE_AVX2", "1");
+                    spawn_and_wait(test_cmd);
+                } else {
+                    eprintln!("Cross-Compiling or non-x86_64 host: Not running stdarch tests");
+                }
+            },
+        );
+    }),
 ];
 
 pub(crate) fn run_tests(
@@ -400,8 +489,10 @@ pub(crate) fn run_tests(
     cg_clif_build_dir: &Path,
     host_triple: &str,
     target_triple: &str,
+    panic_unwind: bool,
 ) {
-    let runner = TestRunner::new(host_triple.to_string(), target_triple.to_string());
+    let runner =
+        TestRunner::new(host_triple.to_string(), target_triple.to_string(), panic_unwind);
 
     if config::get_bool("testsuite.no_sysroot") {
         build_sysroot::build_sysroot(
@@ -411,6 +502,7 @@ pub(crate) fn run_tests(
             cg_clif_build_dir,
             &host_triple,
             &target_triple,
+            panic_unwind,
         );
 
         let _ = fs::remove_dir_all(Path::new("target").join("out"));
@@ -430,6 +522,7 @@ pub(crate) fn run_tests(
             cg_clif_build_dir,
             &host_triple,
             &target_triple,
+            panic_unwind,
         );
     }
 
@@ -450,6 +543,9 @@ struct TestRunner {
     root_dir: PathBuf,
     out_dir: PathBuf,
     jit_supported: bool,
+    /// Whether the sysroot is built with `-Cpanic=unwind`, see
+    /// `build_sysroot::panic_unwind_supported`.
+    panic_unwind: bool,
     rust_flags: String,
     run_wrapper: Vec<String>,
     host_triple: String,
@@ -457,7 +553,7 @@ struct TestRunner {
 }
 
 impl TestRunner {
-    pub fn new(host_triple: String, target_triple: String) -> Self {
+    pub fn new(host_triple: String, target_triple: String, panic_unwind: bool) -> Self {
         let root_dir = env::current_dir().unwrap();
 
         let mut out_dir = root_dir.clone();
@@ -497,6 +593,7 @@ pub fn new(host_triple: String, target_triple: String) -> Self {
             root_dir,
             out_dir,
             jit_supported,
+            panic_unwind,
             rust_flags,
             run_wrapper: run_wrapper.iter().map(|s| s.to_string()).collect(),
             host_triple,
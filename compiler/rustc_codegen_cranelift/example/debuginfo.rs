@@ -0,0 +1,57 @@
+// Checked by the `debuginfo.gdb` test in `build_system/tests.rs`.
+
+#![feature(bench_black_box)]
+
+use std::convert::Infallible;
+use std::hint::black_box;
+
+struct Point {
+    x: i32,
+    y: i64,
+}
+
+#[inline(never)]
+fn break_here() {}
+
+#[inline(never)]
+fn break_under_pressure() -> u64 {
+    0
+}
+
+// Describing the type of `e` used to panic, as `Infallible` has no variants.
+fn unwrap_infallible(r: Result<u8, Infallible>) -> u8 {
+    match r {
+        Ok(v) => v,
+        Err(e) => match e {},
+    }
+}
+
+// Keeps enough temporaries live across a call to spill callee-saved registers, which moves the
+// stack slots further away from the frame pointer. Named locals are kept in stack slots, so the
+// values are only bound through nested expressions.
+#[inline(never)]
+fn register_pressure(seed: u64) -> u64 {
+    let marker = 1234u64;
+    marker
+        + (black_box(seed)
+            + (black_box(seed * 2)
+                + (black_box(seed * 3)
+                    + (black_box(seed * 4)
+                        + (black_box(seed * 5)
+                            + (black_box(seed * 6)
+                                + (black_box(seed * 7) + break_under_pressure())))))))
+}
+
+fn main() {
+    let int = 7i32;
+    let point = Point { x: 2, y: -3 };
+    let tuple = (1u8, true);
+    let byte = unwrap_infallible(Ok(42));
+    {
+        let int = 8u64;
+        break_here();
+        println!("{}", int);
+    }
+    println!("{} {} {} {} {} {}", int, point.x, point.y, tuple.0, tuple.1, byte);
+    println!("{}", register_pressure(1));
+}
@@ -0,0 +1,48 @@
+// Run by the `jit.hot_reload` test in `build_system/tests.rs` with `mode=jit-hot-reload`. Rewrites
+// the body of `version` in its own source file and checks that the running program picks up the
+// change.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[inline(never)]
+fn version() -> u32 {
+    1
+}
+
+fn set_version_body(body: &str) {
+    let header = "fn version() -> u32 {\n";
+    let source = std::fs::read_to_string(file!()).unwrap();
+    let start = source.find(header).unwrap() + header.len();
+    let end = start + source[start..].find("\n}\n").unwrap();
+    std::fs::write(file!(), format!("{}{}{}", &source[..start], body, &source[end..])).unwrap();
+}
+
+fn wait_for_version(expected: u32) {
+    let start = Instant::now();
+    while version() != expected {
+        if start.elapsed() > Duration::from_secs(120) {
+            panic!("timed out waiting for version {}, still at {}", expected, version());
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn main() {
+    assert_eq!(version(), 1);
+
+    set_version_body("    2");
+    wait_for_version(2);
+
+    // Inline asm is not supported in JIT mode. The reload fails with a fatal error and the program
+    // keeps running the old code.
+    set_version_body("    unsafe { std::arch::asm!(\"nop\") }\n    3");
+    thread::sleep(Duration::from_secs(10));
+    assert_eq!(version(), 2);
+
+    // Later reloads still work after a failed one.
+    set_version_body("    4");
+    wait_for_version(4);
+
+    println!("hot reload works");
+}
@@ -0,0 +1,93 @@
+#![feature(bench_black_box)]
+
+use std::cell::Cell;
+use std::hint::black_box;
+use std::panic::{self, AssertUnwindSafe};
+
+struct DropCounter<'a>(&'a Cell<u32>);
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[inline(never)]
+fn panic_with_locals(drops: &Cell<u32>) -> usize {
+    let _local = DropCounter(drops);
+    let _boxed = Box::new(DropCounter(drops));
+    let vec = vec![DropCounter(drops), DropCounter(drops)];
+    if black_box(true) {
+        panic!("unwinding through locals");
+    }
+    vec.len()
+}
+
+// Returns an `i128` and a pair, which are passed differently from the scalars above.
+#[inline(never)]
+fn wide_return(drops: &Cell<u32>, fail: bool) -> (i128, (u8, u64)) {
+    let _local = DropCounter(drops);
+    if fail {
+        panic!("wide return");
+    }
+    (i128::MAX, (1, 2))
+}
+
+fn main() {
+    panic::set_hook(Box::new(|_| {}));
+
+    // Locals of all frames between the panic and `catch_unwind` are dropped.
+    let drops = Cell::new(0);
+    let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _outer = DropCounter(&drops);
+        panic_with_locals(&drops)
+    }))
+    .unwrap_err();
+    assert_eq!(*payload.downcast_ref::<&str>().unwrap(), "unwinding through locals");
+    assert_eq!(drops.get(), 5);
+
+    // Return values of calls that can unwind are passed through.
+    assert_eq!(panic::catch_unwind(|| 42).unwrap(), 42);
+    let drops = Cell::new(0);
+    assert_eq!(wide_return(&drops, false), (i128::MAX, (1, 2)));
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| wide_return(&drops, true))).is_err());
+    assert_eq!(drops.get(), 2);
+
+    // Bounds checks unwind into the cleanup blocks of the function they are in.
+    let drops = Cell::new(0);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _guard = DropCounter(&drops);
+        let array = [1, 2, 3];
+        array[black_box(5)]
+    }));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 1);
+
+    // Drops see that the thread is panicking and a nested `catch_unwind` can be resumed.
+    let drops = Cell::new(0);
+    let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+        struct CheckPanicking;
+        impl Drop for CheckPanicking {
+            fn drop(&mut self) {
+                assert!(std::thread::panicking());
+            }
+        }
+
+        let _guard = DropCounter(&drops);
+        let _check = CheckPanicking;
+        let inner = panic::catch_unwind(|| -> String { panic::resume_unwind(Box::new(7u8)) });
+        panic::resume_unwind(inner.unwrap_err());
+    }))
+    .unwrap_err();
+    assert_eq!(*payload.downcast_ref::<u8>().unwrap(), 7);
+    assert_eq!(drops.get(), 1);
+
+    // Panics in other threads are reported by `join`.
+    let handle = std::thread::spawn(|| {
+        let _vec = vec![1, 2, 3];
+        panic!("in thread");
+    });
+    assert!(handle.join().is_err());
+
+    println!("Unwinding works");
+}
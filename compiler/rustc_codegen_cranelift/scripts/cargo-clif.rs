@@ -8,7 +8,12 @@ fn main() {
     let sysroot = PathBuf::from(env::current_exe().unwrap().parent().unwrap());
 
     let mut rustflags = String::new();
-    rustflags.push_str(" -Cpanic=abort -Zpanic-abort-tests -Zcodegen-backend=");
+    if cfg!(not(panic_unwind)) {
+        // The sysroot was built with `-Cpanic=abort`, see `panic_unwind_supported` in the build
+        // system.
+        rustflags.push_str(" -Cpanic=abort -Zpanic-abort-tests");
+    }
+    rustflags.push_str(" -Zcodegen-backend=");
     rustflags.push_str(
         sysroot
             .join(if cfg!(windows) { "bin" } else { "lib" })
@@ -57,6 +62,20 @@ fn main() {
                 ])
                 .collect()
         }
+        Some("hot-reload-jit") => {
+            env::set_var(
+                "RUSTFLAGS",
+                env::var("RUSTFLAGS").unwrap_or(String::new()) + " -Cprefer-dynamic",
+            );
+            IntoIterator::into_iter(["rustc".to_string()])
+                .chain(env::args().skip(2))
+                .chain([
+                    "--".to_string(),
+                    "-Zunstable-options".to_string(),
+                    "-Cllvm-args=mode=jit-hot-reload".to_string(),
+                ])
+                .collect()
+        }
         _ => env::args().skip(1).collect(),
     };
 
@@ -13,8 +13,12 @@ fn main() {
     );
 
     let mut args = std::env::args_os().skip(1).collect::<Vec<_>>();
-    args.push(OsString::from("-Cpanic=abort"));
-    args.push(OsString::from("-Zpanic-abort-tests"));
+    if cfg!(not(panic_unwind)) {
+        // The sysroot was built with `-Cpanic=abort`, see `panic_unwind_supported` in the build
+        // system.
+        args.push(OsString::from("-Cpanic=abort"));
+        args.push(OsString::from("-Zpanic-abort-tests"));
+    }
     let mut codegen_backend_arg = OsString::from("-Zcodegen-backend=");
     codegen_backend_arg.push(cg_clif_dylib_path);
     args.push(codegen_backend_arg);
@@ -11,6 +11,7 @@
 use rustc_target::spec::abi::Abi;
 
 use cranelift_codegen::ir::{AbiParam, SigRef};
+use smallvec::SmallVec;
 
 use self::pass_mode::*;
 use crate::prelude::*;
@@ -178,6 +179,7 @@ pub(crate) fn codegen_fn_prelude<'tcx>(fx: &mut FunctionCx<'_, '_, 'tcx>, start_
     fx.bcx.ins().nop();
 
     let ssa_analyzed = crate::analyze::analyze(fx);
+    let locals_kept_for_debuginfo = crate::analyze::locals_kept_for_debuginfo(fx);
 
     self::comments::add_args_header_comment(fx);
 
@@ -266,17 +268,21 @@ enum ArgKind<'tcx> {
                 // Ownership of the value at the backing storage for an argument is passed to the
                 // callee per the ABI, so it is fine to borrow the backing storage of this argument
                 // to prevent a copy.
-
-                let place = if let Some(meta) = meta {
-                    CPlace::for_ptr_with_extra(addr, meta, val.layout())
-                } else {
-                    CPlace::for_ptr(addr, val.layout())
-                };
-
-                self::comments::add_local_place_comments(fx, place, local);
-
-                assert_eq!(fx.local_map.push(place), local);
-                continue;
+                //
+                // Sized arguments of user variables are still copied when they are kept for
+                // debuginfo, as only locals in stack slots can be described.
+                if meta.is_some() || !locals_kept_for_debuginfo.contains(local) {
+                    let place = if let Some(meta) = meta {
+                        CPlace::for_ptr_with_extra(addr, meta, val.layout())
+                    } else {
+                        CPlace::for_ptr(addr, val.layout())
+                    };
+
+                    self::comments::add_local_place_comments(fx, place, local);
+
+                    assert_eq!(fx.local_map.push(place), local);
+                    continue;
+                }
             }
         }
 
@@ -335,6 +341,7 @@ pub(crate) fn codegen_terminator_call<'tcx>(
     args: &[Operand<'tcx>],
     destination: Place<'tcx>,
     target: Option<BasicBlock>,
+    cleanup: Option<BasicBlock>,
 ) {
     let fn_ty = fx.monomorphize(func.ty(fx.mir, fx.tcx));
     let fn_sig =
@@ -445,11 +452,6 @@ pub(crate) fn codegen_terminator_call<'tcx>(
     let args = args;
     assert_eq!(fn_abi.args.len(), args.len());
 
-    enum CallTarget {
-        Direct(FuncRef),
-        Indirect(SigRef, Value),
-    }
-
     let (func_ref, first_arg_override) = match instance {
         // Trait object call
         Some(Instance { def: InstanceDef::Virtual(_, idx), .. }) => {
@@ -504,25 +506,17 @@ enum CallTarget {
             )
             .collect::<Vec<Value>>();
 
-        let call_inst = match func_ref {
-            CallTarget::Direct(func_ref) => fx.bcx.ins().call(func_ref, &call_args),
-            CallTarget::Indirect(sig, func_ptr) => {
-                fx.bcx.ins().call_indirect(sig, func_ptr, &call_args)
-            }
-        };
-
         // FIXME find a cleaner way to support varargs
-        if fn_sig.c_variadic {
+        let variadic_params = if fn_sig.c_variadic {
             if !matches!(fn_sig.abi, Abi::C { .. }) {
                 fx.tcx.sess.span_fatal(
                     source_info.span,
                     &format!("Variadic call for non-C abi {:?}", fn_sig.abi),
                 );
             }
-            let sig_ref = fx.bcx.func.dfg.call_signature(call_inst).unwrap();
             let abi_params = call_args
-                .into_iter()
-                .map(|arg| {
+                .iter()
+                .map(|&arg| {
                     let ty = fx.bcx.func.dfg.value_type(arg);
                     if !ty.is_int() {
                         // FIXME set %al to upperbound on float args once floats are supported
@@ -534,10 +528,12 @@ enum CallTarget {
                     AbiParam::new(ty)
                 })
                 .collect::<Vec<AbiParam>>();
-            fx.bcx.func.dfg.signatures[sig_ref].params = abi_params;
-        }
+            Some(abi_params)
+        } else {
+            None
+        };
 
-        call_inst
+        codegen_call(fx, func_ref, &call_args, variadic_params, cleanup)
     });
 
     if let Some(dest) = target {
@@ -552,6 +548,7 @@ pub(crate) fn codegen_drop<'tcx>(
     fx: &mut FunctionCx<'_, '_, 'tcx>,
     source_info: mir::SourceInfo,
     drop_place: CPlace<'tcx>,
+    unwind: Option<BasicBlock>,
 ) {
     let ty = drop_place.layout().ty;
     let drop_instance = Instance::resolve_drop_in_place(fx.tcx, ty).polymorphize(fx.tcx);
@@ -576,7 +573,7 @@ pub(crate) fn codegen_drop<'tcx>(
 
                 let sig = clif_sig_from_fn_abi(fx.tcx, fx.target_config.default_call_conv, &fn_abi);
                 let sig = fx.bcx.import_signature(sig);
-                fx.bcx.ins().call_indirect(sig, drop_fn, &[ptr]);
+                codegen_call(fx, CallTarget::Indirect(sig, drop_fn), &[ptr], None, unwind);
             }
             _ => {
                 assert!(!matches!(drop_instance.def, InstanceDef::Virtual(_, _)));
@@ -604,8 +601,54 @@ pub(crate) fn codegen_drop<'tcx>(
                 }
 
                 let func_ref = fx.get_function_ref(drop_instance);
-                fx.bcx.ins().call(func_ref, &call_args);
+                codegen_call(fx, CallTarget::Direct(func_ref), &call_args, None, unwind);
+            }
+        }
+    }
+}
+
+enum CallTarget {
+    Direct(FuncRef),
+    Indirect(SigRef, Value),
+}
+
+/// Emits a call and returns its results. If landing pads are supported and the callee unwinds,
+/// control continues at `cleanup`. Otherwise unwinding continues past the current function
+/// without running its cleanup blocks.
+fn codegen_call(
+    fx: &mut FunctionCx<'_, '_, '_>,
+    func_ref: CallTarget,
+    call_args: &[Value],
+    variadic_params: Option<Vec<AbiParam>>,
+    cleanup: Option<BasicBlock>,
+) -> SmallVec<[Value; 2]> {
+    match cleanup {
+        Some(cleanup) if fx.cx.landing_pads_supported => {
+            let (sig_ref, callee) = match func_ref {
+                CallTarget::Direct(func_ref) => (
+                    fx.bcx.func.dfg.ext_funcs[func_ref].signature,
+                    fx.bcx.ins().func_addr(fx.pointer_type, func_ref),
+                ),
+                CallTarget::Indirect(sig_ref, func_ptr) => (sig_ref, func_ptr),
+            };
+            let mut sig = fx.bcx.func.dfg.signatures[sig_ref].clone();
+            if let Some(variadic_params) = variadic_params {
+                sig.params = variadic_params;
+            }
+            crate::landing_pads::codegen_invoke(fx, sig, callee, call_args, cleanup)
+        }
+        _ => {
+            let call_inst = match func_ref {
+                CallTarget::Direct(func_ref) => fx.bcx.ins().call(func_ref, call_args),
+                CallTarget::Indirect(sig, func_ptr) => {
+                    fx.bcx.ins().call_indirect(sig, func_ptr, call_args)
+                }
+            };
+            if let Some(variadic_params) = variadic_params {
+                let sig_ref = fx.bcx.func.dfg.call_signature(call_inst).unwrap();
+                fx.bcx.func.dfg.signatures[sig_ref].params = variadic_params;
             }
+            fx.bcx.inst_results(call_inst).iter().copied().collect()
         }
     }
 }
@@ -2,8 +2,10 @@
 
 use crate::prelude::*;
 
+use rustc_index::bit_set::BitSet;
 use rustc_index::vec::IndexVec;
 use rustc_middle::mir::StatementKind::*;
+use rustc_session::config::OptLevel;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub(crate) enum SsaKind {
@@ -26,6 +28,10 @@ pub(crate) fn analyze(fx: &FunctionCx<'_, '_, '_>) -> IndexVec<Local, SsaKind> {
         })
         .collect::<IndexVec<Local, SsaKind>>();
 
+    for local in locals_kept_for_debuginfo(fx).iter() {
+        not_ssa(&mut flag_map, local);
+    }
+
     for bb in fx.mir.basic_blocks().iter() {
         for stmt in bb.statements.iter() {
             match &stmt.kind {
@@ -43,6 +49,23 @@ pub(crate) fn analyze(fx: &FunctionCx<'_, '_, '_>) -> IndexVec<Local, SsaKind> {
     flag_map
 }
 
+/// Locals that user variables are stored in. When not optimizing, these are kept in stack slots for
+/// the entire function, so that debuggers can always find them. This is only done on targets for
+/// which variable locations are emitted, as it would change codegen for nothing otherwise.
+pub(crate) fn locals_kept_for_debuginfo(fx: &FunctionCx<'_, '_, '_>) -> BitSet<Local> {
+    let mut locals = BitSet::new_empty(fx.mir.local_decls.len());
+    let supports_variable_locations =
+        fx.cx.debug_context.as_ref().map_or(false, |cx| cx.supports_variable_locations());
+    if supports_variable_locations && fx.tcx.sess.opts.optimize == OptLevel::No {
+        for var in &fx.mir.var_debug_info {
+            if let VarDebugInfoContents::Place(place) = var.value {
+                locals.insert(place.local);
+            }
+        }
+    }
+    locals
+}
+
 fn not_ssa(flag_map: &mut IndexVec<Local, SsaKind>, local: Local) {
     flag_map[local] = SsaKind::NotSsa;
 }
@@ -81,7 +81,7 @@ pub(crate) fn codegen_fn<'tcx>(
     let clif_comments = crate::pretty_clif::CommentWriter::new(tcx, instance);
 
     let func_debug_cx = if let Some(debug_context) = &mut cx.debug_context {
-        Some(debug_context.define_function(tcx, &symbol_name, mir.span))
+        Some(debug_context.define_function(tcx, instance, &symbol_name, mir))
     } else {
         None
     };
@@ -107,11 +107,21 @@ pub(crate) fn codegen_fn<'tcx>(
 
         clif_comments,
         last_source_file: None,
+        exception_var: None,
         next_ssa_var: 0,
     };
 
     tcx.sess.time("codegen clif ir", || codegen_fn_body(&mut fx, start_block));
 
+    if let Some(func_debug_cx) = &mut fx.func_debug_cx {
+        func_debug_cx.define_variables(
+            fx.cx.debug_context.as_mut().unwrap(),
+            tcx,
+            mir,
+            &fx.local_map,
+        );
+    }
+
     // Recover all necessary data from fx, before accessing func will prevent future access to it.
     let symbol_name = fx.symbol_name;
     let clif_comments = fx.clif_comments;
@@ -276,11 +286,12 @@ fn codegen_fn_body(fx: &mut FunctionCx<'_, '_, '_>, start_block: Block) {
         fx.bcx.switch_to_block(block);
 
         if bb_data.is_cleanup {
-            // Unwinding after panicking is not supported
-            continue;
+            if !fx.cx.landing_pads_supported {
+                // Cleanup blocks are only reachable from landing pads.
+                continue;
+            }
 
-            // FIXME Once unwinding is supported and Cranelift supports marking blocks as cold, do
-            // so for cleanup blocks.
+            fx.bcx.set_cold_block(block);
         }
 
         fx.bcx.ins().nop();
@@ -326,7 +337,7 @@ fn codegen_fn_body(fx: &mut FunctionCx<'_, '_, '_>, start_block: Block) {
             TerminatorKind::Return => {
                 crate::abi::codegen_return(fx);
             }
-            TerminatorKind::Assert { cond, expected, msg, target, cleanup: _ } => {
+            TerminatorKind::Assert { cond, expected, msg, target, cleanup } => {
                 if !fx.tcx.sess.overflow_checks() {
                     if let mir::AssertKind::OverflowNeg(_) = *msg {
                         let target = fx.get_block(*target);
@@ -361,11 +372,12 @@ fn codegen_fn_body(fx: &mut FunctionCx<'_, '_, '_>, start_block: Block) {
                             rustc_hir::LangItem::PanicBoundsCheck,
                             &[index, len, location],
                             source_info.span,
+                            *cleanup,
                         );
                     }
                     _ => {
                         let msg_str = msg.description();
-                        codegen_panic(fx, msg_str, source_info);
+                        codegen_panic(fx, msg_str, source_info, *cleanup);
                     }
                 }
             }
@@ -424,7 +436,7 @@ fn codegen_fn_body(fx: &mut FunctionCx<'_, '_, '_>, start_block: Block) {
                 destination,
                 target,
                 fn_span,
-                cleanup: _,
+                cleanup,
                 from_hir_call: _,
             } => {
                 fx.tcx.sess.time("codegen call", || {
@@ -435,6 +447,7 @@ fn codegen_fn_body(fx: &mut FunctionCx<'_, '_, '_>, start_block: Block) {
                         args,
                         *destination,
                         *target,
+                        *cleanup,
                     )
                 });
             }
@@ -462,8 +475,14 @@ fn codegen_fn_body(fx: &mut FunctionCx<'_, '_, '_>, start_block: Block) {
                     *destination,
                 );
             }
-            TerminatorKind::Resume | TerminatorKind::Abort => {
-                // FIXME implement unwinding
+            TerminatorKind::Resume => {
+                if fx.cx.landing_pads_supported {
+                    crate::landing_pads::codegen_resume(fx);
+                } else {
+                    fx.bcx.ins().trap(TrapCode::UnreachableCodeReached);
+                }
+            }
+            TerminatorKind::Abort => {
                 fx.bcx.ins().trap(TrapCode::UnreachableCodeReached);
             }
             TerminatorKind::Unreachable => {
@@ -476,9 +495,9 @@ fn codegen_fn_body(fx: &mut FunctionCx<'_, '_, '_>, start_block: Block) {
             | TerminatorKind::GeneratorDrop => {
                 bug!("shouldn't exist at codegen {:?}", bb_data.terminator());
             }
-            TerminatorKind::Drop { place, target, unwind: _ } => {
+            TerminatorKind::Drop { place, target, unwind } => {
                 let drop_place = codegen_place(fx, *place);
-                crate::abi::codegen_drop(fx, source_info, drop_place);
+                crate::abi::codegen_drop(fx, source_info, drop_place, *unwind);
 
                 let target_block = fx.get_block(*target);
                 fx.bcx.ins().jump(target_block, &[]);
@@ -909,6 +928,7 @@ pub(crate) fn codegen_panic<'tcx>(
     fx: &mut FunctionCx<'_, '_, 'tcx>,
     msg_str: &str,
     source_info: mir::SourceInfo,
+    cleanup: Option<BasicBlock>,
 ) {
     let location = fx.get_caller_location(source_info).load_scalar(fx);
 
@@ -916,7 +936,7 @@ pub(crate) fn codegen_panic<'tcx>(
     let msg_len = fx.bcx.ins().iconst(fx.pointer_type, i64::try_from(msg_str.len()).unwrap());
     let args = [msg_ptr, msg_len, location];
 
-    codegen_panic_inner(fx, rustc_hir::LangItem::Panic, &args, source_info.span);
+    codegen_panic_inner(fx, rustc_hir::LangItem::Panic, &args, source_info.span, cleanup);
 }
 
 pub(crate) fn codegen_panic_inner<'tcx>(
@@ -924,23 +944,32 @@ pub(crate) fn codegen_panic_inner<'tcx>(
     lang_item: rustc_hir::LangItem,
     args: &[Value],
     span: Span,
+    cleanup: Option<BasicBlock>,
 ) {
     let def_id =
         fx.tcx.lang_items().require(lang_item).unwrap_or_else(|s| fx.tcx.sess.span_fatal(span, &s));
 
     let instance = Instance::mono(fx.tcx, def_id).polymorphize(fx.tcx);
     let symbol_name = fx.tcx.symbol_name(instance).name;
-
-    fx.lib_call(
-        &*symbol_name,
-        vec![
-            AbiParam::new(fx.pointer_type),
-            AbiParam::new(fx.pointer_type),
-            AbiParam::new(fx.pointer_type),
-        ],
-        vec![],
-        args,
-    );
+    let params = vec![
+        AbiParam::new(fx.pointer_type),
+        AbiParam::new(fx.pointer_type),
+        AbiParam::new(fx.pointer_type),
+    ];
+
+    match cleanup {
+        Some(cleanup) if fx.cx.landing_pads_supported => {
+            let sig =
+                Signature { params, returns: vec![], call_conv: fx.target_config.default_call_conv };
+            let func_id = fx.module.declare_function(&symbol_name, Linkage::Import, &sig).unwrap();
+            let func_ref = fx.module.declare_func_in_func(func_id, &mut fx.bcx.func);
+            let func_addr = fx.bcx.ins().func_addr(fx.pointer_type, func_ref);
+            crate::landing_pads::codegen_invoke(fx, sig, func_addr, args, cleanup);
+        }
+        _ => {
+            fx.lib_call(&*symbol_name, params, vec![], args);
+        }
+    }
 
     fx.bcx.ins().trap(TrapCode::UnreachableCodeReached);
 }
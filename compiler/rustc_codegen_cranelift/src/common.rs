@@ -263,7 +263,11 @@ pub(crate) struct FunctionCx<'m, 'clif, 'tcx: 'm> {
     /// For optimization purposes only
     pub(crate) last_source_file: Option<(Lrc<SourceFile>, FileId)>,
 
-    /// This should only be accessed by `CPlace::new_var`.
+    /// The exception that is being unwound while running the cleanup blocks, see
+    /// `landing_pads`.
+    pub(crate) exception_var: Option<Variable>,
+
+    /// This should only be accessed by `CPlace::new_var` and `landing_pads`.
     pub(crate) next_ssa_var: u32,
 }
 
@@ -366,8 +370,12 @@ pub(crate) fn set_debug_loc(&mut self, source_info: mir::SourceInfo) {
                 debug_context.add_source_file(&file)
             };
 
-            let source_loc =
-                self.func_debug_cx.as_mut().unwrap().add_dbg_loc(file_id, line, column);
+            let source_loc = self.func_debug_cx.as_mut().unwrap().add_dbg_loc(
+                file_id,
+                line,
+                column,
+                source_info.scope,
+            );
             self.bcx.set_srcloc(source_loc);
         }
     }
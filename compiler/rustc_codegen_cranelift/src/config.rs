@@ -14,6 +14,9 @@ pub enum CodegenMode {
     Jit,
     /// JIT compile and execute the crate, but only compile functions the first time they are used.
     JitLazy,
+    /// Like `JitLazy`, but recompile functions when the source of the crate changes while the
+    /// program is running. See the "Hot reloading" section of `docs/usage.md` for limitations.
+    JitHotReload,
 }
 
 impl FromStr for CodegenMode {
@@ -24,6 +27,7 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
             "aot" => Ok(CodegenMode::Aot),
             "jit" => Ok(CodegenMode::Jit),
             "jit-lazy" => Ok(CodegenMode::JitLazy),
+            "jit-hot-reload" => Ok(CodegenMode::JitHotReload),
             _ => Err(format!("Unknown codegen mode `{}`", s)),
         }
     }
@@ -63,6 +67,15 @@ pub struct BackendConfig {
     /// Defaults to true when the `CG_CLIF_DISABLE_INCR_CACHE` env var is set to 1 or false
     /// otherwise. Can be set using `-Cllvm-args=disable_incr_cache=...`.
     pub disable_incr_cache: bool,
+
+    /// Advertise the x86 features for which the llvm intrinsics are emulated (SSE3 to SSE4.2, AVX,
+    /// AVX2, FMA, BMI1, BMI2 and ADX) in the emulated cpuid instruction. Programs detecting these
+    /// features at runtime then use the emulated intrinsics, which are much slower than the SSE2
+    /// fallbacks of most crates.
+    ///
+    /// Defaults to true when the `CG_CLIF_EMULATE_AVX2` env var is set to 1 or false otherwise.
+    /// Can be set using `-Cllvm-args=emulate_avx2=...`.
+    pub emulate_avx2: bool,
 }
 
 impl Default for BackendConfig {
@@ -76,6 +89,7 @@ fn default() -> Self {
             display_cg_time: bool_env_var("CG_CLIF_DISPLAY_CG_TIME"),
             enable_verifier: cfg!(debug_assertions) || bool_env_var("CG_CLIF_ENABLE_VERIFIER"),
             disable_incr_cache: bool_env_var("CG_CLIF_DISABLE_INCR_CACHE"),
+            emulate_avx2: bool_env_var("CG_CLIF_EMULATE_AVX2"),
         }
     }
 }
@@ -95,6 +109,7 @@ fn parse_bool(name: &str, value: &str) -> Result<bool, String> {
                     "display_cg_time" => config.display_cg_time = parse_bool(name, value)?,
                     "enable_verifier" => config.enable_verifier = parse_bool(name, value)?,
                     "disable_incr_cache" => config.disable_incr_cache = parse_bool(name, value)?,
+                    "emulate_avx2" => config.emulate_avx2 = parse_bool(name, value)?,
                     _ => return Err(format!("Unknown option `{}`", name)),
                 }
             } else {
@@ -3,16 +3,25 @@
 mod emit;
 mod line_info;
 mod object;
+mod types;
 mod unwind;
 
+use crate::pointer::PointerBase;
 use crate::prelude::*;
 
 use cranelift_codegen::ir::Endianness;
+use cranelift_codegen::isa::unwind::UnwindInst;
 use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::MachSrcLoc;
+
+use rustc_codegen_ssa::debuginfo::type_names;
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_index::vec::IndexVec;
+use rustc_middle::ty::DefIdTree;
 
 use gimli::write::{
-    Address, AttributeValue, DwarfUnit, FileId, LineProgram, LineString, Range, RangeList,
-    UnitEntryId,
+    Address, AttributeValue, DwarfUnit, Expression, FileId, LineProgram, LineString, Range,
+    RangeList, UnitEntryId,
 };
 use gimli::{Encoding, Format, LineEncoding, RunTimeEndian};
 use indexmap::IndexSet;
@@ -25,12 +34,35 @@ pub(crate) struct DebugContext {
 
     dwarf: DwarfUnit,
     unit_range_list: RangeList,
+
+    /// Type DIEs indexed by [`TyCtxt::type_id_hash`].
+    types: FxHashMap<u64, UnitEntryId>,
+    namespaces: FxHashMap<DefId, UnitEntryId>,
+    /// Offset of the frame pointer from the canonical frame address. Locals in stack slots are
+    /// located relative to it. `None` if the frame layout of the target isn't known.
+    fp_offset_from_cfa: Option<i64>,
 }
 
 pub(crate) struct FunctionDebugContext {
     entry_id: UnitEntryId,
     function_source_loc: (FileId, u64, u64),
-    source_loc_set: indexmap::IndexSet<(FileId, u64, u64)>,
+    source_loc_set: indexmap::IndexSet<(FileId, u64, u64, SourceScope)>,
+
+    /// The DIE of every source scope. Scopes inlined by the MIR inliner share the DIE of the scope
+    /// they were inlined into.
+    scopes: IndexVec<SourceScope, UnitEntryId>,
+    scope_parents: IndexVec<SourceScope, Option<SourceScope>>,
+    /// Variables in stack slots, whose location is only known after the function is compiled.
+    variable_locations: Vec<VariableLocation>,
+}
+
+struct VariableLocation {
+    entry_id: UnitEntryId,
+    stack_slot: StackSlot,
+    /// Offset of the variable, or of the first pointer to dereference, in the stack slot.
+    offset: i64,
+    /// The offset to add after every dereference.
+    deref_offsets: Vec<u64>,
 }
 
 impl DebugContext {
@@ -100,42 +132,212 @@ pub(crate) fn new(tcx: TyCtxt<'_>, isa: &dyn TargetIsa) -> Self {
             root.set(gimli::DW_AT_low_pc, AttributeValue::Address(Address::Constant(0)));
         }
 
-        DebugContext { endian, dwarf, unit_range_list: RangeList(Vec::new()) }
+        // Cranelift pushes the frame pointer right below the return address.
+        // FIXME verify the frame layout of other architectures
+        let fp_offset_from_cfa = if tcx.sess.target.arch == "x86_64" {
+            Some(-2 * i64::from(isa.frontend_config().pointer_bytes()))
+        } else {
+            None
+        };
+
+        DebugContext {
+            endian,
+            dwarf,
+            unit_range_list: RangeList(Vec::new()),
+            types: FxHashMap::default(),
+            namespaces: FxHashMap::default(),
+            fp_offset_from_cfa,
+        }
+    }
+
+    /// Whether variables in stack slots can be given a `DW_AT_location` on this target.
+    pub(crate) fn supports_variable_locations(&self) -> bool {
+        self.fp_offset_from_cfa.is_some()
     }
 
-    pub(crate) fn define_function(
+    pub(crate) fn define_function<'tcx>(
         &mut self,
-        tcx: TyCtxt<'_>,
+        tcx: TyCtxt<'tcx>,
+        instance: Instance<'tcx>,
         name: &str,
-        function_span: Span,
+        mir: &Body<'tcx>,
     ) -> FunctionDebugContext {
-        let (file, line, column) = DebugContext::get_span_loc(tcx, function_span, function_span);
+        let (file, line, column) = DebugContext::get_span_loc(tcx, mir.span, mir.span);
 
         let file_id = self.add_source_file(&file);
 
-        // FIXME: add to appropriate scope instead of root
-        let scope = self.dwarf.unit.root();
+        // Shims don't correspond to an item, so they are added to the root scope with their symbol
+        // name instead.
+        let (scope, item_name) = match instance.def {
+            InstanceDef::Item(_) => {
+                let def_id = instance.def_id();
+                let mut item_name = String::new();
+                type_names::push_item_name(tcx, def_id, false, &mut item_name);
+                (self.item_namespace(tcx, tcx.parent(def_id)), item_name)
+            }
+            _ => (self.dwarf.unit.root(), name.to_owned()),
+        };
 
         let entry_id = self.dwarf.unit.add(scope, gimli::DW_TAG_subprogram);
+        let name_id = self.dwarf.strings.add(item_name);
+        let linkage_name_id = self.dwarf.strings.add(name);
         let entry = self.dwarf.unit.get_mut(entry_id);
-        let name_id = self.dwarf.strings.add(name);
         // Gdb requires DW_AT_name. Otherwise the DW_TAG_subprogram is skipped.
         entry.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
-        entry.set(gimli::DW_AT_linkage_name, AttributeValue::StringRef(name_id));
+        entry.set(gimli::DW_AT_linkage_name, AttributeValue::StringRef(linkage_name_id));
 
         entry.set(gimli::DW_AT_decl_file, AttributeValue::FileIndex(Some(file_id)));
         entry.set(gimli::DW_AT_decl_line, AttributeValue::Udata(line));
         entry.set(gimli::DW_AT_decl_column, AttributeValue::Udata(column));
 
+        let mut frame_base = Expression::new();
+        frame_base.op(gimli::DW_OP_call_frame_cfa);
+        entry.set(gimli::DW_AT_frame_base, AttributeValue::Exprloc(frame_base));
+
+        if let InstanceDef::Item(_) = instance.def {
+            if !tcx.is_closure(instance.def_id()) {
+                self.add_template_params(tcx, entry_id, instance.def_id(), instance.substs);
+            }
+        }
+
+        let mut scopes = IndexVec::with_capacity(mir.source_scopes.len());
+        for scope_data in mir.source_scopes.iter() {
+            let scope_entry = match scope_data.parent_scope {
+                None => entry_id,
+                // FIXME describe inlined functions using DW_TAG_inlined_subroutine
+                Some(parent_scope)
+                    if scope_data.inlined.is_some()
+                        || scope_data.inlined_parent_scope.is_some() =>
+                {
+                    scopes[parent_scope]
+                }
+                Some(parent_scope) => {
+                    self.dwarf.unit.add(scopes[parent_scope], gimli::DW_TAG_lexical_block)
+                }
+            };
+            scopes.push(scope_entry);
+        }
+
         FunctionDebugContext {
             entry_id,
             function_source_loc: (file_id, line, column),
             source_loc_set: IndexSet::new(),
+            scopes,
+            scope_parents: mir.source_scopes.iter().map(|data| data.parent_scope).collect(),
+            variable_locations: Vec::new(),
+        }
+    }
+
+    fn item_namespace(&mut self, tcx: TyCtxt<'_>, def_id: DefId) -> UnitEntryId {
+        if let Some(&namespace) = self.namespaces.get(&def_id) {
+            return namespace;
         }
+
+        let def_key = tcx.def_key(def_id);
+        let parent_scope = match def_key.parent {
+            Some(parent) => self.item_namespace(tcx, DefId { krate: def_id.krate, index: parent }),
+            None => self.dwarf.unit.root(),
+        };
+
+        let mut namespace_name = String::new();
+        type_names::push_item_name(tcx, def_id, false, &mut namespace_name);
+
+        let namespace = self.dwarf.unit.add(parent_scope, gimli::DW_TAG_namespace);
+        let name_id = self.dwarf.strings.add(namespace_name);
+        let entry = self.dwarf.unit.get_mut(namespace);
+        entry.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
+
+        self.namespaces.insert(def_id, namespace);
+        namespace
     }
 }
 
 impl FunctionDebugContext {
+    /// Describes the user variables of the function. This has to be called after the prelude of
+    /// the function has been codegened, so that `local_map` is complete.
+    pub(crate) fn define_variables<'tcx>(
+        &mut self,
+        debug_context: &mut DebugContext,
+        tcx: TyCtxt<'tcx>,
+        mir: &Body<'tcx>,
+        local_map: &IndexVec<Local, CPlace<'tcx>>,
+    ) {
+        let cx = RevealAllLayoutCx(tcx);
+
+        'vars: for var in &mir.var_debug_info {
+            let place = match var.value {
+                VarDebugInfoContents::Place(place) => place,
+                VarDebugInfoContents::Const(_) => continue,
+            };
+            // FIXME describe the variables of inlined functions
+            if var.source_info.scope.inlined_instance(&mir.source_scopes).is_some() {
+                continue;
+            }
+
+            let local_place = local_map[place.local];
+            let mut layout = local_place.layout();
+            let mut location = match local_place.inner() {
+                CPlaceInner::Addr(ptr, None) => match ptr.debug_base_and_offset() {
+                    (PointerBase::Stack(stack_slot), offset) => {
+                        Some((stack_slot, i64::from(offset), vec![]))
+                    }
+                    _ => None,
+                },
+                // FIXME describe variables in SSA values using the value label ranges of Cranelift
+                _ => None,
+            };
+            for elem in place.projection {
+                match elem {
+                    ProjectionElem::Field(field, _) => {
+                        let offset = layout.fields.offset(field.index()).bytes();
+                        match &mut location {
+                            Some((_, _, deref_offsets)) if !deref_offsets.is_empty() => {
+                                *deref_offsets.last_mut().unwrap() += offset;
+                            }
+                            Some((_, base_offset, _)) => *base_offset += offset as i64,
+                            None => {}
+                        }
+                        layout = layout.field(&cx, field.index());
+                    }
+                    ProjectionElem::Deref => {
+                        if let Some((_, _, deref_offsets)) = &mut location {
+                            deref_offsets.push(0);
+                        }
+                        layout = cx.layout_of(layout.ty.builtin_deref(true).unwrap().ty);
+                    }
+                    _ => continue 'vars,
+                }
+            }
+
+            let (file, line, _column) =
+                DebugContext::get_span_loc(tcx, mir.span, var.source_info.span);
+            let file_id = debug_context.add_source_file(&file);
+            let type_entry = debug_context.dwarf_ty(tcx, layout.ty);
+
+            let is_arg = place.projection.is_empty()
+                && place.local != RETURN_PLACE
+                && place.local.index() <= mir.arg_count;
+            let tag = if is_arg { gimli::DW_TAG_formal_parameter } else { gimli::DW_TAG_variable };
+            let var_entry = debug_context.dwarf.unit.add(self.scopes[var.source_info.scope], tag);
+            let name_id = debug_context.dwarf.strings.add(var.name.as_str());
+            let entry = debug_context.dwarf.unit.get_mut(var_entry);
+            entry.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
+            entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(type_entry));
+            entry.set(gimli::DW_AT_decl_file, AttributeValue::FileIndex(Some(file_id)));
+            entry.set(gimli::DW_AT_decl_line, AttributeValue::Udata(line));
+
+            // Variables without location are shown as optimized out.
+            if let Some((stack_slot, offset, deref_offsets)) = location {
+                self.variable_locations.push(VariableLocation {
+                    entry_id: var_entry,
+                    stack_slot,
+                    offset,
+                    deref_offsets,
+                });
+            }
+        }
+    }
+
     pub(crate) fn finalize(
         mut self,
         debug_context: &mut DebugContext,
@@ -145,6 +347,8 @@ pub(crate) fn finalize(
         let symbol = func_id.as_u32() as usize;
 
         let end = self.create_debug_lines(debug_context, symbol, context);
+        self.create_scope_ranges(debug_context, symbol, context);
+        self.set_variable_locations(debug_context, context);
 
         debug_context.unit_range_list.0.push(Range::StartLength {
             begin: Address::Symbol { symbol, addend: 0 },
@@ -161,3 +365,101 @@ pub(crate) fn finalize(
         func_entry.set(gimli::DW_AT_high_pc, AttributeValue::Udata(u64::from(end)));
     }
 }
+
+impl FunctionDebugContext {
+    fn create_scope_ranges(
+        &self,
+        debug_context: &mut DebugContext,
+        symbol: usize,
+        context: &Context,
+    ) {
+        let mut scope_ranges = FxIndexMap::<UnitEntryId, Vec<(u32, u32)>>::default();
+
+        let mcr = context.compiled_code().unwrap();
+        for &MachSrcLoc { start, end, loc } in mcr.buffer.get_srclocs_sorted() {
+            if loc.is_default() {
+                continue;
+            }
+            let (_, _, _, scope) = *self.source_loc_set.get_index(loc.bits() as usize).unwrap();
+
+            // Code in a scope is also part of all its parent scopes.
+            let mut scope = Some(scope);
+            let mut last_entry = None;
+            while let Some(current_scope) = scope {
+                let scope_entry = self.scopes[current_scope];
+                if scope_entry == self.entry_id {
+                    break;
+                }
+                if last_entry != Some(scope_entry) {
+                    let ranges = scope_ranges.entry(scope_entry).or_default();
+                    match ranges.last_mut() {
+                        Some((_, range_end)) if *range_end == start => *range_end = end,
+                        _ => ranges.push((start, end)),
+                    }
+                    last_entry = Some(scope_entry);
+                }
+                scope = self.scope_parents[current_scope];
+            }
+        }
+
+        for (scope_entry, ranges) in scope_ranges {
+            let range_list = RangeList(
+                ranges
+                    .into_iter()
+                    .map(|(start, end)| Range::StartLength {
+                        begin: Address::Symbol { symbol, addend: i64::from(start) },
+                        length: u64::from(end - start),
+                    })
+                    .collect(),
+            );
+            let range_list_id = debug_context.dwarf.unit.ranges.add(range_list);
+            debug_context
+                .dwarf
+                .unit
+                .get_mut(scope_entry)
+                .set(gimli::DW_AT_ranges, AttributeValue::RangeListRef(range_list_id));
+        }
+    }
+
+    fn set_variable_locations(&self, debug_context: &mut DebugContext, context: &Context) {
+        let fp_offset_from_cfa = match debug_context.fp_offset_from_cfa {
+            Some(fp_offset_from_cfa) => fp_offset_from_cfa,
+            None => return,
+        };
+
+        // Stack slots are addressed relative to the stack pointer at the end of the prologue. The
+        // prologue first saves the clobbered callee-saved registers right below the frame pointer
+        // and then allocates `frame_size` bytes for the stack slots below them.
+        let mcr = context.compiled_code().unwrap();
+        let clobber_size = mcr
+            .buffer
+            .unwind_info
+            .iter()
+            .find_map(|(_, inst)| match *inst {
+                UnwindInst::DefineNewFrame { offset_downward_to_clobbers, .. } => {
+                    Some(offset_downward_to_clobbers)
+                }
+                _ => None,
+            })
+            .unwrap_or(0);
+        let nominal_sp_offset_from_cfa =
+            fp_offset_from_cfa - i64::from(clobber_size) - i64::from(mcr.frame_size);
+
+        for var in &self.variable_locations {
+            let slot_offset = i64::from(mcr.sized_stackslot_offsets[var.stack_slot]);
+            let mut location = Expression::new();
+            location.op_fbreg(nominal_sp_offset_from_cfa + slot_offset + var.offset);
+            for &offset in &var.deref_offsets {
+                location.op_deref();
+                if offset != 0 {
+                    location.op_plus_uconst(offset);
+                }
+            }
+            debug_context
+                .dwarf
+                .unit
+                .get_mut(var.entry_id)
+                .set(gimli::DW_AT_location, AttributeValue::Exprloc(location));
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Type debuginfo (`DW_TAG_*_type`)
+
+use crate::prelude::*;
+
+use rustc_codegen_ssa::debuginfo::type_names;
+use rustc_middle::ty::layout::IntegerExt;
+use rustc_middle::ty::subst::{GenericArgKind, SubstsRef};
+use rustc_span::DUMMY_SP;
+use rustc_target::abi::{FieldsShape, Primitive, TagEncoding, Variants};
+
+use gimli::write::{AttributeValue, UnitEntryId};
+
+impl DebugContext {
+    /// Returns the DIE describing `ty`, which must be fully monomorphized.
+    pub(crate) fn dwarf_ty<'tcx>(&mut self, tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> UnitEntryId {
+        let type_id = tcx.type_id_hash(ty);
+        if let Some(&type_entry) = self.types.get(&type_id) {
+            return type_entry;
+        }
+
+        let layout = RevealAllLayoutCx(tcx).layout_of(ty);
+        let name = type_names::compute_debuginfo_type_name(tcx, ty, true);
+
+        let base_type = |encoding| (gimli::DW_TAG_base_type, Some(encoding));
+        let (tag, encoding) = match *ty.kind() {
+            ty::Bool => base_type(gimli::DW_ATE_boolean),
+            ty::Char => base_type(gimli::DW_ATE_UTF),
+            ty::Int(_) => base_type(gimli::DW_ATE_signed),
+            ty::Uint(_) => base_type(gimli::DW_ATE_unsigned),
+            ty::Float(_) => base_type(gimli::DW_ATE_float),
+            ty::Ref(_, pointee_ty, _) | ty::RawPtr(ty::TypeAndMut { ty: pointee_ty, .. })
+                if pointee_ty.is_sized(tcx.at(DUMMY_SP), ParamEnv::reveal_all()) =>
+            {
+                (gimli::DW_TAG_pointer_type, None)
+            }
+            ty::Adt(adt_def, _)
+                if adt_def.is_box()
+                    && ty.boxed_ty().is_sized(tcx.at(DUMMY_SP), ParamEnv::reveal_all())
+                    && layout.size == tcx.data_layout.pointer_size =>
+            {
+                (gimli::DW_TAG_pointer_type, None)
+            }
+            ty::FnPtr(_) => (gimli::DW_TAG_pointer_type, None),
+            ty::Array(..) => (gimli::DW_TAG_array_type, None),
+            ty::Adt(adt_def, _) if adt_def.is_union() => (gimli::DW_TAG_union_type, None),
+            _ => (gimli::DW_TAG_structure_type, None),
+        };
+
+        let type_entry = self.dwarf.unit.add(self.dwarf.unit.root(), tag);
+        // Insert the type before describing its contents, as these may refer back to it.
+        self.types.insert(type_id, type_entry);
+
+        let name_id = self.dwarf.strings.add(name);
+        let entry = self.dwarf.unit.get_mut(type_entry);
+        entry.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
+        if let Some(encoding) = encoding {
+            entry.set(gimli::DW_AT_encoding, AttributeValue::Encoding(encoding));
+        }
+        if !layout.is_unsized() {
+            entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(layout.size.bytes()));
+            if tag != gimli::DW_TAG_base_type && tag != gimli::DW_TAG_pointer_type {
+                entry.set(gimli::DW_AT_alignment, AttributeValue::Udata(layout.align.abi.bytes()));
+            }
+        }
+
+        match *ty.kind() {
+            ty::Bool | ty::Char | ty::Int(_) | ty::Uint(_) | ty::Float(_) => {}
+            ty::Ref(_, pointee_ty, _) | ty::RawPtr(ty::TypeAndMut { ty: pointee_ty, .. }) => {
+                self.describe_pointer(tcx, type_entry, tag, pointee_ty)
+            }
+            ty::Adt(adt_def, _) if adt_def.is_box() && tag == gimli::DW_TAG_pointer_type => {
+                self.describe_pointer(tcx, type_entry, tag, ty.boxed_ty())
+            }
+            ty::FnPtr(_) => {
+                let subroutine_entry =
+                    self.dwarf.unit.add(self.dwarf.unit.root(), gimli::DW_TAG_subroutine_type);
+                let entry = self.dwarf.unit.get_mut(type_entry);
+                entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(subroutine_entry));
+            }
+            ty::Array(elem_ty, _) => {
+                let elem_entry = self.dwarf_ty(tcx, elem_ty);
+                let entry = self.dwarf.unit.get_mut(type_entry);
+                entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(elem_entry));
+
+                let subrange_entry = self.dwarf.unit.add(type_entry, gimli::DW_TAG_subrange_type);
+                let count = match layout.fields {
+                    FieldsShape::Array { count, .. } => count,
+                    _ => unreachable!("{:?}", layout.fields),
+                };
+                let entry = self.dwarf.unit.get_mut(subrange_entry);
+                entry.set(gimli::DW_AT_count, AttributeValue::Udata(count));
+            }
+            ty::Adt(adt_def, substs) if adt_def.is_enum() => {
+                self.add_template_params(tcx, type_entry, adt_def.did(), substs);
+                match layout.variants {
+                    // Enums without variants, like `Infallible`, have no fields to describe.
+                    // Their layout still claims a variant with index 0, which doesn't exist.
+                    Variants::Single { .. } if adt_def.variants().is_empty() => {}
+                    Variants::Single { index } => {
+                        let variant = adt_def.variant(index);
+                        let variant_layout = layout.for_variant(&RevealAllLayoutCx(tcx), index);
+                        self.add_fields(tcx, type_entry, variant_layout, |i| {
+                            variant.fields[i].name.to_string()
+                        });
+                    }
+                    Variants::Multiple { .. } => {
+                        self.describe_enum_variants(tcx, type_entry, layout)
+                    }
+                }
+            }
+            ty::Adt(adt_def, substs) => {
+                self.add_template_params(tcx, type_entry, adt_def.did(), substs);
+                let variant = adt_def.non_enum_variant();
+                self.add_fields(tcx, type_entry, layout, |i| variant.fields[i].name.to_string());
+            }
+            ty::Tuple(_) | ty::Closure(..) => {
+                self.add_fields(tcx, type_entry, layout, |i| format!("__{}", i));
+            }
+            // FIXME describe the contents of generators and unsized types
+            _ => {}
+        }
+
+        type_entry
+    }
+
+    fn describe_pointer<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_entry: UnitEntryId,
+        tag: gimli::DwTag,
+        pointee_ty: Ty<'tcx>,
+    ) {
+        if tag == gimli::DW_TAG_pointer_type {
+            let pointee_entry = self.dwarf_ty(tcx, pointee_ty);
+            let entry = self.dwarf.unit.get_mut(type_entry);
+            entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(pointee_entry));
+            return;
+        }
+
+        // Like LLVM, describe fat pointers as a struct of the data pointer and the metadata. Gdb
+        // recognizes slices by the names of these fields.
+        let (data_name, data_ty, meta_name, meta_ty) = match tcx
+            .struct_tail_erasing_lifetimes(pointee_ty, ParamEnv::reveal_all())
+            .kind()
+        {
+            ty::Slice(elem_ty) => ("data_ptr", tcx.mk_imm_ptr(*elem_ty), "length", tcx.types.usize),
+            ty::Str => ("data_ptr", tcx.mk_imm_ptr(tcx.types.u8), "length", tcx.types.usize),
+            ty::Dynamic(..) => (
+                "pointer",
+                tcx.mk_imm_ptr(tcx.types.u8),
+                "vtable",
+                tcx.mk_imm_ptr(tcx.mk_array(tcx.types.usize, 3)),
+            ),
+            _ => ("pointer", tcx.mk_imm_ptr(tcx.types.u8), "metadata", tcx.types.usize),
+        };
+        let pointer_size = tcx.data_layout.pointer_size;
+        self.add_member(tcx, type_entry, Some(data_name), data_ty, Size::ZERO);
+        self.add_member(tcx, type_entry, Some(meta_name), meta_ty, pointer_size);
+    }
+
+    /// Describes an enum with multiple variants using a `DW_TAG_variant_part`. The variant whose
+    /// layout contains the niche doesn't get a `DW_AT_discr_value`, which makes it the default.
+    fn describe_enum_variants<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_entry: UnitEntryId,
+        layout: TyAndLayout<'tcx>,
+    ) {
+        let cx = RevealAllLayoutCx(tcx);
+        let (tag, tag_encoding, tag_field, variants) = match &layout.variants {
+            Variants::Multiple { tag, tag_encoding, tag_field, variants } => {
+                (*tag, tag_encoding, *tag_field, variants)
+            }
+            Variants::Single { .. } => unreachable!(),
+        };
+        let adt_def = match layout.ty.kind() {
+            ty::Adt(adt_def, _) => *adt_def,
+            _ => unreachable!("{:?}", layout.ty),
+        };
+
+        let (tag_ty, tag_signed) = match tag.primitive() {
+            Primitive::Int(int, signed) => (int.to_ty(tcx, signed), signed),
+            Primitive::Pointer => (tcx.types.usize, false),
+            Primitive::F32 | Primitive::F64 => bug!("float tag for {:?}", layout.ty),
+        };
+        let tag_size = tag.size(&cx);
+
+        let variant_part_entry = self.dwarf.unit.add(type_entry, gimli::DW_TAG_variant_part);
+        let tag_entry =
+            self.add_member(tcx, variant_part_entry, None, tag_ty, layout.fields.offset(tag_field));
+        self.dwarf.unit.get_mut(tag_entry).set(gimli::DW_AT_artificial, AttributeValue::Flag(true));
+        self.dwarf
+            .unit
+            .get_mut(variant_part_entry)
+            .set(gimli::DW_AT_discr, AttributeValue::UnitRef(tag_entry));
+
+        for variant_index in variants.indices() {
+            let discr_value = match *tag_encoding {
+                TagEncoding::Direct => {
+                    Some(layout.ty.discriminant_for_variant(tcx, variant_index).unwrap().val)
+                }
+                TagEncoding::Niche { dataful_variant, ref niche_variants, niche_start } => {
+                    if variant_index == dataful_variant {
+                        None
+                    } else {
+                        let niche_value = variant_index.as_u32() - niche_variants.start().as_u32();
+                        Some(u128::from(niche_value).wrapping_add(niche_start))
+                    }
+                }
+            };
+
+            let variant_entry = self.dwarf.unit.add(variant_part_entry, gimli::DW_TAG_variant);
+            if let Some(discr_value) = discr_value {
+                let discr_value = tag_size.truncate(discr_value);
+                let discr_value = if tag_signed {
+                    AttributeValue::Sdata(tag_size.sign_extend(discr_value) as i64)
+                } else {
+                    AttributeValue::Udata(discr_value as u64)
+                };
+                self.dwarf.unit.get_mut(variant_entry).set(gimli::DW_AT_discr_value, discr_value);
+            }
+
+            // The fields of the variant are described by a struct nested in the enum type.
+            let variant = adt_def.variant(variant_index);
+            let variant_layout = layout.for_variant(&cx, variant_index);
+            let variant_struct_entry =
+                self.dwarf.unit.add(type_entry, gimli::DW_TAG_structure_type);
+            let name_id = self.dwarf.strings.add(variant.name.as_str());
+            let entry = self.dwarf.unit.get_mut(variant_struct_entry);
+            entry.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
+            entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(layout.size.bytes()));
+            entry.set(gimli::DW_AT_alignment, AttributeValue::Udata(layout.align.abi.bytes()));
+            self.add_fields(tcx, variant_struct_entry, variant_layout, |i| {
+                variant.fields[i].name.to_string()
+            });
+
+            let member_entry = self.dwarf.unit.add(variant_entry, gimli::DW_TAG_member);
+            let entry = self.dwarf.unit.get_mut(member_entry);
+            entry.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
+            entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(variant_struct_entry));
+            entry.set(gimli::DW_AT_data_member_location, AttributeValue::Udata(0));
+        }
+    }
+
+    fn add_fields<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_entry: UnitEntryId,
+        layout: TyAndLayout<'tcx>,
+        field_name: impl Fn(usize) -> String,
+    ) {
+        for i in 0..layout.fields.count() {
+            let field_ty = layout.field(&RevealAllLayoutCx(tcx), i).ty;
+            let offset = layout.fields.offset(i);
+            self.add_member(tcx, type_entry, Some(&field_name(i)), field_ty, offset);
+        }
+    }
+
+    fn add_member<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        parent_entry: UnitEntryId,
+        name: Option<&str>,
+        ty: Ty<'tcx>,
+        offset: Size,
+    ) -> UnitEntryId {
+        let ty_entry = self.dwarf_ty(tcx, ty);
+        let member_entry = self.dwarf.unit.add(parent_entry, gimli::DW_TAG_member);
+        let name_id = name.map(|name| self.dwarf.strings.add(name));
+        let entry = self.dwarf.unit.get_mut(member_entry);
+        if let Some(name_id) = name_id {
+            entry.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
+        }
+        entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(ty_entry));
+        entry.set(gimli::DW_AT_data_member_location, AttributeValue::Udata(offset.bytes()));
+        member_entry
+    }
+
+    /// Adds a `DW_TAG_template_type_parameter` for every type argument of `def_id`.
+    pub(super) fn add_template_params<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        parent_entry: UnitEntryId,
+        def_id: DefId,
+        substs: SubstsRef<'tcx>,
+    ) {
+        let mut names = Vec::new();
+        let mut generics = Some(tcx.generics_of(def_id));
+        while let Some(g) = generics {
+            names.extend(g.params.iter().rev().map(|param| param.name));
+            generics = g.parent.map(|parent| tcx.generics_of(parent));
+        }
+        names.reverse();
+
+        for (name, arg) in names.into_iter().zip(substs.iter()) {
+            if let GenericArgKind::Type(ty) = arg.unpack() {
+                let ty_entry = self.dwarf_ty(tcx, ty);
+                let param_entry =
+                    self.dwarf.unit.add(parent_entry, gimli::DW_TAG_template_type_parameter);
+                let name_id = self.dwarf.strings.add(name.as_str());
+                let entry = self.dwarf.unit.get_mut(param_entry);
+                entry.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
+                entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(ty_entry));
+            }
+        }
+    }
+}
@@ -1,6 +1,8 @@
 //! The JIT driver uses [`cranelift_jit`] to JIT execute programs without writing any object
 //! files.
 
+pub(crate) mod hot_reload;
+
 use std::cell::RefCell;
 use std::ffi::CString;
 use std::os::raw::{c_char, c_int};
@@ -109,7 +111,7 @@ pub(crate) fn run_jit(tcx: TyCtxt<'_>, backend_config: BackendConfig) -> ! {
     let (mut jit_module, mut cx) = create_jit_module(
         tcx,
         &backend_config,
-        matches!(backend_config.codegen_mode, CodegenMode::JitLazy),
+        matches!(backend_config.codegen_mode, CodegenMode::JitLazy | CodegenMode::JitHotReload),
     );
     let mut cached_context = Context::new();
 
@@ -122,6 +124,12 @@ pub(crate) fn run_jit(tcx: TyCtxt<'_>, backend_config: BackendConfig) -> ! {
         .into_iter()
         .collect::<Vec<(_, (_, _))>>();
 
+    let mut hot_reloader = if matches!(backend_config.codegen_mode, CodegenMode::JitHotReload) {
+        Some(hot_reload::HotReloader::new(tcx, mono_items.iter().map(|&(mono_item, _)| mono_item)))
+    } else {
+        None
+    };
+
     super::time(tcx, backend_config.display_cg_time, "codegen mono items", || {
         super::predefine_mono_items(tcx, &mut jit_module, &mono_items);
         for (mono_item, _) in mono_items {
@@ -139,7 +147,7 @@ pub(crate) fn run_jit(tcx: TyCtxt<'_>, backend_config: BackendConfig) -> ! {
                             )
                         });
                     }
-                    CodegenMode::JitLazy => {
+                    CodegenMode::JitLazy | CodegenMode::JitHotReload => {
                         codegen_shim(tcx, &mut cx, &mut cached_context, &mut jit_module, inst)
                     }
                 },
@@ -210,7 +218,21 @@ pub(crate) fn run_jit(tcx: TyCtxt<'_>, backend_config: BackendConfig) -> ! {
 
     // Handle messages
     loop {
-        match rx.recv().unwrap() {
+        let message = if let Some(hot_reloader) = &mut hot_reloader {
+            // Check for source changes whenever no message arrived for a while
+            match rx.recv_timeout(hot_reload::POLL_INTERVAL) {
+                Ok(message) => message,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    hot_reloader.reload_if_changed();
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => unreachable!(),
+            }
+        } else {
+            rx.recv().unwrap()
+        };
+
+        match message {
             // lazy JIT compilation request - compile requested instance and return pointer to result
             UnsafeMessage::JitFn { instance_ptr, trampoline_ptr, tx } => {
                 tx.send(jit_fn(instance_ptr, trampoline_ptr))
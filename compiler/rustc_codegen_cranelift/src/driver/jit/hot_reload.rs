@@ -0,0 +1,447 @@
+//! Hot reloading of the program run by `-Cllvm-args=mode=jit-hot-reload`.
+//!
+//! While the jitted program runs, the rustc thread polls the source files of the local crate. When
+//! one of them changes, rustc is run again in a nested compiler session with the original
+//! arguments. Thanks to the incremental cache only the queries affected by the change are
+//! recomputed. Once the nested session reaches codegen, every function whose optimized MIR changed
+//! is compiled with the new `TyCtxt` and redefined in the `JITModule` of the running program. The
+//! JIT module is created with hotswapping enabled, which makes all calls and function pointers go
+//! through the GOT. Redefining a function patches its GOT entry, so the next call of the function
+//! from anywhere in the program runs the new code. Frames that are currently executing keep
+//! running the old code.
+//!
+//! Values created by the old code keep existing in statics, on the heap and on the stack. A reload
+//! is therefore skipped if the layout of any type used by both the old and the new code changed,
+//! as the new code would misinterpret those values.
+
+use std::any::Any;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use rustc_codegen_ssa::{CodegenResults, CrateInfo};
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::fx::FxIndexSet;
+use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
+use rustc_metadata::EncodedMetadata;
+use rustc_middle::dep_graph::{WorkProduct, WorkProductId};
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_span::{FileName, Symbol};
+use rustc_target::abi::{FieldsShape, Variants};
+
+use cranelift_module::FuncOrDataId;
+
+use super::{JitState, LAZY_JIT_STATE};
+use crate::prelude::*;
+
+/// How often the source files of the crate are checked for changes.
+pub(super) const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Set while a nested compiler session performs a reload.
+///
+/// `JitState` is not `Send`, but it is only accessed by one thread at a time. The rustc thread
+/// running the jitted program blocks until the thread of the nested session has finished, while
+/// lazy JIT requests of the jitted program queue up in the message channel. The lock is never held
+/// while the nested session can abort with a fatal error.
+static PENDING_RELOAD: Mutex<Option<AssertSend<ReloadState>>> = Mutex::new(None);
+
+struct AssertSend<T>(T);
+unsafe impl<T> Send for AssertSend<T> {}
+
+/// The state handed from the rustc thread running the jitted program to the nested compiler
+/// session and back.
+struct ReloadState {
+    jit_state: JitState,
+    snapshot: CrateSnapshot,
+    /// The source files of the new crate. Only set when the reload succeeded.
+    source_files: Option<Vec<PathBuf>>,
+}
+
+/// Watches the source files of the crate and reloads changed functions into the running program.
+pub(super) struct HotReloader {
+    /// The arguments for the nested compiler sessions.
+    args: Vec<String>,
+    snapshot: CrateSnapshot,
+    source_files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl HotReloader {
+    pub(super) fn new<'tcx>(
+        tcx: TyCtxt<'tcx>,
+        mono_items: impl Iterator<Item = MonoItem<'tcx>>,
+    ) -> Self {
+        if tcx.sess.local_crate_source_file.is_none() {
+            tcx.sess.fatal("hot reloading requires the crate to be read from a file");
+        }
+
+        HotReloader {
+            args: nested_session_args(tcx),
+            snapshot: CrateSnapshot::new(tcx, mono_items),
+            source_files: with_modification_times(local_source_files(tcx)),
+        }
+    }
+
+    /// Reload the crate if any of its source files changed since the last check.
+    pub(super) fn reload_if_changed(&mut self) {
+        let mut changed = false;
+        for (path, mtime) in &mut self.source_files {
+            let new_mtime = modification_time(path);
+            if *mtime != new_mtime {
+                *mtime = new_mtime;
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.reload();
+        }
+    }
+
+    fn reload(&mut self) {
+        let jit_state =
+            LAZY_JIT_STATE.with(|lazy_jit_state| lazy_jit_state.borrow_mut().take().unwrap());
+        *PENDING_RELOAD.lock().unwrap() = Some(AssertSend(ReloadState {
+            jit_state,
+            snapshot: std::mem::take(&mut self.snapshot),
+            source_files: None,
+        }));
+
+        // The nested session runs on a fresh thread, as the session globals of the current session
+        // may not be overwritten. Compilation errors have already been reported by the nested
+        // session and an ICE has been printed by the panic hook. The running program keeps using
+        // the old code until the next change in either case.
+        let args = self.args.clone();
+        let _ = std::thread::Builder::new()
+            .name("hot reload".to_owned())
+            .spawn(move || {
+                rustc_driver::catch_fatal_errors(|| {
+                    rustc_driver::RunCompiler::new(&args, &mut HotReloadCallbacks).run()
+                })
+            })
+            .unwrap()
+            .join();
+
+        let state = PENDING_RELOAD.lock().unwrap().take().unwrap().0;
+        LAZY_JIT_STATE.with(|lazy_jit_state| {
+            *lazy_jit_state.borrow_mut() = Some(state.jit_state);
+        });
+        self.snapshot = state.snapshot;
+        if let Some(source_files) = state.source_files {
+            self.source_files = with_modification_times(source_files);
+        }
+    }
+}
+
+struct HotReloadCallbacks;
+
+impl rustc_driver::Callbacks for HotReloadCallbacks {}
+
+/// The arguments of the original rustc invocation, but only emitting an object file. The backend
+/// doesn't produce any modules for a reload, so nothing is written and nothing is linked.
+fn nested_session_args(tcx: TyCtxt<'_>) -> Vec<String> {
+    let original_args = rustc_driver::args::arg_expand_all(&std::env::args().collect::<Vec<_>>());
+
+    let mut args = vec![];
+    let mut original_args = original_args.into_iter();
+    while let Some(arg) = original_args.next() {
+        if arg == "--emit" {
+            original_args.next();
+        } else if !arg.starts_with("--emit=") {
+            args.push(arg);
+        }
+    }
+    args.push("--emit=obj".to_owned());
+
+    if tcx.sess.opts.incremental.is_none() {
+        // Without an incremental cache every reload would analyze the whole crate again.
+        let incr_dir = tcx
+            .output_filenames(())
+            .out_directory
+            .join(format!("{}-hot-reload-incremental", tcx.crate_name(LOCAL_CRATE)));
+        args.push(format!("-Cincremental={}", incr_dir.display()));
+    }
+
+    args
+}
+
+fn local_source_files(tcx: TyCtxt<'_>) -> Vec<PathBuf> {
+    tcx.sess
+        .source_map()
+        .files()
+        .iter()
+        .filter(|source_file| !source_file.is_imported())
+        .filter_map(|source_file| match &source_file.name {
+            FileName::Real(name) => name.local_path().map(|path| path.to_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn with_modification_times(paths: Vec<PathBuf>) -> Vec<(PathBuf, Option<SystemTime>)> {
+    paths
+        .into_iter()
+        .map(|path| {
+            let mtime = modification_time(&path);
+            (path, mtime)
+        })
+        .collect()
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// The parts of a compiled crate that the next reload is compared against.
+#[derive(Default)]
+struct CrateSnapshot {
+    /// Fingerprint of the optimized MIR of every function, by symbol name.
+    functions: FxHashMap<String, Fingerprint>,
+    /// Name and `type_id_hash` of the type of every static, by symbol name.
+    statics: FxHashMap<String, (String, u64)>,
+    /// Name and layout fingerprint of every type the values of the program may have, by
+    /// `type_id_hash`.
+    layouts: FxHashMap<u64, (String, Fingerprint)>,
+}
+
+impl CrateSnapshot {
+    fn new<'tcx>(tcx: TyCtxt<'tcx>, mono_items: impl Iterator<Item = MonoItem<'tcx>>) -> Self {
+        let mut snapshot = CrateSnapshot::default();
+
+        for mono_item in mono_items {
+            match mono_item {
+                MonoItem::Fn(instance) => {
+                    let mir = tcx.instance_mir(instance.def);
+                    let fingerprint = tcx.with_stable_hashing_context(|mut hcx| {
+                        let mut hasher = StableHasher::new();
+                        mir.hash_stable(&mut hcx, &mut hasher);
+                        hasher.finish()
+                    });
+                    snapshot
+                        .functions
+                        .insert(tcx.symbol_name(instance).name.to_owned(), fingerprint);
+
+                    for local_decl in &mir.local_decls {
+                        let ty = instance.subst_mir_and_normalize_erasing_regions(
+                            tcx,
+                            ParamEnv::reveal_all(),
+                            local_decl.ty,
+                        );
+                        snapshot.record_layouts(tcx, ty);
+                    }
+                }
+                MonoItem::Static(def_id) => {
+                    let instance = Instance::mono(tcx, def_id);
+                    let ty = instance.ty(tcx, ParamEnv::reveal_all());
+                    snapshot.statics.insert(
+                        tcx.symbol_name(instance).name.to_owned(),
+                        (with_no_trimmed_paths!(tcx.def_path_str(def_id)), tcx.type_id_hash(ty)),
+                    );
+                    snapshot.record_layouts(tcx, ty);
+                }
+                MonoItem::GlobalAsm(_) => {}
+            }
+        }
+
+        snapshot
+    }
+
+    /// Record the layout of `ty` and of all types reachable from it through fields and pointers.
+    fn record_layouts<'tcx>(&mut self, tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) {
+        let type_id = tcx.type_id_hash(ty);
+        if self.layouts.contains_key(&type_id) {
+            return;
+        }
+
+        let cx = RevealAllLayoutCx(tcx);
+        let layout = cx.layout_of(ty);
+        let fingerprint = tcx.with_stable_hashing_context(|mut hcx| {
+            let mut hasher = StableHasher::new();
+            layout.layout.hash_stable(&mut hcx, &mut hasher);
+            hasher.finish()
+        });
+        self.layouts.insert(type_id, (with_no_trimmed_paths!(ty.to_string()), fingerprint));
+
+        if let Some(pointee) = ty.builtin_deref(true) {
+            self.record_layouts(tcx, pointee.ty);
+        }
+        self.record_field_layouts(tcx, layout);
+        if let Variants::Multiple { variants, .. } = &layout.variants {
+            for variant_index in variants.indices() {
+                self.record_field_layouts(tcx, layout.for_variant(&cx, variant_index));
+            }
+        }
+    }
+
+    fn record_field_layouts<'tcx>(&mut self, tcx: TyCtxt<'tcx>, layout: TyAndLayout<'tcx>) {
+        let field_count = match &layout.fields {
+            FieldsShape::Primitive => 0,
+            // All elements have the same type. This also covers slices, which have zero elements.
+            FieldsShape::Array { .. } => 1,
+            FieldsShape::Union(count) => count.get(),
+            FieldsShape::Arbitrary { offsets, .. } => offsets.len(),
+        };
+        for i in 0..field_count {
+            self.record_layouts(tcx, layout.field(&RevealAllLayoutCx(tcx), i).ty);
+        }
+    }
+}
+
+/// Whether the current compiler session was started by [`HotReloader`] to perform a reload.
+pub(crate) fn is_reloading() -> bool {
+    PENDING_RELOAD.lock().unwrap().is_some()
+}
+
+/// The result of the codegen of a reload. It doesn't contain any modules, as all functions are
+/// directly defined in the JIT module of the running program.
+pub(crate) struct HotReloadCodegen {
+    metadata: EncodedMetadata,
+    crate_info: CrateInfo,
+}
+
+impl HotReloadCodegen {
+    pub(crate) fn join(self) -> (CodegenResults, FxHashMap<WorkProductId, WorkProduct>) {
+        (
+            CodegenResults {
+                modules: vec![],
+                allocator_module: None,
+                metadata_module: None,
+                metadata: self.metadata,
+                crate_info: self.crate_info,
+            },
+            FxHashMap::default(),
+        )
+    }
+}
+
+/// Puts the reload state back into [`PENDING_RELOAD`] when dropped, including when codegen is
+/// aborted by a fatal error.
+struct RestorePendingReload(Option<AssertSend<ReloadState>>);
+
+impl Drop for RestorePendingReload {
+    fn drop(&mut self) {
+        *PENDING_RELOAD.lock().unwrap() = self.0.take();
+    }
+}
+
+/// Compile all functions that changed since the last reload into the running program.
+///
+/// All changed functions are codegened before the first one is redefined. A fatal error during
+/// codegen therefore leaves the JIT module in the state of the last successful reload.
+pub(crate) fn codegen_reload(tcx: TyCtxt<'_>, metadata: EncodedMetadata) -> Box<dyn Any> {
+    let mut pending_reload = RestorePendingReload(PENDING_RELOAD.lock().unwrap().take());
+    let state = &mut pending_reload.0.as_mut().unwrap().0;
+    let jit_module = &mut state.jit_state.jit_module;
+
+    let (_, cgus) = tcx.collect_and_partition_mono_items(());
+    let mono_items = cgus
+        .iter()
+        .flat_map(|cgu| cgu.items_in_deterministic_order(tcx))
+        .map(|(mono_item, _)| mono_item)
+        .collect::<FxIndexSet<_>>();
+
+    let snapshot = CrateSnapshot::new(tcx, mono_items.iter().copied());
+
+    let changed_fns = mono_items
+        .iter()
+        .filter_map(|mono_item| match *mono_item {
+            MonoItem::Fn(instance) => {
+                let name = tcx.symbol_name(instance).name;
+                (state.snapshot.functions.get(name) != snapshot.functions.get(name))
+                    .then_some(instance)
+            }
+            MonoItem::Static(_) | MonoItem::GlobalAsm(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut incompatibilities = vec![];
+    for (type_id, (name, fingerprint)) in &snapshot.layouts {
+        if matches!(state.snapshot.layouts.get(type_id), Some((_, old)) if old != fingerprint) {
+            incompatibilities.push(format!("the layout of `{}` changed", name));
+        }
+    }
+    for (symbol_name, (name, type_id)) in &snapshot.statics {
+        if matches!(state.snapshot.statics.get(symbol_name), Some((_, old)) if old != type_id) {
+            incompatibilities.push(format!("the type of static `{}` changed", name));
+        }
+    }
+    for &instance in &changed_fns {
+        let name = tcx.symbol_name(instance).name;
+        if let Some(FuncOrDataId::Func(func_id)) = jit_module.get_name(name) {
+            let sig = crate::abi::get_function_sig(tcx, jit_module.isa().triple(), instance);
+            if jit_module.declarations().get_function_decl(func_id).signature != sig {
+                incompatibilities.push(format!(
+                    "the signature of `{}` changed",
+                    with_no_trimmed_paths!(instance.to_string())
+                ));
+            }
+        }
+    }
+
+    let crate_info = CrateInfo::new(tcx, "dummy_target_cpu".to_string());
+
+    if !incompatibilities.is_empty() {
+        incompatibilities.sort();
+        let mut err = tcx.sess.struct_warn("skipping hot reload of incompatible changes");
+        for incompatibility in incompatibilities {
+            err.note(incompatibility);
+        }
+        err.help("values created by the running program would be misinterpreted by the new code");
+        err.help("restart the program to apply these changes");
+        err.emit();
+        return Box::new(HotReloadCodegen { metadata, crate_info });
+    }
+
+    let mut cx = crate::CodegenCx::new(
+        tcx,
+        state.jit_state.backend_config.clone(),
+        jit_module.isa(),
+        false,
+        Symbol::intern("dummy_cgu_name"),
+    );
+    let mut cached_context = Context::new();
+
+    for &mono_item in &mono_items {
+        if let MonoItem::Static(def_id) = mono_item {
+            // Existing statics keep their current value.
+            let name = tcx.symbol_name(Instance::mono(tcx, def_id)).name;
+            if !state.snapshot.statics.contains_key(name) {
+                crate::constant::codegen_static(tcx, jit_module, def_id);
+            }
+        }
+    }
+
+    let mut codegened_fns = Vec::with_capacity(changed_fns.len());
+    for &instance in &changed_fns {
+        let name = tcx.symbol_name(instance).name;
+        let sig = crate::abi::get_function_sig(tcx, jit_module.isa().triple(), instance);
+        let func_id = jit_module.declare_function(name, Linkage::Export, &sig).unwrap();
+        let is_redefinition = state.snapshot.functions.contains_key(name);
+
+        let codegened_func = tcx.sess.time("codegen fn", || {
+            crate::base::codegen_fn(tcx, &mut cx, Function::new(), jit_module, instance)
+        });
+        codegened_fns.push((func_id, is_redefinition, codegened_func));
+    }
+
+    if !cx.global_asm.is_empty() {
+        tcx.sess.fatal("Inline asm is not supported in JIT mode");
+    }
+
+    for (func_id, is_redefinition, codegened_func) in codegened_fns {
+        if is_redefinition {
+            jit_module.prepare_for_function_redefine(func_id).unwrap();
+        }
+        crate::base::compile_fn(&mut cx, &mut cached_context, jit_module, codegened_func);
+    }
+
+    jit_module.finalize_definitions();
+    unsafe { cx.unwind_context.register_jit(jit_module) };
+
+    tcx.sess.note_without_error(format!("hot reloaded {} changed functions", changed_fns.len()));
+
+    state.snapshot = snapshot;
+    state.source_files = Some(local_source_files(tcx));
+
+    Box::new(HotReloadCodegen { metadata, crate_info })
+}
@@ -5,6 +5,9 @@
 /// Emulates a subset of the cpuid x86 instruction.
 ///
 /// This emulates an intel cpu with sse and sse2 support, but which doesn't support anything else.
+/// With `-Cllvm-args=emulate_avx2=true` it additionally supports the SSE family up to SSE4.2, AVX,
+/// AVX2, FMA, BMI1, BMI2 and ADX, which are the features for which the llvm intrinsics are
+/// emulated.
 pub(crate) fn codegen_cpuid_call<'tcx>(
     fx: &mut FunctionCx<'_, '_, 'tcx>,
     leaf: Value,
@@ -32,7 +35,7 @@ pub(crate) fn codegen_cpuid_call<'tcx>(
     switch.emit(&mut fx.bcx, leaf, unsupported_leaf);
 
     fx.bcx.switch_to_block(leaf_0);
-    let max_basic_leaf = fx.bcx.ins().iconst(types::I32, 1);
+    let max_basic_leaf = fx.bcx.ins().iconst(types::I32, if fx.cx.emulate_avx2 { 7 } else { 1 });
     let vend0 = fx.bcx.ins().iconst(types::I32, i64::from(u32::from_le_bytes(*b"Genu")));
     let vend2 = fx.bcx.ins().iconst(types::I32, i64::from(u32::from_le_bytes(*b"ineI")));
     let vend1 = fx.bcx.ins().iconst(types::I32, i64::from(u32::from_le_bytes(*b"ntel")));
@@ -41,14 +44,37 @@ pub(crate) fn codegen_cpuid_call<'tcx>(
     fx.bcx.switch_to_block(leaf_1);
     let cpu_signature = fx.bcx.ins().iconst(types::I32, 0);
     let additional_information = fx.bcx.ins().iconst(types::I32, 0);
-    let ecx_features = fx.bcx.ins().iconst(types::I32, 0);
+    let ecx_features = fx.bcx.ins().iconst(
+        types::I32,
+        if fx.cx.emulate_avx2 {
+            1 << 0 /* sse3 */
+                | 1 << 9 /* ssse3 */
+                | 1 << 12 /* fma */
+                | 1 << 19 /* sse4.1 */
+                | 1 << 20 /* sse4.2 */
+                | 1 << 23 /* popcnt */
+                | 1 << 27 /* osxsave */
+                | 1 << 28 /* avx */
+        } else {
+            0
+        },
+    );
     let edx_features = fx.bcx.ins().iconst(types::I32, 1 << 25 /* sse */ | 1 << 26 /* sse2 */);
     fx.bcx.ins().jump(dest, &[cpu_signature, additional_information, ecx_features, edx_features]);
 
     fx.bcx.switch_to_block(leaf_7);
-    // This leaf technically has subleaves, but we just return zero for all subleaves.
+    // This leaf technically has subleaves, but we just return the features of subleaf 0 for all
+    // subleaves.
     let zero = fx.bcx.ins().iconst(types::I32, 0);
-    fx.bcx.ins().jump(dest, &[zero, zero, zero, zero]);
+    let ebx_features = fx.bcx.ins().iconst(
+        types::I32,
+        if fx.cx.emulate_avx2 {
+            1 << 3 /* bmi1 */ | 1 << 5 /* avx2 */ | 1 << 8 /* bmi2 */ | 1 << 19 /* adx */
+        } else {
+            0
+        },
+    );
+    fx.bcx.ins().jump(dest, &[zero, ebx_features, zero, zero]);
 
     fx.bcx.switch_to_block(leaf_8000_0000);
     let extended_max_basic_leaf = fx.bcx.ins().iconst(types::I32, 0);
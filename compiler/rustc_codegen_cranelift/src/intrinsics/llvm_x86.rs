@@ -0,0 +1,1947 @@
+//! Emulate x86 LLVM intrinsics
+
+use crate::intrinsics::*;
+use crate::prelude::*;
+
+use rustc_middle::ty::subst::SubstsRef;
+
+pub(crate) fn codegen_x86_llvm_intrinsic_call<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    intrinsic: &str,
+    _substs: SubstsRef<'tcx>,
+    args: &[mir::Operand<'tcx>],
+    ret: CPlace<'tcx>,
+    target: Option<BasicBlock>,
+) {
+    match intrinsic {
+        // Used by `is_x86_feature_detected!`
+        "llvm.x86.xgetbv" => {
+            intrinsic_args!(fx, args => (xcr_no); intrinsic);
+            let xcr_no = xcr_no.load_scalar(fx);
+
+            // Report the x87, SSE and AVX state as enabled in XCR0, matching the features
+            // reported by the emulated cpuid.
+            let is_xcr0 = fx.bcx.ins().icmp_imm(IntCC::Equal, xcr_no, 0);
+            let enabled_state = fx.bcx.ins().iconst(types::I64, 0b111);
+            let zero = fx.bcx.ins().iconst(types::I64, 0);
+            let res = fx.bcx.ins().select(is_xcr0, enabled_state, zero);
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+        }
+
+        "llvm.x86.sse.sfence" | "llvm.x86.sse2.lfence" | "llvm.x86.sse2.mfence" => {
+            fx.bcx.ins().fence();
+        }
+        // Spin loop hint
+        "llvm.x86.sse2.pause" => {}
+        // Flushing a cache line or zeroing the upper halves of the vector registers has no effect
+        // that is observable by Rust code.
+        "llvm.x86.sse2.clflush" | "llvm.x86.avx.vzeroall" | "llvm.x86.avx.vzeroupper" => {}
+
+        // Used by `_mm_movemask_epi8` and `_mm256_movemask_epi8`
+        "llvm.x86.sse2.pmovmskb.128"
+        | "llvm.x86.avx2.pmovmskb"
+        | "llvm.x86.sse.movmsk.ps"
+        | "llvm.x86.sse2.movmsk.pd"
+        | "llvm.x86.avx.movmsk.ps.256"
+        | "llvm.x86.avx.movmsk.pd.256" => {
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_ty = fx.clif_type(lane_ty).unwrap();
+            assert!(lane_count <= 32);
+
+            let mut res = fx.bcx.ins().iconst(types::I32, 0);
+
+            for lane in (0..lane_count).rev() {
+                let a_lane =
+                    a.value_field(fx, mir::Field::new(lane.try_into().unwrap())).load_scalar(fx);
+
+                // cast float to int
+                let a_lane = match lane_ty {
+                    types::F32 => fx.bcx.ins().bitcast(types::I32, a_lane),
+                    types::F64 => fx.bcx.ins().bitcast(types::I64, a_lane),
+                    _ => a_lane,
+                };
+
+                // extract sign bit of an int
+                let a_lane_sign = fx.bcx.ins().ushr_imm(a_lane, i64::from(lane_ty.bits() - 1));
+
+                // shift sign bit into result
+                let a_lane_sign = clif_intcast(fx, a_lane_sign, types::I32, false);
+                res = fx.bcx.ins().ishl_imm(res, 1);
+                res = fx.bcx.ins().bor(res, a_lane_sign);
+            }
+
+            let res = CValue::by_val(res, fx.layout_of(fx.tcx.types.i32));
+            ret.write_cvalue(fx, res);
+        }
+
+        "llvm.x86.sse.cmp.ps"
+        | "llvm.x86.sse2.cmp.pd"
+        | "llvm.x86.avx.cmp.ps.256"
+        | "llvm.x86.avx.cmp.pd.256" => {
+            let (x, y, kind) = match args {
+                [x, y, kind] => (x, y, kind),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let x = codegen_operand(fx, x);
+            let y = codegen_operand(fx, y);
+            let kind = imm_arg(fx, intrinsic, kind);
+
+            simd_pair_for_each_lane(fx, x, y, ret, &|fx, _lane_ty, res_lane_ty, x_lane, y_lane| {
+                let res_lane = x86_fcmp(fx, kind, x_lane, y_lane);
+                bool_to_zero_or_max_uint(fx, res_lane_ty, res_lane)
+            });
+        }
+        "llvm.x86.sse.cmp.ss" | "llvm.x86.sse2.cmp.sd" => {
+            let (x, y, kind) = match args {
+                [x, y, kind] => (x, y, kind),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let x = codegen_operand(fx, x);
+            let y = codegen_operand(fx, y);
+            let kind = imm_arg(fx, intrinsic, kind);
+            let (_, lane_ty) = x.layout().ty.simd_size_and_type(fx.tcx);
+
+            scalar_lane_op(fx, x, y, ret, &|fx, x_lane, y_lane| {
+                let res_lane = x86_fcmp(fx, kind, x_lane, y_lane);
+                bool_to_zero_or_max_uint(fx, lane_ty, res_lane)
+            });
+        }
+        _ if is_comi(intrinsic) => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            // The ordered and unordered variants only differ in which NaNs raise an exception.
+            let flt_cc = match intrinsic.split('.').nth(3).unwrap().trim_start_matches("ucomi") {
+                "eq" | "comieq" => FloatCC::Equal,
+                "lt" | "comilt" => FloatCC::LessThan,
+                "le" | "comile" => FloatCC::LessThanOrEqual,
+                "gt" | "comigt" => FloatCC::GreaterThan,
+                "ge" | "comige" => FloatCC::GreaterThanOrEqual,
+                "neq" | "comineq" => FloatCC::NotEqual,
+                _ => unreachable!("{intrinsic}"),
+            };
+
+            let a_lane = a.value_lane(fx, 0).load_scalar(fx);
+            let b_lane = b.value_lane(fx, 0).load_scalar(fx);
+            let res = fx.bcx.ins().fcmp(flt_cc, a_lane, b_lane);
+            let res = fx.bcx.ins().bint(types::I32, res);
+            ret.write_cvalue(fx, CValue::by_val(res, fx.layout_of(fx.tcx.types.i32)));
+        }
+
+        "llvm.x86.sse.add.ss"
+        | "llvm.x86.sse.sub.ss"
+        | "llvm.x86.sse.mul.ss"
+        | "llvm.x86.sse.div.ss"
+        | "llvm.x86.sse.min.ss"
+        | "llvm.x86.sse.max.ss"
+        | "llvm.x86.sse2.min.sd"
+        | "llvm.x86.sse2.max.sd" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            let op = intrinsic.split('.').nth(3).unwrap();
+            scalar_lane_op(fx, a, b, ret, &|fx, a_lane, b_lane| match op {
+                "add" => fx.bcx.ins().fadd(a_lane, b_lane),
+                "sub" => fx.bcx.ins().fsub(a_lane, b_lane),
+                "mul" => fx.bcx.ins().fmul(a_lane, b_lane),
+                "div" => fx.bcx.ins().fdiv(a_lane, b_lane),
+                "min" => x86_fmin(fx, a_lane, b_lane),
+                "max" => x86_fmax(fx, a_lane, b_lane),
+                _ => unreachable!("{intrinsic}"),
+            });
+        }
+        "llvm.x86.sse.sqrt.ss"
+        | "llvm.x86.sse2.sqrt.sd"
+        | "llvm.x86.sse.rcp.ss"
+        | "llvm.x86.sse.rsqrt.ss" => {
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let op = intrinsic.split('.').nth(3).unwrap();
+            scalar_lane_op(fx, a, a, ret, &|fx, a_lane, _| x86_float_unop(fx, op, a_lane));
+        }
+        "llvm.x86.sse.sqrt.ps"
+        | "llvm.x86.sse2.sqrt.pd"
+        | "llvm.x86.avx.sqrt.ps.256"
+        | "llvm.x86.sse.rcp.ps"
+        | "llvm.x86.avx.rcp.ps.256"
+        | "llvm.x86.sse.rsqrt.ps"
+        | "llvm.x86.avx.rsqrt.ps.256" => {
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let op = intrinsic.split('.').nth(3).unwrap();
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| {
+                x86_float_unop(fx, op, lane)
+            });
+        }
+        "llvm.x86.sse.min.ps"
+        | "llvm.x86.sse2.min.pd"
+        | "llvm.x86.avx.min.ps.256"
+        | "llvm.x86.avx.min.pd.256" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            simd_pair_for_each_lane(
+                fx,
+                a,
+                b,
+                ret,
+                &|fx, _lane_ty, _res_lane_ty, a_lane, b_lane| x86_fmin(fx, a_lane, b_lane),
+            );
+        }
+        "llvm.x86.sse.max.ps"
+        | "llvm.x86.sse2.max.pd"
+        | "llvm.x86.avx.max.ps.256"
+        | "llvm.x86.avx.max.pd.256" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            simd_pair_for_each_lane(
+                fx,
+                a,
+                b,
+                ret,
+                &|fx, _lane_ty, _res_lane_ty, a_lane, b_lane| x86_fmax(fx, a_lane, b_lane),
+            );
+        }
+        "llvm.x86.sse3.addsub.ps"
+        | "llvm.x86.sse3.addsub.pd"
+        | "llvm.x86.avx.addsub.ps.256"
+        | "llvm.x86.avx.addsub.pd.256" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            let (lane_count, _) = a.layout().ty.simd_size_and_type(fx.tcx);
+            for lane in 0..lane_count {
+                let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+                let b_lane = b.value_lane(fx, lane).load_scalar(fx);
+                let res_lane = if lane % 2 == 0 {
+                    fx.bcx.ins().fsub(a_lane, b_lane)
+                } else {
+                    fx.bcx.ins().fadd(a_lane, b_lane)
+                };
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.sse3.hadd.ps"
+        | "llvm.x86.sse3.hadd.pd"
+        | "llvm.x86.avx.hadd.ps.256"
+        | "llvm.x86.avx.hadd.pd.256" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            horizontal_op(fx, a, b, ret, &|fx, x, y| fx.bcx.ins().fadd(x, y));
+        }
+        "llvm.x86.sse3.hsub.ps"
+        | "llvm.x86.sse3.hsub.pd"
+        | "llvm.x86.avx.hsub.ps.256"
+        | "llvm.x86.avx.hsub.pd.256" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            horizontal_op(fx, a, b, ret, &|fx, x, y| fx.bcx.ins().fsub(x, y));
+        }
+        "llvm.x86.sse41.dpps" | "llvm.x86.sse41.dppd" | "llvm.x86.avx.dp.ps.256" => {
+            let (a, b, imm8) = match args {
+                [a, b, imm8] => (a, b, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let imm8 = imm_arg(fx, intrinsic, imm8);
+
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_ty = fx.clif_type(lane_ty).unwrap();
+            let lanes_per_block = 16 / u64::from(lane_ty.bytes());
+            for block in (0..lane_count).step_by(lanes_per_block as usize) {
+                let mut products = vec![];
+                for i in 0..lanes_per_block {
+                    let product = if imm8 & (1 << (4 + i)) != 0 {
+                        let a_lane = a.value_lane(fx, block + i).load_scalar(fx);
+                        let b_lane = b.value_lane(fx, block + i).load_scalar(fx);
+                        fx.bcx.ins().fmul(a_lane, b_lane)
+                    } else {
+                        float_const(fx, lane_ty, 0.0)
+                    };
+                    products.push(product);
+                }
+                // Sum the products pairwise, like the hardware does.
+                while products.len() > 1 {
+                    products = products
+                        .chunks(2)
+                        .map(|pair| fx.bcx.ins().fadd(pair[0], pair[1]))
+                        .collect();
+                }
+                for i in 0..lanes_per_block {
+                    let res_lane = if imm8 & (1 << i) != 0 {
+                        products[0]
+                    } else {
+                        float_const(fx, lane_ty, 0.0)
+                    };
+                    write_lane(fx, ret, block + i, res_lane);
+                }
+            }
+        }
+        "llvm.x86.sse41.round.ps"
+        | "llvm.x86.sse41.round.pd"
+        | "llvm.x86.avx.round.ps.256"
+        | "llvm.x86.avx.round.pd.256" => {
+            let (a, rounding) = match args {
+                [a, rounding] => (a, rounding),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let rounding = imm_arg(fx, intrinsic, rounding);
+
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| {
+                x86_round(fx, rounding, lane)
+            });
+        }
+        "llvm.x86.sse41.round.ss" | "llvm.x86.sse41.round.sd" => {
+            let (a, b, rounding) = match args {
+                [a, b, rounding] => (a, b, rounding),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let rounding = imm_arg(fx, intrinsic, rounding);
+
+            scalar_lane_op(fx, a, b, ret, &|fx, _, b_lane| x86_round(fx, rounding, b_lane));
+        }
+
+        "llvm.x86.fma.vfmadd.ss"
+        | "llvm.x86.fma.vfmadd.sd"
+        | "llvm.x86.fma.vfmsub.ss"
+        | "llvm.x86.fma.vfmsub.sd"
+        | "llvm.x86.fma.vfnmadd.ss"
+        | "llvm.x86.fma.vfnmadd.sd"
+        | "llvm.x86.fma.vfnmsub.ss"
+        | "llvm.x86.fma.vfnmsub.sd" => {
+            intrinsic_args!(fx, args => (a, b, c); intrinsic);
+
+            let op = intrinsic.split('.').nth(3).unwrap();
+            let (lane_count, _) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let a_lane = a.value_lane(fx, 0).load_scalar(fx);
+            let b_lane = b.value_lane(fx, 0).load_scalar(fx);
+            let c_lane = c.value_lane(fx, 0).load_scalar(fx);
+            let res_lane = x86_fma(fx, op, a_lane, b_lane, c_lane);
+            write_lane(fx, ret, 0, res_lane);
+            for lane in 1..lane_count {
+                let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+                write_lane(fx, ret, lane, a_lane);
+            }
+        }
+        "llvm.x86.fma.vfmsub.ps"
+        | "llvm.x86.fma.vfmsub.pd"
+        | "llvm.x86.fma.vfmsub.ps.256"
+        | "llvm.x86.fma.vfmsub.pd.256"
+        | "llvm.x86.fma.vfnmadd.ps"
+        | "llvm.x86.fma.vfnmadd.pd"
+        | "llvm.x86.fma.vfnmadd.ps.256"
+        | "llvm.x86.fma.vfnmadd.pd.256"
+        | "llvm.x86.fma.vfnmsub.ps"
+        | "llvm.x86.fma.vfnmsub.pd"
+        | "llvm.x86.fma.vfnmsub.ps.256"
+        | "llvm.x86.fma.vfnmsub.pd.256"
+        | "llvm.x86.fma.vfmaddsub.ps"
+        | "llvm.x86.fma.vfmaddsub.pd"
+        | "llvm.x86.fma.vfmaddsub.ps.256"
+        | "llvm.x86.fma.vfmaddsub.pd.256"
+        | "llvm.x86.fma.vfmsubadd.ps"
+        | "llvm.x86.fma.vfmsubadd.pd"
+        | "llvm.x86.fma.vfmsubadd.ps.256"
+        | "llvm.x86.fma.vfmsubadd.pd.256" => {
+            intrinsic_args!(fx, args => (a, b, c); intrinsic);
+
+            let op = intrinsic.split('.').nth(3).unwrap();
+            let (lane_count, _) = a.layout().ty.simd_size_and_type(fx.tcx);
+            for lane in 0..lane_count {
+                let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+                let b_lane = b.value_lane(fx, lane).load_scalar(fx);
+                let c_lane = c.value_lane(fx, lane).load_scalar(fx);
+                // The even lanes of `vfmaddsub` subtract and the odd lanes add, and the other way
+                // around for `vfmsubadd`.
+                let lane_op = match (op, lane % 2) {
+                    ("vfmaddsub", 0) | ("vfmsubadd", 1) => "vfmsub",
+                    ("vfmaddsub", _) | ("vfmsubadd", _) => "vfmadd",
+                    (op, _) => op,
+                };
+                let res_lane = x86_fma(fx, lane_op, a_lane, b_lane, c_lane);
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+
+        "llvm.x86.sse.cvtss2si"
+        | "llvm.x86.sse.cvtss2si64"
+        | "llvm.x86.sse.cvttss2si"
+        | "llvm.x86.sse.cvttss2si64"
+        | "llvm.x86.sse2.cvtsd2si"
+        | "llvm.x86.sse2.cvtsd2si64"
+        | "llvm.x86.sse2.cvttsd2si"
+        | "llvm.x86.sse2.cvttsd2si64" => {
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let truncate = intrinsic.contains(".cvtt");
+            let int_ty = fx.clif_type(ret.layout().ty).unwrap();
+            let a_lane = a.value_lane(fx, 0).load_scalar(fx);
+            let res = x86_fcvt_to_sint(fx, a_lane, int_ty, truncate);
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+        }
+        "llvm.x86.sse.cvtsi2ss" | "llvm.x86.sse.cvtsi642ss" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+            let b = b.load_scalar(fx);
+
+            let (lane_count, _) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let res_lane = fx.bcx.ins().fcvt_from_sint(types::F32, b);
+            write_lane(fx, ret, 0, res_lane);
+            for lane in 1..lane_count {
+                let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+                write_lane(fx, ret, lane, a_lane);
+            }
+        }
+        "llvm.x86.sse2.cvtsd2ss" | "llvm.x86.sse2.cvtss2sd" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_ty = fx.clif_type(lane_ty).unwrap();
+            let b_lane = b.value_lane(fx, 0).load_scalar(fx);
+            let res_lane = if lane_ty == types::F32 {
+                fx.bcx.ins().fdemote(types::F32, b_lane)
+            } else {
+                fx.bcx.ins().fpromote(types::F64, b_lane)
+            };
+            write_lane(fx, ret, 0, res_lane);
+            for lane in 1..lane_count {
+                let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+                write_lane(fx, ret, lane, a_lane);
+            }
+        }
+        "llvm.x86.sse2.cvtdq2ps" | "llvm.x86.avx.cvtdq2.ps.256" => {
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| {
+                fx.bcx.ins().fcvt_from_sint(types::F32, lane)
+            });
+        }
+        "llvm.x86.sse2.cvtps2dq"
+        | "llvm.x86.sse2.cvttps2dq"
+        | "llvm.x86.avx.cvt.ps2dq.256"
+        | "llvm.x86.avx.cvtt.ps2dq.256" => {
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let truncate = intrinsic.contains(".cvtt");
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| {
+                x86_fcvt_to_sint(fx, lane, types::I32, truncate)
+            });
+        }
+        // These convert two or four `f64` lanes and zero the remaining lanes of the result.
+        "llvm.x86.sse2.cvtpd2ps"
+        | "llvm.x86.avx.cvt.pd2.ps.256"
+        | "llvm.x86.sse2.cvtpd2dq"
+        | "llvm.x86.sse2.cvttpd2dq"
+        | "llvm.x86.avx.cvt.pd2dq.256"
+        | "llvm.x86.avx.cvtt.pd2dq.256" => {
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let truncate = intrinsic.contains(".cvtt");
+            let (lane_count, _) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let (ret_lane_count, ret_lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            let ret_lane_ty = fx.clif_type(ret_lane_ty).unwrap();
+            for lane in 0..ret_lane_count {
+                let res_lane = if lane < lane_count {
+                    let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+                    if ret_lane_ty == types::F32 {
+                        fx.bcx.ins().fdemote(types::F32, a_lane)
+                    } else {
+                        x86_fcvt_to_sint(fx, a_lane, types::I32, truncate)
+                    }
+                } else {
+                    zero_value(fx, ret_lane_ty)
+                };
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.sse2.cvtps2pd" => {
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let (ret_lane_count, _) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            for lane in 0..ret_lane_count {
+                let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+                let res_lane = fx.bcx.ins().fpromote(types::F64, a_lane);
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+
+        "llvm.x86.sse2.pslli.w"
+        | "llvm.x86.sse2.pslli.d"
+        | "llvm.x86.sse2.pslli.q"
+        | "llvm.x86.sse2.psrli.w"
+        | "llvm.x86.sse2.psrli.d"
+        | "llvm.x86.sse2.psrli.q"
+        | "llvm.x86.sse2.psrai.w"
+        | "llvm.x86.sse2.psrai.d"
+        | "llvm.x86.avx2.pslli.w"
+        | "llvm.x86.avx2.pslli.d"
+        | "llvm.x86.avx2.pslli.q"
+        | "llvm.x86.avx2.psrli.w"
+        | "llvm.x86.avx2.psrli.d"
+        | "llvm.x86.avx2.psrli.q"
+        | "llvm.x86.avx2.psrai.w"
+        | "llvm.x86.avx2.psrai.d" => {
+            let (a, imm8) = match args {
+                [a, imm8] => (a, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let imm8 = imm_arg(fx, intrinsic, imm8);
+
+            let kind = ShiftKind::from_intrinsic(intrinsic);
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| {
+                let count = fx.bcx.ins().iconst(types::I64, imm8 as i64);
+                x86_shift(fx, kind, lane, count)
+            });
+        }
+        "llvm.x86.sse2.psll.w"
+        | "llvm.x86.sse2.psll.d"
+        | "llvm.x86.sse2.psll.q"
+        | "llvm.x86.sse2.psrl.w"
+        | "llvm.x86.sse2.psrl.d"
+        | "llvm.x86.sse2.psrl.q"
+        | "llvm.x86.sse2.psra.w"
+        | "llvm.x86.sse2.psra.d"
+        | "llvm.x86.avx2.psll.w"
+        | "llvm.x86.avx2.psll.d"
+        | "llvm.x86.avx2.psll.q"
+        | "llvm.x86.avx2.psrl.w"
+        | "llvm.x86.avx2.psrl.d"
+        | "llvm.x86.avx2.psrl.q"
+        | "llvm.x86.avx2.psra.w"
+        | "llvm.x86.avx2.psra.d" => {
+            intrinsic_args!(fx, args => (a, count); intrinsic);
+
+            // All lanes are shifted by the lower 64 bits of `count`.
+            let (count_lane_count, count_lane_ty) = count.layout().ty.simd_size_and_type(fx.tcx);
+            let count_lane_bits = fx.clif_type(count_lane_ty).unwrap().bits();
+            let mut shift_count = fx.bcx.ins().iconst(types::I64, 0);
+            for lane in 0..count_lane_count.min(u64::from(64 / count_lane_bits)) {
+                let count_lane = count.value_lane(fx, lane).load_scalar(fx);
+                let count_lane = clif_intcast(fx, count_lane, types::I64, false);
+                let count_lane =
+                    fx.bcx.ins().ishl_imm(count_lane, (lane * u64::from(count_lane_bits)) as i64);
+                shift_count = fx.bcx.ins().bor(shift_count, count_lane);
+            }
+
+            let kind = ShiftKind::from_intrinsic(intrinsic);
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| {
+                x86_shift(fx, kind, lane, shift_count)
+            });
+        }
+        "llvm.x86.avx2.psllv.d"
+        | "llvm.x86.avx2.psllv.d.256"
+        | "llvm.x86.avx2.psllv.q"
+        | "llvm.x86.avx2.psllv.q.256"
+        | "llvm.x86.avx2.psrlv.d"
+        | "llvm.x86.avx2.psrlv.d.256"
+        | "llvm.x86.avx2.psrlv.q"
+        | "llvm.x86.avx2.psrlv.q.256"
+        | "llvm.x86.avx2.psrav.d"
+        | "llvm.x86.avx2.psrav.d.256" => {
+            intrinsic_args!(fx, args => (a, count); intrinsic);
+
+            let kind = ShiftKind::from_intrinsic(intrinsic);
+            simd_pair_for_each_lane(
+                fx,
+                a,
+                count,
+                ret,
+                &|fx, _lane_ty, _res_lane_ty, lane, count_lane| {
+                    let count_lane = clif_intcast(fx, count_lane, types::I64, false);
+                    x86_shift(fx, kind, lane, count_lane)
+                },
+            );
+        }
+
+        "llvm.x86.sse2.pmaxs.w"
+        | "llvm.x86.sse2.pmaxu.b"
+        | "llvm.x86.sse2.pmins.w"
+        | "llvm.x86.sse2.pminu.b"
+        | "llvm.x86.sse41.pmaxsb"
+        | "llvm.x86.sse41.pmaxuw"
+        | "llvm.x86.sse41.pmaxsd"
+        | "llvm.x86.sse41.pmaxud"
+        | "llvm.x86.sse41.pminsb"
+        | "llvm.x86.sse41.pminuw"
+        | "llvm.x86.sse41.pminsd"
+        | "llvm.x86.sse41.pminud"
+        | "llvm.x86.avx2.pmaxs.b"
+        | "llvm.x86.avx2.pmaxs.w"
+        | "llvm.x86.avx2.pmaxs.d"
+        | "llvm.x86.avx2.pmaxu.b"
+        | "llvm.x86.avx2.pmaxu.w"
+        | "llvm.x86.avx2.pmaxu.d"
+        | "llvm.x86.avx2.pmins.b"
+        | "llvm.x86.avx2.pmins.w"
+        | "llvm.x86.avx2.pmins.d"
+        | "llvm.x86.avx2.pminu.b"
+        | "llvm.x86.avx2.pminu.w"
+        | "llvm.x86.avx2.pminu.d" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            // The signedness is part of the lane type of the arguments.
+            let is_max = intrinsic.contains(".pmax");
+            simd_pair_for_each_lane(fx, a, b, ret, &|fx, lane_ty, _res_lane_ty, a_lane, b_lane| {
+                let cc = match (lane_ty.kind(), is_max) {
+                    (ty::Int(_), true) => IntCC::SignedGreaterThan,
+                    (ty::Int(_), false) => IntCC::SignedLessThan,
+                    (ty::Uint(_), true) => IntCC::UnsignedGreaterThan,
+                    (ty::Uint(_), false) => IntCC::UnsignedLessThan,
+                    _ => unreachable!("{:?}", lane_ty),
+                };
+                let cmp = fx.bcx.ins().icmp(cc, a_lane, b_lane);
+                fx.bcx.ins().select(cmp, a_lane, b_lane)
+            });
+        }
+        "llvm.x86.sse2.pavg.b"
+        | "llvm.x86.sse2.pavg.w"
+        | "llvm.x86.avx2.pavg.b"
+        | "llvm.x86.avx2.pavg.w" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            simd_pair_for_each_lane(fx, a, b, ret, &|fx, lane_ty, _res_lane_ty, a_lane, b_lane| {
+                let lane_ty = fx.clif_type(lane_ty).unwrap();
+                let a_lane = fx.bcx.ins().uextend(types::I32, a_lane);
+                let b_lane = fx.bcx.ins().uextend(types::I32, b_lane);
+                let sum = fx.bcx.ins().iadd(a_lane, b_lane);
+                let sum = fx.bcx.ins().iadd_imm(sum, 1);
+                let avg = fx.bcx.ins().ushr_imm(sum, 1);
+                fx.bcx.ins().ireduce(lane_ty, avg)
+            });
+        }
+        "llvm.x86.sse2.pmulh.w"
+        | "llvm.x86.sse2.pmulhu.w"
+        | "llvm.x86.avx2.pmulh.w"
+        | "llvm.x86.avx2.pmulhu.w" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            simd_pair_for_each_lane(fx, a, b, ret, &|fx, lane_ty, _res_lane_ty, a_lane, b_lane| {
+                let signed = matches!(lane_ty.kind(), ty::Int(_));
+                let a_lane = clif_intcast(fx, a_lane, types::I32, signed);
+                let b_lane = clif_intcast(fx, b_lane, types::I32, signed);
+                let product = fx.bcx.ins().imul(a_lane, b_lane);
+                let high = fx.bcx.ins().ushr_imm(product, 16);
+                fx.bcx.ins().ireduce(types::I16, high)
+            });
+        }
+        "llvm.x86.ssse3.pmul.hr.sw.128" | "llvm.x86.avx2.pmul.hr.sw" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            simd_pair_for_each_lane(
+                fx,
+                a,
+                b,
+                ret,
+                &|fx, _lane_ty, _res_lane_ty, a_lane, b_lane| {
+                    let a_lane = fx.bcx.ins().sextend(types::I32, a_lane);
+                    let b_lane = fx.bcx.ins().sextend(types::I32, b_lane);
+                    let product = fx.bcx.ins().imul(a_lane, b_lane);
+                    let product = fx.bcx.ins().sshr_imm(product, 14);
+                    let product = fx.bcx.ins().iadd_imm(product, 1);
+                    let product = fx.bcx.ins().sshr_imm(product, 1);
+                    fx.bcx.ins().ireduce(types::I16, product)
+                },
+            );
+        }
+        "llvm.x86.sse2.pmulu.dq"
+        | "llvm.x86.sse41.pmuldq"
+        | "llvm.x86.avx2.pmulu.dq"
+        | "llvm.x86.avx2.pmul.dq" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            // Multiplies the even 32-bit lanes into 64-bit lanes.
+            let (_, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let signed = matches!(lane_ty.kind(), ty::Int(_));
+            let (ret_lane_count, _) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            for lane in 0..ret_lane_count {
+                let a_lane = a.value_lane(fx, lane * 2).load_scalar(fx);
+                let b_lane = b.value_lane(fx, lane * 2).load_scalar(fx);
+                let a_lane = clif_intcast(fx, a_lane, types::I64, signed);
+                let b_lane = clif_intcast(fx, b_lane, types::I64, signed);
+                let res_lane = fx.bcx.ins().imul(a_lane, b_lane);
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.sse2.pmadd.wd"
+        | "llvm.x86.avx2.pmadd.wd"
+        | "llvm.x86.ssse3.pmadd.ub.sw.128"
+        | "llvm.x86.avx2.pmadd.ub.sw" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            // `pmadd.wd` multiplies signed words and adds the pairs with wrapping, while
+            // `pmadd.ub.sw` multiplies unsigned bytes of `a` with signed bytes of `b` and adds the
+            // pairs with signed saturation.
+            let bytes = intrinsic.contains(".ub.sw");
+            let (ret_lane_count, ret_lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            let ret_lane_ty = fx.clif_type(ret_lane_ty).unwrap();
+            for lane in 0..ret_lane_count {
+                let mut sum = fx.bcx.ins().iconst(types::I32, 0);
+                for i in lane * 2..lane * 2 + 2 {
+                    let a_lane = a.value_lane(fx, i).load_scalar(fx);
+                    let b_lane = b.value_lane(fx, i).load_scalar(fx);
+                    let a_lane = clif_intcast(fx, a_lane, types::I32, !bytes);
+                    let b_lane = clif_intcast(fx, b_lane, types::I32, true);
+                    let product = fx.bcx.ins().imul(a_lane, b_lane);
+                    sum = fx.bcx.ins().iadd(sum, product);
+                }
+                let res_lane = if bytes { x86_saturate(fx, sum, ret_lane_ty, true) } else { sum };
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.sse2.psad.bw" | "llvm.x86.avx2.psad.bw" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            let (ret_lane_count, _) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            for lane in 0..ret_lane_count {
+                let mut sum = fx.bcx.ins().iconst(types::I64, 0);
+                for i in lane * 8..lane * 8 + 8 {
+                    let a_lane = a.value_lane(fx, i).load_scalar(fx);
+                    let b_lane = b.value_lane(fx, i).load_scalar(fx);
+                    let diff = abs_diff_u8(fx, a_lane, b_lane, types::I64);
+                    sum = fx.bcx.ins().iadd(sum, diff);
+                }
+                write_lane(fx, ret, lane, sum);
+            }
+        }
+        "llvm.x86.sse41.mpsadbw" | "llvm.x86.avx2.mpsadbw" => {
+            let (a, b, imm8) = match args {
+                [a, b, imm8] => (a, b, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let imm8 = imm_arg(fx, intrinsic, imm8);
+
+            // Each 128-bit block computes eight sums of absolute differences between a four byte
+            // group of `b` and the eight four byte windows starting in a group of `a`. The AVX2
+            // variant takes the offsets of the second block from bits 3 to 5 of `imm8`.
+            let (ret_lane_count, _) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            for block in 0..ret_lane_count / 8 {
+                let block_imm = imm8 >> (3 * block);
+                let a_offset = block * 16 + ((block_imm >> 2) & 1) * 4;
+                let b_offset = block * 16 + (block_imm & 3) * 4;
+                for i in 0..8 {
+                    let mut sum = fx.bcx.ins().iconst(types::I16, 0);
+                    for j in 0..4 {
+                        let a_lane = a.value_lane(fx, a_offset + i + j).load_scalar(fx);
+                        let b_lane = b.value_lane(fx, b_offset + j).load_scalar(fx);
+                        let diff = abs_diff_u8(fx, a_lane, b_lane, types::I16);
+                        sum = fx.bcx.ins().iadd(sum, diff);
+                    }
+                    write_lane(fx, ret, block * 8 + i, sum);
+                }
+            }
+        }
+        "llvm.x86.sse41.phminposuw" => {
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let mut min = a.value_lane(fx, 0).load_scalar(fx);
+            let mut min_idx = fx.bcx.ins().iconst(types::I16, 0);
+            for lane in 1..8 {
+                let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+                let is_less = fx.bcx.ins().icmp(IntCC::UnsignedLessThan, a_lane, min);
+                min = fx.bcx.ins().select(is_less, a_lane, min);
+                let lane_idx = fx.bcx.ins().iconst(types::I16, lane as i64);
+                min_idx = fx.bcx.ins().select(is_less, lane_idx, min_idx);
+            }
+            write_lane(fx, ret, 0, min);
+            write_lane(fx, ret, 1, min_idx);
+            for lane in 2..8 {
+                let zero = fx.bcx.ins().iconst(types::I16, 0);
+                write_lane(fx, ret, lane, zero);
+            }
+        }
+        "llvm.x86.ssse3.pabs.b.128"
+        | "llvm.x86.ssse3.pabs.w.128"
+        | "llvm.x86.ssse3.pabs.d.128"
+        | "llvm.x86.avx2.pabs.b"
+        | "llvm.x86.avx2.pabs.w"
+        | "llvm.x86.avx2.pabs.d" => {
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| {
+                let is_negative = fx.bcx.ins().icmp_imm(IntCC::SignedLessThan, lane, 0);
+                let negated = fx.bcx.ins().ineg(lane);
+                fx.bcx.ins().select(is_negative, negated, lane)
+            });
+        }
+        "llvm.x86.ssse3.psign.b.128"
+        | "llvm.x86.ssse3.psign.w.128"
+        | "llvm.x86.ssse3.psign.d.128"
+        | "llvm.x86.avx2.psign.b"
+        | "llvm.x86.avx2.psign.w"
+        | "llvm.x86.avx2.psign.d" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            simd_pair_for_each_lane(fx, a, b, ret, &|fx, lane_ty, _res_lane_ty, a_lane, b_lane| {
+                let lane_ty = fx.clif_type(lane_ty).unwrap();
+                let is_negative = fx.bcx.ins().icmp_imm(IntCC::SignedLessThan, b_lane, 0);
+                let is_zero = fx.bcx.ins().icmp_imm(IntCC::Equal, b_lane, 0);
+                let negated = fx.bcx.ins().ineg(a_lane);
+                let zero = fx.bcx.ins().iconst(lane_ty, 0);
+                let res_lane = fx.bcx.ins().select(is_zero, zero, a_lane);
+                fx.bcx.ins().select(is_negative, negated, res_lane)
+            });
+        }
+        "llvm.x86.ssse3.phadd.w.128"
+        | "llvm.x86.ssse3.phadd.d.128"
+        | "llvm.x86.avx2.phadd.w"
+        | "llvm.x86.avx2.phadd.d" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            horizontal_op(fx, a, b, ret, &|fx, x, y| fx.bcx.ins().iadd(x, y));
+        }
+        "llvm.x86.ssse3.phsub.w.128"
+        | "llvm.x86.ssse3.phsub.d.128"
+        | "llvm.x86.avx2.phsub.w"
+        | "llvm.x86.avx2.phsub.d" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            horizontal_op(fx, a, b, ret, &|fx, x, y| fx.bcx.ins().isub(x, y));
+        }
+        "llvm.x86.ssse3.phadd.sw.128"
+        | "llvm.x86.avx2.phadd.sw"
+        | "llvm.x86.ssse3.phsub.sw.128"
+        | "llvm.x86.avx2.phsub.sw" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            let is_add = intrinsic.contains(".phadd");
+            horizontal_op(fx, a, b, ret, &|fx, x, y| {
+                let x = fx.bcx.ins().sextend(types::I32, x);
+                let y = fx.bcx.ins().sextend(types::I32, y);
+                let res = if is_add { fx.bcx.ins().iadd(x, y) } else { fx.bcx.ins().isub(x, y) };
+                x86_saturate(fx, res, types::I16, true)
+            });
+        }
+        "llvm.x86.sse2.packsswb.128"
+        | "llvm.x86.sse2.packssdw.128"
+        | "llvm.x86.sse2.packuswb.128"
+        | "llvm.x86.sse41.packusdw"
+        | "llvm.x86.avx2.packsswb"
+        | "llvm.x86.avx2.packssdw"
+        | "llvm.x86.avx2.packuswb"
+        | "llvm.x86.avx2.packusdw" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            // Each 128-bit block of the result contains the saturated lanes of the corresponding
+            // block of `a` followed by those of `b`.
+            let (lane_count, _) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let (_, ret_lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            let signed = matches!(ret_lane_ty.kind(), ty::Int(_));
+            let ret_lane_ty = fx.clif_type(ret_lane_ty).unwrap();
+            let lanes_per_block = 16 / u64::from(ret_lane_ty.bytes()) / 2;
+            for lane in 0..lane_count {
+                let block = lane / lanes_per_block;
+                let idx = lane % lanes_per_block;
+                for (i, src) in [a, b].into_iter().enumerate() {
+                    let src_lane = src.value_lane(fx, lane).load_scalar(fx);
+                    let res_lane = x86_saturate(fx, src_lane, ret_lane_ty, signed);
+                    let ret_idx = (block * 2 + i as u64) * lanes_per_block + idx;
+                    write_lane(fx, ret, ret_idx, res_lane);
+                }
+            }
+        }
+
+        "llvm.x86.sse41.pblendvb"
+        | "llvm.x86.sse41.blendvps"
+        | "llvm.x86.sse41.blendvpd"
+        | "llvm.x86.avx2.pblendvb"
+        | "llvm.x86.avx.blendv.ps.256"
+        | "llvm.x86.avx.blendv.pd.256" => {
+            intrinsic_args!(fx, args => (a, b, mask); intrinsic);
+
+            let (lane_count, _) = a.layout().ty.simd_size_and_type(fx.tcx);
+            for lane in 0..lane_count {
+                let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+                let b_lane = b.value_lane(fx, lane).load_scalar(fx);
+                let mask_lane = mask.value_lane(fx, lane).load_scalar(fx);
+                let use_b = sign_bit_set(fx, mask_lane);
+                let res_lane = fx.bcx.ins().select(use_b, b_lane, a_lane);
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.sse41.blendps" | "llvm.x86.sse41.blendpd" | "llvm.x86.sse41.pblendw" => {
+            let (a, b, imm8) = match args {
+                [a, b, imm8] => (a, b, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let imm8 = imm_arg(fx, intrinsic, imm8);
+
+            let (lane_count, _) = a.layout().ty.simd_size_and_type(fx.tcx);
+            for lane in 0..lane_count {
+                let src = if imm8 & (1 << (lane % 8)) != 0 { b } else { a };
+                let res_lane = src.value_lane(fx, lane).load_scalar(fx);
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.sse41.insertps" => {
+            let (a, b, imm8) = match args {
+                [a, b, imm8] => (a, b, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let imm8 = imm_arg(fx, intrinsic, imm8);
+
+            let src_lane = (imm8 >> 6) & 3;
+            let dest_lane = (imm8 >> 4) & 3;
+            for lane in 0..4 {
+                let res_lane = if imm8 & (1 << lane) != 0 {
+                    fx.bcx.ins().f32const(0.0)
+                } else if lane == dest_lane {
+                    b.value_lane(fx, src_lane).load_scalar(fx)
+                } else {
+                    a.value_lane(fx, lane).load_scalar(fx)
+                };
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.ssse3.pshuf.b.128" | "llvm.x86.avx2.pshuf.b" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            // Shuffles the bytes within each 128-bit block. A set high bit in the index zeroes the
+            // byte instead.
+            let (a_ptr, _) = a.force_stack(fx);
+            let (lane_count, _) = a.layout().ty.simd_size_and_type(fx.tcx);
+            for lane in 0..lane_count {
+                let idx = b.value_lane(fx, lane).load_scalar(fx);
+                let zero_lane = fx.bcx.ins().band_imm(idx, 0x80);
+                let idx = fx.bcx.ins().band_imm(idx, 0xf);
+                let idx = fx.bcx.ins().iadd_imm(idx, (lane / 16 * 16) as i64);
+                let byte = load_dynamic_lane(fx, a_ptr, types::I8, idx);
+                let zero = fx.bcx.ins().iconst(types::I8, 0);
+                let res_lane = fx.bcx.ins().select(zero_lane, zero, byte);
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.avx.vpermilvar.ps"
+        | "llvm.x86.avx.vpermilvar.ps.256"
+        | "llvm.x86.avx.vpermilvar.pd"
+        | "llvm.x86.avx.vpermilvar.pd.256"
+        | "llvm.x86.avx2.permd"
+        | "llvm.x86.avx2.permps" => {
+            intrinsic_args!(fx, args => (a, idx); intrinsic);
+
+            // `vpermilvar` permutes the lanes within each 128-bit block, using bit 1 of the index
+            // for `f64` lanes. `permd` and `permps` permute across the whole vector.
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_ty = fx.clif_type(lane_ty).unwrap();
+            let (block_lanes, idx_shift) = if intrinsic.contains(".vpermilvar.") {
+                (16 / u64::from(lane_ty.bytes()), if lane_ty == types::F64 { 1 } else { 0 })
+            } else {
+                (lane_count, 0)
+            };
+            let (a_ptr, _) = a.force_stack(fx);
+            for lane in 0..lane_count {
+                let idx_lane = idx.value_lane(fx, lane).load_scalar(fx);
+                let idx_lane = fx.bcx.ins().ushr_imm(idx_lane, idx_shift);
+                let idx_lane = fx.bcx.ins().band_imm(idx_lane, (block_lanes - 1) as i64);
+                let idx_lane =
+                    fx.bcx.ins().iadd_imm(idx_lane, (lane / block_lanes * block_lanes) as i64);
+                let res_lane = load_dynamic_lane(fx, a_ptr, lane_ty, idx_lane);
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.avx.vperm2f128.ps.256"
+        | "llvm.x86.avx.vperm2f128.pd.256"
+        | "llvm.x86.avx.vperm2f128.si.256"
+        | "llvm.x86.avx2.vperm2i128" => {
+            let (a, b, imm8) = match args {
+                [a, b, imm8] => (a, b, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let imm8 = imm_arg(fx, intrinsic, imm8);
+
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_ty = fx.clif_type(lane_ty).unwrap();
+            let half = lane_count / 2;
+            for dest_half in 0..2 {
+                let control = (imm8 >> (dest_half * 4)) & 0xf;
+                let src = if control & 2 != 0 { b } else { a };
+                let src_half = control & 1;
+                for i in 0..half {
+                    let res_lane = if control & 8 != 0 {
+                        zero_value(fx, lane_ty)
+                    } else {
+                        src.value_lane(fx, src_half * half + i).load_scalar(fx)
+                    };
+                    write_lane(fx, ret, dest_half * half + i, res_lane);
+                }
+            }
+        }
+
+        "llvm.x86.sse41.ptestz"
+        | "llvm.x86.sse41.ptestc"
+        | "llvm.x86.sse41.ptestnzc"
+        | "llvm.x86.avx.ptestz.256"
+        | "llvm.x86.avx.ptestc.256"
+        | "llvm.x86.avx.ptestnzc.256"
+        | "llvm.x86.avx.vtestz.ps"
+        | "llvm.x86.avx.vtestc.ps"
+        | "llvm.x86.avx.vtestnzc.ps"
+        | "llvm.x86.avx.vtestz.ps.256"
+        | "llvm.x86.avx.vtestc.ps.256"
+        | "llvm.x86.avx.vtestnzc.ps.256"
+        | "llvm.x86.avx.vtestz.pd"
+        | "llvm.x86.avx.vtestc.pd"
+        | "llvm.x86.avx.vtestnzc.pd"
+        | "llvm.x86.avx.vtestz.pd.256"
+        | "llvm.x86.avx.vtestc.pd.256"
+        | "llvm.x86.avx.vtestnzc.pd.256" => {
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            // ZF is set if `a & b` is zero and CF if `!a & b` is zero. `vtest` only looks at the
+            // sign bits of the lanes.
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let a_lane_ty = fx.clif_type(lane_ty).unwrap();
+            let int_ty = match a_lane_ty {
+                types::F32 => types::I32,
+                types::F64 => types::I64,
+                ty => ty,
+            };
+            let sign_bits_only = intrinsic.contains(".vtest");
+            let mut and = fx.bcx.ins().iconst(int_ty, 0);
+            let mut and_not = fx.bcx.ins().iconst(int_ty, 0);
+            for lane in 0..lane_count {
+                let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+                let b_lane = b.value_lane(fx, lane).load_scalar(fx);
+                let (a_lane, b_lane) = if a_lane_ty.is_float() {
+                    (fx.bcx.ins().bitcast(int_ty, a_lane), fx.bcx.ins().bitcast(int_ty, b_lane))
+                } else {
+                    (a_lane, b_lane)
+                };
+                let a_and_b = fx.bcx.ins().band(a_lane, b_lane);
+                let not_a_and_b = fx.bcx.ins().band_not(b_lane, a_lane);
+                and = fx.bcx.ins().bor(and, a_and_b);
+                and_not = fx.bcx.ins().bor(and_not, not_a_and_b);
+            }
+            if sign_bits_only {
+                let sign_bit = 1i64 << (int_ty.bits() - 1);
+                and = fx.bcx.ins().band_imm(and, sign_bit);
+                and_not = fx.bcx.ins().band_imm(and_not, sign_bit);
+            }
+            let zf = fx.bcx.ins().icmp_imm(IntCC::Equal, and, 0);
+            let zf = fx.bcx.ins().bint(types::I32, zf);
+            let cf = fx.bcx.ins().icmp_imm(IntCC::Equal, and_not, 0);
+            let cf = fx.bcx.ins().bint(types::I32, cf);
+            let res = if intrinsic.contains("testz") {
+                zf
+            } else if intrinsic.contains("testc") {
+                cf
+            } else {
+                let either = fx.bcx.ins().bor(zf, cf);
+                fx.bcx.ins().bxor_imm(either, 1)
+            };
+            ret.write_cvalue(fx, CValue::by_val(res, fx.layout_of(fx.tcx.types.i32)));
+        }
+
+        _ if intrinsic.starts_with("llvm.x86.sse42.pcmpistr")
+            || intrinsic.starts_with("llvm.x86.sse42.pcmpestr") =>
+        {
+            let (a, lengths, b, imm8) = match args {
+                [a, b, imm8] => (a, None, b, imm8),
+                [a, la, b, lb, imm8] => (a, Some((la, lb)), b, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let lengths = lengths.map(|(la, lb)| {
+                (codegen_operand(fx, la).load_scalar(fx), codegen_operand(fx, lb).load_scalar(fx))
+            });
+            let imm8 = imm_arg(fx, intrinsic, imm8);
+
+            let res = codegen_pcmpstr(fx, a, b, lengths, imm8);
+            let mask = bits_to_mask(fx, &res.int_res2);
+            let elements = res.int_res2.len() as i64;
+
+            let output = &intrinsic["llvm.x86.sse42.pcmpistr".len()..];
+            if output == "m128" {
+                codegen_pcmpstrm(fx, &res.int_res2, mask, imm8, ret);
+            } else {
+                let res = match output {
+                    "i128" => {
+                        let idx = if imm8 & 0x40 == 0 {
+                            fx.bcx.ins().ctz(mask)
+                        } else {
+                            let leading_zeros = fx.bcx.ins().clz(mask);
+                            let max_bit = fx.bcx.ins().iconst(types::I32, 31);
+                            fx.bcx.ins().isub(max_bit, leading_zeros)
+                        };
+                        let is_zero = fx.bcx.ins().icmp_imm(IntCC::Equal, mask, 0);
+                        let no_match = fx.bcx.ins().iconst(types::I32, elements);
+                        fx.bcx.ins().select(is_zero, no_match, idx)
+                    }
+                    // CF
+                    "ic128" => {
+                        let is_nonzero = fx.bcx.ins().icmp_imm(IntCC::NotEqual, mask, 0);
+                        fx.bcx.ins().bint(types::I32, is_nonzero)
+                    }
+                    // ZF
+                    "iz128" => fx.bcx.ins().bxor_imm(res.b_full, 1),
+                    // SF
+                    "is128" => fx.bcx.ins().bxor_imm(res.a_full, 1),
+                    // OF
+                    "io128" => res.int_res2[0],
+                    // CF == 0 && ZF == 0
+                    "ia128" => {
+                        let is_zero = fx.bcx.ins().icmp_imm(IntCC::Equal, mask, 0);
+                        let is_zero = fx.bcx.ins().bint(types::I32, is_zero);
+                        fx.bcx.ins().band(is_zero, res.b_full)
+                    }
+                    _ => unreachable!("{intrinsic}"),
+                };
+                ret.write_cvalue(fx, CValue::by_val(res, fx.layout_of(fx.tcx.types.i32)));
+            }
+        }
+        "llvm.x86.sse42.crc32.32.8"
+        | "llvm.x86.sse42.crc32.32.16"
+        | "llvm.x86.sse42.crc32.32.32"
+        | "llvm.x86.sse42.crc32.64.64" => {
+            intrinsic_args!(fx, args => (crc, v); intrinsic);
+            let crc = crc.load_scalar(fx);
+            let v = v.load_scalar(fx);
+
+            // Bitwise CRC-32C (Castagnoli) with the reflected polynomial.
+            let crc_ty = fx.bcx.func.dfg.value_type(crc);
+            let v_bits = fx.bcx.func.dfg.value_type(v).bits();
+            let mut res = clif_intcast(fx, crc, types::I32, false);
+            let v = clif_intcast(fx, v, types::I64, false);
+            for bit in 0..v_bits {
+                let v_bit = fx.bcx.ins().ushr_imm(v, i64::from(bit));
+                let v_bit = fx.bcx.ins().ireduce(types::I32, v_bit);
+                let lsb = fx.bcx.ins().bxor(res, v_bit);
+                let lsb = fx.bcx.ins().band_imm(lsb, 1);
+                let poly_mask = fx.bcx.ins().ineg(lsb);
+                let poly = fx.bcx.ins().band_imm(poly_mask, 0x82F6_3B78);
+                res = fx.bcx.ins().ushr_imm(res, 1);
+                res = fx.bcx.ins().bxor(res, poly);
+            }
+            let res = clif_intcast(fx, res, crc_ty, false);
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+        }
+
+        "llvm.x86.sse2.storeu.dq"
+        | "llvm.x86.sse2.storeu.pd"
+        | "llvm.x86.avx.storeu.dq.256"
+        | "llvm.x86.avx.storeu.ps.256"
+        | "llvm.x86.avx.storeu.pd.256" => {
+            intrinsic_args!(fx, args => (mem_addr, a); intrinsic);
+            let mem_addr = mem_addr.load_scalar(fx);
+
+            // FIXME correctly handle the unalignment
+            let dest = CPlace::for_ptr(Pointer::new(mem_addr), a.layout());
+            dest.write_cvalue(fx, a);
+        }
+        "llvm.x86.sse3.ldu.dq" | "llvm.x86.avx.ldu.dq.256" => {
+            intrinsic_args!(fx, args => (mem_addr); intrinsic);
+            let mem_addr = mem_addr.load_scalar(fx);
+
+            let (lane_count, lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_ty = fx.clif_type(lane_ty).unwrap();
+            for lane in 0..lane_count {
+                let offset = (lane * u64::from(lane_ty.bytes())) as i32;
+                let res_lane = fx.bcx.ins().load(lane_ty, MemFlags::new(), mem_addr, offset);
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.avx.vbroadcastf128.ps.256" | "llvm.x86.avx.vbroadcastf128.pd.256" => {
+            intrinsic_args!(fx, args => (mem_addr); intrinsic);
+            let mem_addr = mem_addr.load_scalar(fx);
+
+            let (lane_count, lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_ty = fx.clif_type(lane_ty).unwrap();
+            for lane in 0..lane_count {
+                let offset = (lane % (lane_count / 2) * u64::from(lane_ty.bytes())) as i32;
+                let res_lane = fx.bcx.ins().load(lane_ty, MemFlags::new(), mem_addr, offset);
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.avx.maskload.ps"
+        | "llvm.x86.avx.maskload.pd"
+        | "llvm.x86.avx.maskload.ps.256"
+        | "llvm.x86.avx.maskload.pd.256"
+        | "llvm.x86.avx2.maskload.d"
+        | "llvm.x86.avx2.maskload.q"
+        | "llvm.x86.avx2.maskload.d.256"
+        | "llvm.x86.avx2.maskload.q.256" => {
+            intrinsic_args!(fx, args => (mem_addr, mask); intrinsic);
+            let mem_addr = mem_addr.load_scalar(fx);
+
+            let (lane_count, lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_ty = fx.clif_type(lane_ty).unwrap();
+            for lane in 0..lane_count {
+                let mask_lane = mask.value_lane(fx, lane).load_scalar(fx);
+                let enabled = sign_bit_set(fx, mask_lane);
+                let addr =
+                    fx.bcx.ins().iadd_imm(mem_addr, (lane * u64::from(lane_ty.bytes())) as i64);
+                let zero = zero_value(fx, lane_ty);
+                let res_lane = conditional_load(fx, enabled, addr, lane_ty, zero);
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+        "llvm.x86.avx.maskstore.ps"
+        | "llvm.x86.avx.maskstore.pd"
+        | "llvm.x86.avx.maskstore.ps.256"
+        | "llvm.x86.avx.maskstore.pd.256"
+        | "llvm.x86.avx2.maskstore.d"
+        | "llvm.x86.avx2.maskstore.q"
+        | "llvm.x86.avx2.maskstore.d.256"
+        | "llvm.x86.avx2.maskstore.q.256" => {
+            intrinsic_args!(fx, args => (mem_addr, mask, a); intrinsic);
+            let mem_addr = mem_addr.load_scalar(fx);
+
+            masked_store(fx, mem_addr, mask, a);
+        }
+        "llvm.x86.sse2.maskmov.dqu" => {
+            intrinsic_args!(fx, args => (a, mask, mem_addr); intrinsic);
+            let mem_addr = mem_addr.load_scalar(fx);
+
+            masked_store(fx, mem_addr, mask, a);
+        }
+        _ if intrinsic.starts_with("llvm.x86.avx2.gather.") => {
+            let (src, slice, offsets, mask, scale) = match args {
+                [src, slice, offsets, mask, scale] => (src, slice, offsets, mask, scale),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let src = codegen_operand(fx, src);
+            let slice = codegen_operand(fx, slice).load_scalar(fx);
+            let offsets = codegen_operand(fx, offsets);
+            let mask = codegen_operand(fx, mask);
+            let scale = imm_arg(fx, intrinsic, scale);
+
+            // Only the lanes with the sign bit of the mask set are loaded, the others are taken
+            // from `src`. There may be more offsets than lanes in the result.
+            let (lane_count, lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_ty = fx.clif_type(lane_ty).unwrap();
+            for lane in 0..lane_count {
+                let src_lane = src.value_lane(fx, lane).load_scalar(fx);
+                let mask_lane = mask.value_lane(fx, lane).load_scalar(fx);
+                let offset = offsets.value_lane(fx, lane).load_scalar(fx);
+                let offset = clif_intcast(fx, offset, fx.pointer_type, true);
+                let offset = fx.bcx.ins().imul_imm(offset, scale as i64);
+                let addr = fx.bcx.ins().iadd(slice, offset);
+                let enabled = sign_bit_set(fx, mask_lane);
+                let res_lane = conditional_load(fx, enabled, addr, lane_ty, src_lane);
+                write_lane(fx, ret, lane, res_lane);
+            }
+        }
+
+        "llvm.x86.bmi.bextr.32" | "llvm.x86.bmi.bextr.64" => {
+            intrinsic_args!(fx, args => (a, control); intrinsic);
+            let a = a.load_scalar(fx);
+            let control = control.load_scalar(fx);
+
+            let ty = fx.bcx.func.dfg.value_type(a);
+            let start = fx.bcx.ins().band_imm(control, 0xff);
+            let len = fx.bcx.ins().ushr_imm(control, 8);
+            let len = fx.bcx.ins().band_imm(len, 0xff);
+            let zero = fx.bcx.ins().iconst(ty, 0);
+            let shifted = fx.bcx.ins().ushr(a, start);
+            let start_too_large = fx.bcx.ins().icmp_imm(
+                IntCC::UnsignedGreaterThanOrEqual,
+                start,
+                i64::from(ty.bits()),
+            );
+            let shifted = fx.bcx.ins().select(start_too_large, zero, shifted);
+            let res = zero_high_bits(fx, shifted, len);
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+        }
+        "llvm.x86.bmi.bzhi.32" | "llvm.x86.bmi.bzhi.64" => {
+            intrinsic_args!(fx, args => (a, index); intrinsic);
+            let a = a.load_scalar(fx);
+            let index = index.load_scalar(fx);
+
+            let index = fx.bcx.ins().band_imm(index, 0xff);
+            let res = zero_high_bits(fx, a, index);
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+        }
+        "llvm.x86.bmi.pdep.32" | "llvm.x86.bmi.pdep.64" => {
+            intrinsic_args!(fx, args => (a, mask); intrinsic);
+            let a = a.load_scalar(fx);
+            let mask = mask.load_scalar(fx);
+
+            // Deposit the low bits of `a` at the positions of the set bits of `mask`.
+            let ty = fx.bcx.func.dfg.value_type(a);
+            let mut src = a;
+            let mut res = fx.bcx.ins().iconst(ty, 0);
+            for bit in 0..ty.bits() {
+                let mask_bit = fx.bcx.ins().ushr_imm(mask, i64::from(bit));
+                let mask_bit = fx.bcx.ins().band_imm(mask_bit, 1);
+                let src_bit = fx.bcx.ins().band(src, mask_bit);
+                let src_bit = fx.bcx.ins().ishl_imm(src_bit, i64::from(bit));
+                res = fx.bcx.ins().bor(res, src_bit);
+                src = fx.bcx.ins().ushr(src, mask_bit);
+            }
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+        }
+        "llvm.x86.bmi.pext.32" | "llvm.x86.bmi.pext.64" => {
+            intrinsic_args!(fx, args => (a, mask); intrinsic);
+            let a = a.load_scalar(fx);
+            let mask = mask.load_scalar(fx);
+
+            // Extract the bits of `a` at the positions of the set bits of `mask` into the low bits.
+            let ty = fx.bcx.func.dfg.value_type(a);
+            let mut dest_bit = fx.bcx.ins().iconst(ty, 0);
+            let mut res = fx.bcx.ins().iconst(ty, 0);
+            for bit in 0..ty.bits() {
+                let mask_bit = fx.bcx.ins().ushr_imm(mask, i64::from(bit));
+                let mask_bit = fx.bcx.ins().band_imm(mask_bit, 1);
+                let src_bit = fx.bcx.ins().ushr_imm(a, i64::from(bit));
+                let src_bit = fx.bcx.ins().band(src_bit, mask_bit);
+                let src_bit = fx.bcx.ins().ishl(src_bit, dest_bit);
+                res = fx.bcx.ins().bor(res, src_bit);
+                dest_bit = fx.bcx.ins().iadd(dest_bit, mask_bit);
+            }
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+        }
+
+        "llvm.x86.addcarry.32" | "llvm.x86.addcarry.64" => {
+            intrinsic_args!(fx, args => (c_in, a, b); intrinsic);
+            let c_in = c_in.load_scalar(fx);
+
+            let (c_out, sum) = llvm_add_sub(fx, BinOp::Add, c_in, a, b);
+            let layout = fx.layout_of(fx.tcx.mk_tup([fx.tcx.types.u8, a.layout().ty].iter()));
+            ret.write_cvalue(fx, CValue::by_val_pair(c_out, sum, layout));
+        }
+        "llvm.x86.subborrow.32" | "llvm.x86.subborrow.64" => {
+            intrinsic_args!(fx, args => (b_in, a, b); intrinsic);
+            let b_in = b_in.load_scalar(fx);
+
+            let (b_out, diff) = llvm_add_sub(fx, BinOp::Sub, b_in, a, b);
+            let layout = fx.layout_of(fx.tcx.mk_tup([fx.tcx.types.u8, a.layout().ty].iter()));
+            ret.write_cvalue(fx, CValue::by_val_pair(b_out, diff, layout));
+        }
+        "llvm.x86.addcarryx.u32" | "llvm.x86.addcarryx.u64" => {
+            intrinsic_args!(fx, args => (c_in, a, b, out); intrinsic);
+            let c_in = c_in.load_scalar(fx);
+            let out = out.load_scalar(fx);
+
+            let (c_out, sum) = llvm_add_sub(fx, BinOp::Add, c_in, a, b);
+            fx.bcx.ins().store(MemFlags::trusted(), sum, out, 0);
+            ret.write_cvalue(fx, CValue::by_val(c_out, fx.layout_of(fx.tcx.types.u8)));
+        }
+
+        _ => {
+            fx.tcx.sess.warn(&format!(
+                "unsupported x86 llvm intrinsic {}; replacing with trap",
+                intrinsic
+            ));
+            crate::trap::trap_unimplemented(fx, intrinsic);
+            return;
+        }
+    }
+
+    let dest = target.expect("all llvm intrinsics used by stdlib should return");
+    let ret_block = fx.get_block(dest);
+    fx.bcx.ins().jump(ret_block, &[]);
+}
+
+/// Returns the value of an immediate argument. LLVM requires these to be constants.
+fn imm_arg<'tcx>(fx: &FunctionCx<'_, '_, 'tcx>, intrinsic: &str, arg: &mir::Operand<'tcx>) -> u64 {
+    let imm = crate::constant::mir_operand_get_const_val(fx, arg)
+        .unwrap_or_else(|| panic!("immediate argument of {} not const", intrinsic));
+    let size = fx.layout_of(fx.monomorphize(arg.ty(fx.mir, fx.tcx))).size;
+    let bits = imm.try_to_bits(size).unwrap_or_else(|| panic!("imm not scalar: {:?}", imm));
+    u64::try_from(bits).unwrap()
+}
+
+fn is_comi(intrinsic: &str) -> bool {
+    ["llvm.x86.sse.comi", "llvm.x86.sse.ucomi", "llvm.x86.sse2.comi", "llvm.x86.sse2.ucomi"]
+        .iter()
+        .any(|prefix| intrinsic.starts_with(prefix))
+}
+
+fn write_lane<'tcx>(fx: &mut FunctionCx<'_, '_, 'tcx>, ret: CPlace<'tcx>, lane: u64, val: Value) {
+    let ret_lane = ret.place_lane(fx, lane);
+    let layout = ret_lane.layout();
+    ret_lane.write_cvalue(fx, CValue::by_val(val, layout));
+}
+
+fn zero_value(fx: &mut FunctionCx<'_, '_, '_>, ty: Type) -> Value {
+    if ty.is_float() { float_const(fx, ty, 0.0) } else { fx.bcx.ins().iconst(ty, 0) }
+}
+
+fn float_const(fx: &mut FunctionCx<'_, '_, '_>, ty: Type, val: f64) -> Value {
+    match ty {
+        types::F32 => fx.bcx.ins().f32const(val as f32),
+        types::F64 => fx.bcx.ins().f64const(val),
+        _ => unreachable!("{:?}", ty),
+    }
+}
+
+/// Computes the lowest lane using `f` and copies the other lanes from `a`, like the scalar `ss`
+/// and `sd` instructions do.
+fn scalar_lane_op<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    a: CValue<'tcx>,
+    b: CValue<'tcx>,
+    ret: CPlace<'tcx>,
+    f: &dyn Fn(&mut FunctionCx<'_, '_, 'tcx>, Value, Value) -> Value,
+) {
+    let (lane_count, _) = a.layout().ty.simd_size_and_type(fx.tcx);
+
+    let a_lane = a.value_lane(fx, 0).load_scalar(fx);
+    let b_lane = b.value_lane(fx, 0).load_scalar(fx);
+    let res_lane = f(fx, a_lane, b_lane);
+    write_lane(fx, ret, 0, res_lane);
+
+    for lane in 1..lane_count {
+        let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+        write_lane(fx, ret, lane, a_lane);
+    }
+}
+
+/// Applies `f` to adjacent pairs of lanes, like the horizontal add and subtract instructions do.
+/// Each 128-bit block of the result contains the pairs of the corresponding block of `a` followed
+/// by those of `b`.
+fn horizontal_op<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    a: CValue<'tcx>,
+    b: CValue<'tcx>,
+    ret: CPlace<'tcx>,
+    f: &dyn Fn(&mut FunctionCx<'_, '_, 'tcx>, Value, Value) -> Value,
+) {
+    let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+    let lanes_per_block = 16 / fx.layout_of(lane_ty).size.bytes();
+    let pairs_per_block = lanes_per_block / 2;
+
+    for block in (0..lane_count).step_by(lanes_per_block as usize) {
+        for (i, src) in [a, b].into_iter().enumerate() {
+            for pair in 0..pairs_per_block {
+                let x = src.value_lane(fx, block + pair * 2).load_scalar(fx);
+                let y = src.value_lane(fx, block + pair * 2 + 1).load_scalar(fx);
+                let res_lane = f(fx, x, y);
+                write_lane(fx, ret, block + i as u64 * pairs_per_block + pair, res_lane);
+            }
+        }
+    }
+}
+
+/// Evaluates a comparison predicate of `cmpps`, `cmppd`, `cmpss` and `cmpsd`. The AVX versions
+/// take predicates from 0 to 31, where bit 4 only selects whether QNaNs raise an exception.
+fn x86_fcmp(fx: &mut FunctionCx<'_, '_, '_>, kind: u64, x: Value, y: Value) -> Value {
+    let flt_cc = match kind & 0xf {
+        0 => FloatCC::Equal,
+        1 => FloatCC::LessThan,
+        2 => FloatCC::LessThanOrEqual,
+        3 => FloatCC::Unordered,
+        4 => FloatCC::NotEqual,
+        5 => FloatCC::UnorderedOrGreaterThanOrEqual,
+        6 => FloatCC::UnorderedOrGreaterThan,
+        7 => FloatCC::Ordered,
+        8 => FloatCC::UnorderedOrEqual,
+        9 => FloatCC::UnorderedOrLessThan,
+        10 => FloatCC::UnorderedOrLessThanOrEqual,
+        11 => return fx.bcx.ins().bconst(types::B1, false),
+        12 => FloatCC::OrderedNotEqual,
+        13 => FloatCC::GreaterThanOrEqual,
+        14 => FloatCC::GreaterThan,
+        15 => return fx.bcx.ins().bconst(types::B1, true),
+        _ => unreachable!(),
+    };
+    fx.bcx.ins().fcmp(flt_cc, x, y)
+}
+
+/// `minps` and friends return the second operand if either operand is NaN or both are zero.
+fn x86_fmin(fx: &mut FunctionCx<'_, '_, '_>, a: Value, b: Value) -> Value {
+    let is_less = fx.bcx.ins().fcmp(FloatCC::LessThan, a, b);
+    fx.bcx.ins().select(is_less, a, b)
+}
+
+/// `maxps` and friends return the second operand if either operand is NaN or both are zero.
+fn x86_fmax(fx: &mut FunctionCx<'_, '_, '_>, a: Value, b: Value) -> Value {
+    let is_greater = fx.bcx.ins().fcmp(FloatCC::GreaterThan, a, b);
+    fx.bcx.ins().select(is_greater, a, b)
+}
+
+/// The reciprocal approximations are computed exactly, which is within the maximum relative
+/// error the hardware guarantees.
+fn x86_float_unop(fx: &mut FunctionCx<'_, '_, '_>, op: &str, a: Value) -> Value {
+    let ty = fx.bcx.func.dfg.value_type(a);
+    match op {
+        "sqrt" => fx.bcx.ins().sqrt(a),
+        "rcp" => {
+            let one = float_const(fx, ty, 1.0);
+            fx.bcx.ins().fdiv(one, a)
+        }
+        "rsqrt" => {
+            let one = float_const(fx, ty, 1.0);
+            let sqrt = fx.bcx.ins().sqrt(a);
+            fx.bcx.ins().fdiv(one, sqrt)
+        }
+        _ => unreachable!("{op}"),
+    }
+}
+
+fn x86_fma(fx: &mut FunctionCx<'_, '_, '_>, op: &str, a: Value, b: Value, c: Value) -> Value {
+    match op {
+        "vfmadd" => fx.bcx.ins().fma(a, b, c),
+        "vfmsub" => {
+            let c = fx.bcx.ins().fneg(c);
+            fx.bcx.ins().fma(a, b, c)
+        }
+        "vfnmadd" => {
+            let a = fx.bcx.ins().fneg(a);
+            fx.bcx.ins().fma(a, b, c)
+        }
+        "vfnmsub" => {
+            let a = fx.bcx.ins().fneg(a);
+            let c = fx.bcx.ins().fneg(c);
+            fx.bcx.ins().fma(a, b, c)
+        }
+        _ => unreachable!("{op}"),
+    }
+}
+
+/// Rounds according to the rounding control of `roundps` and friends. Bit 2 selects the rounding
+/// mode of MXCSR, which is assumed to be the default round to nearest even.
+fn x86_round(fx: &mut FunctionCx<'_, '_, '_>, rounding: u64, val: Value) -> Value {
+    if rounding & 0b100 != 0 {
+        return fx.bcx.ins().nearest(val);
+    }
+    match rounding & 0b11 {
+        0 => fx.bcx.ins().nearest(val),
+        1 => fx.bcx.ins().floor(val),
+        2 => fx.bcx.ins().ceil(val),
+        3 => fx.bcx.ins().trunc(val),
+        _ => unreachable!(),
+    }
+}
+
+/// Converts a float to a signed integer like the x86 conversion instructions do. NaN and out of
+/// range values result in the "integer indefinite" value, which is the minimum of `int_ty`.
+/// Without `truncate` the value is rounded to nearest even, which is the default rounding mode of
+/// MXCSR.
+fn x86_fcvt_to_sint(
+    fx: &mut FunctionCx<'_, '_, '_>,
+    val: Value,
+    int_ty: Type,
+    truncate: bool,
+) -> Value {
+    let float_ty = fx.bcx.func.dfg.value_type(val);
+    let val = if truncate { fx.bcx.ins().trunc(val) } else { fx.bcx.ins().nearest(val) };
+
+    let limit = 2f64.powi(int_ty.bits() as i32 - 1);
+    let min = float_const(fx, float_ty, -limit);
+    let max = float_const(fx, float_ty, limit);
+    let not_too_small = fx.bcx.ins().fcmp(FloatCC::GreaterThanOrEqual, val, min);
+    let not_too_large = fx.bcx.ins().fcmp(FloatCC::LessThan, val, max);
+
+    let res = fx.bcx.ins().fcvt_to_sint_sat(int_ty, val);
+    let indefinite = fx.bcx.ins().iconst(int_ty, -(1i64 << (int_ty.bits() - 1)));
+    let res = fx.bcx.ins().select(not_too_small, res, indefinite);
+    fx.bcx.ins().select(not_too_large, res, indefinite)
+}
+
+/// Clamps the signed integer `val` to the range of `ty` and truncates it to `ty`.
+fn x86_saturate(fx: &mut FunctionCx<'_, '_, '_>, val: Value, ty: Type, signed: bool) -> Value {
+    let (min, max) = if signed {
+        (-(1i64 << (ty.bits() - 1)), (1i64 << (ty.bits() - 1)) - 1)
+    } else {
+        (0, (1i64 << ty.bits()) - 1)
+    };
+    let val_ty = fx.bcx.func.dfg.value_type(val);
+    let min_val = fx.bcx.ins().iconst(val_ty, min);
+    let max_val = fx.bcx.ins().iconst(val_ty, max);
+    let too_small = fx.bcx.ins().icmp_imm(IntCC::SignedLessThan, val, min);
+    let val = fx.bcx.ins().select(too_small, min_val, val);
+    let too_large = fx.bcx.ins().icmp_imm(IntCC::SignedGreaterThan, val, max);
+    let val = fx.bcx.ins().select(too_large, max_val, val);
+    fx.bcx.ins().ireduce(ty, val)
+}
+
+/// Returns the absolute difference of the unsigned bytes `a` and `b` as `ty`.
+fn abs_diff_u8(fx: &mut FunctionCx<'_, '_, '_>, a: Value, b: Value, ty: Type) -> Value {
+    let a = fx.bcx.ins().uextend(ty, a);
+    let b = fx.bcx.ins().uextend(ty, b);
+    let a_minus_b = fx.bcx.ins().isub(a, b);
+    let b_minus_a = fx.bcx.ins().isub(b, a);
+    let is_less = fx.bcx.ins().icmp(IntCC::UnsignedLessThan, a, b);
+    fx.bcx.ins().select(is_less, b_minus_a, a_minus_b)
+}
+
+/// Returns whether the sign bit of a mask lane is set. The masked loads and stores, the variable
+/// blends and the gathers only look at the sign bit.
+fn sign_bit_set(fx: &mut FunctionCx<'_, '_, '_>, lane: Value) -> Value {
+    let lane = match fx.bcx.func.dfg.value_type(lane) {
+        types::F32 => fx.bcx.ins().bitcast(types::I32, lane),
+        types::F64 => fx.bcx.ins().bitcast(types::I64, lane),
+        _ => lane,
+    };
+    fx.bcx.ins().icmp_imm(IntCC::SignedLessThan, lane, 0)
+}
+
+/// Clears the bits of `val` from bit `index` upwards, keeping `val` as is if `index` is at least
+/// the width of `val`.
+fn zero_high_bits(fx: &mut FunctionCx<'_, '_, '_>, val: Value, index: Value) -> Value {
+    let ty = fx.bcx.func.dfg.value_type(val);
+    let one = fx.bcx.ins().iconst(ty, 1);
+    let mask = fx.bcx.ins().ishl(one, index);
+    let mask = fx.bcx.ins().isub(mask, one);
+    let masked = fx.bcx.ins().band(val, mask);
+    let index_too_large =
+        fx.bcx.ins().icmp_imm(IntCC::UnsignedGreaterThanOrEqual, index, i64::from(ty.bits()));
+    fx.bcx.ins().select(index_too_large, val, masked)
+}
+
+/// Loads the lane `idx` of the vector at `ptr`, where `idx` isn't known at compile time.
+fn load_dynamic_lane(
+    fx: &mut FunctionCx<'_, '_, '_>,
+    ptr: Pointer,
+    lane_ty: Type,
+    idx: Value,
+) -> Value {
+    let idx = clif_intcast(fx, idx, fx.pointer_type, false);
+    let offset = fx.bcx.ins().imul_imm(idx, i64::from(lane_ty.bytes()));
+    ptr.offset_value(fx, offset).load(fx, lane_ty, MemFlags::trusted())
+}
+
+/// Loads a `ty` from `addr` if `enabled` is true and returns `fallback` otherwise. The load is
+/// skipped for disabled lanes, so masked out lanes can't fault.
+fn conditional_load(
+    fx: &mut FunctionCx<'_, '_, '_>,
+    enabled: Value,
+    addr: Value,
+    ty: Type,
+    fallback: Value,
+) -> Value {
+    let load_block = fx.bcx.create_block();
+    let next_block = fx.bcx.create_block();
+    let res = fx.bcx.append_block_param(next_block, ty);
+
+    fx.bcx.ins().brnz(enabled, load_block, &[]);
+    fx.bcx.ins().jump(next_block, &[fallback]);
+
+    fx.bcx.switch_to_block(load_block);
+    let loaded = fx.bcx.ins().load(ty, MemFlags::new(), addr, 0);
+    fx.bcx.ins().jump(next_block, &[loaded]);
+
+    fx.bcx.switch_to_block(next_block);
+    res
+}
+
+/// Stores the lanes of `a` whose mask lane has the sign bit set to `mem_addr`.
+fn masked_store<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    mem_addr: Value,
+    mask: CValue<'tcx>,
+    a: CValue<'tcx>,
+) {
+    let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+    let lane_size = fx.layout_of(lane_ty).size.bytes();
+    for lane in 0..lane_count {
+        let a_lane = a.value_lane(fx, lane).load_scalar(fx);
+        let mask_lane = mask.value_lane(fx, lane).load_scalar(fx);
+        let enabled = sign_bit_set(fx, mask_lane);
+
+        let store_block = fx.bcx.create_block();
+        let next_block = fx.bcx.create_block();
+        fx.bcx.ins().brnz(enabled, store_block, &[]);
+        fx.bcx.ins().jump(next_block, &[]);
+
+        fx.bcx.switch_to_block(store_block);
+        let offset = i32::try_from(lane * lane_size).unwrap();
+        fx.bcx.ins().store(MemFlags::new(), a_lane, mem_addr, offset);
+        fx.bcx.ins().jump(next_block, &[]);
+
+        fx.bcx.switch_to_block(next_block);
+    }
+}
+
+#[derive(Copy, Clone)]
+enum ShiftKind {
+    Left,
+    LogicalRight,
+    ArithmeticRight,
+}
+
+impl ShiftKind {
+    fn from_intrinsic(intrinsic: &str) -> Self {
+        if intrinsic.contains(".psll") {
+            ShiftKind::Left
+        } else if intrinsic.contains(".psrl") {
+            ShiftKind::LogicalRight
+        } else if intrinsic.contains(".psra") {
+            ShiftKind::ArithmeticRight
+        } else {
+            unreachable!("{intrinsic}")
+        }
+    }
+}
+
+/// Shifts `lane` by the 64-bit `count`. Unlike the Cranelift shifts, counts larger than the lane
+/// width don't wrap around but result in zero, or in copies of the sign bit for arithmetic right
+/// shifts.
+fn x86_shift(fx: &mut FunctionCx<'_, '_, '_>, kind: ShiftKind, lane: Value, count: Value) -> Value {
+    let lane_ty = fx.bcx.func.dfg.value_type(lane);
+    let lane_bits = i64::from(lane_ty.bits());
+    let too_large = fx.bcx.ins().icmp_imm(IntCC::UnsignedGreaterThanOrEqual, count, lane_bits);
+    match kind {
+        ShiftKind::Left | ShiftKind::LogicalRight => {
+            let shifted = match kind {
+                ShiftKind::Left => fx.bcx.ins().ishl(lane, count),
+                _ => fx.bcx.ins().ushr(lane, count),
+            };
+            let zero = fx.bcx.ins().iconst(lane_ty, 0);
+            fx.bcx.ins().select(too_large, zero, shifted)
+        }
+        ShiftKind::ArithmeticRight => {
+            let max_count = fx.bcx.ins().iconst(types::I64, lane_bits - 1);
+            let count = fx.bcx.ins().select(too_large, max_count, count);
+            fx.bcx.ins().sshr(lane, count)
+        }
+    }
+}
+
+/// The result of the SSE4.2 string comparison instructions before it is turned into an index, a
+/// mask or a flag.
+struct PcmpstrResult {
+    /// `IntRes2` of the Intel manual, holding 1 or 0 for each element.
+    int_res2: Vec<Value>,
+    /// Whether all elements of `a` and `b` respectively are valid, which determines the SF and ZF
+    /// flags.
+    a_full: Value,
+    b_full: Value,
+}
+
+/// Emulates the comparison of `pcmpistr*` and `pcmpestr*`. `lengths` contains the explicit
+/// lengths of `a` and `b` for `pcmpestr*`. Otherwise the strings end at the first zero element.
+fn codegen_pcmpstr<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    a: CValue<'tcx>,
+    b: CValue<'tcx>,
+    lengths: Option<(Value, Value)>,
+    imm8: u64,
+) -> PcmpstrResult {
+    let words = imm8 & 1 != 0;
+    let signed = imm8 & 2 != 0;
+
+    let a_elements = pcmpstr_elements(fx, a, words, signed);
+    let b_elements = pcmpstr_elements(fx, b, words, signed);
+    let a_valid = pcmpstr_validity(fx, &a_elements, lengths.map(|(la, _)| la));
+    let b_valid = pcmpstr_validity(fx, &b_elements, lengths.map(|(_, lb)| lb));
+    let n = a_elements.len();
+
+    let compare = |fx: &mut FunctionCx<'_, '_, 'tcx>, cc, x, y| {
+        let res = fx.bcx.ins().icmp(cc, x, y);
+        fx.bcx.ins().bint(types::I32, res)
+    };
+
+    let mut int_res1 = vec![];
+    match (imm8 >> 2) & 3 {
+        // Equal any
+        0 => {
+            for j in 0..n {
+                let mut res = fx.bcx.ins().iconst(types::I32, 0);
+                for i in 0..n {
+                    let eq = compare(fx, IntCC::Equal, a_elements[i], b_elements[j]);
+                    let eq = fx.bcx.ins().band(eq, a_valid[i]);
+                    res = fx.bcx.ins().bor(res, eq);
+                }
+                int_res1.push(fx.bcx.ins().band(res, b_valid[j]));
+            }
+        }
+        // Ranges
+        1 => {
+            for j in 0..n {
+                let mut res = fx.bcx.ins().iconst(types::I32, 0);
+                for i in (0..n).step_by(2) {
+                    let ge_lower =
+                        compare(fx, IntCC::SignedLessThanOrEqual, a_elements[i], b_elements[j]);
+                    let le_upper = compare(
+                        fx,
+                        IntCC::SignedGreaterThanOrEqual,
+                        a_elements[i + 1],
+                        b_elements[j],
+                    );
+                    let in_range = fx.bcx.ins().band(ge_lower, le_upper);
+                    let in_range = fx.bcx.ins().band(in_range, a_valid[i]);
+                    let in_range = fx.bcx.ins().band(in_range, a_valid[i + 1]);
+                    res = fx.bcx.ins().bor(res, in_range);
+                }
+                int_res1.push(fx.bcx.ins().band(res, b_valid[j]));
+            }
+        }
+        // Equal each
+        2 => {
+            for i in 0..n {
+                let eq = compare(fx, IntCC::Equal, a_elements[i], b_elements[i]);
+                let both_valid = fx.bcx.ins().band(a_valid[i], b_valid[i]);
+                let eq = fx.bcx.ins().band(eq, both_valid);
+                let either_valid = fx.bcx.ins().bor(a_valid[i], b_valid[i]);
+                let both_invalid = fx.bcx.ins().bxor_imm(either_valid, 1);
+                int_res1.push(fx.bcx.ins().bor(eq, both_invalid));
+            }
+        }
+        // Equal ordered
+        3 => {
+            for j in 0..n {
+                let mut res = fx.bcx.ins().iconst(types::I32, 1);
+                for k in 0..n - j {
+                    let eq = compare(fx, IntCC::Equal, a_elements[k], b_elements[j + k]);
+                    let eq = fx.bcx.ins().band(eq, b_valid[j + k]);
+                    // The needle `a` matches once it has ended.
+                    let a_ended = fx.bcx.ins().bxor_imm(a_valid[k], 1);
+                    let matches = fx.bcx.ins().bor(eq, a_ended);
+                    res = fx.bcx.ins().band(res, matches);
+                }
+                int_res1.push(res);
+            }
+        }
+        _ => unreachable!(),
+    }
+
+    let int_res2 = match (imm8 >> 4) & 3 {
+        0 | 2 => int_res1,
+        1 => int_res1.into_iter().map(|res| fx.bcx.ins().bxor_imm(res, 1)).collect(),
+        3 => int_res1
+            .into_iter()
+            .zip(&b_valid)
+            .map(|(res, &valid)| fx.bcx.ins().bxor(res, valid))
+            .collect(),
+        _ => unreachable!(),
+    };
+
+    PcmpstrResult { int_res2, a_full: a_valid[n - 1], b_full: b_valid[n - 1] }
+}
+
+/// Splits a `pcmpistr*` or `pcmpestr*` operand into its byte or word elements, extended to `i32`.
+fn pcmpstr_elements<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    val: CValue<'tcx>,
+    words: bool,
+    signed: bool,
+) -> Vec<Value> {
+    let mut elements = vec![];
+    if words {
+        for i in 0..8 {
+            let low = val.value_lane(fx, i * 2).load_scalar(fx);
+            let high = val.value_lane(fx, i * 2 + 1).load_scalar(fx);
+            let low = clif_intcast(fx, low, types::I32, false);
+            let high = clif_intcast(fx, high, types::I32, signed);
+            let high = fx.bcx.ins().ishl_imm(high, 8);
+            elements.push(fx.bcx.ins().bor(low, high));
+        }
+    } else {
+        for i in 0..16 {
+            let byte = val.value_lane(fx, i).load_scalar(fx);
+            elements.push(clif_intcast(fx, byte, types::I32, signed));
+        }
+    }
+    elements
+}
+
+/// Returns 1 for every element that is part of the string and 0 for the others. Explicit lengths
+/// are made absolute and saturated to the element count like the hardware does.
+fn pcmpstr_validity(
+    fx: &mut FunctionCx<'_, '_, '_>,
+    elements: &[Value],
+    len: Option<Value>,
+) -> Vec<Value> {
+    let n = elements.len() as i64;
+    match len {
+        Some(len) => {
+            let negated = fx.bcx.ins().ineg(len);
+            let is_negative = fx.bcx.ins().icmp_imm(IntCC::SignedLessThan, len, 0);
+            let len = fx.bcx.ins().select(is_negative, negated, len);
+            // `i32::MIN` stays negative, but is larger than `n` as an unsigned value.
+            let max_len = fx.bcx.ins().iconst(types::I32, n);
+            let too_long = fx.bcx.ins().icmp_imm(IntCC::UnsignedGreaterThan, len, n);
+            let len = fx.bcx.ins().select(too_long, max_len, len);
+            (0..n)
+                .map(|i| {
+                    let valid = fx.bcx.ins().icmp_imm(IntCC::UnsignedGreaterThan, len, i);
+                    fx.bcx.ins().bint(types::I32, valid)
+                })
+                .collect()
+        }
+        None => {
+            let mut valid = fx.bcx.ins().iconst(types::I32, 1);
+            elements
+                .iter()
+                .map(|&element| {
+                    let is_nonzero = fx.bcx.ins().icmp_imm(IntCC::NotEqual, element, 0);
+                    let is_nonzero = fx.bcx.ins().bint(types::I32, is_nonzero);
+                    valid = fx.bcx.ins().band(valid, is_nonzero);
+                    valid
+                })
+                .collect()
+        }
+    }
+}
+
+fn bits_to_mask(fx: &mut FunctionCx<'_, '_, '_>, bits: &[Value]) -> Value {
+    let mut mask = fx.bcx.ins().iconst(types::I32, 0);
+    for (i, &bit) in bits.iter().enumerate() {
+        let bit = fx.bcx.ins().ishl_imm(bit, i as i64);
+        mask = fx.bcx.ins().bor(mask, bit);
+    }
+    mask
+}
+
+/// Writes the mask result of `pcmpistrm` and `pcmpestrm`. Bit 6 of `imm8` selects between a bit
+/// mask in the lowest bits and a mask with all bits of the matching elements set.
+fn codegen_pcmpstrm<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    int_res2: &[Value],
+    mask: Value,
+    imm8: u64,
+    ret: CPlace<'tcx>,
+) {
+    let bytes_per_element = 16 / int_res2.len() as u64;
+    for lane in 0..16 {
+        let res_lane = if imm8 & 0x40 == 0 {
+            if lane < 2 {
+                let mask = fx.bcx.ins().ushr_imm(mask, (lane * 8) as i64);
+                fx.bcx.ins().ireduce(types::I8, mask)
+            } else {
+                fx.bcx.ins().iconst(types::I8, 0)
+            }
+        } else {
+            let bit = int_res2[(lane / bytes_per_element) as usize];
+            let bit = fx.bcx.ins().ireduce(types::I8, bit);
+            fx.bcx.ins().ineg(bit)
+        };
+        write_lane(fx, ret, lane, res_lane);
+    }
+}
+
+/// Adds or subtracts `a` and `b` with an incoming carry or borrow and returns the outgoing carry
+/// or borrow and the result.
+fn llvm_add_sub<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    bin_op: BinOp,
+    cb_in: Value,
+    a: CValue<'tcx>,
+    b: CValue<'tcx>,
+) -> (Value, Value) {
+    assert_eq!(a.layout().ty, b.layout().ty, "{:?} operands must have the same type", bin_op);
+    assert!(
+        a.layout().ty == fx.tcx.types.u32 || a.layout().ty == fx.tcx.types.u64,
+        "{:?} operands must be u32 or u64",
+        bin_op
+    );
+    let int_ty = fx.clif_type(a.layout().ty).unwrap();
+
+    // c + carry -> c + first intermediate carry or borrow respectively
+    let int0 = crate::num::codegen_checked_int_binop(fx, bin_op, a, b);
+    let c = int0.value_field(fx, mir::Field::new(0));
+    let cb0 = int0.value_field(fx, mir::Field::new(1)).load_scalar(fx);
+
+    // c + carry -> c + second intermediate carry or borrow respectively
+    let cb_in_as_int = fx.bcx.ins().uextend(int_ty, cb_in);
+    let cb_in_as_int = CValue::by_val(cb_in_as_int, a.layout());
+    let int1 = crate::num::codegen_checked_int_binop(fx, bin_op, c, cb_in_as_int);
+    let (c, cb1) = int1.load_scalar_pair(fx);
+
+    // carry0 | carry1 -> carry or borrow respectively
+    let cb_out = fx.bcx.ins().bor(cb0, cb1);
+
+    (cb_out, c)
+}
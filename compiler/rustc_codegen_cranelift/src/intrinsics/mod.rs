@@ -14,6 +14,7 @@ macro_rules! intrinsic_args {
 
 mod cpuid;
 mod llvm;
+mod llvm_x86;
 mod simd;
 
 pub(crate) use cpuid::codegen_cpuid_call;
@@ -201,7 +202,12 @@ pub(crate) fn codegen_intrinsic_call<'tcx>(
                 fx.bcx.ins().trap(TrapCode::User(0));
             }
             sym::transmute => {
-                crate::base::codegen_panic(fx, "Transmuting to uninhabited type.", source_info);
+                crate::base::codegen_panic(
+                    fx,
+                    "Transmuting to uninhabited type.",
+                    source_info,
+                    None,
+                );
             }
             _ => unimplemented!("unsupported instrinsic {}", intrinsic),
         }
@@ -725,6 +731,7 @@ fn swap(bcx: &mut FunctionBuilder<'_>, v: Value) -> Value {
                         fx,
                         &format!("attempted to instantiate uninhabited type `{}`", layout.ty),
                         source_info,
+                        None,
                     )
                 });
                 return;
@@ -739,6 +746,7 @@ fn swap(bcx: &mut FunctionBuilder<'_>, v: Value) -> Value {
                             layout.ty
                         ),
                         source_info,
+                        None,
                     );
                 });
                 return;
@@ -753,6 +761,7 @@ fn swap(bcx: &mut FunctionBuilder<'_>, v: Value) -> Value {
                             layout.ty
                         ),
                         source_info,
+                        None,
                     )
                 });
                 return;
@@ -1204,20 +1213,9 @@ fn swap(bcx: &mut FunctionBuilder<'_>, v: Value) -> Value {
             intrinsic_args!(fx, args => (f, data, catch_fn); intrinsic);
             let f = f.load_scalar(fx);
             let data = data.load_scalar(fx);
-            let _catch_fn = catch_fn.load_scalar(fx);
+            let catch_fn = catch_fn.load_scalar(fx);
 
-            // FIXME once unwinding is supported, change this to actually catch panics
-            let f_sig = fx.bcx.func.import_signature(Signature {
-                call_conv: fx.target_config.default_call_conv,
-                params: vec![AbiParam::new(fx.bcx.func.dfg.value_type(data))],
-                returns: vec![],
-            });
-
-            fx.bcx.ins().call_indirect(f_sig, f, &[data]);
-
-            let layout = ret.layout();
-            let ret_val = CValue::const_val(fx, layout, ty::ScalarInt::null(layout.size));
-            ret.write_cvalue(fx, ret_val);
+            crate::landing_pads::codegen_try(fx, f, data, catch_fn, ret);
         }
 
         sym::fadd_fast | sym::fsub_fast | sym::fmul_fast | sym::fdiv_fast | sym::frem_fast => {
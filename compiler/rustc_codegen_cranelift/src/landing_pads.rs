@@ -0,0 +1,340 @@
+//! Unwinding into cleanup blocks and catching panics.
+//!
+//! Cranelift doesn't support exception edges, so a Cranelift function can't contain landing pads.
+//! Instead a call that unwinds to a cleanup block calls a small trampoline written in assembly,
+//! which is assembled by the external assembler together with the rest of the `global_asm!` of the
+//! codegen unit. The trampoline calls `f(data)` at a call site that is covered by an LSDA with a
+//! catch-all handler. It returns the exception object when `f` unwinds and null otherwise. For
+//! calls `f` is a thunk generated for the signature of the callee, which loads the callee and its
+//! arguments from `data`, calls it and stores the return values back into `data`.
+//!
+//! When the trampoline returns an exception, it is stored in a variable and control continues at
+//! the cleanup block. The `Resume` terminator at the end of the cleanup blocks then passes the
+//! exception to `_Unwind_Resume` to continue unwinding. The `try` intrinsic calls the trampoline
+//! directly with the closure it got and passes the exception to `catch_fn`.
+//!
+//! Because the trampoline catches everything, the search phase of the unwinder already stops at
+//! the innermost frame that has cleanup blocks rather than at the `catch_unwind` that will handle
+//! the panic. Every frame with cleanup blocks resumes unwinding after running them, so the end
+//! result is the same. Panics raised by the checks of intrinsics like `assert_inhabited` don't
+//! run the cleanup blocks of the frame they are raised in.
+
+use std::fmt::Write;
+
+use rustc_target::spec::PanicStrategy;
+use smallvec::SmallVec;
+
+use crate::prelude::*;
+
+/// The size of each slot of the block passed to the invoke thunks, big enough for every value
+/// type that can be passed to a function.
+const SLOT_SIZE: u32 = 16;
+
+/// The invoke thunks of a codegen unit by the signature of the callee they call.
+pub(crate) type InvokeThunks = FxHashMap<Signature, FuncId>;
+
+pub(crate) fn landing_pads_supported(
+    tcx: TyCtxt<'_>,
+    backend_config: &crate::BackendConfig,
+) -> bool {
+    let target = &tcx.sess.target;
+    cfg!(feature = "inline_asm")
+        && matches!(backend_config.codegen_mode, crate::config::CodegenMode::Aot)
+        && tcx.sess.panic_strategy() == PanicStrategy::Unwind
+        && target.arch == "x86_64"
+        && !target.is_like_windows
+        && !target.is_like_osx
+}
+
+/// Calls `callee` with `args` and returns its return values. If the callee unwinds, control
+/// continues at `cleanup` instead.
+pub(crate) fn codegen_invoke(
+    fx: &mut FunctionCx<'_, '_, '_>,
+    sig: Signature,
+    callee: Value,
+    args: &[Value],
+    cleanup: BasicBlock,
+) -> SmallVec<[Value; 2]> {
+    assert!(fx.cx.landing_pads_supported);
+
+    let returns = sig.returns.iter().map(|ret| ret.value_type).collect::<Vec<_>>();
+    let thunk = get_or_define_invoke_thunk(fx, sig);
+    let thunk = fx.module.declare_func_in_func(thunk, &mut fx.bcx.func);
+    let thunk = fx.bcx.ins().func_addr(fx.pointer_type, thunk);
+
+    // The first slot holds the callee, the following ones the arguments and after the call the
+    // return values.
+    let slot_count = 1 + std::cmp::max(args.len(), returns.len());
+    let data = fx.bcx.create_sized_stack_slot(StackSlotData {
+        kind: StackSlotKind::ExplicitSlot,
+        size: u32::try_from(slot_count).unwrap() * SLOT_SIZE,
+    });
+    let data = Pointer::stack_slot(data);
+    data.store(fx, callee, MemFlags::trusted());
+    for (i, &arg) in args.iter().enumerate() {
+        data.offset_i64(fx, slot_offset(i)).store(fx, arg, MemFlags::trusted());
+    }
+
+    let data_addr = data.get_addr(fx);
+    let exception = call_trampoline(fx, thunk, data_addr);
+
+    let landing_pad = fx.bcx.create_block();
+    let call_returned = fx.bcx.create_block();
+    fx.bcx.set_cold_block(landing_pad);
+    fx.bcx.ins().brnz(exception, landing_pad, &[]);
+    fx.bcx.ins().jump(call_returned, &[]);
+
+    fx.bcx.switch_to_block(landing_pad);
+    let exception_var = exception_var(fx);
+    fx.bcx.def_var(exception_var, exception);
+    let cleanup_block = fx.get_block(cleanup);
+    fx.bcx.ins().jump(cleanup_block, &[]);
+
+    fx.bcx.switch_to_block(call_returned);
+    returns
+        .into_iter()
+        .enumerate()
+        .map(|(i, ty)| data.offset_i64(fx, slot_offset(i)).load(fx, ty, MemFlags::trusted()))
+        .collect()
+}
+
+/// Continues unwinding with the exception that was caught by the landing pad that led to the
+/// current cleanup block.
+pub(crate) fn codegen_resume(fx: &mut FunctionCx<'_, '_, '_>) {
+    let exception_var = exception_var(fx);
+    let exception = fx.bcx.use_var(exception_var);
+    fx.lib_call("_Unwind_Resume", vec![AbiParam::new(fx.pointer_type)], vec![], &[exception]);
+    fx.bcx.ins().trap(TrapCode::UnreachableCodeReached);
+}
+
+pub(crate) fn codegen_try<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    f: Value,
+    data: Value,
+    catch_fn: Value,
+    ret: CPlace<'tcx>,
+) {
+    if !fx.cx.landing_pads_supported {
+        // Panics can't be caught, so they either abort or unwind past the caller of `try`.
+        let f_sig = fx.bcx.func.import_signature(Signature {
+            call_conv: fx.target_config.default_call_conv,
+            params: vec![AbiParam::new(fx.bcx.func.dfg.value_type(data))],
+            returns: vec![],
+        });
+
+        fx.bcx.ins().call_indirect(f_sig, f, &[data]);
+
+        let layout = ret.layout();
+        let ret_val = CValue::const_val(fx, layout, ty::ScalarInt::null(layout.size));
+        ret.write_cvalue(fx, ret_val);
+        return;
+    }
+
+    let exception = call_trampoline(fx, f, data);
+
+    let catch_block = fx.bcx.create_block();
+    let done_block = fx.bcx.create_block();
+    fx.bcx.set_cold_block(catch_block);
+    fx.bcx.ins().brnz(exception, catch_block, &[]);
+    fx.bcx.ins().jump(done_block, &[]);
+
+    fx.bcx.switch_to_block(catch_block);
+    let catch_sig = fx.bcx.func.import_signature(Signature {
+        call_conv: fx.target_config.default_call_conv,
+        params: vec![AbiParam::new(fx.pointer_type), AbiParam::new(fx.pointer_type)],
+        returns: vec![],
+    });
+    fx.bcx.ins().call_indirect(catch_sig, catch_fn, &[data, exception]);
+    fx.bcx.ins().jump(done_block, &[]);
+
+    fx.bcx.switch_to_block(done_block);
+    let caught = fx.bcx.ins().icmp_imm(IntCC::NotEqual, exception, 0);
+    let caught = fx.bcx.ins().bint(types::I32, caught);
+    ret.write_cvalue(fx, CValue::by_val(caught, ret.layout()));
+}
+
+fn slot_offset(index: usize) -> i64 {
+    i64::from(SLOT_SIZE) * (1 + i64::try_from(index).unwrap())
+}
+
+fn exception_var(fx: &mut FunctionCx<'_, '_, '_>) -> Variable {
+    if let Some(var) = fx.exception_var {
+        return var;
+    }
+
+    let var = Variable::with_u32(fx.next_ssa_var);
+    fx.next_ssa_var += 1;
+    fx.bcx.declare_var(var, fx.pointer_type);
+    fx.exception_var = Some(var);
+    var
+}
+
+/// Calls `f(data)` through the trampoline and returns the exception if `f` unwinds or null.
+fn call_trampoline(fx: &mut FunctionCx<'_, '_, '_>, f: Value, data: Value) -> Value {
+    let trampoline_name = format!(
+        "__cg_clif_invoke_{}",
+        fx.cx.cgu_name.as_str().replace('.', "__").replace('-', "_")
+    );
+    if !fx.cx.invoke_trampoline_emitted {
+        fx.cx.invoke_trampoline_emitted = true;
+        fx.cx.global_asm.push_str(&generate_invoke_trampoline(&trampoline_name));
+    }
+
+    let trampoline = fx
+        .module
+        .declare_function(
+            &trampoline_name,
+            Linkage::Import,
+            &Signature {
+                call_conv: CallConv::SystemV,
+                params: vec![AbiParam::new(fx.pointer_type); 2],
+                returns: vec![AbiParam::new(fx.pointer_type)],
+            },
+        )
+        .unwrap();
+    let trampoline = fx.module.declare_func_in_func(trampoline, &mut fx.bcx.func);
+    if fx.clif_comments.enabled() {
+        fx.add_comment(trampoline, &trampoline_name);
+    }
+
+    let call = fx.bcx.ins().call(trampoline, &[f, data]);
+    fx.bcx.inst_results(call)[0]
+}
+
+/// Returns `fn(data: *mut u8)`, which calls the function pointer in the first slot of `data` with
+/// the arguments in the following slots and stores the return values into them.
+fn get_or_define_invoke_thunk(fx: &mut FunctionCx<'_, '_, '_>, sig: Signature) -> FuncId {
+    if let Some(&thunk) = fx.cx.invoke_thunks.get(&sig) {
+        return thunk;
+    }
+
+    let pointer_type = fx.pointer_type;
+    let thunk_sig = Signature {
+        call_conv: CallConv::SystemV,
+        params: vec![AbiParam::new(pointer_type)],
+        returns: vec![],
+    };
+    let thunk = fx.module.declare_anonymous_function(&thunk_sig).unwrap();
+
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(ExternalName::user(0, thunk.as_u32()), thunk_sig);
+    {
+        let mut func_ctx = FunctionBuilderContext::new();
+        let mut bcx = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let data = bcx.append_block_param(block, pointer_type);
+
+        let callee = bcx.ins().load(pointer_type, MemFlags::trusted(), data, 0);
+        let args = sig
+            .params
+            .iter()
+            .enumerate()
+            .map(|(i, param)| {
+                let offset = i32::try_from(slot_offset(i)).unwrap();
+                bcx.ins().load(param.value_type, MemFlags::trusted(), data, offset)
+            })
+            .collect::<Vec<_>>();
+        let sig_ref = bcx.import_signature(sig.clone());
+        let call = bcx.ins().call_indirect(sig_ref, callee, &args);
+        let results = bcx.inst_results(call).to_vec();
+        for (i, result) in results.into_iter().enumerate() {
+            let offset = i32::try_from(slot_offset(i)).unwrap();
+            bcx.ins().store(MemFlags::trusted(), result, data, offset);
+        }
+        bcx.ins().return_(&[]);
+
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    fx.module.define_function(thunk, &mut ctx).unwrap();
+    // The thunk is unwound through, so it needs unwind info just like any other function.
+    fx.cx.unwind_context.add_function(thunk, &ctx, fx.module.isa());
+
+    fx.cx.invoke_thunks.insert(sig, thunk);
+    thunk
+}
+
+/// Generates `fn(f: fn(*mut u8), data: *mut u8) -> *mut u8` for x86_64 ELF targets.
+fn generate_invoke_trampoline(name: &str) -> String {
+    let mut asm = String::new();
+
+    // The personality function is referenced indirectly through a pointer in a comdat section.
+    // This is the same as what LLVM emits, so the pointer is shared with LLVM compiled objects.
+    asm.push_str(
+        "
+.hidden DW.ref.rust_eh_personality
+.weak DW.ref.rust_eh_personality
+.section .data.DW.ref.rust_eh_personality,\"awG\",@progbits,DW.ref.rust_eh_personality,comdat
+.p2align 3
+.type DW.ref.rust_eh_personality,@object
+.size DW.ref.rust_eh_personality,8
+DW.ref.rust_eh_personality:
+.quad rust_eh_personality
+",
+    );
+
+    // The push keeps the stack 16 byte aligned for the call. The personality function passes the
+    // exception to the landing pad in rax, which is then returned as is.
+    writeln!(
+        asm,
+        "
+.globl {name}
+.hidden {name}
+.type {name},@function
+.section .text.{name},\"ax\",@progbits
+.intel_syntax noprefix
+{name}:
+.cfi_startproc
+.cfi_personality 0x9b, DW.ref.rust_eh_personality
+.cfi_lsda 0x1b, .L{name}_lsda
+    push rax
+    .cfi_def_cfa_offset 16
+    mov rax, rdi
+    mov rdi, rsi
+.L{name}_call_begin:
+    call rax
+.L{name}_call_end:
+    xor eax, eax
+.L{name}_landing_pad:
+    pop rcx
+    .cfi_def_cfa_offset 8
+    ret
+.cfi_endproc
+.size {name}, .-{name}
+.att_syntax"
+    )
+    .unwrap();
+
+    // The LSDA has a single call site entry for the call to `f` with an action that catches
+    // everything. Everything else in the trampoline isn't allowed to unwind.
+    writeln!(
+        asm,
+        "
+.section .gcc_except_table.{name},\"a\",@progbits
+.p2align 2
+.L{name}_lsda:
+    .byte 0xff
+    .byte 0x9b
+    .uleb128 .L{name}_ttype_base - .L{name}_ttype_ref
+.L{name}_ttype_ref:
+    .byte 0x1
+    .uleb128 .L{name}_cs_end - .L{name}_cs_begin
+.L{name}_cs_begin:
+    .uleb128 .L{name}_call_begin - {name}
+    .uleb128 .L{name}_call_end - .L{name}_call_begin
+    .uleb128 .L{name}_landing_pad - {name}
+    .byte 0x1
+.L{name}_cs_end:
+    .byte 0x1
+    .byte 0x0
+    .p2align 2
+    .long 0
+.L{name}_ttype_base:
+.text"
+    )
+    .unwrap();
+
+    asm
+}
@@ -63,6 +63,7 @@
 mod global_asm;
 mod inline_asm;
 mod intrinsics;
+mod landing_pads;
 mod linkage;
 mod main_shim;
 mod num;
@@ -130,9 +131,15 @@ struct CodegenCx {
     should_write_ir: bool,
     global_asm: String,
     inline_asm_index: Cell<usize>,
+    /// Whether calls can unwind into cleanup blocks and the `try` intrinsic can catch panics,
+    /// see `landing_pads`.
+    landing_pads_supported: bool,
+    invoke_trampoline_emitted: bool,
+    invoke_thunks: crate::landing_pads::InvokeThunks,
     debug_context: Option<DebugContext>,
     unwind_context: UnwindContext,
     cgu_name: Symbol,
+    emulate_avx2: bool,
 }
 
 impl CodegenCx {
@@ -158,9 +165,16 @@ fn new(
             should_write_ir: crate::pretty_clif::should_write_ir(tcx),
             global_asm: String::new(),
             inline_asm_index: Cell::new(0),
+            landing_pads_supported: crate::landing_pads::landing_pads_supported(
+                tcx,
+                &backend_config,
+            ),
+            invoke_trampoline_emitted: false,
+            invoke_thunks: Default::default(),
             debug_context,
             unwind_context,
             cgu_name,
+            emulate_avx2: backend_config.emulate_avx2,
         }
     }
 }
@@ -203,7 +217,12 @@ fn codegen_crate(
         let config = self.config.borrow().clone().unwrap();
         match config.codegen_mode {
             CodegenMode::Aot => driver::aot::run_aot(tcx, config, metadata, need_metadata_module),
-            CodegenMode::Jit | CodegenMode::JitLazy => {
+            CodegenMode::Jit | CodegenMode::JitLazy | CodegenMode::JitHotReload => {
+                #[cfg(feature = "jit")]
+                if driver::jit::hot_reload::is_reloading() {
+                    return driver::jit::hot_reload::codegen_reload(tcx, metadata);
+                }
+
                 #[cfg(feature = "jit")]
                 driver::jit::run_jit(tcx, config);
 
@@ -219,6 +238,14 @@ fn join_codegen(
         sess: &Session,
         _outputs: &OutputFilenames,
     ) -> Result<(CodegenResults, FxHashMap<WorkProductId, WorkProduct>), ErrorGuaranteed> {
+        #[cfg(feature = "jit")]
+        if ongoing_codegen.is::<driver::jit::hot_reload::HotReloadCodegen>() {
+            return Ok(ongoing_codegen
+                .downcast::<driver::jit::hot_reload::HotReloadCodegen>()
+                .unwrap()
+                .join());
+        }
+
         Ok(ongoing_codegen
             .downcast::<driver::aot::OngoingCodegen>()
             .unwrap()
@@ -254,7 +254,7 @@ fn codegen_inline_asm(&mut self, template: &[InlineAsmTemplatePiece], rust_opera
                 }
 
                 InlineAsmOperandRef::Const { ref string } => {
-                    constants_len += string.len() + att_dialect as usize;
+                    constants_len += string.len();
                 }
 
                 InlineAsmOperandRef::SymFn { instance } => {
@@ -373,23 +373,27 @@ fn codegen_inline_asm(&mut self, template: &[InlineAsmTemplatePiece], rust_opera
 
         // 3. Build the template string
 
-        let mut template_str = String::with_capacity(estimate_template_length(template, constants_len, att_dialect));
+        let template = remove_line_comments(template);
+        let mut template_str = String::with_capacity(estimate_template_length(&template, constants_len, att_dialect));
         if att_dialect {
             template_str.push_str(ATT_SYNTAX_INS);
         }
 
-        for piece in template {
+        for piece in &template {
             match *piece {
                 InlineAsmTemplatePiece::String(ref string) => {
-                    // TODO(@Commeownist): switch to `Iterator::intersperse` once it's stable
-                    let mut iter = string.split('%');
-                    if let Some(s) = iter.next() {
-                        template_str.push_str(s);
-                    }
-
-                    for s in iter {
-                        template_str.push_str("%%");
-                        template_str.push_str(s);
+                    for c in string.chars() {
+                        match c {
+                            '%' => template_str.push_str("%%"),
+                            // On x86, GCC uses `{`, `|` and `}` to choose between the assembler
+                            // dialects, so they must be escaped to be output literally, like in
+                            // the AVX-512 masking syntax `{k1}`.
+                            '{' | '|' | '}' if is_x86 => {
+                                template_str.push('%');
+                                template_str.push(c);
+                            }
+                            _ => template_str.push(c),
+                        }
                     }
                 }
                 InlineAsmTemplatePiece::Placeholder { operand_idx, modifier, span: _ } => {
@@ -448,10 +452,9 @@ fn codegen_inline_asm(&mut self, template: &[InlineAsmTemplatePiece], rust_opera
                         }
 
                         InlineAsmOperandRef::Const { ref string } => {
-                            // Const operands get injected directly into the template
-                            if att_dialect {
-                                template_str.push('$');
-                            }
+                            // Const operands get injected directly into the template. Like with
+                            // LLVM, the `$` prefix of immediates in AT&T syntax is part of the
+                            // template.
                             template_str.push_str(string);
                         }
                     }
@@ -543,60 +546,123 @@ fn estimate_template_length(template: &[InlineAsmTemplatePiece], constants_len:
     res
 }
 
+/// Removes the `//` line comments from the template, including the placeholders they contain.
+///
+/// LLVM strips these comments itself, but GCC passes the template to the assembler as-is and gas
+/// only supports `//` comments on some targets.
+fn remove_line_comments(template: &[InlineAsmTemplatePiece]) -> Vec<InlineAsmTemplatePiece> {
+    let mut pieces = Vec::with_capacity(template.len());
+    let mut in_comment = false;
+    let mut in_string = false;
+    for piece in template {
+        match *piece {
+            InlineAsmTemplatePiece::String(ref string) => {
+                let mut result = String::with_capacity(string.len());
+                let mut chars = string.chars().peekable();
+                while let Some(c) = chars.next() {
+                    if in_comment {
+                        if c == '\n' {
+                            in_comment = false;
+                            result.push(c);
+                        }
+                        continue;
+                    }
+                    match c {
+                        '"' => in_string = !in_string,
+                        // A character literal like `'"'` or `'\n'`. The closing quote is optional
+                        // in gas.
+                        '\'' if !in_string => {
+                            result.push(c);
+                            if let Some(literal) = chars.next() {
+                                result.push(literal);
+                                if literal == '\\' {
+                                    if let Some(escaped) = chars.next() {
+                                        result.push(escaped);
+                                    }
+                                }
+                            }
+                            if chars.peek() == Some(&'\'') {
+                                result.push('\'');
+                                chars.next();
+                            }
+                            continue;
+                        }
+                        '\\' if in_string => {
+                            result.push(c);
+                            if let Some(escaped) = chars.next() {
+                                result.push(escaped);
+                            }
+                            continue;
+                        }
+                        '\n' => in_string = false,
+                        '/' if !in_string && chars.peek() == Some(&'/') => {
+                            in_comment = true;
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    result.push(c);
+                }
+                pieces.push(InlineAsmTemplatePiece::String(result));
+            }
+            InlineAsmTemplatePiece::Placeholder { .. } => {
+                if !in_comment {
+                    pieces.push(piece.clone());
+                }
+            }
+        }
+    }
+    pieces
+}
+
 /// Converts a register class to a GCC constraint code.
 fn reg_to_gcc(reg: InlineAsmRegOrRegClass) -> ConstraintOrRegister {
     let constraint = match reg {
-        // For vector registers LLVM wants the register name to match the type size.
+        // For explicit registers, we have to create a register variable: https://stackoverflow.com/a/31774784/389119
         InlineAsmRegOrRegClass::Reg(reg) => {
-            match reg {
-                InlineAsmReg::X86(_) => {
-                    // TODO(antoyo): add support for vector register.
-                    //
-                    // // For explicit registers, we have to create a register variable: https://stackoverflow.com/a/31774784/389119
-                    return ConstraintOrRegister::Register(match reg.name() {
-                        // Some of registers' names does not map 1-1 from rust to gcc
-                        "st(0)" => "st",
-
-                        name => name,
-                    });
-                }
-
-                _ => unimplemented!(),
-            }
+            return ConstraintOrRegister::Register(explicit_reg_to_gcc(reg));
         },
         InlineAsmRegOrRegClass::RegClass(reg) => match reg {
-            InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::preg) => unimplemented!(),
-            InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::reg) => unimplemented!(),
-            InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg) => unimplemented!(),
-            InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg_low16) => unimplemented!(),
-            InlineAsmRegClass::Arm(ArmInlineAsmRegClass::reg) => unimplemented!(),
+            InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::reg) => "r",
+            InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg) => "w",
+            InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg_low16) => "x",
+            InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::preg) => {
+                unreachable!("clobber-only")
+            },
+            InlineAsmRegClass::Arm(ArmInlineAsmRegClass::reg) => "r",
             InlineAsmRegClass::Arm(ArmInlineAsmRegClass::sreg)
             | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::dreg_low16)
-            | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg_low8) => unimplemented!(),
+            | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg_low8) => "t",
             InlineAsmRegClass::Arm(ArmInlineAsmRegClass::sreg_low16)
             | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::dreg_low8)
-            | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg_low4) => unimplemented!(),
+            | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg_low4) => "x",
             InlineAsmRegClass::Arm(ArmInlineAsmRegClass::dreg)
-            | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg) => unimplemented!(),
-            InlineAsmRegClass::Avr(_) => unimplemented!(),
-            InlineAsmRegClass::Bpf(_) => unimplemented!(),
-            InlineAsmRegClass::Hexagon(HexagonInlineAsmRegClass::reg) => unimplemented!(),
-            InlineAsmRegClass::Mips(MipsInlineAsmRegClass::reg) => unimplemented!(),
-            InlineAsmRegClass::Mips(MipsInlineAsmRegClass::freg) => unimplemented!(),
-            InlineAsmRegClass::Msp430(_) => unimplemented!(),
-            InlineAsmRegClass::Nvptx(NvptxInlineAsmRegClass::reg16) => unimplemented!(),
-            InlineAsmRegClass::Nvptx(NvptxInlineAsmRegClass::reg32) => unimplemented!(),
-            InlineAsmRegClass::Nvptx(NvptxInlineAsmRegClass::reg64) => unimplemented!(),
-            InlineAsmRegClass::PowerPC(PowerPCInlineAsmRegClass::reg) => unimplemented!(),
-            InlineAsmRegClass::PowerPC(PowerPCInlineAsmRegClass::reg_nonzero) => unimplemented!(),
-            InlineAsmRegClass::PowerPC(PowerPCInlineAsmRegClass::freg) => unimplemented!(),
+            | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg) => "w",
+            InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg) => "r",
+            InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_upper) => "d",
+            InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_pair) => "r",
+            InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_iw) => "w",
+            InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_ptr) => "e",
+            // The size of the register is chosen from the type of the operand.
+            InlineAsmRegClass::Bpf(_) => "r",
+            InlineAsmRegClass::Hexagon(HexagonInlineAsmRegClass::reg) => "r",
+            InlineAsmRegClass::Mips(MipsInlineAsmRegClass::reg) => "r",
+            InlineAsmRegClass::Mips(MipsInlineAsmRegClass::freg) => "f",
+            InlineAsmRegClass::Msp430(Msp430InlineAsmRegClass::reg) => "r",
+            // The size of the register is chosen from the type of the operand.
+            InlineAsmRegClass::Nvptx(_) => "r",
+            InlineAsmRegClass::PowerPC(PowerPCInlineAsmRegClass::reg) => "r",
+            InlineAsmRegClass::PowerPC(PowerPCInlineAsmRegClass::reg_nonzero) => "b",
+            InlineAsmRegClass::PowerPC(PowerPCInlineAsmRegClass::freg) => "f",
             InlineAsmRegClass::PowerPC(PowerPCInlineAsmRegClass::cr)
             | InlineAsmRegClass::PowerPC(PowerPCInlineAsmRegClass::xer) => {
                 unreachable!("clobber-only")
             },
-            InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::reg) => unimplemented!(),
-            InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::freg) => unimplemented!(),
-            InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::vreg) => unimplemented!(),
+            InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::reg) => "r",
+            InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::freg) => "f",
+            InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::vreg) => {
+                unreachable!("clobber-only")
+            },
             InlineAsmRegClass::X86(X86InlineAsmRegClass::reg) => "r",
             InlineAsmRegClass::X86(X86InlineAsmRegClass::reg_abcd) => "Q",
             InlineAsmRegClass::X86(X86InlineAsmRegClass::reg_byte) => "q",
@@ -604,16 +670,20 @@ fn reg_to_gcc(reg: InlineAsmRegOrRegClass) -> ConstraintOrRegister {
             | InlineAsmRegClass::X86(X86InlineAsmRegClass::ymm_reg) => "x",
             InlineAsmRegClass::X86(X86InlineAsmRegClass::zmm_reg) => "v",
             InlineAsmRegClass::X86(X86InlineAsmRegClass::kreg) => "Yk",
-            InlineAsmRegClass::X86(X86InlineAsmRegClass::kreg0) => unimplemented!(),
-            InlineAsmRegClass::Wasm(WasmInlineAsmRegClass::local) => unimplemented!(),
             InlineAsmRegClass::X86(
-                X86InlineAsmRegClass::x87_reg | X86InlineAsmRegClass::mmx_reg | X86InlineAsmRegClass::tmm_reg,
+                X86InlineAsmRegClass::x87_reg
+                | X86InlineAsmRegClass::mmx_reg
+                | X86InlineAsmRegClass::kreg0
+                | X86InlineAsmRegClass::tmm_reg,
             ) => unreachable!("clobber-only"),
+            InlineAsmRegClass::Wasm(WasmInlineAsmRegClass::local) => {
+                bug!("GCC backend does not support WebAssembly")
+            }
             InlineAsmRegClass::SpirV(SpirVInlineAsmRegClass::reg) => {
                 bug!("GCC backend does not support SPIR-V")
             }
-            InlineAsmRegClass::S390x(S390xInlineAsmRegClass::reg) => unimplemented!(),
-            InlineAsmRegClass::S390x(S390xInlineAsmRegClass::freg) => unimplemented!(),
+            InlineAsmRegClass::S390x(S390xInlineAsmRegClass::reg) => "r",
+            InlineAsmRegClass::S390x(S390xInlineAsmRegClass::freg) => "f",
             InlineAsmRegClass::Err => unreachable!(),
         }
     };
@@ -621,15 +691,33 @@ fn reg_to_gcc(reg: InlineAsmRegOrRegClass) -> ConstraintOrRegister {
     ConstraintOrRegister::Constraint(constraint)
 }
 
+/// Converts an explicit register to the name GCC uses for it in register variables and clobbers.
+fn explicit_reg_to_gcc(reg: InlineAsmReg) -> &'static str {
+    let name = reg.name();
+    match reg {
+        // Some of registers' names does not map 1-1 from rust to gcc
+        InlineAsmReg::X86(_) if name == "st(0)" => "st",
+        // Rust names AVR register pairs like `r25r24`, while GCC refers to them by the lower
+        // register.
+        InlineAsmReg::Avr(_) => match name.rfind('r') {
+            Some(index) if index > 0 => &name[index..],
+            _ => name,
+        },
+        _ => name,
+    }
+}
+
 /// Type to use for outputs that are discarded. It doesn't really matter what
 /// the type is, as long as it is valid for the constraint code.
 fn dummy_output_type<'gcc, 'tcx>(cx: &CodegenCx<'gcc, 'tcx>, reg: InlineAsmRegClass) -> Type<'gcc> {
     match reg {
         InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::reg) => cx.type_i32(),
-        InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::preg) => unimplemented!(),
+        InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::preg) => {
+            unreachable!("clobber-only")
+        },
         InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg)
         | InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg_low16) => {
-            unimplemented!()
+            cx.type_vector(cx.type_i64(), 2)
         }
         InlineAsmRegClass::Arm(ArmInlineAsmRegClass::reg)=> cx.type_i32(),
         InlineAsmRegClass::Arm(ArmInlineAsmRegClass::sreg)
@@ -640,14 +728,19 @@ fn dummy_output_type<'gcc, 'tcx>(cx: &CodegenCx<'gcc, 'tcx>, reg: InlineAsmRegCl
         InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg)
         | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg_low8)
         | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg_low4) => {
-            unimplemented!()
+            cx.type_vector(cx.type_i64(), 2)
         }
-        InlineAsmRegClass::Avr(_) => unimplemented!(),
-        InlineAsmRegClass::Bpf(_) => unimplemented!(),
+        InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg)
+        | InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_upper) => cx.type_i8(),
+        InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_pair)
+        | InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_iw)
+        | InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_ptr) => cx.type_i16(),
+        InlineAsmRegClass::Bpf(BpfInlineAsmRegClass::reg) => cx.type_i64(),
+        InlineAsmRegClass::Bpf(BpfInlineAsmRegClass::wreg) => cx.type_i32(),
         InlineAsmRegClass::Hexagon(HexagonInlineAsmRegClass::reg) => cx.type_i32(),
         InlineAsmRegClass::Mips(MipsInlineAsmRegClass::reg) => cx.type_i32(),
         InlineAsmRegClass::Mips(MipsInlineAsmRegClass::freg) => cx.type_f32(),
-        InlineAsmRegClass::Msp430(_) => unimplemented!(),
+        InlineAsmRegClass::Msp430(Msp430InlineAsmRegClass::reg) => cx.type_i16(),
         InlineAsmRegClass::Nvptx(NvptxInlineAsmRegClass::reg16) => cx.type_i16(),
         InlineAsmRegClass::Nvptx(NvptxInlineAsmRegClass::reg32) => cx.type_i32(),
         InlineAsmRegClass::Nvptx(NvptxInlineAsmRegClass::reg64) => cx.type_i64(),
@@ -660,21 +753,27 @@ fn dummy_output_type<'gcc, 'tcx>(cx: &CodegenCx<'gcc, 'tcx>, reg: InlineAsmRegCl
         },
         InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::reg) => cx.type_i32(),
         InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::freg) => cx.type_f32(),
-        InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::vreg) => cx.type_f32(),
+        InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::vreg) => {
+            unreachable!("clobber-only")
+        },
         InlineAsmRegClass::X86(X86InlineAsmRegClass::reg)
         | InlineAsmRegClass::X86(X86InlineAsmRegClass::reg_abcd) => cx.type_i32(),
         InlineAsmRegClass::X86(X86InlineAsmRegClass::reg_byte) => cx.type_i8(),
-        InlineAsmRegClass::X86(X86InlineAsmRegClass::mmx_reg) => unimplemented!(),
         InlineAsmRegClass::X86(X86InlineAsmRegClass::xmm_reg)
         | InlineAsmRegClass::X86(X86InlineAsmRegClass::ymm_reg)
         | InlineAsmRegClass::X86(X86InlineAsmRegClass::zmm_reg) => cx.type_f32(),
-        InlineAsmRegClass::X86(X86InlineAsmRegClass::x87_reg) => unimplemented!(),
         InlineAsmRegClass::X86(X86InlineAsmRegClass::kreg) => cx.type_i16(),
-        InlineAsmRegClass::X86(X86InlineAsmRegClass::kreg0) => cx.type_i16(),
-        InlineAsmRegClass::X86(X86InlineAsmRegClass::tmm_reg) => unimplemented!(),
-        InlineAsmRegClass::Wasm(WasmInlineAsmRegClass::local) => cx.type_i32(),
+        InlineAsmRegClass::X86(
+            X86InlineAsmRegClass::x87_reg
+            | X86InlineAsmRegClass::mmx_reg
+            | X86InlineAsmRegClass::kreg0
+            | X86InlineAsmRegClass::tmm_reg,
+        ) => unreachable!("clobber-only"),
+        InlineAsmRegClass::Wasm(WasmInlineAsmRegClass::local) => {
+            bug!("GCC backend does not support WebAssembly")
+        }
         InlineAsmRegClass::SpirV(SpirVInlineAsmRegClass::reg) => {
-            bug!("LLVM backend does not support SPIR-V")
+            bug!("GCC backend does not support SPIR-V")
         },
         InlineAsmRegClass::S390x(S390xInlineAsmRegClass::reg) => cx.type_i32(),
         InlineAsmRegClass::S390x(S390xInlineAsmRegClass::freg) => cx.type_f64(),
@@ -691,22 +790,12 @@ fn codegen_global_asm(&self, template: &[InlineAsmTemplatePiece], operands: &[Gl
             && options.contains(InlineAsmOptions::ATT_SYNTAX);
 
         // Build the template string
+        let template = remove_line_comments(template);
         let mut template_str = String::new();
-        for piece in template {
+        for piece in &template {
             match *piece {
                 InlineAsmTemplatePiece::String(ref string) => {
-                    for line in string.lines() {
-                        // NOTE: gcc does not allow inline comment, so remove them.
-                        let line =
-                            if let Some(index) = line.rfind("//") {
-                                &line[..index]
-                            }
-                            else {
-                                line
-                            };
-                        template_str.push_str(line);
-                        template_str.push('\n');
-                    }
+                    template_str.push_str(string);
                 },
                 InlineAsmTemplatePiece::Placeholder { operand_idx, modifier: _, span: _ } => {
                     match operands[operand_idx] {
@@ -753,32 +842,41 @@ fn codegen_global_asm(&self, template: &[InlineAsmTemplatePiece], operands: &[Gl
 fn modifier_to_gcc(arch: InlineAsmArch, reg: InlineAsmRegClass, modifier: Option<char>) -> Option<char> {
     match reg {
         InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::reg) => modifier,
-        InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::preg) => modifier,
         InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg)
         | InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg_low16) => {
-            unimplemented!()
+            if modifier == Some('v') { None } else { modifier }
         }
-        InlineAsmRegClass::Arm(ArmInlineAsmRegClass::reg)  => unimplemented!(),
+        InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::preg) => {
+            unreachable!("clobber-only")
+        }
+        InlineAsmRegClass::Arm(ArmInlineAsmRegClass::reg) => None,
         InlineAsmRegClass::Arm(ArmInlineAsmRegClass::sreg)
-        | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::sreg_low16) => unimplemented!(),
+        | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::sreg_low16) => None,
         InlineAsmRegClass::Arm(ArmInlineAsmRegClass::dreg)
         | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::dreg_low16)
-        | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::dreg_low8) => unimplemented!(),
+        | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::dreg_low8) => Some('P'),
         InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg)
         | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg_low8)
         | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg_low4) => {
-            unimplemented!()
+            if modifier.is_none() { Some('q') } else { modifier }
         }
-        InlineAsmRegClass::Avr(_) => unimplemented!(),
-        InlineAsmRegClass::Bpf(_) => unimplemented!(),
-        InlineAsmRegClass::Hexagon(_) => unimplemented!(),
-        InlineAsmRegClass::Mips(_) => unimplemented!(),
-        InlineAsmRegClass::Msp430(_) => unimplemented!(),
-        InlineAsmRegClass::Nvptx(_) => unimplemented!(),
-        InlineAsmRegClass::PowerPC(_) => unimplemented!(),
+        // GCC prints the lower and the upper register of a pair with `A` and `B`.
+        InlineAsmRegClass::Avr(_) => match modifier {
+            Some('h') => Some('B'),
+            Some('l') => Some('A'),
+            _ => None,
+        },
+        InlineAsmRegClass::Bpf(_) => None,
+        InlineAsmRegClass::Hexagon(_) => None,
+        InlineAsmRegClass::Mips(_) => None,
+        InlineAsmRegClass::Msp430(_) => None,
+        InlineAsmRegClass::Nvptx(_) => None,
+        InlineAsmRegClass::PowerPC(_) => None,
         InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::reg)
-        | InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::freg) => unimplemented!(),
-        InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::vreg) => unimplemented!(),
+        | InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::freg) => None,
+        InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::vreg) => {
+            unreachable!("clobber-only")
+        }
         InlineAsmRegClass::X86(X86InlineAsmRegClass::reg)
         | InlineAsmRegClass::X86(X86InlineAsmRegClass::reg_abcd) => match modifier {
             None => if arch == InlineAsmArch::X86_64 { Some('q') } else { Some('k') },
@@ -802,16 +900,154 @@ fn modifier_to_gcc(arch: InlineAsmArch, reg: InlineAsmRegClass, modifier: Option
             _ => unreachable!(),
         },
         InlineAsmRegClass::X86(X86InlineAsmRegClass::kreg) => None,
-        InlineAsmRegClass::X86(X86InlineAsmRegClass::kreg0) => None,
-        InlineAsmRegClass::X86(X86InlineAsmRegClass::x87_reg | X86InlineAsmRegClass::mmx_reg | X86InlineAsmRegClass::tmm_reg) => {
+        InlineAsmRegClass::X86(X86InlineAsmRegClass::x87_reg | X86InlineAsmRegClass::mmx_reg | X86InlineAsmRegClass::kreg0 | X86InlineAsmRegClass::tmm_reg) => {
             unreachable!("clobber-only")
         }
-        InlineAsmRegClass::Wasm(WasmInlineAsmRegClass::local) => unimplemented!(),
+        InlineAsmRegClass::Wasm(WasmInlineAsmRegClass::local) => {
+            bug!("GCC backend does not support WebAssembly")
+        }
         InlineAsmRegClass::SpirV(SpirVInlineAsmRegClass::reg) => {
-            bug!("LLVM backend does not support SPIR-V")
+            bug!("GCC backend does not support SPIR-V")
         },
-        InlineAsmRegClass::S390x(S390xInlineAsmRegClass::reg) => unimplemented!(),
-        InlineAsmRegClass::S390x(S390xInlineAsmRegClass::freg) => unimplemented!(),
+        InlineAsmRegClass::S390x(_) => None,
         InlineAsmRegClass::Err => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constraint(reg: InlineAsmRegClass) -> &'static str {
+        match reg_to_gcc(InlineAsmRegOrRegClass::RegClass(reg)) {
+            ConstraintOrRegister::Constraint(constraint) => constraint,
+            ConstraintOrRegister::Register(register) => panic!("unexpected register {}", register),
+        }
+    }
+
+    fn register(reg: InlineAsmReg) -> &'static str {
+        match reg_to_gcc(InlineAsmRegOrRegClass::Reg(reg)) {
+            ConstraintOrRegister::Constraint(constraint) => panic!("unexpected constraint {}", constraint),
+            ConstraintOrRegister::Register(register) => register,
+        }
+    }
+
+    fn without_line_comments(template: &[&str]) -> Vec<String> {
+        let template = template.iter()
+            .map(|string| InlineAsmTemplatePiece::String(string.to_string()))
+            .collect::<Vec<_>>();
+        remove_line_comments(&template).into_iter()
+            .map(|piece| match piece {
+                InlineAsmTemplatePiece::String(string) => string,
+                InlineAsmTemplatePiece::Placeholder { .. } => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn line_comments() {
+        assert_eq!(without_line_comments(&["mov eax, 1 // comment\nret"]), ["mov eax, 1 \nret"]);
+        assert_eq!(without_line_comments(&[".ascii \"//\\\"//\" // comment"]), [".ascii \"//\\\"//\" "]);
+        assert_eq!(without_line_comments(&["mov al, '\"' // comment\nmov bl, '/' // comment"]), ["mov al, '\"' \nmov bl, '/' "]);
+        assert_eq!(without_line_comments(&["mov al, '\\'' // comment"]), ["mov al, '\\'' "]);
+        assert_eq!(without_line_comments(&["movb $'\", %al // comment"]), ["movb $'\", %al "]);
+        assert_eq!(without_line_comments(&["nop // comment ", "\nnop"]), ["nop ", "\nnop"]);
+    }
+
+    #[test]
+    fn aarch64() {
+        assert_eq!(constraint(InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::reg)), "r");
+        assert_eq!(constraint(InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg)), "w");
+        assert_eq!(constraint(InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg_low16)), "x");
+        assert_eq!(register(InlineAsmReg::AArch64(AArch64InlineAsmReg::x0)), "x0");
+        assert_eq!(modifier_to_gcc(InlineAsmArch::AArch64, InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg), Some('v')), None);
+        assert_eq!(modifier_to_gcc(InlineAsmArch::AArch64, InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg), Some('d')), Some('d'));
+    }
+
+    #[test]
+    fn arm() {
+        assert_eq!(constraint(InlineAsmRegClass::Arm(ArmInlineAsmRegClass::reg)), "r");
+        assert_eq!(constraint(InlineAsmRegClass::Arm(ArmInlineAsmRegClass::sreg)), "t");
+        assert_eq!(constraint(InlineAsmRegClass::Arm(ArmInlineAsmRegClass::dreg_low8)), "x");
+        assert_eq!(constraint(InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg)), "w");
+        assert_eq!(register(InlineAsmReg::Arm(ArmInlineAsmReg::r0)), "r0");
+        assert_eq!(modifier_to_gcc(InlineAsmArch::Arm, InlineAsmRegClass::Arm(ArmInlineAsmRegClass::dreg), None), Some('P'));
+        assert_eq!(modifier_to_gcc(InlineAsmArch::Arm, InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg), None), Some('q'));
+    }
+
+    #[test]
+    fn avr() {
+        assert_eq!(constraint(InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_upper)), "d");
+        assert_eq!(constraint(InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_iw)), "w");
+        assert_eq!(constraint(InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_ptr)), "e");
+        assert_eq!(register(InlineAsmReg::Avr(AvrInlineAsmReg::r25r24)), "r24");
+        assert_eq!(modifier_to_gcc(InlineAsmArch::Avr, InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_pair), Some('h')), Some('B'));
+        assert_eq!(modifier_to_gcc(InlineAsmArch::Avr, InlineAsmRegClass::Avr(AvrInlineAsmRegClass::reg_pair), Some('l')), Some('A'));
+    }
+
+    #[test]
+    fn bpf() {
+        assert_eq!(constraint(InlineAsmRegClass::Bpf(BpfInlineAsmRegClass::reg)), "r");
+        assert_eq!(constraint(InlineAsmRegClass::Bpf(BpfInlineAsmRegClass::wreg)), "r");
+    }
+
+    #[test]
+    fn hexagon() {
+        assert_eq!(constraint(InlineAsmRegClass::Hexagon(HexagonInlineAsmRegClass::reg)), "r");
+    }
+
+    #[test]
+    fn mips() {
+        assert_eq!(constraint(InlineAsmRegClass::Mips(MipsInlineAsmRegClass::reg)), "r");
+        assert_eq!(constraint(InlineAsmRegClass::Mips(MipsInlineAsmRegClass::freg)), "f");
+        assert_eq!(modifier_to_gcc(InlineAsmArch::Mips, InlineAsmRegClass::Mips(MipsInlineAsmRegClass::freg), None), None);
+    }
+
+    #[test]
+    fn msp430() {
+        assert_eq!(constraint(InlineAsmRegClass::Msp430(Msp430InlineAsmRegClass::reg)), "r");
+    }
+
+    #[test]
+    fn nvptx() {
+        assert_eq!(constraint(InlineAsmRegClass::Nvptx(NvptxInlineAsmRegClass::reg16)), "r");
+        assert_eq!(constraint(InlineAsmRegClass::Nvptx(NvptxInlineAsmRegClass::reg64)), "r");
+    }
+
+    #[test]
+    fn powerpc() {
+        assert_eq!(constraint(InlineAsmRegClass::PowerPC(PowerPCInlineAsmRegClass::reg)), "r");
+        assert_eq!(constraint(InlineAsmRegClass::PowerPC(PowerPCInlineAsmRegClass::reg_nonzero)), "b");
+        assert_eq!(constraint(InlineAsmRegClass::PowerPC(PowerPCInlineAsmRegClass::freg)), "f");
+        assert_eq!(register(InlineAsmReg::PowerPC(PowerPCInlineAsmReg::r0)), "r0");
+    }
+
+    #[test]
+    fn riscv() {
+        assert_eq!(constraint(InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::reg)), "r");
+        assert_eq!(constraint(InlineAsmRegClass::RiscV(RiscVInlineAsmRegClass::freg)), "f");
+        assert_eq!(register(InlineAsmReg::RiscV(RiscVInlineAsmReg::f0)), "f0");
+    }
+
+    #[test]
+    fn s390x() {
+        assert_eq!(constraint(InlineAsmRegClass::S390x(S390xInlineAsmRegClass::reg)), "r");
+        assert_eq!(constraint(InlineAsmRegClass::S390x(S390xInlineAsmRegClass::freg)), "f");
+        assert_eq!(register(InlineAsmReg::S390x(S390xInlineAsmReg::f0)), "f0");
+    }
+
+    #[test]
+    fn x86() {
+        assert_eq!(constraint(InlineAsmRegClass::X86(X86InlineAsmRegClass::reg_abcd)), "Q");
+        assert_eq!(constraint(InlineAsmRegClass::X86(X86InlineAsmRegClass::reg_byte)), "q");
+        assert_eq!(constraint(InlineAsmRegClass::X86(X86InlineAsmRegClass::xmm_reg)), "x");
+        assert_eq!(constraint(InlineAsmRegClass::X86(X86InlineAsmRegClass::ymm_reg)), "x");
+        assert_eq!(constraint(InlineAsmRegClass::X86(X86InlineAsmRegClass::zmm_reg)), "v");
+        assert_eq!(constraint(InlineAsmRegClass::X86(X86InlineAsmRegClass::kreg)), "Yk");
+        assert_eq!(register(InlineAsmReg::X86(X86InlineAsmReg::st0)), "st");
+        assert_eq!(modifier_to_gcc(InlineAsmArch::X86, InlineAsmRegClass::X86(X86InlineAsmRegClass::reg), None), Some('k'));
+        assert_eq!(modifier_to_gcc(InlineAsmArch::X86_64, InlineAsmRegClass::X86(X86InlineAsmRegClass::reg), None), Some('q'));
+        assert_eq!(modifier_to_gcc(InlineAsmArch::X86_64, InlineAsmRegClass::X86(X86InlineAsmRegClass::ymm_reg), None), Some('t'));
+        assert_eq!(modifier_to_gcc(InlineAsmArch::X86_64, InlineAsmRegClass::X86(X86InlineAsmRegClass::zmm_reg), Some('x')), Some('x'));
+    }
+}
@@ -49,6 +49,11 @@ while [[ $# -gt 0 ]]; do
             shift
             ;;
 
+        "--test-asm")
+            func=test_asm
+            shift
+            ;;
+
         "--test-libcore")
             func=test_libcore
             shift
@@ -225,10 +230,7 @@ function extended_sysroot_tests() {
     popd
 }
 
-function test_rustc() {
-    echo
-    echo "[TEST] rust-lang/rust"
-
+function setup_rustc() {
     rust_toolchain=$(cat rust-toolchain | grep channel | sed 's/channel = "\(.*\)"/\1/')
 
     git clone https://github.com/rust-lang/rust.git || true
@@ -255,6 +257,15 @@ EOF
     rustc -V | cut -d' ' -f3 | tr -d '('
     git checkout $(rustc -V | cut -d' ' -f3 | tr -d '(') src/test
 
+    RUSTC_ARGS="-Zpanic-abort-tests -Csymbol-mangling-version=v0 -Zcodegen-backend="$(pwd)"/../target/"$CHANNEL"/librustc_codegen_gcc."$dylib_ext" --sysroot "$(pwd)"/../build_sysroot/sysroot -Cpanic=abort"
+}
+
+function test_rustc() {
+    echo
+    echo "[TEST] rust-lang/rust"
+
+    setup_rustc
+
     for test in $(rg -i --files-with-matches "//(\[\w+\])?~|// error-pattern:|// build-fail|// run-fail|-Cllvm-args" src/test/ui); do
       rm $test
     done
@@ -268,12 +279,45 @@ EOF
     git checkout src/test/ui/type-alias-impl-trait/auxiliary/cross_crate_ice.rs
     git checkout src/test/ui/type-alias-impl-trait/auxiliary/cross_crate_ice2.rs
 
-    RUSTC_ARGS="-Zpanic-abort-tests -Csymbol-mangling-version=v0 -Zcodegen-backend="$(pwd)"/../target/"$CHANNEL"/librustc_codegen_gcc."$dylib_ext" --sysroot "$(pwd)"/../build_sysroot/sysroot -Cpanic=abort"
-
     echo "[TEST] rustc test suite"
     COMPILETEST_FORCE_STAGE0=1 ./x.py test --run always --stage 0 src/test/ui/ --rustc-args "$RUSTC_ARGS"
 }
 
+function test_asm() {
+    echo
+    echo "[TEST] inline assembly"
+
+    if [[ "$HOST_TRIPLE" != "x86_64-unknown-linux-gnu" ]]; then
+        echo "Skipping: the inline assembly tests are only run on x86_64-unknown-linux-gnu"
+        return
+    fi
+
+    setup_rustc
+
+    # The GCC backend always emits Intel syntax on x86, remove the tests passing other flags to LLVM.
+    for test in $(rg --files-with-matches -- "-C ?llvm-args" src/test/ui/asm/ src/test/assembly/asm/); do
+      sed -i "/-C \?llvm-args=--x86-asm-syntax=intel/d" $test
+      if rg --quiet -- "-C ?llvm-args" $test; then
+        rm $test
+      fi
+    done
+
+    # Only keep the tests and revisions for the host, the GCC backend can't cross-compile.
+    for test in $(rg --files-with-matches -- "--target" src/test/ui/asm/ src/test/assembly/asm/); do
+      revisions=$(rg --only-matching --replace '$1' "^//\[(\w+)\] compile-flags: .*--target $HOST_TRIPLE" $test | xargs)
+      if [[ -n "$revisions" ]]; then
+        sed -i "s#^// *revisions:.*#// revisions: $revisions#" $test
+      elif ! rg --quiet -- "--target $HOST_TRIPLE" $test; then
+        rm $test
+      fi
+    done
+
+    COMPILETEST_FORCE_STAGE0=1 ./x.py test --run always --stage 0 src/test/ui/asm/ src/test/assembly/asm/ --rustc-args "$RUSTC_ARGS"
+
+    # `setup_rustc` entered the rust checkout, leave it for the tests that run after this one.
+    cd ..
+}
+
 function clean_ui_tests() {
     find rust/build/x86_64-unknown-linux-gnu/test/ui/ -name stamp -exec rm -rf {} \;
 }
@@ -285,6 +329,7 @@ function all() {
     std_tests
     test_libcore
     extended_sysroot_tests
+    test_asm
     test_rustc
 }
 
@@ -15,8 +15,18 @@
      ret"
 );
 
+global_asm!("
+    .global const_asm
+const_asm:
+    movl ${}, %eax // return the constant
+    ret",
+    const 42,
+    options(att_syntax)
+);
+
 extern "C" {
     fn add_asm(a: i64, b: i64) -> i64;
+    fn const_asm() -> u32;
 }
 
 pub unsafe fn mem_cpy(dst: *mut u8, src: *const u8, len: usize) {
@@ -123,7 +133,7 @@ fn main() {
     // check const (ATT syntax)
     let mut x: u64 = 42;
     unsafe {
-        asm!("add {}, {}",
+        asm!("add ${}, {}",
             const 1,
             inout(reg) x,
             options(att_syntax)
@@ -162,6 +172,18 @@ extern "C" fn foo() -> u64 { 42 }
     assert_eq!(x, 42);
 
     assert_eq!(unsafe { add_asm(40, 2) }, 42);
+    assert_eq!(unsafe { const_asm() }, 42);
+
+    // check comments
+    let x: u64;
+    unsafe {
+        asm!(
+            "mov {}, 5 // the placeholder in this comment is removed too: {}",
+            out(reg) x,
+            const 0,
+        );
+    }
+    assert_eq!(x, 5);
 
     let array1 = [1u8, 2, 3];
     let mut array2 = [0u8, 0, 0];
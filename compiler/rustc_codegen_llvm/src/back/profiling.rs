@@ -27,7 +27,7 @@ pub struct LlvmSelfProfiler<'a> {
 
 impl<'a> LlvmSelfProfiler<'a> {
     pub fn new(profiler: Arc<SelfProfiler>) -> Self {
-        let llvm_pass_event_kind = profiler.alloc_string("LLVM Pass");
+        let llvm_pass_event_kind = profiler.get_or_alloc_cached_string("LLVM Pass");
         Self { profiler, stack: Vec::default(), llvm_pass_event_kind }
     }
 
@@ -147,6 +147,10 @@ pub fn get_fn<'ll, 'tcx>(cx: &CodegenCx<'ll, 'tcx>, instance: Instance<'tcx>) ->
                             }
                         }
                     }
+                } else if instance.upstream_monomorphization(tcx).is_some() {
+                    // This is instantiated in a generics hub crate, which
+                    // exports it even though we are not sharing generics. It
+                    // cannot be `hidden`.
                 } else {
                     // When not sharing generics, all instances are in the same
                     // crate and have hidden visibility
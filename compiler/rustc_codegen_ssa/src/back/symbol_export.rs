@@ -273,7 +273,8 @@ fn exported_symbols_provider_local<'tcx>(
         ));
     }
 
-    if tcx.sess.opts.share_generics() && tcx.local_crate_exports_generics() {
+    let share_generics = tcx.sess.opts.share_generics() && tcx.local_crate_exports_generics();
+    if share_generics || tcx.is_generics_hub(LOCAL_CRATE) {
         use rustc_middle::mir::mono::{Linkage, MonoItem, Visibility};
         use rustc_middle::ty::InstanceDef;
 
@@ -282,7 +283,10 @@ fn exported_symbols_provider_local<'tcx>(
         // needs to be exported.
         // However, on platforms that don't allow for Rust dylibs, having
         // external linkage is enough for monomorphization to be linked to.
-        let need_visibility = tcx.sess.target.dynamic_linking && !tcx.sess.target.only_cdylib;
+        // A generics hub only shares the instances listed in its profile, which
+        // are exactly the ones the partitioner did not leave hidden.
+        let need_visibility =
+            (tcx.sess.target.dynamic_linking && !tcx.sess.target.only_cdylib) || !share_generics;
 
         let (_, cgus) = tcx.collect_and_partition_mono_items(());
 
@@ -375,9 +379,11 @@ fn upstream_monomorphizations_provider(
             match substs_map.entry(substs) {
                 Occupied(mut e) => {
                     // If there are multiple monomorphizations available,
-                    // we select one deterministically.
+                    // we select one deterministically, preferring those
+                    // provided by a generics hub.
                     let other_cnum = *e.get();
-                    if tcx.stable_crate_id(other_cnum) > tcx.stable_crate_id(cnum) {
+                    let key = |cnum| (!tcx.is_generics_hub(cnum), tcx.stable_crate_id(cnum));
+                    if key(other_cnum) > key(cnum) {
                         e.insert(cnum);
                     }
                 }
@@ -28,6 +28,7 @@
 use rustc_middle::middle::lang_items;
 use rustc_middle::mir::mono::{CodegenUnit, CodegenUnitNameBuilder, MonoItem};
 use rustc_middle::ty::layout::{HasTyCtxt, LayoutOf, TyAndLayout};
+use rustc_middle::ty::print::with_no_trimmed_paths;
 use rustc_middle::ty::query::Providers;
 use rustc_middle::ty::{self, Instance, Ty, TyCtxt};
 use rustc_session::cgu_reuse_tracker::CguReuse;
@@ -39,9 +40,12 @@
 
 use std::collections::BTreeSet;
 use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::time::{Duration, Instant};
 
 use itertools::Itertools;
+use serde::Serialize;
 
 pub fn bin_op_to_icmp_predicate(op: hir::BinOpKind, signed: bool) -> IntPredicate {
     match op {
@@ -638,6 +642,10 @@ pub fn codegen_crate<B: ExtraBackendMethods>(
         codegen_units.iter().map(|cgu| determine_cgu_reuse(tcx, &cgu)).collect::<Vec<_>>()
     });
 
+    if tcx.sess.opts.unstable_opts.cgu_report {
+        dump_cgu_report(tcx, &codegen_units, &cgu_reuse);
+    }
+
     let mut total_codegen_time = Duration::new(0, 0);
     let start_rss = tcx.sess.time_passes().then(|| get_resident_set_size());
 
@@ -918,6 +926,144 @@ pub fn provide(providers: &mut Providers) {
     };
 }
 
+#[derive(Serialize)]
+struct CguReport {
+    crate_name: String,
+    incremental: bool,
+    target_cgu_count: usize,
+    /// The codegen units, sorted by name.
+    codegen_units: Vec<CodegenUnitReport>,
+}
+
+#[derive(Serialize)]
+struct CodegenUnitReport {
+    name: String,
+    size_estimate: usize,
+    /// The mono items of the codegen unit, sorted by name.
+    items: Vec<CguItemReport>,
+    /// The codegen units that were merged into this one, empty if none were.
+    merged_from: Vec<MergedCguReport>,
+    merge_reason: Option<String>,
+    reuse: &'static str,
+    reuse_reason: &'static str,
+    /// The chain of dependencies that changed, ending with the root cause.
+    invalidated_by: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct CguItemReport {
+    name: String,
+    linkage: String,
+    visibility: String,
+    size_estimate: usize,
+}
+
+#[derive(Serialize)]
+struct MergedCguReport {
+    name: String,
+    size_estimate: usize,
+}
+
+/// Writes the `-Zcgu-report` report, which lists the codegen units of the crate, how the
+/// partitioner formed them and whether they could be reused from the incremental cache.
+fn dump_cgu_report<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    codegen_units: &[&CodegenUnit<'tcx>],
+    cgu_reuse: &[CguReuse],
+) {
+    let target_cgu_count = tcx.sess.codegen_units();
+
+    let mut cgus: Vec<_> = codegen_units
+        .iter()
+        .zip(cgu_reuse)
+        .map(|(cgu, &reuse)| {
+            let mut items: Vec<_> = cgu
+                .items()
+                .iter()
+                .map(|(item, &(linkage, visibility))| CguItemReport {
+                    name: with_no_trimmed_paths!(item.to_string()),
+                    linkage: format!("{:?}", linkage),
+                    visibility: format!("{:?}", visibility),
+                    size_estimate: item.size_estimate(tcx),
+                })
+                .collect();
+            items.sort_by(|a, b| a.name.cmp(&b.name));
+
+            let merged_from: Vec<_> = cgu
+                .merged_cgus()
+                .iter()
+                .map(|&(name, size_estimate)| MergedCguReport {
+                    name: name.to_string(),
+                    size_estimate,
+                })
+                .collect();
+            let merge_reason = (!merged_from.is_empty()).then(|| {
+                format!(
+                    "the two smallest codegen units were merged repeatedly until at most {} \
+                     were left",
+                    target_cgu_count
+                )
+            });
+
+            let mut invalidated_by = Vec::new();
+            let (reuse, reuse_reason) = match reuse {
+                CguReuse::PostLto => ("reused", "nothing it depends on changed"),
+                CguReuse::PreLto => (
+                    "loaded-from-cache",
+                    "nothing it depends on changed, its bitcode is loaded from the cache and \
+                     optimized again by LTO",
+                ),
+                CguReuse::No if !tcx.dep_graph.is_fully_enabled() => {
+                    ("codegened", "incremental compilation is disabled")
+                }
+                CguReuse::No
+                    if tcx.dep_graph.previous_work_product(&cgu.work_product_id()).is_none() =>
+                {
+                    ("codegened", "it did not exist in the previous session")
+                }
+                CguReuse::No => {
+                    invalidated_by = tcx
+                        .dep_graph
+                        .red_dependency_chain(tcx, &cgu.codegen_dep_node(tcx))
+                        .iter()
+                        .map(|dep_node| format!("{:?}", dep_node))
+                        .collect();
+                    ("codegened", "something it depends on changed")
+                }
+            };
+
+            CodegenUnitReport {
+                name: cgu.name().to_string(),
+                size_estimate: cgu.size_estimate(),
+                items,
+                merged_from,
+                merge_reason,
+                reuse,
+                reuse_reason,
+                invalidated_by,
+            }
+        })
+        .collect();
+    cgus.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let report = CguReport {
+        crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+        incremental: tcx.dep_graph.is_fully_enabled(),
+        target_cgu_count,
+        codegen_units: cgus,
+    };
+
+    let path = tcx.output_filenames(()).with_extension("cgu_report.json");
+    let result = File::create(&path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &report)?;
+        writer.flush()
+    });
+    if let Err(err) = result {
+        tcx.sess.err(&format!("failed to write CGU report to `{}`: {}", path.display(), err));
+    }
+}
+
 fn determine_cgu_reuse<'tcx>(tcx: TyCtxt<'tcx>, cgu: &CodegenUnit<'tcx>) -> CguReuse {
     if !tcx.dep_graph.is_fully_enabled() {
         return CguReuse::No;
@@ -0,0 +1,172 @@
+//! Bookkeeping for the resource limits of const evaluation, `#[const_eval_limit]` and
+//! `#[const_eval_memory_limit]`. When one of them is reached, this is used to explain where the
+//! steps or the memory went.
+
+use std::cell::{Cell, RefCell};
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_errors::{pluralize, Diagnostic, MultiSpan};
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_session::Limit;
+use rustc_span::Span;
+use rustc_target::abi::Size;
+
+use crate::interpret::{AllocId, Frame, InterpError, InterpResult, ResourceExhaustionInfo};
+
+/// The number of functions and allocation sites that are pointed at when a limit is reached.
+const REPORT_LEN: usize = 5;
+
+pub(crate) struct ConstEvalBudget<'tcx> {
+    /// The maximum number of bytes that may be allocated at the same time.
+    ///
+    /// Setting this to `0` disables the limit.
+    memory_limit: Limit,
+    /// The number of steps taken so far by each frame on the stack, not counting its callees.
+    frame_steps: Vec<u64>,
+    /// The number of steps taken in each function by frames that have already been popped.
+    steps: FxIndexMap<ty::Instance<'tcx>, u64>,
+    /// The total size of all live allocations. This and `live_allocations` are updated in
+    /// `adjust_allocation`, which only has shared access to the machine. Both stay empty when
+    /// there is no memory limit.
+    live_bytes: Cell<u64>,
+    /// The size of each live allocation, and the span of the code that allocated it.
+    live_allocations: RefCell<FxIndexMap<AllocId, (Size, Span)>>,
+}
+
+impl<'tcx> ConstEvalBudget<'tcx> {
+    pub(crate) fn new(memory_limit: Limit) -> Self {
+        ConstEvalBudget {
+            memory_limit,
+            frame_steps: Vec::new(),
+            steps: FxIndexMap::default(),
+            live_bytes: Cell::new(0),
+            live_allocations: RefCell::new(FxIndexMap::default()),
+        }
+    }
+
+    pub(crate) fn push_frame(&mut self) {
+        self.frame_steps.push(0);
+    }
+
+    pub(crate) fn pop_frame(&mut self, instance: ty::Instance<'tcx>) {
+        let steps = self.frame_steps.pop().unwrap();
+        *self.steps.entry(instance).or_insert(0) += steps;
+    }
+
+    /// Called for every step, i.e. every terminator, taken by the topmost frame.
+    #[inline]
+    pub(crate) fn step(&mut self) {
+        if let Some(steps) = self.frame_steps.last_mut() {
+            *steps += 1;
+        }
+    }
+
+    pub(crate) fn allocate(&self, id: AllocId, size: Size, span: Span) -> InterpResult<'tcx> {
+        // Without a limit, nothing is ever reported, so there is nothing to keep track of.
+        if self.memory_limit.0 == 0 {
+            return Ok(());
+        }
+        let live_bytes = self.live_bytes.get() + size.bytes();
+        if live_bytes > self.memory_limit.0 as u64 {
+            throw_exhaust!(MemoryLimitReached)
+        }
+        self.live_bytes.set(live_bytes);
+        self.live_allocations.borrow_mut().insert(id, (size, span));
+        Ok(())
+    }
+
+    pub(crate) fn deallocate(&self, id: AllocId) {
+        if self.memory_limit.0 == 0 {
+            return;
+        }
+        if let Some((size, _)) = self.live_allocations.borrow_mut().swap_remove(&id) {
+            self.live_bytes.set(self.live_bytes.get() - size.bytes());
+        }
+    }
+
+    /// Explains where the budget went if `error` is caused by reaching one of the limits.
+    pub(crate) fn report(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        stack: &[Frame<'_, 'tcx, AllocId, ()>],
+        error: &InterpError<'tcx>,
+        diag: &mut Diagnostic,
+    ) {
+        match error {
+            InterpError::ResourceExhaustion(ResourceExhaustionInfo::StepLimitReached) => {
+                self.report_steps(tcx, stack, diag);
+            }
+            InterpError::ResourceExhaustion(ResourceExhaustionInfo::MemoryLimitReached) => {
+                self.report_memory(diag);
+                self.report_steps(tcx, stack, diag);
+            }
+            _ => {}
+        }
+    }
+
+    fn report_steps(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        stack: &[Frame<'_, 'tcx, AllocId, ()>],
+        diag: &mut Diagnostic,
+    ) {
+        let mut steps = self.steps.clone();
+        for (frame, &frame_steps) in std::iter::zip(stack, &self.frame_steps) {
+            *steps.entry(frame.instance).or_insert(0) += frame_steps;
+        }
+        let mut steps: Vec<_> = steps.into_iter().filter(|&(_, steps)| steps > 0).collect();
+        // The sort is stable, which keeps the output deterministic.
+        steps.sort_by(|(_, a), (_, b)| b.cmp(a));
+        steps.truncate(REPORT_LEN);
+        if steps.is_empty() {
+            return;
+        }
+
+        let mut span =
+            MultiSpan::from_spans(steps.iter().map(|(i, _)| tcx.def_span(i.def_id())).collect());
+        for (instance, steps) in steps {
+            span.push_span_label(
+                tcx.def_span(instance.def_id()),
+                format!("{} steps in `{}`", steps, instance),
+            );
+        }
+        diag.span_note(span, "most steps were taken in these functions");
+    }
+
+    fn report_memory(&self, diag: &mut Diagnostic) {
+        let mut sites = FxIndexMap::<Span, (u64, usize)>::default();
+        for &(size, span) in self.live_allocations.borrow().values() {
+            let (bytes, count) = sites.entry(span).or_insert((0, 0));
+            *bytes += size.bytes();
+            *count += 1;
+        }
+        let mut sites: Vec<_> = sites.into_iter().collect();
+        sites.sort_by(|(_, (a, _)), (_, (b, _))| b.cmp(a));
+        sites.truncate(REPORT_LEN);
+        if sites.is_empty() {
+            return;
+        }
+
+        let mut span = MultiSpan::from_spans(sites.iter().map(|&(span, _)| span).collect());
+        for (site, (bytes, count)) in sites {
+            let label = if count == 1 {
+                format!("{} byte{} allocated here", bytes, pluralize!(bytes))
+            } else {
+                format!(
+                    "{} byte{} allocated here in {} allocations",
+                    bytes,
+                    pluralize!(bytes),
+                    count
+                )
+            };
+            span.push_span_label(site, label);
+        }
+        diag.span_note(
+            span,
+            format!(
+                "{} bytes were allocated when the limit was reached, most of them here",
+                self.live_bytes.get()
+            ),
+        );
+    }
+}
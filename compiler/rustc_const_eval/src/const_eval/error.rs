@@ -103,12 +103,13 @@ pub fn report_as_error(&self, tcx: TyCtxtAt<'tcx>, message: &str) -> ErrorHandle
         self.struct_error(tcx, message, |_| {})
     }
 
-    pub fn report_as_lint(
+    pub fn struct_lint(
         &self,
         tcx: TyCtxtAt<'tcx>,
         message: &str,
         lint_root: hir::HirId,
         span: Option<Span>,
+        decorate: impl FnOnce(&mut Diagnostic),
     ) -> ErrorHandled {
         self.struct_generic(
             tcx,
@@ -127,11 +128,22 @@ pub fn report_as_lint(
                         }
                     }
                 }
+                decorate(lint);
             },
             Some(lint_root),
         )
     }
 
+    pub fn report_as_lint(
+        &self,
+        tcx: TyCtxtAt<'tcx>,
+        message: &str,
+        lint_root: hir::HirId,
+        span: Option<Span>,
+    ) -> ErrorHandled {
+        self.struct_lint(tcx, message, lint_root, span, |_| {})
+    }
+
     /// Create a diagnostic for this const eval error.
     ///
     /// Sets the message passed in via `message` and adds span labels with detailed error
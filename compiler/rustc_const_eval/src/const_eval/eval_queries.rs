@@ -1,3 +1,5 @@
+use super::history::report_undefined_behavior;
+use super::trace::ConstEvalTrace;
 use super::{CompileTimeEvalContext, CompileTimeInterpreter, ConstEvalErr};
 use crate::interpret::eval_nullary_intrinsic;
 use crate::interpret::{
@@ -6,6 +8,7 @@
     ScalarMaybeUninit, StackPopCleanup,
 };
 
+use rustc_errors::Diagnostic;
 use rustc_hir::def::DefKind;
 use rustc_middle::mir;
 use rustc_middle::mir::interpret::ErrorHandled;
@@ -55,6 +58,8 @@ fn eval_body_using_ecx<'mir, 'tcx>(
         cid.promoted.map_or_else(String::new, |p| format!("::promoted[{:?}]", p))
     );
 
+    ecx.machine.trace = ConstEvalTrace::new(tcx);
+
     ecx.push_stack_frame(
         cid.instance,
         body,
@@ -63,7 +68,12 @@ fn eval_body_using_ecx<'mir, 'tcx>(
     )?;
 
     // The main interpreter loop.
-    ecx.run()?;
+    let res = ecx.run();
+    // Also print the profile if the evaluation failed, as it may help to find out why.
+    if let Some(trace) = &ecx.machine.trace {
+        trace.print_profile();
+    }
+    res?;
 
     // Intern the result
     let intern_kind = if cid.promoted.is_some() {
@@ -100,6 +110,7 @@ pub(super) fn mk_eval_cx<'mir, 'tcx>(
         param_env,
         CompileTimeInterpreter::new(
             tcx.const_eval_limit(),
+            tcx.const_eval_memory_limit(),
             can_access_statics,
             /*check_alignment:*/ false,
         ),
@@ -312,6 +323,7 @@ pub fn eval_to_allocation_raw_provider<'tcx>(
         // they do not have to behave "as if" they were evaluated at runtime.
         CompileTimeInterpreter::new(
             tcx.const_eval_limit(),
+            tcx.const_eval_memory_limit(),
             /*can_access_statics:*/ is_static,
             /*check_alignment:*/ tcx.sess.opts.unstable_opts.extra_const_ub_checks,
         ),
@@ -321,6 +333,12 @@ pub fn eval_to_allocation_raw_provider<'tcx>(
     match res.and_then(|body| eval_body_using_ecx(&mut ecx, cid, &body)) {
         Err(error) => {
             let err = ConstEvalErr::new(&ecx, error, None);
+            // If a resource limit was reached, point out where the steps or memory went. For
+            // undefined behavior, point out the history of the allocation involved.
+            let decorate = |diag: &mut Diagnostic| {
+                ecx.machine.budget.report(*ecx.tcx, ecx.stack(), &err.error, diag);
+                report_undefined_behavior(&ecx, &err.error, diag);
+            };
             // Some CTFE errors raise just a lint, not a hard error; see
             // <https://github.com/rust-lang/rust/issues/71800>.
             let is_hard_err = if let Some(def) = def.as_local() {
@@ -350,14 +368,15 @@ pub fn eval_to_allocation_raw_provider<'tcx>(
                     }
                 };
 
-                Err(err.report_as_error(ecx.tcx.at(err.span), &msg))
+                Err(err.struct_error(ecx.tcx.at(err.span), &msg, decorate))
             } else {
                 let hir_id = tcx.hir().local_def_id_to_hir_id(def.as_local().unwrap().did);
-                Err(err.report_as_lint(
+                Err(err.struct_lint(
                     tcx.at(tcx.def_span(def.did)),
                     "any use of this value will cause an error",
                     hir_id,
                     Some(err.span),
+                    decorate,
                 ))
             }
         }
@@ -0,0 +1,86 @@
+//! Records where the allocations of an evaluation were created and freed, so that errors about
+//! undefined behavior can point there, like Miri does.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_errors::Diagnostic;
+use rustc_span::Span;
+
+use super::CompileTimeEvalContext;
+use crate::interpret::{AllocId, InterpError, UndefinedBehaviorInfo};
+
+/// The number of freed allocations whose history is kept. Evaluations that allocate in a loop
+/// would otherwise keep growing the history without bound.
+const FREED_HISTORY_LEN: usize = 256;
+
+#[derive(Default)]
+pub(crate) struct AllocHistory {
+    /// Where each live allocation made by the evaluation was created. This is updated in
+    /// `adjust_allocation`, which only has shared access to the machine.
+    live: RefCell<FxHashMap<AllocId, Span>>,
+    /// Where the most recently freed allocations were created and freed, oldest first.
+    freed: VecDeque<(AllocId, Span, Span)>,
+}
+
+impl AllocHistory {
+    pub(crate) fn allocate(&self, id: AllocId, span: Span) {
+        self.live.borrow_mut().insert(id, span);
+    }
+
+    pub(crate) fn deallocate(&mut self, id: AllocId, span: Span) {
+        if let Some(allocated) = self.live.get_mut().remove(&id) {
+            if self.freed.len() == FREED_HISTORY_LEN {
+                self.freed.pop_front();
+            }
+            self.freed.push_back((id, allocated, span));
+        }
+    }
+
+    /// Where `id` was created and, if it has been freed already, where that happened.
+    fn get(&self, id: AllocId) -> Option<(Span, Option<Span>)> {
+        if let Some(&allocated) = self.live.borrow().get(&id) {
+            return Some((allocated, None));
+        }
+        self.freed
+            .iter()
+            .find(|&&(freed, ..)| freed == id)
+            .map(|&(_, allocated, deallocated)| (allocated, Some(deallocated)))
+    }
+}
+
+/// If `error` is undefined behavior involving an allocation, points out where that allocation was
+/// created and freed, and what it contains.
+///
+/// Errors found when validating the final value (see `validity.rs`) are not covered: validation
+/// runs after interning, and its errors only describe the offending value, not the allocation.
+pub(crate) fn report_undefined_behavior<'mir, 'tcx>(
+    ecx: &CompileTimeEvalContext<'mir, 'tcx>,
+    error: &InterpError<'tcx>,
+    diag: &mut Diagnostic,
+) {
+    use UndefinedBehaviorInfo::*;
+    let alloc_id = match error {
+        InterpError::UndefinedBehavior(
+            PointerUseAfterFree(alloc_id)
+            | PointerOutOfBounds { alloc_id, .. }
+            | WriteToReadOnly(alloc_id)
+            | InvalidUninitBytes(Some((alloc_id, _))),
+        ) => *alloc_id,
+        InterpError::UndefinedBehavior(UnterminatedCString(ptr)) => ptr.provenance,
+        _ => return,
+    };
+    // Allocations that were not made by this evaluation, like those of other constants, have no
+    // history, and their contents are not interesting either. Neither do allocations that were
+    // freed long before the error.
+    if let Some((allocated, deallocated)) = ecx.machine.history.get(alloc_id) {
+        diag.span_note(allocated, format!("{:?} was allocated here", alloc_id));
+        if let Some(deallocated) = deallocated {
+            diag.span_note(deallocated, format!("{:?} was deallocated here", alloc_id));
+        }
+        if let Some(bytes) = ecx.display_local_alloc(alloc_id) {
+            diag.note(format!("the raw bytes of {:?} ({}", alloc_id, bytes));
+        }
+    }
+}
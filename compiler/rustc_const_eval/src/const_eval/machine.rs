@@ -1,7 +1,7 @@
 use rustc_hir::def::DefKind;
 use rustc_middle::mir;
 use rustc_middle::ty::{self, Ty, TyCtxt};
-use std::borrow::Borrow;
+use std::borrow::{Borrow, Cow};
 use std::collections::hash_map::Entry;
 use std::hash::Hash;
 
@@ -17,11 +17,14 @@
 use rustc_target::spec::abi::Abi as CallAbi;
 
 use crate::interpret::{
-    self, compile_time_machine, AllocId, ConstAllocation, Frame, ImmTy, InterpCx, InterpResult,
-    OpTy, PlaceTy, Pointer, Scalar, StackPopUnwind,
+    self, compile_time_machine, AllocId, AllocRange, Allocation, ConstAllocation, Frame, ImmTy,
+    InterpCx, InterpResult, OpTy, PlaceTy, Pointer, Scalar, StackPopJump, StackPopUnwind,
 };
 
+use super::budget::ConstEvalBudget;
 use super::error::*;
+use super::history::AllocHistory;
+use super::trace::ConstEvalTrace;
 
 impl<'mir, 'tcx> InterpCx<'mir, 'tcx, CompileTimeInterpreter<'mir, 'tcx>> {
     /// "Intercept" a function call to a panic-related function
@@ -91,6 +94,13 @@ pub struct CompileTimeInterpreter<'mir, 'tcx> {
     /// Setting this to `0` disables the limit and allows the interpreter to run forever.
     pub steps_remaining: usize,
 
+    /// Tracks the steps and memory used by the evaluation, to enforce the memory limit and to
+    /// explain where they went when a limit is reached.
+    pub(super) budget: ConstEvalBudget<'tcx>,
+
+    /// Where the allocations of the evaluation were created and freed, for error messages.
+    pub(super) history: AllocHistory,
+
     /// The virtual call stack.
     pub(crate) stack: Vec<Frame<'mir, 'tcx, AllocId, ()>>,
 
@@ -104,19 +114,26 @@ pub struct CompileTimeInterpreter<'mir, 'tcx> {
 
     /// Whether to check alignment during evaluation.
     check_alignment: bool,
+
+    /// The state of `-Zconst-eval-trace`, if it is enabled for this evaluation.
+    pub(super) trace: Option<ConstEvalTrace<'tcx>>,
 }
 
 impl<'mir, 'tcx> CompileTimeInterpreter<'mir, 'tcx> {
     pub(crate) fn new(
         const_eval_limit: Limit,
+        const_eval_memory_limit: Limit,
         can_access_statics: bool,
         check_alignment: bool,
     ) -> Self {
         CompileTimeInterpreter {
             steps_remaining: const_eval_limit.0,
+            budget: ConstEvalBudget::new(const_eval_memory_limit),
+            history: AllocHistory::default(),
             stack: Vec::new(),
             can_access_statics,
             check_alignment,
+            trace: None,
         }
     }
 }
@@ -451,6 +468,12 @@ fn binary_ptr_op(
     }
 
     fn before_terminator(ecx: &mut InterpCx<'mir, 'tcx, Self>) -> InterpResult<'tcx> {
+        let machine = &mut ecx.machine;
+        machine.budget.step();
+        if let Some(trace) = &mut machine.trace {
+            trace.terminator(machine.stack.len(), machine.stack.last().unwrap());
+        }
+
         // The step limit has already been hit in a previous call to `before_terminator`.
         if ecx.machine.steps_remaining == 0 {
             return Ok(());
@@ -485,6 +508,67 @@ fn init_frame_extra(
         }
     }
 
+    fn after_stack_push(ecx: &mut InterpCx<'mir, 'tcx, Self>) -> InterpResult<'tcx> {
+        let tcx = *ecx.tcx;
+        let machine = &mut ecx.machine;
+        machine.budget.push_frame();
+        if let Some(trace) = &mut machine.trace {
+            trace.push_frame(tcx, machine.stack.len(), machine.stack.last().unwrap());
+        }
+        Ok(())
+    }
+
+    fn after_stack_pop(
+        ecx: &mut InterpCx<'mir, 'tcx, Self>,
+        frame: Frame<'mir, 'tcx>,
+        unwinding: bool,
+    ) -> InterpResult<'tcx, StackPopJump> {
+        // CTFE does not support unwinding from panics
+        assert!(!unwinding);
+        let machine = &mut ecx.machine;
+        machine.budget.pop_frame(frame.instance);
+        if let Some(trace) = &mut machine.trace {
+            // `frame` has already been removed from the stack.
+            trace.pop_frame(machine.stack.len() + 1, &frame);
+        }
+        Ok(StackPopJump::Normal)
+    }
+
+    fn adjust_allocation<'b>(
+        ecx: &InterpCx<'mir, 'tcx, Self>,
+        id: AllocId,
+        alloc: Cow<'b, Allocation>,
+        kind: Option<interpret::MemoryKind<Self::MemoryKind>>,
+    ) -> InterpResult<'tcx, Cow<'b, Allocation>> {
+        // Globals are not allocated by the evaluation, so they have no kind here.
+        if let Some(kind) = kind {
+            let span = ecx.cur_span();
+            ecx.machine.budget.allocate(id, alloc.size(), span)?;
+            ecx.machine.history.allocate(id, span);
+            if let Some(trace) = &ecx.machine.trace {
+                trace.allocation(ecx.machine.stack.len(), id, alloc.size(), alloc.align, kind);
+            }
+        }
+        Ok(alloc)
+    }
+
+    fn before_memory_deallocation(
+        _tcx: TyCtxt<'tcx>,
+        machine: &mut Self,
+        _alloc_extra: &mut (),
+        (alloc_id, ()): (AllocId, ()),
+        _range: AllocRange,
+    ) -> InterpResult<'tcx> {
+        machine.budget.deallocate(alloc_id);
+        if let Some(frame) = machine.stack.last() {
+            machine.history.deallocate(alloc_id, frame.current_span());
+        }
+        if let Some(trace) = &machine.trace {
+            trace.deallocation(machine.stack.len(), alloc_id);
+        }
+        Ok(())
+    }
+
     #[inline(always)]
     fn stack<'a>(
         ecx: &'a InterpCx<'mir, 'tcx, Self>,
@@ -11,10 +11,13 @@
     Scalar,
 };
 
+mod budget;
 mod error;
 mod eval_queries;
 mod fn_queries;
+mod history;
 mod machine;
+mod trace;
 mod valtrees;
 
 pub use error::*;
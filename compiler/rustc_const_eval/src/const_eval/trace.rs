@@ -0,0 +1,130 @@
+//! Implementation of `-Zconst-eval-trace`, which prints what the interpreter does while
+//! evaluating a given const item or const fn.
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_target::abi::{Align, Size};
+
+use crate::interpret::{AllocId, Frame, MemoryKind};
+
+const PREFIX: &str = "const-eval-trace:";
+
+/// The tracing state of a single evaluation.
+pub(crate) struct ConstEvalTrace<'tcx> {
+    /// The path of the item to trace, as passed to `-Zconst-eval-trace`.
+    filter: String,
+    /// The depth of the outermost frame of the traced item, while it is on the stack. Everything
+    /// that happens in frames at this depth or deeper is traced.
+    active_depth: Option<usize>,
+    /// The number of steps taken in each traced function, in the order the functions were first
+    /// entered. Like for `#[const_eval_limit]`, every executed terminator counts as one step.
+    steps: FxIndexMap<ty::Instance<'tcx>, u64>,
+}
+
+impl<'tcx> ConstEvalTrace<'tcx> {
+    pub(crate) fn new(tcx: TyCtxt<'tcx>) -> Option<Self> {
+        let filter = tcx.sess.opts.unstable_opts.const_eval_trace.clone()?;
+        Some(ConstEvalTrace { filter, active_depth: None, steps: FxIndexMap::default() })
+    }
+
+    /// Whether `instance` is the item requested by `-Zconst-eval-trace`. The item may be named by
+    /// its full path or by any suffix of it, like `TABLE` or `tables::TABLE`.
+    fn matches(&self, tcx: TyCtxt<'tcx>, instance: ty::Instance<'tcx>) -> bool {
+        let path = with_no_trimmed_paths!(tcx.def_path_str(instance.def_id()));
+        path == self.filter
+            || path.strip_suffix(&*self.filter).map_or(false, |prefix| prefix.ends_with("::"))
+    }
+
+    fn is_active(&self) -> bool {
+        self.active_depth.is_some()
+    }
+
+    fn print(&self, depth: usize, msg: std::fmt::Arguments<'_>) {
+        eprintln!("{} {:indent$}{}", PREFIX, "", msg, indent = depth * 2);
+    }
+
+    /// Called after `frame` was pushed as the `depth`th frame of the stack.
+    pub(crate) fn push_frame(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        depth: usize,
+        frame: &Frame<'_, 'tcx, AllocId, ()>,
+    ) {
+        if !self.is_active() && self.matches(tcx, frame.instance) {
+            self.active_depth = Some(depth);
+        }
+        if self.is_active() {
+            let span = tcx.sess.source_map().span_to_embeddable_string(frame.body.span);
+            self.print(depth, format_args!("-> `{}` at {}", frame.instance, span));
+            self.steps.entry(frame.instance).or_insert(0);
+        }
+    }
+
+    /// Called after `frame`, which was the `depth`th frame of the stack, was popped.
+    pub(crate) fn pop_frame(&mut self, depth: usize, frame: &Frame<'_, 'tcx, AllocId, ()>) {
+        if self.is_active() {
+            self.print(depth, format_args!("<- `{}`", frame.instance));
+        }
+        if self.active_depth == Some(depth) {
+            self.active_depth = None;
+        }
+    }
+
+    /// Called before the terminator of the current basic block of `frame` is executed.
+    pub(crate) fn terminator(&mut self, depth: usize, frame: &Frame<'_, 'tcx, AllocId, ()>) {
+        if !self.is_active() {
+            return;
+        }
+        *self.steps.entry(frame.instance).or_insert(0) += 1;
+        if let Ok(loc) = frame.loc {
+            let terminator = frame.body.basic_blocks()[loc.block].terminator();
+            self.print(depth, format_args!("{:?}: {:?}", loc.block, terminator.kind));
+        }
+    }
+
+    pub(crate) fn allocation<K: std::fmt::Display>(
+        &self,
+        depth: usize,
+        id: AllocId,
+        size: Size,
+        align: Align,
+        kind: MemoryKind<K>,
+    ) {
+        if self.is_active() {
+            self.print(
+                depth,
+                format_args!(
+                    "{:?}: allocated {} bytes with alignment {} for {}",
+                    id,
+                    size.bytes(),
+                    align.bytes(),
+                    kind
+                ),
+            );
+        }
+    }
+
+    pub(crate) fn deallocation(&self, depth: usize, id: AllocId) {
+        if self.is_active() {
+            self.print(depth, format_args!("{:?}: deallocated", id));
+        }
+    }
+
+    /// Prints the number of steps taken in each traced function, most expensive first. This is
+    /// called at the end of the evaluation, whether it succeeded or not.
+    pub(crate) fn print_profile(&self) {
+        if self.steps.is_empty() {
+            return;
+        }
+        let total: u64 = self.steps.values().sum();
+        eprintln!("{} {} steps in total, per function:", PREFIX, total);
+        let mut steps: Vec<_> = self.steps.iter().collect();
+        // The sort is stable, so functions with the same number of steps stay in the order they
+        // were first entered.
+        steps.sort_by(|(_, a), (_, b)| b.cmp(a));
+        for (instance, steps) in steps {
+            eprintln!("{} {:>10}  `{}`", PREFIX, steps, instance);
+        }
+    }
+}
@@ -48,6 +48,14 @@ struct InternVisitor<'rt, 'mir, 'tcx, M: CompileTimeMachine<'mir, 'tcx, const_ev
     /// A list of all encountered allocations. After type-based interning, we traverse this list to
     /// also intern allocations that are only referenced by a raw pointer or inside a union.
     leftover_allocations: &'rt mut FxHashSet<AllocId>,
+    /// The allocations pointed to by raw pointers behind shared references in a constant, and
+    /// whether all of these pointers are `*const` pointers outside of an `UnsafeCell` that can't
+    /// reach interior mutability. Heap allocations are only promoted to immutable memory if this
+    /// is the case.
+    raw_pointees: &'rt mut FxHashMap<AllocId, bool>,
+    /// Whether we are looking at the outermost allocation, i.e. at the value of the constant
+    /// itself rather than at something behind a reference.
+    is_root: bool,
     /// The root kind of the value that we're looking at. This field is never mutated for a
     /// particular allocation. It is primarily used to make as many allocations as possible
     /// read-only so LLVM can place them in const memory.
@@ -151,6 +159,64 @@ fn intern_shallow(
     ) -> Option<IsStaticOrFn> {
         intern_shallow(self.ecx, self.leftover_allocations, alloc_id, mode, ty)
     }
+
+    /// Raw pointers (and boxes) are not followed, the allocations they point to are interned
+    /// together with the other leftovers. Heap allocations behind shared references may end up in
+    /// the final value of a constant though, so we remember how they are pointed to.
+    fn record_raw_pointee(&mut self, ptr: &MPlaceTy<'tcx>, immutable: bool) {
+        if self.mode != InternMode::Const || self.is_root {
+            return;
+        }
+        // Errors are reported by validation.
+        let Ok(value) = self.ecx.read_immediate(&ptr.into()) else { return };
+        let Ok(pointee) = self.ecx.ref_to_mplace(&value) else { return };
+        if let Some(alloc_id) = pointee.ptr.provenance {
+            // The pointee is not visited, so we have to check the whole type for `UnsafeCell`
+            // instead, including everything that can be reached through further pointers. Those
+            // allocations are interned as leftovers as well.
+            let immutable = immutable
+                && !self.inside_unsafe_cell
+                && self.is_deeply_frozen(pointee.layout.ty, &mut FxHashSet::default());
+            *self.raw_pointees.entry(alloc_id).or_insert(true) &= immutable;
+        }
+    }
+
+    /// Whether `ty` and all types that can be reached through pointers from it are `Freeze`.
+    /// Types whose contents we can't know, like trait objects and type parameters, are assumed to
+    /// have interior mutability.
+    fn is_deeply_frozen(&self, ty: Ty<'tcx>, visited: &mut FxHashSet<Ty<'tcx>>) -> bool {
+        // Recursive types like linked lists were already checked higher up.
+        if !visited.insert(ty) {
+            return true;
+        }
+        if !ty.is_freeze(self.ecx.tcx, self.ecx.param_env) {
+            return false;
+        }
+        match *ty.kind() {
+            ty::Bool
+            | ty::Char
+            | ty::Int(_)
+            | ty::Uint(_)
+            | ty::Float(_)
+            | ty::Str
+            | ty::Never
+            | ty::FnDef(..)
+            | ty::FnPtr(_) => true,
+            // This also covers `Box`, whose fields include the pointer to its contents.
+            ty::Adt(adt_def, substs) => adt_def
+                .all_fields()
+                .all(|field| self.is_deeply_frozen(field.ty(*self.ecx.tcx, substs), visited)),
+            ty::Tuple(tys) => tys.iter().all(|ty| self.is_deeply_frozen(ty, visited)),
+            ty::Array(ty, _)
+            | ty::Slice(ty)
+            | ty::RawPtr(ty::TypeAndMut { ty, .. })
+            | ty::Ref(_, ty, _) => self.is_deeply_frozen(ty, visited),
+            ty::Closure(_, substs) => {
+                substs.as_closure().upvar_tys().all(|ty| self.is_deeply_frozen(ty, visited))
+            }
+            _ => false,
+        }
+    }
 }
 
 impl<'rt, 'mir, 'tcx: 'mir, M: CompileTimeMachine<'mir, 'tcx, const_eval::MemoryKind>>
@@ -163,6 +229,12 @@ fn ecx(&self) -> &InterpCx<'mir, 'tcx, M> {
         &self.ecx
     }
 
+    fn visit_box(&mut self, ptr: &MPlaceTy<'tcx>) -> InterpResult<'tcx> {
+        // The contents of a box behind a shared reference can't be mutated either.
+        self.record_raw_pointee(ptr, true);
+        Ok(())
+    }
+
     fn visit_aggregate(
         &mut self,
         mplace: &MPlaceTy<'tcx>,
@@ -313,6 +385,9 @@ fn visit_value(&mut self, mplace: &MPlaceTy<'tcx>) -> InterpResult<'tcx> {
                 }
             }
             Ok(())
+        } else if let ty::RawPtr(ty::TypeAndMut { mutbl, .. }) = *ty.kind() {
+            self.record_raw_pointee(mplace, mutbl == Mutability::Not);
+            Ok(())
         } else {
             // Not a reference -- proceed recursively.
             self.walk_value(mplace)
@@ -358,6 +433,7 @@ pub fn intern_const_alloc_recursive<
     // be available in a typed way. They get interned at the end.
     let mut ref_tracking = RefTracking::empty();
     let leftover_allocations = &mut FxHashSet::default();
+    let raw_pointees = &mut FxHashMap::default();
 
     // start with the outermost allocation
     intern_shallow(
@@ -378,6 +454,8 @@ pub fn intern_const_alloc_recursive<
             ecx,
             mode,
             leftover_allocations,
+            raw_pointees,
+            is_root: mplace == *ret,
             inside_unsafe_cell: false,
         }
         .visit_value(&mplace);
@@ -405,7 +483,9 @@ pub fn intern_const_alloc_recursive<
     debug!(?todo);
     debug!("dead_alloc_map: {:#?}", ecx.memory.dead_alloc_map);
     while let Some(alloc_id) = todo.pop() {
-        if let Some((_, mut alloc)) = ecx.memory.alloc_map.remove(&alloc_id) {
+        if let Some((kind, mut alloc)) = ecx.memory.alloc_map.remove(&alloc_id) {
+            let is_heap = kind == MemoryKind::Machine(const_eval::MemoryKind::Heap);
+            let promoted_heap = is_heap && raw_pointees.get(&alloc_id) == Some(&true);
             // We can't call the `intern_shallow` method here, as its logic is tailored to safe
             // references and a `leftover_allocations` set (where we only have a todo-list here).
             // So we hand-roll the interning logic here again.
@@ -428,22 +508,46 @@ pub fn intern_const_alloc_recursive<
                     alloc.mutability = Mutability::Not;
                 }
                 InternKind::Constant => {
-                    // If it's a constant, we should not have any "leftovers" as everything
-                    // is tracked by const-checking.
-                    // FIXME: downgrade this to a warning? It rejects some legitimate consts,
-                    // such as `const CONST_RAW: *const Vec<i32> = &Vec::new() as *const _;`.
-                    ecx.tcx
-                        .sess
-                        .span_err(ecx.tcx.span, "untyped pointers are not allowed in constant");
-                    // For better errors later, mark the allocation as immutable.
+                    // Heap allocations that are only pointed to by `*const` pointers behind shared
+                    // references, such as the buffer of a `&Vec<T>`, are promoted to immutable
+                    // memory just like the data behind the reference itself. Mutating them would
+                    // be UB for the same reasons as for promoteds above.
+                    if !promoted_heap {
+                        // Otherwise, we should not have any "leftovers" as everything
+                        // is tracked by const-checking.
+                        // FIXME: downgrade this to a warning? It rejects some legitimate consts,
+                        // such as `const CONST_RAW: *const Vec<i32> = &Vec::new() as *const _;`.
+                        let mut err = ecx.tcx.sess.struct_span_err(
+                            ecx.tcx.span,
+                            "untyped pointers are not allowed in constant",
+                        );
+                        if is_heap {
+                            err.note(
+                                "heap allocations can only be part of the final value of a \
+                                 constant when they are behind a shared reference and only \
+                                 pointed to by `*const` pointers to types without interior \
+                                 mutability",
+                            );
+                        }
+                        err.emit();
+                    }
+                    // Either way, mark the allocation as immutable. This is required for
+                    // promoted heap allocations and gives better errors later otherwise.
                     alloc.mutability = Mutability::Not;
                 }
             }
             let alloc = tcx.intern_const_alloc(alloc);
             tcx.set_alloc_id_memory(alloc_id, alloc);
-            for &(_, alloc_id) in alloc.inner().relocations().iter() {
-                if leftover_allocations.insert(alloc_id) {
-                    todo.push(alloc_id);
+            for &(_, inner_alloc_id) in alloc.inner().relocations().iter() {
+                if promoted_heap && intern_kind == InternKind::Constant {
+                    // The types of the values in a heap allocation are unknown here, but the type
+                    // of the pointer to this allocation was checked to not reach any `UnsafeCell`
+                    // when it was recorded. So everything that is only reachable through an
+                    // immutable heap allocation is immutable as well.
+                    raw_pointees.entry(inner_alloc_id).or_insert(true);
+                }
+                if leftover_allocations.insert(inner_alloc_id) {
+                    todo.push(inner_alloc_id);
                 }
             }
         } else if ecx.memory.dead_alloc_map.contains_key(&alloc_id) {
@@ -465,16 +465,6 @@ fn call_extra_fn(
         match fn_val {}
     }
 
-    #[inline(always)]
-    fn adjust_allocation<'b>(
-        _ecx: &InterpCx<$mir, $tcx, Self>,
-        _id: AllocId,
-        alloc: Cow<'b, Allocation>,
-        _kind: Option<MemoryKind<Self::MemoryKind>>,
-    ) -> InterpResult<$tcx, Cow<'b, Allocation<Self::Provenance>>> {
-        Ok(alloc)
-    }
-
     fn extern_static_base_pointer(
         ecx: &InterpCx<$mir, $tcx, Self>,
         def_id: DefId,
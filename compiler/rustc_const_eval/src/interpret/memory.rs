@@ -781,6 +781,13 @@ pub fn dump_allocs<'a>(&'a self, mut allocs: Vec<AllocId>) -> DumpAllocs<'a, 'mi
         DumpAllocs { ecx: self, allocs }
     }
 
+    /// Render the bytes of the given allocation if it is still live in local memory. Unlike
+    /// `dump_alloc`, this does not follow pointers, so it is suitable for error messages.
+    pub fn display_local_alloc(&self, id: AllocId) -> Option<String> {
+        let (_, alloc) = self.memory.alloc_map.get(id)?;
+        Some(display_allocation(*self.tcx, alloc).to_string())
+    }
+
     /// Print leaked memory. Allocations reachable from `static_roots` or a `Global` allocation
     /// are not considered leaked. Leaks whose kind `may_leak()` returns true are not reported.
     pub fn leak_report(&self, static_roots: &[AllocId]) -> usize {
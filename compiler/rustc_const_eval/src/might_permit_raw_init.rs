@@ -14,6 +14,7 @@ pub fn might_permit_raw_init<'tcx>(
 
     if strict {
         let machine = CompileTimeInterpreter::new(
+            Limit::new(0),
             Limit::new(0),
             /*can_access_statics:*/ false,
             /*check_alignment:*/ true,
@@ -81,6 +81,7 @@
 //!
 //! [mm]: https://github.com/rust-lang/measureme/
 
+use self::export::{EventLabel, EventRecorder, RecordingGuard};
 use crate::cold_path;
 use crate::fx::FxHashMap;
 
@@ -99,6 +100,10 @@
 use parking_lot::RwLock;
 use smallvec::SmallVec;
 
+mod export;
+#[cfg(test)]
+mod tests;
+
 bitflags::bitflags! {
     struct EventFilter: u32 {
         const GENERIC_ACTIVITIES  = 1 << 0;
@@ -142,7 +147,20 @@ struct EventFilter: u32 {
     ("artifact-sizes", EventFilter::ARTIFACT_SIZES),
 ];
 
+/// The formats in which the self-profiler can write the events it recorded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SelfProfileFormat {
+    /// Only the raw event data, for use with the `measureme` tools.
+    Raw,
+    /// A Chrome trace-event JSON file, in addition to the raw event data.
+    Chrome,
+    /// A summary of the items that took the most time printed to stderr, in addition to the raw
+    /// event data.
+    Summary,
+}
+
 /// Something that uniquely identifies a query invocation.
+#[derive(Clone, Copy)]
 pub struct QueryInvocationId(pub u32);
 
 /// A reference to the SelfProfiler. It can be cloned and sent across thread
@@ -286,7 +304,12 @@ pub fn generic_activity_with_arg<A>(
             } else {
                 builder.from_label(event_label)
             };
-            TimingGuard::start(profiler, profiler.generic_activity_event_kind, event_id)
+            TimingGuard::start_with_label(
+                profiler,
+                profiler.generic_activity_event_kind,
+                event_id,
+                EventLabel::String(event_label),
+            )
         })
     }
 
@@ -346,7 +369,12 @@ pub fn generic_activity_with_arg_recorder<F>(
             } else {
                 builder.from_label(event_label)
             };
-            TimingGuard::start(profiler, profiler.generic_activity_event_kind, event_id)
+            TimingGuard::start_with_label(
+                profiler,
+                profiler.generic_activity_event_kind,
+                event_id,
+                EventLabel::String(event_label),
+            )
         })
     }
 
@@ -395,7 +423,12 @@ pub fn generic_activity_with_args(
             } else {
                 builder.from_label(event_label)
             };
-            TimingGuard::start(profiler, profiler.generic_activity_event_kind, event_id)
+            TimingGuard::start_with_label(
+                profiler,
+                profiler.generic_activity_event_kind,
+                event_id,
+                EventLabel::String(event_label),
+            )
         })
     }
 
@@ -431,13 +464,18 @@ pub fn query_blocked(&self) -> TimingGuard<'_> {
     /// Start profiling how long it takes to load a query result from the
     /// incremental compilation on-disk cache. Profiling continues until the
     /// TimingGuard returned from this call is dropped.
+    ///
+    /// The event is attributed to the query invocation that the result is loaded for, just like
+    /// the event of a query provider that finished with `finish_with_query_invocation_id`.
     #[inline(always)]
-    pub fn incr_cache_loading(&self) -> TimingGuard<'_> {
+    pub fn incr_cache_loading(&self, query_invocation_id: QueryInvocationId) -> TimingGuard<'_> {
         self.exec(EventFilter::INCR_CACHE_LOADS, |profiler| {
-            TimingGuard::start(
+            let event_id = EventId::from_virtual(StringId::new_virtual(query_invocation_id.0));
+            TimingGuard::start_with_label(
                 profiler,
                 profiler.incremental_load_result_event_kind,
-                EventId::INVALID,
+                event_id,
+                EventLabel::Query(query_invocation_id),
             )
         })
     }
@@ -471,6 +509,10 @@ fn instant_query_event(
                 EventId::from_virtual(event_id),
                 thread_id,
             );
+            if let Some(recorder) = &profiler.recorder {
+                let label = EventLabel::Query(query_invocation_id);
+                recorder.record_instant(event_kind(profiler), label, thread_id);
+            }
 
             TimingGuard::none()
         }));
@@ -545,6 +587,10 @@ pub struct SelfProfiler {
     query_blocked_event_kind: StringId,
     query_cache_hit_event_kind: StringId,
     artifact_size_event_kind: StringId,
+
+    /// Keeps the recorded events in memory if they are to be exported in another format than
+    /// the raw event data.
+    recorder: Option<EventRecorder>,
 }
 
 impl SelfProfiler {
@@ -553,6 +599,7 @@ pub fn new(
         crate_name: Option<&str>,
         event_filters: Option<&[String]>,
         counter_name: &str,
+        format: SelfProfileFormat,
     ) -> Result<SelfProfiler, Box<dyn Error + Send + Sync>> {
         fs::create_dir_all(output_directory)?;
 
@@ -566,14 +613,19 @@ pub fn new(
         let profiler =
             Profiler::with_counter(&path, measureme::counters::Counter::by_name(counter_name)?)?;
 
-        let query_event_kind = profiler.alloc_string("Query");
-        let generic_activity_event_kind = profiler.alloc_string("GenericActivity");
-        let incremental_load_result_event_kind = profiler.alloc_string("IncrementalLoadResult");
-        let incremental_result_hashing_event_kind =
-            profiler.alloc_string("IncrementalResultHashing");
-        let query_blocked_event_kind = profiler.alloc_string("QueryBlocked");
-        let query_cache_hit_event_kind = profiler.alloc_string("QueryCacheHit");
-        let artifact_size_event_kind = profiler.alloc_string("ArtifactSize");
+        // The event kinds go through the string cache so that their names can be looked up when
+        // exporting the events.
+        let mut string_cache = FxHashMap::default();
+        let mut alloc_event_kind = |kind: &str| {
+            *string_cache.entry(kind.to_owned()).or_insert_with(|| profiler.alloc_string(kind))
+        };
+        let query_event_kind = alloc_event_kind("Query");
+        let generic_activity_event_kind = alloc_event_kind("GenericActivity");
+        let incremental_load_result_event_kind = alloc_event_kind("IncrementalLoadResult");
+        let incremental_result_hashing_event_kind = alloc_event_kind("IncrementalResultHashing");
+        let query_blocked_event_kind = alloc_event_kind("QueryBlocked");
+        let query_cache_hit_event_kind = alloc_event_kind("QueryCacheHit");
+        let artifact_size_event_kind = alloc_event_kind("ArtifactSize");
 
         let mut event_filter_mask = EventFilter::empty();
 
@@ -604,14 +656,24 @@ pub fn new(
                         .join(", ")
                 );
             }
+        } else if format == SelfProfileFormat::Summary {
+            // The summary shows how often query results were found in the in-memory cache.
+            event_filter_mask = EventFilter::DEFAULT | EventFilter::QUERY_CACHE_HITS;
         } else {
             event_filter_mask = EventFilter::DEFAULT;
         }
 
+        let recorder = match format {
+            SelfProfileFormat::Raw => None,
+            SelfProfileFormat::Chrome | SelfProfileFormat::Summary => {
+                Some(EventRecorder::new(format, path, crate_name))
+            }
+        };
+
         Ok(SelfProfiler {
             profiler,
             event_filter_mask,
-            string_cache: RwLock::new(FxHashMap::default()),
+            string_cache: RwLock::new(string_cache),
             query_event_kind,
             generic_activity_event_kind,
             incremental_load_result_event_kind,
@@ -619,6 +681,7 @@ pub fn new(
             query_blocked_event_kind,
             query_cache_hit_event_kind,
             artifact_size_event_kind,
+            recorder,
         })
     }
 
@@ -670,6 +733,18 @@ pub fn bulk_map_query_invocation_id_to_single_string<I>(&self, from: I, to: Stri
         self.profiler.bulk_map_virtual_to_single_concrete_string(from, to);
     }
 
+    /// Remembers which query the given invocations belong to. This is only needed when the events
+    /// are exported by the compiler itself, see `-Zself-profile-format`. Otherwise, the query
+    /// strings are mapped in the raw event data.
+    pub fn record_query_names<I>(&self, query_name: &'static str, query_invocation_ids: I)
+    where
+        I: Iterator<Item = QueryInvocationId>,
+    {
+        if let Some(recorder) = &self.recorder {
+            recorder.record_query_names(query_name, query_invocation_ids);
+        }
+    }
+
     pub fn query_key_recording_enabled(&self) -> bool {
         self.event_filter_mask.contains(EventFilter::QUERY_KEYS)
     }
@@ -679,8 +754,23 @@ pub fn event_id_builder(&self) -> EventIdBuilder<'_> {
     }
 }
 
+impl Drop for SelfProfiler {
+    fn drop(&mut self) {
+        // The profiler is dropped together with the session, so all events have been recorded by
+        // now.
+        if let Some(recorder) = &mut self.recorder {
+            let string_cache = self.string_cache.read();
+            let strings = string_cache.iter().map(|(s, &id)| (id, &s[..])).collect();
+            recorder.export(&strings);
+        }
+    }
+}
+
 #[must_use]
-pub struct TimingGuard<'a>(Option<measureme::TimingGuard<'a>>);
+pub struct TimingGuard<'a> {
+    guard: Option<measureme::TimingGuard<'a>>,
+    recording: Option<RecordingGuard<'a>>,
+}
 
 impl<'a> TimingGuard<'a> {
     #[inline]
@@ -688,28 +778,49 @@ pub fn start(
         profiler: &'a SelfProfiler,
         event_kind: StringId,
         event_id: EventId,
+    ) -> TimingGuard<'a> {
+        let event_label = EventLabel::String(event_id.to_string_id());
+        TimingGuard::start_with_label(profiler, event_kind, event_id, event_label)
+    }
+
+    /// Like `start`, but with the label that `event_id` was built from, which is what exported
+    /// events are named after.
+    #[inline]
+    fn start_with_label(
+        profiler: &'a SelfProfiler,
+        event_kind: StringId,
+        event_id: EventId,
+        event_label: EventLabel,
     ) -> TimingGuard<'a> {
         let thread_id = get_thread_id();
         let raw_profiler = &profiler.profiler;
         let timing_guard =
             raw_profiler.start_recording_interval_event(event_kind, event_id, thread_id);
-        TimingGuard(Some(timing_guard))
+        let recording = profiler
+            .recorder
+            .as_ref()
+            .map(|recorder| recorder.start_interval(event_kind, event_label, thread_id));
+        TimingGuard { guard: Some(timing_guard), recording }
     }
 
     #[inline]
     pub fn finish_with_query_invocation_id(self, query_invocation_id: QueryInvocationId) {
-        if let Some(guard) = self.0 {
+        let TimingGuard { guard, recording } = self;
+        if let Some(guard) = guard {
             cold_path(|| {
                 let event_id = StringId::new_virtual(query_invocation_id.0);
                 let event_id = EventId::from_virtual(event_id);
                 guard.finish_with_override_event_id(event_id);
+                if let Some(mut recording) = recording {
+                    recording.set_query_invocation_id(query_invocation_id);
+                }
             });
         }
     }
 
     #[inline]
     pub fn none() -> TimingGuard<'a> {
-        TimingGuard(None)
+        TimingGuard { guard: None, recording: None }
     }
 
     #[inline(always)]
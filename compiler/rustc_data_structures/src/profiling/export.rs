@@ -0,0 +1,368 @@
+//! Exports the events recorded by the self-profiler in formats that can be used without the
+//! `measureme` tools, see `-Zself-profile-format`: a Chrome trace-event file, or a summary of the
+//! items that took the most time, printed to stderr.
+//!
+//! To do so, the events are kept in memory in addition to being written to the raw profiling
+//! data, in a buffer per thread so that recording them does not contend on a lock. They are only
+//! looked at once the profiler is dropped. By then, the query invocations have
+//! been mapped to the names of their queries by `SelfProfiler::record_query_names`.
+
+use super::{QueryInvocationId, SelfProfileFormat};
+use crate::fx::{FxHashMap, FxIndexMap};
+
+use measureme::StringId;
+use parking_lot::Mutex;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, Instant};
+use thread_local::ThreadLocal;
+
+/// The number of items listed by `-Zself-profile-format=summary`.
+const SUMMARY_LEN: usize = 20;
+
+/// What an event is about.
+#[derive(Clone, Copy)]
+pub(super) enum EventLabel {
+    /// A string from the profiler's string cache, like the name of a generic activity.
+    String(StringId),
+    /// A query invocation, whose query is only known once its name has been recorded.
+    Query(QueryInvocationId),
+}
+
+struct RecordedEvent {
+    kind: StringId,
+    label: EventLabel,
+    thread_id: u32,
+    start: Instant,
+    /// `None` for instant events, like query cache hits.
+    end: Option<Instant>,
+}
+
+/// Keeps the events recorded by the self-profiler in memory, so that they can be exported when
+/// profiling ends.
+pub(super) struct EventRecorder {
+    format: SelfProfileFormat,
+    /// The path of the raw profiling data. The Chrome trace is written next to it.
+    path: PathBuf,
+    crate_name: String,
+    start: Instant,
+    /// The events recorded by each thread.
+    events: ThreadLocal<RefCell<Vec<RecordedEvent>>>,
+    query_names: Mutex<FxHashMap<u32, &'static str>>,
+}
+
+impl EventRecorder {
+    pub(super) fn new(format: SelfProfileFormat, path: PathBuf, crate_name: &str) -> Self {
+        EventRecorder {
+            format,
+            path,
+            crate_name: crate_name.to_owned(),
+            start: Instant::now(),
+            events: ThreadLocal::new(),
+            query_names: Mutex::new(FxHashMap::default()),
+        }
+    }
+
+    pub(super) fn start_interval(
+        &self,
+        kind: StringId,
+        label: EventLabel,
+        thread_id: u32,
+    ) -> RecordingGuard<'_> {
+        RecordingGuard { recorder: self, kind, label, thread_id, start: Instant::now() }
+    }
+
+    pub(super) fn record_instant(&self, kind: StringId, label: EventLabel, thread_id: u32) {
+        let event = RecordedEvent { kind, label, thread_id, start: Instant::now(), end: None };
+        self.push(event);
+    }
+
+    fn push(&self, event: RecordedEvent) {
+        self.events.get_or_default().borrow_mut().push(event);
+    }
+
+    pub(super) fn record_query_names<I>(&self, query_name: &'static str, query_invocation_ids: I)
+    where
+        I: Iterator<Item = QueryInvocationId>,
+    {
+        let mut query_names = self.query_names.lock();
+        query_names.extend(query_invocation_ids.map(|id| (id.0, query_name)));
+    }
+
+    /// Writes the recorded events in the requested format. `strings` maps the `StringId`s of the
+    /// profiler's string cache back to their contents.
+    pub(super) fn export(&mut self, strings: &FxHashMap<StringId, &str>) {
+        let query_names = self.query_names.get_mut();
+        let mut events: Vec<_> = self
+            .events
+            .iter_mut()
+            .flat_map(|events| events.get_mut().iter())
+            .map(|event| {
+                let name = match event.label {
+                    EventLabel::String(id) => strings.get(&id).copied(),
+                    EventLabel::Query(id) => query_names.get(&id.0).copied(),
+                };
+                Event {
+                    kind: strings.get(&event.kind).copied().unwrap_or("<unknown>"),
+                    name: name.unwrap_or("<unknown>"),
+                    thread_id: event.thread_id,
+                    start: event.start - self.start,
+                    end: event.end.map(|end| end - self.start),
+                }
+            })
+            .collect();
+        // Keep the output independent of which thread's buffer came first.
+        events.sort_by_key(|event| (event.start, event.thread_id));
+
+        match self.format {
+            SelfProfileFormat::Raw => {}
+            SelfProfileFormat::Chrome => {
+                let path = self.path.with_extension("json");
+                let result = File::create(&path).and_then(|file| {
+                    let mut file = BufWriter::new(file);
+                    write_chrome_trace(&mut file, &self.crate_name, process::id(), &events)
+                });
+                if let Err(e) = result {
+                    eprintln!(
+                        "warning: failed to write self-profile trace to `{}`: {}",
+                        path.display(),
+                        e
+                    );
+                }
+            }
+            SelfProfileFormat::Summary => {
+                // There is nothing sensible to do if stderr is gone.
+                let _ = write_summary(&mut io::stderr().lock(), &self.crate_name, &events);
+            }
+        }
+    }
+}
+
+/// Records an interval event when dropped.
+pub(super) struct RecordingGuard<'a> {
+    recorder: &'a EventRecorder,
+    kind: StringId,
+    label: EventLabel,
+    thread_id: u32,
+    start: Instant,
+}
+
+impl RecordingGuard<'_> {
+    pub(super) fn set_query_invocation_id(&mut self, query_invocation_id: QueryInvocationId) {
+        self.label = EventLabel::Query(query_invocation_id);
+    }
+}
+
+impl Drop for RecordingGuard<'_> {
+    fn drop(&mut self) {
+        let event = RecordedEvent {
+            kind: self.kind,
+            label: self.label,
+            thread_id: self.thread_id,
+            start: self.start,
+            end: Some(Instant::now()),
+        };
+        self.recorder.push(event);
+    }
+}
+
+/// A recorded event with its names resolved, and timestamps relative to the start of profiling.
+pub(super) struct Event<'a> {
+    pub kind: &'a str,
+    pub name: &'a str,
+    pub thread_id: u32,
+    pub start: Duration,
+    pub end: Option<Duration>,
+}
+
+/// A string quoted and escaped for use in JSON.
+pub(super) struct JsonStr<'a>(pub &'a str);
+
+impl fmt::Display for JsonStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1000.0
+}
+
+/// Writes `events` in the Chrome trace-event format. Each thread gets its own track, on which
+/// nested events, like queries invoked by other queries, are shown below each other.
+pub(super) fn write_chrome_trace<W: Write>(
+    w: &mut W,
+    crate_name: &str,
+    pid: u32,
+    events: &[Event<'_>],
+) -> io::Result<()> {
+    write!(
+        w,
+        r#"{{"traceEvents":[{{"name":"process_name","ph":"M","pid":{},"args":{{"name":{}}}}}"#,
+        pid,
+        JsonStr(&format!("rustc {}", crate_name)),
+    )?;
+    for event in events {
+        let (name, kind, ts) = (JsonStr(event.name), JsonStr(event.kind), micros(event.start));
+        match event.end {
+            Some(end) => write!(
+                w,
+                ",\n{{\"name\":{},\"cat\":{},\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":{},\"tid\":{}}}",
+                name,
+                kind,
+                ts,
+                micros(end.saturating_sub(event.start)),
+                pid,
+                event.thread_id,
+            )?,
+            None => write!(
+                w,
+                ",\n{{\"name\":{},\"cat\":{},\"ph\":\"i\",\"s\":\"t\",\"ts\":{:.3},\"pid\":{},\"tid\":{}}}",
+                name, kind, ts, pid, event.thread_id,
+            )?,
+        }
+    }
+    writeln!(w, "\n]}}")?;
+    w.flush()
+}
+
+#[derive(Default)]
+pub(super) struct SummaryRow<'a> {
+    pub name: &'a str,
+    /// The time spent in this item, not counting the items nested in it.
+    pub self_time: Duration,
+    /// The time spent in this item, including the items nested in it.
+    pub time: Duration,
+    /// The number of times this item was executed, for queries.
+    pub executions: usize,
+    /// The number of times the result of this query was found in the in-memory cache.
+    pub cache_hits: usize,
+    /// The number of times the result of this query was loaded from the incremental cache,
+    /// instead of being executed.
+    pub incr_loads: usize,
+}
+
+/// Aggregates `events` by name. The rows are sorted by self time, longest first.
+pub(super) fn summarize<'a>(events: &[Event<'a>]) -> Vec<SummaryRow<'a>> {
+    let mut rows = FxIndexMap::<&str, SummaryRow<'_>>::default();
+    let mut child_time = FxHashMap::<&str, Duration>::default();
+
+    // Go through the interval events of each thread such that an event comes right after the
+    // events it is nested in, and keep track of those in `stack`.
+    let mut intervals: Vec<_> = events.iter().filter(|event| event.end.is_some()).collect();
+    intervals.sort_by_key(|event| (event.thread_id, event.start, Reverse(event.end)));
+    let mut stack: Vec<&Event<'_>> = Vec::new();
+    for event in intervals {
+        while stack.last().map_or(false, |parent| {
+            parent.thread_id != event.thread_id || parent.end.unwrap() <= event.start
+        }) {
+            stack.pop();
+        }
+
+        let duration = event.end.unwrap().saturating_sub(event.start);
+        if let Some(parent) = stack.last() {
+            *child_time.entry(parent.name).or_default() += duration;
+        }
+        stack.push(event);
+
+        let row = rows.entry(event.name).or_default();
+        row.time += duration;
+        match event.kind {
+            "Query" => row.executions += 1,
+            "IncrementalLoadResult" => row.incr_loads += 1,
+            _ => {}
+        }
+    }
+    for event in events.iter().filter(|event| event.end.is_none()) {
+        if event.kind == "QueryCacheHit" {
+            rows.entry(event.name).or_default().cache_hits += 1;
+        }
+    }
+
+    let mut rows: Vec<_> = rows
+        .into_iter()
+        .map(|(name, row)| {
+            let child_time = child_time.get(name).copied().unwrap_or_default();
+            SummaryRow { name, self_time: row.time.saturating_sub(child_time), ..row }
+        })
+        .collect();
+    rows.sort_by_key(|row| Reverse(row.self_time));
+    rows
+}
+
+/// Writes a table of the items of `events` that took the most time.
+pub(super) fn write_summary<W: Write>(
+    w: &mut W,
+    crate_name: &str,
+    events: &[Event<'_>],
+) -> io::Result<()> {
+    let rows = summarize(events);
+    let total: Duration = rows.iter().map(|row| row.self_time).sum();
+    let shown = &rows[..rows.len().min(SUMMARY_LEN)];
+    let name_width = shown.iter().map(|row| row.name.len()).max().unwrap_or(0).max("Item".len());
+
+    writeln!(
+        w,
+        "self-profile summary for `{}`: {} of {} items, sorted by self time",
+        crate_name,
+        shown.len(),
+        rows.len()
+    )?;
+    writeln!(
+        w,
+        "{:<name_width$}  {:>12}  {:>10}  {:>12}  {:>10}  {:>10}  {:>11}  {:>14}",
+        "Item",
+        "Self time",
+        "% of total",
+        "Time",
+        "Executions",
+        "Cache hits",
+        "Incr. loads",
+        "Incr. hit rate",
+    )?;
+    for row in shown {
+        let percent = if total.is_zero() {
+            0.0
+        } else {
+            row.self_time.as_secs_f64() * 100.0 / total.as_secs_f64()
+        };
+        let hit_rate = if row.incr_loads == 0 {
+            "-".to_owned()
+        } else {
+            let rate = row.incr_loads as f64 * 100.0 / (row.incr_loads + row.executions) as f64;
+            format!("{:.1}%", rate)
+        };
+        writeln!(
+            w,
+            "{:<name_width$}  {:>12}  {:>9.2}%  {:>12}  {:>10}  {:>10}  {:>11}  {:>14}",
+            row.name,
+            format!("{:.3?}", row.self_time),
+            percent,
+            format!("{:.3?}", row.time),
+            row.executions,
+            row.cache_hits,
+            row.incr_loads,
+            hit_rate,
+        )?;
+    }
+    writeln!(w, "total self time: {:.3?}", total)
+}
@@ -0,0 +1,73 @@
+use super::export::{summarize, write_chrome_trace, Event, JsonStr};
+use std::time::Duration;
+
+fn interval<'a>(kind: &'a str, name: &'a str, thread_id: u32, start: u64, end: u64) -> Event<'a> {
+    Event {
+        kind,
+        name,
+        thread_id,
+        start: Duration::from_micros(start),
+        end: Some(Duration::from_micros(end)),
+    }
+}
+
+fn instant<'a>(kind: &'a str, name: &'a str, thread_id: u32, start: u64) -> Event<'a> {
+    Event { kind, name, thread_id, start: Duration::from_micros(start), end: None }
+}
+
+#[test]
+fn test_summarize_self_time() {
+    let events = [
+        interval("GenericActivity", "analysis", 1, 0, 100),
+        interval("Query", "typeck", 1, 10, 50),
+        interval("Query", "type_of", 1, 20, 30),
+        interval("Query", "typeck", 1, 60, 70),
+        // Events on other threads are not nested in the events of this thread.
+        interval("GenericActivity", "codegen_module", 2, 10, 90),
+    ];
+    let rows = summarize(&events);
+
+    let row = |name| rows.iter().find(|row| row.name == name).unwrap();
+    assert_eq!(row("analysis").self_time, Duration::from_micros(50));
+    assert_eq!(row("analysis").time, Duration::from_micros(100));
+    assert_eq!(row("typeck").self_time, Duration::from_micros(40));
+    assert_eq!(row("typeck").executions, 2);
+    assert_eq!(row("type_of").self_time, Duration::from_micros(10));
+    assert_eq!(row("codegen_module").self_time, Duration::from_micros(80));
+
+    let names: Vec<_> = rows.iter().map(|row| row.name).collect();
+    assert_eq!(names, ["codegen_module", "analysis", "typeck", "type_of"]);
+}
+
+#[test]
+fn test_summarize_recursion() {
+    let events = [
+        interval("Query", "eval_to_const_value_raw", 1, 0, 30),
+        interval("Query", "eval_to_const_value_raw", 1, 10, 20),
+    ];
+    let rows = summarize(&events);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].self_time, Duration::from_micros(30));
+    assert_eq!(rows[0].executions, 2);
+}
+
+#[test]
+fn test_json_str() {
+    assert_eq!(JsonStr("typeck").to_string(), r#""typeck""#);
+    assert_eq!(JsonStr("a \"b\" \\ c\n\u{1}").to_string(), r#""a \"b\" \\ c\n\u0001""#);
+}
+
+#[test]
+fn test_chrome_trace() {
+    let events = [interval("Query", "typeck", 3, 1, 3), instant("QueryCacheHit", "type_of", 3, 2)];
+    let mut trace = Vec::new();
+    write_chrome_trace(&mut trace, "foo", 42, &events).unwrap();
+    assert_eq!(
+        String::from_utf8(trace).unwrap(),
+        r#"{"traceEvents":[{"name":"process_name","ph":"M","pid":42,"args":{"name":"rustc foo"}},
+{"name":"typeck","cat":"Query","ph":"X","ts":1.000,"dur":2.000,"pid":42,"tid":3},
+{"name":"type_of","cat":"QueryCacheHit","ph":"i","s":"t","ts":2.000,"pid":42,"tid":3}
+]}
+"#
+    );
+}
@@ -452,6 +452,11 @@ struct HandlerInner {
     ///
     /// [RFC-2383]: https://rust-lang.github.io/rfcs/2383-lint-reasons.html
     fulfilled_expectations: FxHashSet<LintExpectationId>,
+
+    /// Diagnostics that have been held back to be emitted in a deterministic order
+    /// once the parallel section that emitted them ends. `None` when no such section
+    /// is running. See [`Handler::emit_in_deterministic_order`].
+    held_diagnostics: Option<Vec<Diagnostic>>,
 }
 
 /// A key denoting where from a diagnostic was stashed.
@@ -593,6 +598,7 @@ pub fn with_emitter_and_flags(
                 check_unstable_expect_diagnostics: false,
                 unstable_expect_diagnostics: Vec::new(),
                 fulfilled_expectations: Default::default(),
+                held_diagnostics: None,
             }),
         }
     }
@@ -1025,6 +1031,37 @@ pub fn emit_diagnostic(&self, diagnostic: &mut Diagnostic) -> Option<ErrorGuaran
         self.inner.borrow_mut().emit_diagnostic(diagnostic)
     }
 
+    /// Runs `f`, holding back the diagnostics emitted while it runs and emitting them
+    /// sorted by their primary span once it returns (or unwinds). This makes the output
+    /// of work that is spread over several threads independent of thread scheduling.
+    ///
+    /// Only the printing is deferred: error counts, deduplication and stashing behave as
+    /// usual. Fatal errors and bugs are printed right away, after everything held so far.
+    pub fn emit_in_deterministic_order<R>(&self, f: impl FnOnce() -> R) -> R {
+        struct ReleaseOnDrop<'a>(&'a Handler);
+
+        impl Drop for ReleaseOnDrop<'_> {
+            fn drop(&mut self) {
+                let mut inner = self.0.inner.borrow_mut();
+                inner.release_held_diagnostics();
+                inner.held_diagnostics = None;
+            }
+        }
+
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.held_diagnostics.is_some() {
+                // Nested parallel sections are ordered by the outermost one.
+                drop(inner);
+                return f();
+            }
+            inner.held_diagnostics = Some(Vec::new());
+        }
+
+        let _release = ReleaseOnDrop(self);
+        f()
+    }
+
     fn emit_diag_at_span(
         &self,
         mut diag: Diagnostic,
@@ -1108,6 +1145,19 @@ fn force_print_diagnostic(&mut self, mut db: Diagnostic) {
         self.emitter.emit_diagnostic(&mut db);
     }
 
+    /// Prints the diagnostics held back by [`Handler::emit_in_deterministic_order`] so far,
+    /// ordered by primary span and then by message.
+    fn release_held_diagnostics(&mut self) {
+        let Some(held) = &mut self.held_diagnostics else { return };
+        let mut held = std::mem::take(held);
+        held.sort_by_cached_key(|diagnostic| {
+            (diagnostic.span.primary_span(), format!("{:?}", diagnostic.message))
+        });
+        for diagnostic in &held {
+            self.emitter.emit_diagnostic(diagnostic);
+        }
+    }
+
     /// Emit all stashed diagnostics.
     fn emit_stashed_diagnostics(&mut self) -> Option<ErrorGuaranteed> {
         let has_errors = self.has_errors();
@@ -1217,7 +1267,15 @@ fn emit_diagnostic(&mut self, diagnostic: &mut Diagnostic) -> Option<ErrorGuaran
 
             diagnostic.children.drain_filter(already_emitted_sub).for_each(|_| {});
 
-            self.emitter.emit_diagnostic(&diagnostic);
+            match &mut self.held_diagnostics {
+                Some(held) if !matches!(diagnostic.level, Level::Bug | Level::Fatal) => {
+                    held.push(diagnostic.clone());
+                }
+                _ => {
+                    self.release_held_diagnostics();
+                    self.emitter.emit_diagnostic(&diagnostic);
+                }
+            }
             if diagnostic.is_error() {
                 self.deduplicated_err_count += 1;
             } else if let Warning(_) = diagnostic.level {
@@ -457,6 +457,8 @@ pub fn set(&self, features: &mut Features, span: Span) {
     /// Allows function attribute `#[no_coverage]`, to bypass coverage
     /// instrumentation of that function.
     (active, no_coverage, "1.53.0", Some(84605), None),
+    /// Allows `#[no_panic]`, which checks that a function cannot reach a panic.
+    (active, no_panic, "1.65.0", None, None),
     /// Allows the use of `no_sanitize` attribute.
     (active, no_sanitize, "1.42.0", Some(39699), None),
     /// Allows using the `non_exhaustive_omitted_patterns` lint.
@@ -353,6 +353,10 @@ pub struct BuiltinAttribute {
         const_eval_limit, CrateLevel, template!(NameValueStr: "N"), ErrorFollowing,
         const_eval_limit, experimental!(const_eval_limit)
     ),
+    gated!(
+        const_eval_memory_limit, CrateLevel, template!(NameValueStr: "N"), ErrorFollowing,
+        const_eval_limit, experimental!(const_eval_memory_limit)
+    ),
     gated!(
         move_size_limit, CrateLevel, template!(NameValueStr: "N"), ErrorFollowing,
         large_assignments, experimental!(move_size_limit)
@@ -471,6 +475,7 @@ pub struct BuiltinAttribute {
         cmse_nonsecure_entry, Normal, template!(Word), WarnFollowing,
         experimental!(cmse_nonsecure_entry)
     ),
+    gated!(no_panic, Normal, template!(Word), WarnFollowing, experimental!(no_panic)),
     // RFC 2632
     gated!(
         const_trait, Normal, template!(Word), WarnFollowing, const_trait_impl,
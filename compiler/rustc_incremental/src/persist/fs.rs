@@ -89,6 +89,13 @@
 //! so, it will also place a read lock on that the respective session directory
 //! so that it won't be deleted while the metadata hashes are loaded.
 //!
+//! ## Shared cache
+//!
+//! With `-Zincremental-shared-cache`, a session that finds no finalized
+//! session directory to copy from is seeded from a content-addressed store
+//! shared with other build directories instead, and finalized sessions are
+//! published to that store. See the `shared_cache` module for details.
+//!
 //! ## Preconditions
 //!
 //! This system relies on two features being available in the file system in
@@ -118,6 +125,8 @@
 
 use rand::{thread_rng, RngCore};
 
+use super::shared_cache;
+
 #[cfg(test)]
 mod tests;
 
@@ -256,6 +265,14 @@ pub fn prepare_session_directory(
         let source_directory = find_source_directory(&crate_dir, &source_directories_already_tried);
 
         let Some(source_directory) = source_directory else {
+            // There's nowhere to copy from locally, but there may be a shared
+            // cache to seed the session directory from.
+            if shared_cache::seed_session_directory(sess, &crate_dir, &session_dir) {
+                debug!("seeded session directory from the shared cache");
+                sess.init_incr_comp_session(session_dir, directory_lock, true);
+                return Ok(());
+            }
+
             // There's nowhere to copy from, we're done
             debug!(
                 "no source directory found. Continuing with empty session \
@@ -376,7 +393,11 @@ pub fn finalize_session_directory(sess: &Session, svh: Svh) {
             debug!("finalize_session_directory() - directory renamed successfully");
 
             // This unlocks the directory
-            sess.finalize_incr_comp_session(new_path);
+            sess.finalize_incr_comp_session(new_path.clone());
+
+            if !sess.has_errors_or_delayed_span_bugs() {
+                shared_cache::publish_session_directory(sess, &new_path);
+            }
         }
         Err(e) => {
             // Warn about the error. However, no need to abort compilation now.
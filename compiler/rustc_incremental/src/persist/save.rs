@@ -43,6 +43,10 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
             tcx.dep_graph.print_incremental_info()
         }
 
+        if sess.opts.unstable_opts.incremental_explain {
+            tcx.dep_graph.print_incremental_explanation()
+        }
+
         join(
             move || {
                 sess.time("incr_comp_persist_result_cache", || {
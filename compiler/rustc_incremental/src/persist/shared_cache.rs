@@ -0,0 +1,532 @@
+//! A content-addressed store for incremental compilation sessions that can be
+//! shared between build directories, worktrees and machines. It is enabled
+//! with `-Zincremental-shared-cache=<dir>`.
+//!
+//! The store has the following layout:
+//!
+//! ```text
+//! {store}/objects/{xx}/{yyyy...}   a file from a session directory, named by
+//!                                  the fingerprint of its contents
+//! {store}/manifests/{crate-dir}-{options-hash}
+//!                                  the files making up the latest published
+//!                                  session of a crate
+//! {store}/manifests/{crate-dir}-{options-hash}.used
+//!                                  rewritten whenever the manifest seeds a
+//!                                  session directory
+//! {store}/tmp/                     files that are still being written
+//! {store}/gc.lock                  see "Synchronization" below
+//! {store}/last-gc                  modified whenever the store is collected
+//! ```
+//!
+//! Since files are stored under the fingerprint of their contents, dep-graphs,
+//! query caches and object files that are identical across sessions or crates
+//! are only stored once. A manifest lists the file names of a session
+//! directory together with the fingerprints of their contents. It is named
+//! after the crate directory (that is, the crate name and `StableCrateId`) and
+//! the hash of the tracked command-line options, so that builds of the same
+//! crate with different options don't replace each other's entries.
+//!
+//! When the compiler starts a session for which there is no finalized session
+//! directory to copy from, it seeds the new session directory from the
+//! manifest instead. When a session directory has been finalized, its files
+//! are published to the store and its manifest replaces the previous one.
+//! Whether seeded files can actually be reused is still decided by the usual
+//! validation of the dep-graph and the work products, so a stale entry only
+//! costs the time spent copying it.
+//!
+//! Files are always copied into and out of the store, never hard-linked: the
+//! files of a session directory end up hard-linked into later sessions and
+//! into the output directory, and anything writing to one of those in place
+//! would otherwise corrupt the store. Seeded files are checked against their
+//! fingerprint, so an object that was corrupted anyway is not used.
+//!
+//! To avoid hashing every file of every finalized session, a session directory
+//! contains an index (`INDEX_FILENAME`) listing the fingerprint of each of its
+//! files together with the size and modification time the file had when it
+//! was hashed. The index is carried over to the next session along with the
+//! files themselves, so only files that have been rewritten since are hashed
+//! again when the session is published.
+//!
+//! ## Synchronization
+//!
+//! Objects and manifests are written to `tmp` first and then renamed into
+//! place, so readers never see partially written files. Concurrent writers of
+//! the same object are harmless since they write the same contents.
+//!
+//! A manifest is only ever replaced by publishing a newer session. Seeding a
+//! session directory from it rewrites its `.used` marker instead, so that a
+//! process seeding from an old manifest can't undo another process publishing
+//! a new one in the meantime. Garbage collection deletes manifests that have
+//! neither been published nor used for `MANIFEST_EXPIRY`, as told by the
+//! modification times of the manifest and of its marker, and then all objects
+//! that no manifest refers to.
+//! Every process that reads or writes the store holds a shared lock on
+//! `gc.lock`, and garbage collection only runs while holding an exclusive lock
+//! on it. This way, no objects are deleted between a process reading a
+//! manifest and linking the objects it lists. A process that cannot acquire
+//! its lock does not wait, it just doesn't use the store this time.
+
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::stable_hasher::StableHasher;
+use rustc_data_structures::{base_n, flock};
+use rustc_session::Session;
+
+use std::fs as std_fs;
+use std::hash::Hasher;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::{thread_rng, RngCore};
+
+use super::fs::lock_file_path;
+
+#[cfg(test)]
+mod tests;
+
+const OBJECTS_DIR: &str = "objects";
+const MANIFESTS_DIR: &str = "manifests";
+const TMP_DIR: &str = "tmp";
+const GC_LOCK_FILENAME: &str = "gc.lock";
+const LAST_GC_FILENAME: &str = "last-gc";
+const INDEX_FILENAME: &str = "shared-cache-index";
+const USED_MARKER_SUFFIX: &str = ".used";
+
+/// Manifests that have been neither published nor used for this long are
+/// deleted by garbage collection.
+const MANIFEST_EXPIRY: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// The minimum time between two garbage collections of the store.
+const GC_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// An open store, locked against garbage collection.
+struct Store<'a> {
+    sess: &'a Session,
+    root: &'a Path,
+    _lock: flock::Lock,
+}
+
+impl<'a> Store<'a> {
+    /// Opens the store configured with `-Zincremental-shared-cache`. Returns
+    /// `None` if there is no store or if it is being garbage collected.
+    fn open(sess: &'a Session) -> Option<Self> {
+        let root = sess.opts.unstable_opts.incremental_shared_cache.as_deref()?;
+
+        for dir in [OBJECTS_DIR, MANIFESTS_DIR, TMP_DIR] {
+            if let Err(err) = std_fs::create_dir_all(root.join(dir)) {
+                sess.warn(&format!(
+                    "could not create shared incremental compilation cache `{}`: {}",
+                    root.display(),
+                    err
+                ));
+                return None;
+            }
+        }
+
+        match flock::Lock::new(
+            &root.join(GC_LOCK_FILENAME),
+            false, // don't wait
+            true,  // create the lock file
+            false, // shared, only garbage collection locks exclusively
+        ) {
+            Ok(lock) => Some(Store { sess, root, _lock: lock }),
+            Err(err) => {
+                debug!("could not lock shared incremental compilation cache: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Returns the path of the manifest for the crate whose incremental
+    /// compilation files are kept in `crate_dir`.
+    fn manifest_path(&self, crate_dir: &Path) -> PathBuf {
+        let crate_dir_name = crate_dir.file_name().unwrap().to_string_lossy();
+        let options_hash = self.sess.opts.dep_tracking_hash(false);
+        let manifest_name = format!(
+            "{}-{}",
+            crate_dir_name,
+            base_n::encode(options_hash as u128, base_n::CASE_INSENSITIVE)
+        );
+        self.root.join(MANIFESTS_DIR).join(manifest_name)
+    }
+
+    fn object_path(&self, object_name: &str) -> PathBuf {
+        let (prefix, rest) = object_name.split_at(2);
+        self.root.join(OBJECTS_DIR).join(prefix).join(rest)
+    }
+
+    fn tmp_path(&self) -> PathBuf {
+        let name = base_n::encode(thread_rng().next_u64() as u128, base_n::CASE_INSENSITIVE);
+        self.root.join(TMP_DIR).join(name)
+    }
+
+    /// Atomically replaces `path` with a file containing `contents`.
+    fn write_file(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let tmp_path = self.tmp_path();
+        std_fs::write(&tmp_path, contents)?;
+        self.rename_into_place(&tmp_path, path)
+    }
+
+    fn rename_into_place(&self, tmp_path: &Path, path: &Path) -> io::Result<()> {
+        std_fs::rename(tmp_path, path).map_err(|err| {
+            let _ = std_fs::remove_file(tmp_path);
+            err
+        })
+    }
+
+    /// Copies the file at `path` into the store as `object_name`, unless the
+    /// store already contains that object. Returns whether it has been added.
+    fn store_object(&self, path: &Path, object_name: &str) -> io::Result<bool> {
+        let object_path = self.object_path(object_name);
+        if object_path.exists() {
+            return Ok(false);
+        }
+
+        std_fs::create_dir_all(object_path.parent().unwrap())?;
+        let tmp_path = self.tmp_path();
+        std_fs::copy(path, &tmp_path)?;
+        self.rename_into_place(&tmp_path, &object_path)?;
+        Ok(true)
+    }
+
+    /// Reads the object `name`, checking that its contents still have the
+    /// fingerprint it is named after.
+    fn read_object(&self, name: &str) -> io::Result<Vec<u8>> {
+        let object_path = self.object_path(name);
+        let contents = std_fs::read(&object_path)?;
+        if object_name(&contents) != name {
+            // Remove the corrupted object, so that the next session that
+            // produces this file publishes it again.
+            let _ = std_fs::remove_file(&object_path);
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "the contents don't match the fingerprint of the object",
+            ));
+        }
+        Ok(contents)
+    }
+}
+
+/// Returns the name under which a file with the given contents is stored.
+fn object_name(contents: &[u8]) -> String {
+    let mut hasher = StableHasher::new();
+    hasher.write(contents);
+    let (a, b) = hasher.finish::<Fingerprint>().as_value();
+    format!("{:016x}{:016x}", a, b)
+}
+
+fn is_object_name(name: &str) -> bool {
+    name.len() == 32 && name.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Parses a manifest into the pairs of file names and object names it
+/// consists of. Returns `None` if the manifest is malformed.
+fn parse_manifest(manifest: &str) -> Option<Vec<(&str, &str)>> {
+    manifest
+        .lines()
+        .map(|line| {
+            let (object_name, file_name) = line.split_once(' ')?;
+            let is_plain_file_name = !file_name.is_empty()
+                && file_name != "."
+                && file_name != ".."
+                && !file_name.contains(|c| c == '/' || c == '\\');
+            (is_object_name(object_name) && is_plain_file_name).then_some((file_name, object_name))
+        })
+        .collect()
+}
+
+/// The size and modification time (in nanoseconds since the Unix epoch) of a
+/// file, used to tell whether it has changed since it was last hashed.
+type FileStamp = (u64, u128);
+
+fn file_stamp(metadata: &std_fs::Metadata) -> Option<FileStamp> {
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((metadata.len(), modified.as_nanos()))
+}
+
+/// Parses the index of a session directory into a map from file names to the
+/// object name and stamp the file had when it was hashed. The index is only
+/// a cache, so malformed lines are ignored.
+fn parse_index(index: &str) -> FxHashMap<&str, (&str, FileStamp)> {
+    index
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(4, ' ');
+            let object_name = parts.next().filter(|name| is_object_name(name))?;
+            let len = parts.next()?.parse().ok()?;
+            let modified = parts.next()?.parse().ok()?;
+            let file_name = parts.next()?;
+            Some((file_name, (object_name, (len, modified))))
+        })
+        .collect()
+}
+
+fn push_index_entry(index: &mut String, file_name: &str, object_name: &str, stamp: FileStamp) {
+    index.push_str(&format!("{} {} {} {}\n", object_name, stamp.0, stamp.1, file_name));
+}
+
+/// Atomically replaces the index of `session_dir`. The old index may be hard
+/// linked into another session directory, so it must not be written in place.
+fn write_index(session_dir: &Path, index: &str) {
+    let tmp_path = session_dir.join(format!("{}.tmp", INDEX_FILENAME));
+    let result = std_fs::write(&tmp_path, index)
+        .and_then(|()| std_fs::rename(&tmp_path, session_dir.join(INDEX_FILENAME)));
+    if let Err(err) = result {
+        debug!("could not write shared cache index of `{}`: {}", session_dir.display(), err);
+        let _ = std_fs::remove_file(&tmp_path);
+    }
+}
+
+/// Returns the path of the file whose modification time tells when the
+/// manifest at `manifest_path` was last used.
+fn used_marker_path(manifest_path: &Path) -> PathBuf {
+    let mut path = manifest_path.as_os_str().to_owned();
+    path.push(USED_MARKER_SUFFIX);
+    PathBuf::from(path)
+}
+
+fn is_older_than(modified: SystemTime, age: Duration) -> bool {
+    SystemTime::now().duration_since(modified).map_or(false, |elapsed| elapsed > age)
+}
+
+/// Fills the empty session directory `session_dir` from the shared cache if it
+/// has an entry for the crate kept in `crate_dir`. Returns whether it did.
+pub(super) fn seed_session_directory(sess: &Session, crate_dir: &Path, session_dir: &Path) -> bool {
+    let Some(store) = Store::open(sess) else { return false };
+
+    let manifest_path = store.manifest_path(crate_dir);
+    let manifest = match std_fs::read_to_string(&manifest_path) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            if err.kind() != ErrorKind::NotFound {
+                sess.warn(&format!(
+                    "could not read shared incremental compilation cache manifest `{}`: {}",
+                    manifest_path.display(),
+                    err
+                ));
+            }
+            return false;
+        }
+    };
+    let Some(entries) = parse_manifest(&manifest) else {
+        sess.warn(&format!(
+            "ignoring malformed shared incremental compilation cache manifest `{}`",
+            manifest_path.display()
+        ));
+        return false;
+    };
+
+    let mut index = String::new();
+    for (i, &(file_name, object_name)) in entries.iter().enumerate() {
+        let file_path = session_dir.join(file_name);
+        let result = store.read_object(object_name).and_then(|contents| {
+            std_fs::write(&file_path, contents)?;
+            std_fs::metadata(&file_path)
+        });
+        let metadata = match result {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                sess.warn(&format!(
+                    "could not copy `{}` from the shared incremental compilation cache: {}",
+                    store.object_path(object_name).display(),
+                    err
+                ));
+
+                // Don't leave an incomplete session behind.
+                for &(file_name, _) in &entries[..=i] {
+                    let _ = std_fs::remove_file(session_dir.join(file_name));
+                }
+                return false;
+            }
+        };
+        if let Some(stamp) = file_stamp(&metadata) {
+            push_index_entry(&mut index, file_name, object_name, stamp);
+        }
+    }
+    write_index(session_dir, &index);
+
+    // Mark the manifest as recently used, so that it is not garbage collected.
+    // The manifest itself is left alone: another process may have published a
+    // newer one since we read it.
+    if let Err(err) = store.write_file(&used_marker_path(&manifest_path), &[]) {
+        debug!("could not mark manifest `{}` as used: {}", manifest_path.display(), err);
+    }
+
+    if sess.opts.unstable_opts.incremental_info {
+        eprintln!("[incremental] session directory: {} files from the shared cache", entries.len());
+    }
+
+    true
+}
+
+/// Publishes the files of the finalized session directory `session_dir` to the
+/// shared cache, making it the cache's entry for its crate.
+pub(super) fn publish_session_directory(sess: &Session, session_dir: &Path) {
+    let Some(store) = Store::open(sess) else { return };
+
+    // Keep the session directory from being garbage collected while we read
+    // from it, see `copy_files`.
+    let Ok(_session_lock) = flock::Lock::new(
+        &lock_file_path(session_dir),
+        false, // don't wait
+        false, // don't create
+        false,
+    ) else {
+        return;
+    };
+
+    let old_index = std_fs::read_to_string(session_dir.join(INDEX_FILENAME)).unwrap_or_default();
+    let hashed_files = parse_index(&old_index);
+
+    let mut manifest = String::new();
+    let mut index = String::new();
+    let mut files_hashed = 0;
+    let mut files_stored = 0;
+    let result = session_dir.read_dir().and_then(|entries| {
+        for entry in entries {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let Some(file_name) = file_name.to_str() else {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("file name `{}` is not valid UTF-8", file_name.to_string_lossy()),
+                ));
+            };
+
+            if file_name.starts_with(INDEX_FILENAME) {
+                continue;
+            }
+
+            // Only hash the files that changed since they were last hashed.
+            let path = entry.path();
+            let stamp = file_stamp(&entry.metadata()?);
+            let name = match hashed_files.get(file_name) {
+                Some(&(name, hashed_stamp)) if Some(hashed_stamp) == stamp => name.to_owned(),
+                _ => {
+                    files_hashed += 1;
+                    object_name(&std_fs::read(&path)?)
+                }
+            };
+
+            files_stored += store.store_object(&path, &name)? as usize;
+            manifest.push_str(&format!("{} {}\n", name, file_name));
+            if let Some(stamp) = stamp {
+                push_index_entry(&mut index, file_name, &name, stamp);
+            }
+        }
+
+        store.write_file(&store.manifest_path(session_dir.parent().unwrap()), manifest.as_bytes())
+    });
+
+    if let Err(err) = result {
+        sess.warn(&format!(
+            "could not publish incremental compilation session to the shared cache `{}`: {}",
+            store.root.display(),
+            err
+        ));
+        return;
+    }
+
+    write_index(session_dir, &index);
+
+    if sess.opts.unstable_opts.incremental_info {
+        eprintln!(
+            "[incremental] shared cache: {} files hashed, {} new files published",
+            files_hashed, files_stored
+        );
+    }
+
+    let root = store.root;
+    drop(store);
+    garbage_collect(sess, root);
+}
+
+/// Deletes expired manifests and the objects that are no longer referenced by
+/// any manifest, unless the store has been collected recently or is in use.
+fn garbage_collect(sess: &Session, root: &Path) {
+    let last_gc_path = root.join(LAST_GC_FILENAME);
+    if let Ok(last_gc) = std_fs::metadata(&last_gc_path).and_then(|m| m.modified()) {
+        if !is_older_than(last_gc, GC_INTERVAL) {
+            return;
+        }
+    }
+
+    let Ok(_lock) = flock::Lock::new(
+        &root.join(GC_LOCK_FILENAME),
+        false, // don't wait
+        true,  // create the lock file
+        true,  // exclusive
+    ) else {
+        return;
+    };
+
+    if let Err(err) = garbage_collect_store(root) {
+        sess.warn(&format!(
+            "failed to garbage collect shared incremental compilation cache `{}`: {}",
+            root.display(),
+            err
+        ));
+    }
+}
+
+fn garbage_collect_store(root: &Path) -> io::Result<()> {
+    std_fs::write(root.join(LAST_GC_FILENAME), [])?;
+
+    let mut live_objects = FxHashSet::default();
+    for entry in root.join(MANIFESTS_DIR).read_dir()? {
+        let entry = entry?;
+        let path = entry.path();
+        let marked_manifest = path.to_str().and_then(|p| p.strip_suffix(USED_MARKER_SUFFIX));
+        if let Some(manifest_path) = marked_manifest {
+            // Markers are deleted along with their manifest, or here if the
+            // manifest is already gone.
+            if !Path::new(manifest_path).exists() {
+                std_fs::remove_file(&path)?;
+            }
+            continue;
+        }
+
+        let marker_path = used_marker_path(&path);
+        let mut last_used = entry.metadata()?.modified()?;
+        if let Ok(marker_modified) = std_fs::metadata(&marker_path).and_then(|m| m.modified()) {
+            last_used = last_used.max(marker_modified);
+        }
+        if is_older_than(last_used, MANIFEST_EXPIRY) {
+            debug!("garbage_collect_store() - deleting expired manifest {}", path.display());
+            std_fs::remove_file(&path)?;
+            let _ = std_fs::remove_file(&marker_path);
+            continue;
+        }
+
+        let manifest = std_fs::read_to_string(&path)?;
+        match parse_manifest(&manifest) {
+            Some(entries) => {
+                live_objects.extend(entries.into_iter().map(|(_, object)| object.to_owned()))
+            }
+            None => std_fs::remove_file(&path)?,
+        }
+    }
+
+    for prefix_dir in root.join(OBJECTS_DIR).read_dir()? {
+        let prefix_dir = prefix_dir?;
+        let prefix = prefix_dir.file_name();
+        for object in prefix_dir.path().read_dir()? {
+            let object = object?;
+            let object_name =
+                format!("{}{}", prefix.to_string_lossy(), object.file_name().to_string_lossy());
+            if !live_objects.contains(&object_name) {
+                debug!("garbage_collect_store() - deleting object {}", object_name);
+                std_fs::remove_file(object.path())?;
+            }
+        }
+    }
+
+    // Everybody else writing to the store holds a shared lock, so whatever is
+    // left in `tmp` belongs to a process that died.
+    for entry in root.join(TMP_DIR).read_dir()? {
+        std_fs::remove_file(entry?.path())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+#[test]
+fn test_object_name() {
+    let name = object_name(b"contents");
+    assert!(is_object_name(&name));
+    assert_eq!(name, object_name(b"contents"));
+    assert_ne!(name, object_name(b"other contents"));
+    assert!(is_object_name(&object_name(b"")));
+}
+
+#[test]
+fn test_parse_manifest() {
+    let object = "0123456789abcdef0123456789abcdef";
+    let manifest = format!("{0} dep-graph.bin\n{0} foo.foo.1234-cgu.0.rcgu.o\n", object);
+    assert_eq!(
+        parse_manifest(&manifest),
+        Some(vec![("dep-graph.bin", object), ("foo.foo.1234-cgu.0.rcgu.o", object)])
+    );
+    assert_eq!(parse_manifest(""), Some(vec![]));
+}
+
+#[test]
+fn test_parse_malformed_manifest() {
+    let object = "0123456789abcdef0123456789abcdef";
+    assert_eq!(parse_manifest("dep-graph.bin"), None);
+    assert_eq!(parse_manifest("0123 dep-graph.bin"), None);
+    assert_eq!(parse_manifest(&format!("{} ../dep-graph.bin", object)), None);
+    assert_eq!(parse_manifest(&format!("{} ..", object)), None);
+    assert_eq!(parse_manifest(&format!("{} ", object)), None);
+    assert_eq!(parse_manifest(&format!("{} a\\b", object)), None);
+}
+
+#[test]
+fn test_parse_index() {
+    let object = "0123456789abcdef0123456789abcdef";
+    let mut index = String::new();
+    push_index_entry(&mut index, "dep-graph.bin", object, (12, 1_600_000_000_000_000_001));
+    push_index_entry(&mut index, "a file", object, (0, 0));
+    index.push_str("malformed\n");
+    index.push_str(&format!("{} twelve 0 query-cache.bin\n", object));
+
+    let parsed = parse_index(&index);
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed["dep-graph.bin"], (object, (12, 1_600_000_000_000_000_001)));
+    assert_eq!(parsed["a file"], (object, (0, 0)));
+}
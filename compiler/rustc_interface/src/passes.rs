@@ -581,6 +581,11 @@ fn write_out_deps(
         });
         files.extend(extra_tracked_files);
 
+        // The `--lint-config` file sets lint levels, so changing it must trigger a rebuild.
+        if let Some(lint_config) = &sess.opts.lint_config {
+            files.push(escape_dep_filename(&lint_config.path.display().to_string()));
+        }
+
         if sess.binary_dep_depinfo() {
             if let Some(ref backend) = sess.opts.unstable_opts.codegen_backend {
                 if backend.contains('.') {
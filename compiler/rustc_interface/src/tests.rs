@@ -2,6 +2,7 @@
 use crate::interface::parse_cfgspecs;
 
 use rustc_data_structures::fx::FxHashSet;
+use rustc_data_structures::profiling::SelfProfileFormat;
 use rustc_errors::{emitter::HumanReadableErrorType, registry, ColorConfig};
 use rustc_session::config::InstrumentCoverage;
 use rustc_session::config::Strip;
@@ -10,11 +11,12 @@
     rustc_optgroups, ErrorOutputType, ExternLocation, LocationDetail, Options, Passes,
 };
 use rustc_session::config::{
-    BranchProtection, Externs, OomStrategy, OutputType, OutputTypes, PAuthKey, PacRet,
-    ProcMacroExecutionStrategy, SymbolManglingVersion, WasiExecModel,
+    BranchProtection, Externs, MonoReportFormat, OomStrategy, OutputType, OutputTypes, PAuthKey,
+    PacRet, ProcMacroExecutionStrategy, SymbolManglingVersion, WasiExecModel,
 };
 use rustc_session::config::{CFGuard, ExternEntry, LinkerPluginLto, LtoCli, SwitchWithOptPath};
 use rustc_session::lint::Level;
+use rustc_session::lint_config::{LintConfig, LintConfigEntry};
 use rustc_session::search_paths::SearchPath;
 use rustc_session::utils::{CanonicalizedPath, NativeLib, NativeLibKind};
 use rustc_session::{build_session, getopts, DiagnosticOutput, Session};
@@ -286,6 +288,25 @@ fn test_lint_cap_hash_different() {
     assert_non_crate_hash_different(&v2, &v3);
 }
 
+#[test]
+fn test_lint_config_hash_different() {
+    let mut v1 = Options::default();
+    let mut v2 = Options::default();
+    let v3 = Options::default();
+
+    let lint_config = |level| LintConfig {
+        path: "lints.toml".into(),
+        lints: vec![LintConfigEntry { name: String::from("a"), level, reason: None }],
+        overrides: vec![],
+    };
+    v1.lint_config = Some(lint_config(Level::Deny));
+    v2.lint_config = Some(lint_config(Level::Allow));
+
+    assert_non_crate_hash_different(&v1, &v2);
+    assert_non_crate_hash_different(&v1, &v3);
+    assert_non_crate_hash_different(&v2, &v3);
+}
+
 #[test]
 fn test_search_paths_tracking_hash_different_order() {
     let mut v1 = Options::default();
@@ -645,6 +666,8 @@ macro_rules! untracked {
     // Make sure that changing an [UNTRACKED] option leaves the hash unchanged.
     // This list is in alphabetical order.
     untracked!(assert_incr_state, Some(String::from("loaded")));
+    untracked!(cgu_report, true);
+    untracked!(const_eval_trace, Some(String::from("TABLE")));
     untracked!(deduplicate_diagnostics, false);
     untracked!(dep_tasks, true);
     untracked!(dlltool, Some(PathBuf::from("custom_dlltool.exe")));
@@ -660,8 +683,10 @@ macro_rules! untracked {
     untracked!(future_incompat_test, true);
     untracked!(hir_stats, true);
     untracked!(identify_regions, true);
+    untracked!(incremental_explain, true);
     untracked!(incremental_ignore_spans, true);
     untracked!(incremental_info, true);
+    untracked!(incremental_shared_cache, Some(PathBuf::from("abc")));
     untracked!(incremental_verify_ich, true);
     untracked!(input_stats, true);
     untracked!(keep_hygiene_data, true);
@@ -671,6 +696,7 @@ macro_rules! untracked {
     untracked!(macro_backtrace, true);
     untracked!(meta_stats, true);
     untracked!(mir_pretty_relative_line_numbers, true);
+    untracked!(mono_report, Some(MonoReportFormat::Json));
     untracked!(nll_facts, true);
     untracked!(no_analysis, true);
     untracked!(no_interleave_lints, true);
@@ -686,10 +712,12 @@ macro_rules! untracked {
     untracked!(print_type_sizes, true);
     untracked!(proc_macro_backtrace, true);
     untracked!(proc_macro_execution_strategy, ProcMacroExecutionStrategy::CrossThread);
+    untracked!(public_api_hash, true);
     untracked!(query_dep_graph, true);
     untracked!(save_analysis, true);
     untracked!(self_profile, SwitchWithOptPath::Enabled(None));
     untracked!(self_profile_events, Some(vec![String::new()]));
+    untracked!(self_profile_format, SelfProfileFormat::Chrome);
     untracked!(span_debug, true);
     untracked!(span_free_formats, true);
     untracked!(temps_dir, Some(String::from("abc")));
@@ -736,6 +764,7 @@ macro_rules! tracked {
     tracked!(debug_macros, true);
     tracked!(dep_info_omit_d_target, true);
     tracked!(drop_tracking, true);
+    tracked!(explain_borrowck, Some(String::from("abc")));
     tracked!(export_executable_symbols, true);
     tracked!(dual_proc_macros, true);
     tracked!(dwarf_version, Some(5));
@@ -744,6 +773,7 @@ macro_rules! tracked {
     tracked!(force_unstable_if_unmarked, true);
     tracked!(fuel, Some(("abc".to_string(), 99)));
     tracked!(function_sections, Some(false));
+    tracked!(generics_hub, Some(PathBuf::from("abc")));
     tracked!(human_readable_cgu_names, true);
     tracked!(inline_in_all_cgus, Some(true));
     tracked!(inline_mir, Some(true));
@@ -772,8 +802,10 @@ macro_rules! tracked {
     tracked!(pick_stable_methods_before_any_unstable, false);
     tracked!(plt, Some(true));
     tracked!(polonius, true);
+    tracked!(polymorphize, true);
     tracked!(precise_enum_drop_elaboration, false);
     tracked!(print_fuel, Some("abc".to_string()));
+    tracked!(print_polymorphization, true);
     tracked!(profile, true);
     tracked!(profile_emit, Some(PathBuf::from("abc")));
     tracked!(profiler_runtime, "abc".to_string());
@@ -45,6 +45,7 @@
 
 use std::cell::Cell;
 use std::iter;
+use std::path::Path;
 use std::slice;
 
 /// Information about the registered lints.
@@ -323,6 +324,51 @@ pub fn check_lint_name_cmdline(
         lint_name: &str,
         level: Level,
         registered_tools: &RegisteredTools,
+    ) {
+        self.check_lint_name_outside_source(sess, lint_name, level, registered_tools, || {
+            format!(
+                "requested on the command line with `{} {}`",
+                match level {
+                    Level::Allow => "-A",
+                    Level::Warn => "-W",
+                    Level::ForceWarn(_) => "--force-warn",
+                    Level::Deny => "-D",
+                    Level::Forbid => "-F",
+                    Level::Expect(_) => {
+                        unreachable!("lints with the level of `expect` should not run this code");
+                    }
+                },
+                lint_name
+            )
+        })
+    }
+
+    /// Checks the validity of a lint name from the `--lint-config` file.
+    pub fn check_lint_name_config(
+        &self,
+        sess: &Session,
+        lint_name: &str,
+        level: Level,
+        registered_tools: &RegisteredTools,
+        config_path: &Path,
+    ) {
+        self.check_lint_name_outside_source(sess, lint_name, level, registered_tools, || {
+            format!(
+                "requested in the lint configuration `{}` with `{} = \"{}\"`",
+                config_path.display(),
+                lint_name,
+                level.as_str()
+            )
+        })
+    }
+
+    fn check_lint_name_outside_source(
+        &self,
+        sess: &Session,
+        lint_name: &str,
+        level: Level,
+        registered_tools: &RegisteredTools,
+        requested: impl FnOnce() -> String,
     ) {
         let (tool_name, lint_name_only) = parse_lint_and_tool_name(lint_name);
         if lint_name_only == crate::WARNINGS.name_lower() && matches!(level, Level::ForceWarn(_)) {
@@ -371,21 +417,7 @@ pub fn check_lint_name_cmdline(
         };
 
         if let Some(mut db) = db {
-            let msg = format!(
-                "requested on the command line with `{} {}`",
-                match level {
-                    Level::Allow => "-A",
-                    Level::Warn => "-W",
-                    Level::ForceWarn(_) => "--force-warn",
-                    Level::Deny => "-D",
-                    Level::Forbid => "-F",
-                    Level::Expect(_) => {
-                        unreachable!("lints with the level of `expect` should not run this code");
-                    }
-                },
-                lint_name
-            );
-            db.note(&msg);
+            db.note(&requested());
             db.emit();
         }
     }
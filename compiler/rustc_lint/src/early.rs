@@ -81,11 +81,21 @@ fn visit_param(&mut self, param: &'a ast::Param) {
     }
 
     fn visit_item(&mut self, it: &'a ast::Item) {
+        // A module loaded from another file may be covered by `--lint-config` overrides.
+        let overrides = match &it.kind {
+            ast::ItemKind::Mod(_, ast::ModKind::Loaded(_, ast::Inline::No, spans)) => {
+                Some(self.context.builder.push_lint_config_overrides(spans.inner_span))
+            }
+            _ => None,
+        };
         self.with_lint_attrs(it.id, &it.attrs, |cx| {
             run_early_pass!(cx, check_item, it);
             ast_visit::walk_item(cx, it);
             run_early_pass!(cx, check_item_post, it);
-        })
+        });
+        if let Some(overrides) = overrides {
+            self.context.builder.pop(overrides);
+        }
     }
 
     fn visit_foreign_item(&mut self, it: &'a ast::ForeignItem) {
@@ -322,6 +332,9 @@ impl EarlyLintPass for EarlyLintPassObjects<'_> {
 /// This trait generalizes over those nodes.
 pub trait EarlyCheckNode<'a>: Copy {
     fn id(self) -> ast::NodeId;
+    /// The span of the contents of the node, if it is the root of a file, which decides the
+    /// `--lint-config` overrides that apply to it.
+    fn file_span(self) -> Option<Span>;
     fn attrs<'b>(self) -> &'b [ast::Attribute]
     where
         'a: 'b;
@@ -334,6 +347,9 @@ impl<'a> EarlyCheckNode<'a> for &'a ast::Crate {
     fn id(self) -> ast::NodeId {
         ast::CRATE_NODE_ID
     }
+    fn file_span(self) -> Option<Span> {
+        Some(self.spans.inner_span)
+    }
     fn attrs<'b>(self) -> &'b [ast::Attribute]
     where
         'a: 'b,
@@ -354,6 +370,11 @@ impl<'a> EarlyCheckNode<'a> for (ast::NodeId, &'a [ast::Attribute], &'a [P<ast::
     fn id(self) -> ast::NodeId {
         self.0
     }
+    fn file_span(self) -> Option<Span> {
+        // Freshly loaded modules are checked on their own, like crates, but only the items of
+        // the module are known here, not the file it was loaded from.
+        None
+    }
     fn attrs<'b>(self) -> &'b [ast::Attribute]
     where
         'a: 'b,
@@ -389,7 +410,12 @@ fn early_lint_node<'a>(
         pass,
     };
 
+    let overrides =
+        check_node.file_span().map(|span| cx.context.builder.push_lint_config_overrides(span));
     cx.with_lint_attrs(check_node.id(), check_node.attrs(), |cx| check_node.check(cx));
+    if let Some(overrides) = overrides {
+        cx.context.builder.pop(overrides);
+    }
     cx.context.buffered
 }
 
@@ -17,10 +17,11 @@
     builtin::{self, FORBIDDEN_LINT_GROUPS, SINGLE_USE_LIFETIMES, UNFULFILLED_LINT_EXPECTATIONS},
     Level, Lint, LintExpectationId, LintId,
 };
+use rustc_session::lint_config::{LintConfig, LintConfigEntry};
 use rustc_session::parse::{add_feature_diagnostics, feature_err};
 use rustc_session::Session;
 use rustc_span::symbol::{sym, Symbol};
-use rustc_span::{Span, DUMMY_SP};
+use rustc_span::{FileName, Span, DUMMY_SP};
 use tracing::debug;
 
 fn lint_levels(tcx: TyCtxt<'_>, (): ()) -> LintLevelMap {
@@ -32,12 +33,15 @@ fn lint_levels(tcx: TyCtxt<'_>, (): ()) -> LintLevelMap {
 
     builder.levels.id_to_set.reserve(krate.owners.len() + 1);
 
+    let overrides =
+        builder.levels.push_lint_config_overrides(tcx.hir().root_module().spans.inner_span);
     let push =
         builder.levels.push(tcx.hir().attrs(hir::CRATE_HIR_ID), true, Some(hir::CRATE_HIR_ID));
 
     builder.levels.register_id(hir::CRATE_HIR_ID);
     tcx.hir().walk_toplevel_module(&mut builder);
     builder.levels.pop(push);
+    builder.levels.pop(overrides);
 
     builder.levels.update_unstable_expectation_ids();
     builder.levels.build_map()
@@ -82,6 +86,9 @@ pub fn new(
         };
         builder.process_command_line(sess, store);
         assert_eq!(builder.sets.list.len(), 1);
+        if let Some(lint_config) = &sess.opts.lint_config {
+            builder.process_lint_config(lint_config, lint_config.lints.iter());
+        }
         builder
     }
 
@@ -131,6 +138,63 @@ fn process_command_line(&mut self, sess: &Session, store: &LintStore) {
         }
     }
 
+    /// Pushes the lint levels set by the `--lint-config` file. These take precedence over the
+    /// lint flags, except for `--force-warn`, but not over lint attributes. Lowering a lint
+    /// forbidden with `-F` or by the file itself is an error.
+    fn process_lint_config<'a>(
+        &mut self,
+        lint_config: &LintConfig,
+        lints: impl Iterator<Item = &'a LintConfigEntry>,
+    ) {
+        let prev = self.cur;
+        self.cur = self.sets.list.push(LintSet { specs: FxHashMap::default(), parent: prev });
+
+        let path = Symbol::intern(&lint_config.path.display().to_string());
+        for entry in lints {
+            self.store.check_lint_name_config(
+                self.sess,
+                &entry.name,
+                entry.level,
+                self.registered_tools,
+                &lint_config.path,
+            );
+            let Ok(ids) = self.store.find_lints(&entry.name) else {
+                // errors handled in check_lint_name_config above
+                continue
+            };
+            let name = Symbol::intern(&entry.name);
+            let reason = entry.reason.as_deref().map(Symbol::intern);
+            for id in ids {
+                // Like for lint attributes, lowering a forbidden lint is an error.
+                if self.check_gated_lint(id, DUMMY_SP) {
+                    let src = LintLevelSource::ConfigFile(name, path, reason);
+                    self.insert_spec(id, (entry.level, src));
+                }
+            }
+        }
+    }
+
+    /// Pushes the lint levels of the `--lint-config` overrides that apply to the source file
+    /// `span` is in. This is used when entering a module loaded from another file, before pushing
+    /// its attributes, which still take precedence.
+    ///
+    /// Don't forget to call `pop`!
+    pub(crate) fn push_lint_config_overrides(&mut self, span: Span) -> BuilderPush {
+        let prev = self.cur;
+        let sess = self.sess;
+        if let Some(lint_config) = &sess.opts.lint_config {
+            if let FileName::Real(file) = sess.source_map().span_to_filename(span) {
+                if let Some(file) = file.local_path() {
+                    let lints: Vec<_> = lint_config.overrides_for(file).collect();
+                    if !lints.is_empty() {
+                        self.process_lint_config(lint_config, lints.into_iter());
+                    }
+                }
+            }
+        }
+        BuilderPush { prev, changed: prev != self.cur }
+    }
+
     /// Attempts to insert the `id` to `level_src` map entry. If unsuccessful
     /// (e.g. if a forbid was already inserted on the same scope), then emits a
     /// diagnostic with no change to `specs`.
@@ -156,6 +220,7 @@ fn insert_spec(&mut self, id: LintId, (level, src): LevelAndSource) {
                     LintLevelSource::Default => false,
                     LintLevelSource::Node(symbol, _, _) => self.store.is_lint_group(symbol),
                     LintLevelSource::CommandLine(symbol, _) => self.store.is_lint_group(symbol),
+                    LintLevelSource::ConfigFile(symbol, _, _) => self.store.is_lint_group(symbol),
                 };
                 debug!(
                     "fcw_warning={:?}, specs.get(&id) = {:?}, old_src={:?}, id_name={:?}",
@@ -166,7 +231,11 @@ fn insert_spec(&mut self, id: LintId, (level, src): LevelAndSource) {
                 );
 
                 let decorate_diag = |diag: &mut Diagnostic| {
-                    diag.span_label(src.span(), "overruled by previous forbid");
+                    if let LintLevelSource::ConfigFile(_, path, _) = src {
+                        diag.note(&format!("overruled in the lint configuration `{}`", path));
+                    } else {
+                        diag.span_label(src.span(), "overruled by previous forbid");
+                    }
                     match old_src {
                         LintLevelSource::Default => {
                             diag.note(&format!(
@@ -183,6 +252,15 @@ fn insert_spec(&mut self, id: LintId, (level, src): LevelAndSource) {
                         LintLevelSource::CommandLine(_, _) => {
                             diag.note("`forbid` lint level was set on command line");
                         }
+                        LintLevelSource::ConfigFile(_, path, reason) => {
+                            diag.note(&format!(
+                                "`forbid` lint level was set in the lint configuration `{}`",
+                                path
+                            ));
+                            if let Some(rationale) = reason {
+                                diag.note(rationale.as_str());
+                            }
+                        }
                     }
                 };
                 if !fcw_warning {
@@ -742,9 +820,23 @@ fn visit_param(&mut self, param: &'tcx hir::Param<'tcx>) {
     }
 
     fn visit_item(&mut self, it: &'tcx hir::Item<'tcx>) {
+        // A module loaded from another file may be covered by `--lint-config` overrides.
+        let source_map = self.tcx.sess.source_map();
+        let overrides = match it.kind {
+            hir::ItemKind::Mod(module)
+                if source_map.span_to_filename(it.span)
+                    != source_map.span_to_filename(module.spans.inner_span) =>
+            {
+                Some(self.levels.push_lint_config_overrides(module.spans.inner_span))
+            }
+            _ => None,
+        };
         self.with_lint_attrs(it.hir_id(), |builder| {
             intravisit::walk_item(builder, it);
         });
+        if let Some(overrides) = overrides {
+            self.levels.pop(overrides);
+        }
     }
 
     fn visit_foreign_item(&mut self, it: &'tcx hir::ForeignItem<'tcx>) {
@@ -83,6 +83,9 @@ enum MetadataKind {
         }
         MetadataKind::Uncompressed | MetadataKind::Compressed => {
             encode_metadata(tcx, &metadata_filename);
+            if tcx.sess.opts.unstable_opts.public_api_hash {
+                write_public_api_hash(tcx, &out_filename);
+            }
         }
     };
 
@@ -118,6 +121,26 @@ enum MetadataKind {
     (metadata, need_metadata_module)
 }
 
+/// Writes the public API hash of the crate for `-Zpublic-api-hash`, next to the final path
+/// of its metadata `metadata_out_filename`, which is in the output directory whether or not
+/// the metadata is emitted. The file is only replaced if the hash changed, so build systems
+/// can also use its modification time.
+fn write_public_api_hash(tcx: TyCtxt<'_>, metadata_out_filename: &Path) {
+    let out_filename = metadata_out_filename.with_extension("public-api-hash");
+    let hash = tcx.public_api_hash(LOCAL_CRATE).to_string();
+    if fs::read_to_string(&out_filename).map_or(true, |old_hash| old_hash != hash) {
+        if let Err(e) = fs::write(&out_filename, &hash) {
+            tcx.sess.fatal(&format!("failed to write {}: {}", out_filename.display(), e));
+        }
+    }
+    if tcx.sess.opts.json_artifact_notifications {
+        tcx.sess
+            .parse_sess
+            .span_diagnostic
+            .emit_artifact_notification(&out_filename, "public-api-hash");
+    }
+}
+
 #[cfg(not(target_os = "linux"))]
 pub fn non_durable_rename(src: &Path, dst: &Path) -> std::io::Result<()> {
     std::fs::rename(src, dst)
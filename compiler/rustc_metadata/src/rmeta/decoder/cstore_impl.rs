@@ -246,6 +246,7 @@ fn into_args(self) -> (DefId, SimplifiedType) {
     has_global_allocator => { cdata.root.has_global_allocator }
     has_panic_handler => { cdata.root.has_panic_handler }
     is_profiler_runtime => { cdata.root.profiler_runtime }
+    is_generics_hub => { cdata.root.generics_hub }
     required_panic_strategy => { cdata.root.required_panic_strategy }
     panic_in_drop_strategy => { cdata.root.panic_in_drop_strategy }
     extern_crate => {
@@ -272,6 +273,7 @@ fn into_args(self) -> (DefId, SimplifiedType) {
     native_libraries => { cdata.get_native_libraries(tcx.sess).collect() }
     foreign_modules => { cdata.get_foreign_modules(tcx.sess).map(|m| (m.def_id, m)).collect() }
     crate_hash => { cdata.root.hash }
+    public_api_hash => { cdata.root.public_api_hash.unwrap_or(cdata.root.hash) }
     crate_host_hash => { cdata.host_hash }
     crate_name => { cdata.root.name }
 
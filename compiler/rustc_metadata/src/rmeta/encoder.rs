@@ -702,6 +702,12 @@ fn encode_crate_root(&mut self) -> LazyValue<CrateRoot> {
             extra_filename: tcx.sess.opts.cg.extra_filename.clone(),
             triple: tcx.sess.opts.target_triple.clone(),
             hash: tcx.crate_hash(LOCAL_CRATE),
+            public_api_hash: tcx
+                .sess
+                .opts
+                .unstable_opts
+                .public_api_hash
+                .then(|| tcx.public_api_hash(LOCAL_CRATE)),
             stable_crate_id: tcx.def_path_hash(LOCAL_CRATE.as_def_id()).stable_crate_id(),
             required_panic_strategy: tcx.required_panic_strategy(LOCAL_CRATE),
             panic_in_drop_strategy: tcx.sess.opts.unstable_opts.panic_in_drop,
@@ -712,6 +718,7 @@ fn encode_crate_root(&mut self) -> LazyValue<CrateRoot> {
             proc_macro_data,
             debugger_visualizers,
             compiler_builtins: tcx.sess.contains_name(&attrs, sym::compiler_builtins),
+            generics_hub: tcx.is_generics_hub(LOCAL_CRATE),
             needs_allocator: tcx.sess.contains_name(&attrs, sym::needs_allocator),
             needs_panic_runtime: tcx.sess.contains_name(&attrs, sym::needs_panic_runtime),
             no_builtins: tcx.sess.contains_name(&attrs, sym::no_builtins),
@@ -826,7 +833,7 @@ fn encode_crate_root(&mut self) -> LazyValue<CrateRoot> {
     }
 }
 
-fn should_encode_visibility(def_kind: DefKind) -> bool {
+pub(super) fn should_encode_visibility(def_kind: DefKind) -> bool {
     match def_kind {
         DefKind::Mod
         | DefKind::Struct
@@ -906,7 +913,7 @@ fn should_encode_stability(def_kind: DefKind) -> bool {
 /// - we skip `optimized_mir` for check runs.
 ///
 /// Return a pair, resp. for CTFE and for LLVM.
-fn should_encode_mir(tcx: TyCtxt<'_>, def_id: LocalDefId) -> (bool, bool) {
+pub(super) fn should_encode_mir(tcx: TyCtxt<'_>, def_id: LocalDefId) -> (bool, bool) {
     match tcx.def_kind(def_id) {
         // Constructors
         DefKind::Ctor(_, _) => {
@@ -984,7 +991,7 @@ fn should_encode_variances(def_kind: DefKind) -> bool {
     }
 }
 
-fn should_encode_generics(def_kind: DefKind) -> bool {
+pub(super) fn should_encode_generics(def_kind: DefKind) -> bool {
     match def_kind {
         DefKind::Struct
         | DefKind::Union
@@ -2341,6 +2348,7 @@ pub fn provide(providers: &mut Providers) {
             traits.sort_by_cached_key(|&def_id| tcx.def_path_hash(def_id));
             tcx.arena.alloc_slice(&traits)
         },
+        public_api_hash: public_api::public_api_hash,
 
         ..*providers
     }
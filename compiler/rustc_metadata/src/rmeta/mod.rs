@@ -43,6 +43,7 @@
 mod decoder;
 mod def_path_hash_map;
 mod encoder;
+mod public_api;
 mod table;
 
 pub(crate) fn rustc_version() -> String {
@@ -215,6 +216,8 @@ pub(crate) struct CrateRoot {
     triple: TargetTriple,
     extra_filename: String,
     hash: Svh,
+    /// See the `public_api_hash` query. Only computed with `-Zpublic-api-hash`.
+    public_api_hash: Option<Svh>,
     stable_crate_id: StableCrateId,
     required_panic_strategy: Option<PanicStrategy>,
     panic_in_drop_strategy: PanicStrategy,
@@ -252,6 +255,7 @@ pub(crate) struct CrateRoot {
     source_map: LazyTable<u32, LazyValue<rustc_span::SourceFile>>,
 
     compiler_builtins: bool,
+    generics_hub: bool,
     needs_allocator: bool,
     needs_panic_runtime: bool,
     no_builtins: bool,
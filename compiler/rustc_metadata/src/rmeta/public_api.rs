@@ -0,0 +1,162 @@
+//! Computes the public API hash of the local crate, see the `public_api_hash` query.
+
+use crate::rmeta::encoder::{should_encode_generics, should_encode_mir, should_encode_visibility};
+
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
+use rustc_data_structures::svh::Svh;
+use rustc_hir as hir;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{CrateNum, LocalDefId, LOCAL_CRATE};
+use rustc_middle::ty::TyCtxt;
+use rustc_query_system::ich::StableHashingContext;
+
+/// Hashes everything about the local crate that dependents compiled against its
+/// metadata can observe:
+///
+/// - the kind, visibility, generics and type or signature of every item that is
+///   reachable from other crates, including the fields and variants of types and
+///   the hidden types of `impl Trait` types, whose auto traits leak to dependents,
+/// - all trait impls, which are visible wherever both the trait and the type are,
+/// - the MIR that is encoded for dependents to inline, instantiate or evaluate at
+///   compile time, whether or not the item it belongs to is reachable,
+/// - the public API hashes of all upstream crates and the options that are part of
+///   the crate hash.
+///
+/// Spans are not hashed, except in the MIR exported to dependents: the code they
+/// instantiate or inline from it embeds its spans in panic locations and debuginfo.
+/// So unlike the crate hash, the public API hash does not change when only the
+/// bodies of private or non-inlinable functions change, or when code that is not
+/// exported as MIR is just moved around.
+pub(super) fn public_api_hash(tcx: TyCtxt<'_>, cnum: CrateNum) -> Svh {
+    debug_assert_eq!(cnum, LOCAL_CRATE);
+
+    let access_levels = tcx.privacy_access_levels(());
+    let mut api_items: Vec<LocalDefId> = access_levels
+        .map
+        .keys()
+        .copied()
+        .filter(|&def_id| access_levels.is_reachable(def_id))
+        .chain(tcx.all_local_trait_impls(()).values().flatten().copied())
+        .collect();
+    api_items.sort_by_cached_key(|&def_id| tcx.def_path_hash(def_id.to_def_id()));
+    api_items.dedup();
+
+    let mut mir_items: Vec<(LocalDefId, bool, bool)> = tcx
+        .mir_keys(())
+        .iter()
+        .map(|&def_id| {
+            let (encode_const, encode_opt) = should_encode_mir(tcx, def_id);
+            (def_id, encode_const, encode_opt)
+        })
+        .filter(|&(_, encode_const, encode_opt)| encode_const || encode_opt)
+        .collect();
+    mir_items.sort_by_cached_key(|&(def_id, ..)| tcx.def_path_hash(def_id.to_def_id()));
+
+    let mut upstream_crates: Vec<_> = tcx
+        .crates(())
+        .iter()
+        .map(|&cnum| (tcx.stable_crate_id(cnum), tcx.public_api_hash(cnum)))
+        .collect();
+    upstream_crates.sort_unstable_by_key(|&(stable_crate_id, _)| stable_crate_id);
+
+    let hash: Fingerprint = tcx.with_stable_hashing_context(|mut hcx| {
+        let mut hasher = StableHasher::new();
+        hcx.while_hashing_spans(false, |hcx| {
+            for &def_id in &api_items {
+                hash_api_item(tcx, hcx, &mut hasher, def_id);
+            }
+            upstream_crates.hash_stable(hcx, &mut hasher);
+            tcx.sess.opts.dep_tracking_hash(true).hash_stable(hcx, &mut hasher);
+            tcx.sess.local_stable_crate_id().hash_stable(hcx, &mut hasher);
+        });
+        hcx.while_hashing_spans(true, |hcx| {
+            for &(def_id, encode_const, encode_opt) in &mir_items {
+                tcx.def_path_hash(def_id.to_def_id()).hash_stable(hcx, &mut hasher);
+                if encode_opt {
+                    tcx.optimized_mir(def_id).hash_stable(hcx, &mut hasher);
+                }
+                if encode_const {
+                    tcx.mir_for_ctfe(def_id).hash_stable(hcx, &mut hasher);
+                }
+                tcx.promoted_mir(def_id).hash_stable(hcx, &mut hasher);
+            }
+        });
+        hasher.finish()
+    });
+
+    Svh::new(hash.to_smaller_hash())
+}
+
+fn hash_api_item<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    hcx: &mut StableHashingContext<'_>,
+    hasher: &mut StableHasher,
+    def_id: LocalDefId,
+) {
+    let local_def_id = def_id;
+    let def_id = def_id.to_def_id();
+    let def_kind = tcx.def_kind(def_id);
+
+    tcx.def_path_hash(def_id).hash_stable(hcx, hasher);
+    def_kind.hash_stable(hcx, hasher);
+    if should_encode_visibility(def_kind) {
+        tcx.visibility(def_id).hash_stable(hcx, hasher);
+    }
+    if should_encode_generics(def_kind) {
+        tcx.generics_of(def_id).hash_stable(hcx, hasher);
+        tcx.predicates_of(def_id).hash_stable(hcx, hasher);
+    }
+
+    match def_kind {
+        DefKind::Fn | DefKind::AssocFn | DefKind::Ctor(..) => {
+            tcx.fn_sig(def_id).hash_stable(hcx, hasher);
+            tcx.codegen_fn_attrs(def_id).hash_stable(hcx, hasher);
+            if !matches!(def_kind, DefKind::Ctor(..)) {
+                tcx.constness(def_id).hash_stable(hcx, hasher);
+            }
+        }
+        DefKind::Struct | DefKind::Union | DefKind::Enum => {
+            let adt_def = tcx.adt_def(def_id);
+            adt_def.hash_stable(hcx, hasher);
+            for field in adt_def.all_fields() {
+                tcx.type_of(field.did).hash_stable(hcx, hasher);
+            }
+        }
+        DefKind::Const
+        | DefKind::AssocConst
+        | DefKind::Static(..)
+        | DefKind::TyAlias
+        | DefKind::Field
+        | DefKind::Impl => {
+            tcx.type_of(def_id).hash_stable(hcx, hasher);
+            if def_kind == DefKind::Impl {
+                tcx.impl_trait_ref(def_id).hash_stable(hcx, hasher);
+                tcx.impl_polarity(def_id).hash_stable(hcx, hasher);
+                tcx.associated_item_def_ids(def_id).hash_stable(hcx, hasher);
+            }
+        }
+        DefKind::AssocTy => {
+            // Associated types in traits only have a type if they have a default.
+            if tcx.impl_defaultness(def_id).has_value() {
+                tcx.type_of(def_id).hash_stable(hcx, hasher);
+            }
+        }
+        DefKind::OpaqueTy => {
+            // Dependents see the bounds of an `impl Trait` type, but also the auto traits its
+            // hidden type implements, so the hidden type is part of the public API too.
+            tcx.explicit_item_bounds(def_id).hash_stable(hcx, hasher);
+            tcx.type_of(def_id).hash_stable(hcx, hasher);
+        }
+        DefKind::Trait => {
+            tcx.trait_def(def_id).hash_stable(hcx, hasher);
+            tcx.associated_item_def_ids(def_id).hash_stable(hcx, hasher);
+        }
+        DefKind::Macro(_) => {
+            if let hir::ItemKind::Macro(macro_def, _) = &tcx.hir().expect_item(local_def_id).kind {
+                macro_def.hash_stable(hcx, hasher);
+            }
+        }
+        _ => {}
+    }
+}
@@ -501,7 +501,9 @@ pub fn body_owners(self) -> impl Iterator<Item = LocalDefId> + 'hir {
 
     #[inline]
     pub fn par_body_owners(self, f: impl Fn(LocalDefId) + Sync + Send) {
-        par_for_each_in(&self.tcx.hir_crate_items(()).body_owners[..], |&def_id| f(def_id));
+        self.tcx.sess.with_deterministic_diagnostics(|| {
+            par_for_each_in(&self.tcx.hir_crate_items(()).body_owners[..], |&def_id| f(def_id))
+        });
     }
 
     pub fn ty_param_owner(self, def_id: LocalDefId) -> LocalDefId {
@@ -636,7 +638,9 @@ pub fn for_each_module(self, mut f: impl FnMut(LocalDefId)) {
     #[inline]
     pub fn par_for_each_module(self, f: impl Fn(LocalDefId) + Sync + Send) {
         let crate_items = self.tcx.hir_crate_items(());
-        par_for_each_in(&crate_items.submodules[..], |module| f(*module))
+        self.tcx.sess.with_deterministic_diagnostics(|| {
+            par_for_each_in(&crate_items.submodules[..], |module| f(*module))
+        });
     }
 
     /// Returns an iterator for the nodes in the ancestor tree of the `current_id`
@@ -29,6 +29,10 @@ pub enum LintLevelSource {
     /// The provided `Level` is the level specified on the command line.
     /// (The actual level may be lower due to `--cap-lints`.)
     CommandLine(Symbol, Level),
+
+    /// Lint level was set by the `--lint-config` file, whose path is the
+    /// second `Symbol`.
+    ConfigFile(Symbol, Symbol, Option<Symbol> /* reason */),
 }
 
 impl LintLevelSource {
@@ -37,6 +41,7 @@ pub fn name(&self) -> Symbol {
             LintLevelSource::Default => symbol::kw::Default,
             LintLevelSource::Node(name, _, _) => name,
             LintLevelSource::CommandLine(name, _) => name,
+            LintLevelSource::ConfigFile(name, _, _) => name,
         }
     }
 
@@ -45,6 +50,7 @@ pub fn span(&self) -> Span {
             LintLevelSource::Default => DUMMY_SP,
             LintLevelSource::Node(_, span, _) => span,
             LintLevelSource::CommandLine(_, _) => DUMMY_SP,
+            LintLevelSource::ConfigFile(_, _, _) => DUMMY_SP,
         }
     }
 }
@@ -261,6 +267,23 @@ pub fn explain_lint_level_source(
                 ));
             }
         }
+        LintLevelSource::ConfigFile(lint_config_name, path, reason) => {
+            if let Some(rationale) = reason {
+                err.note(rationale.as_str());
+            }
+            let level_str = level.as_str();
+            if lint_config_name.as_str() == name {
+                err.note_once(&format!(
+                    "requested in the lint configuration `{}` with `{} = \"{}\"`",
+                    path, name, level_str
+                ));
+            } else {
+                err.note_once(&format!(
+                    "`{} = \"{}\"` implied by `{} = \"{}\"` in the lint configuration `{}`",
+                    name, level_str, lint_config_name, level_str, path
+                ));
+            }
+        }
         LintLevelSource::Node(lint_attr_name, src, reason) => {
             if let Some(rationale) = reason {
                 err.note(rationale.as_str());
@@ -97,6 +97,9 @@ pub struct CodegenFnAttrFlags: u32 {
         const REALLOCATOR               = 1 << 18;
         /// `#[rustc_allocator_zeroed]`: a hint to LLVM that the function only allocates zeroed memory.
         const ALLOCATOR_ZEROED          = 1 << 19;
+        /// `#[no_panic]`: the function must not be able to reach a panic, which is checked
+        /// for each of its instances after mono item collection.
+        const NO_PANIC                  = 1 << 20;
     }
 }
 
@@ -1,8 +1,9 @@
 //! Registering limits:
 //! * recursion_limit,
 //! * move_size_limit,
-//! * type_length_limit, and
-//! * const_eval_limit
+//! * type_length_limit,
+//! * const_eval_limit, and
+//! * const_eval_memory_limit
 //!
 //! There are various parts of the compiler that must impose arbitrary limits
 //! on how deeply they recurse to prevent stack overflow. Users can override
@@ -39,6 +40,12 @@ pub fn provide(providers: &mut ty::query::Providers) {
             sym::const_eval_limit,
             1_000_000,
         ),
+        const_eval_memory_limit: get_limit(
+            tcx.hir().krate_attrs(),
+            tcx.sess,
+            sym::const_eval_memory_limit,
+            0,
+        ),
     }
 }
 
@@ -439,6 +439,10 @@ pub enum ResourceExhaustionInfo {
     ///
     /// The exact limit is set by the `const_eval_limit` attribute.
     StepLimitReached,
+    /// The program had too much memory allocated at the same time.
+    ///
+    /// The exact limit is set by the `const_eval_memory_limit` attribute.
+    MemoryLimitReached,
     /// There is not enough memory to perform an allocation.
     MemoryExhausted,
 }
@@ -453,6 +457,9 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             StepLimitReached => {
                 write!(f, "exceeded interpreter step limit (see `#[const_eval_limit]`)")
             }
+            MemoryLimitReached => {
+                write!(f, "exceeded interpreter memory limit (see `#[const_eval_memory_limit]`)")
+            }
             MemoryExhausted => {
                 write!(f, "tried to allocate more memory than available to compiler")
             }
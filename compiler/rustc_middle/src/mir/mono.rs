@@ -227,6 +227,9 @@ pub struct CodegenUnit<'tcx> {
     /// True if this is CGU is used to hold code coverage information for dead code,
     /// false otherwise.
     is_code_coverage_dead_code_cgu: bool,
+    /// The codegen units that were merged into this one to reach the target number of codegen
+    /// units, in merge order, each with its size estimate at the time of the merge.
+    merged_cgus: Vec<(Symbol, usize)>,
 }
 
 /// Specifies the linkage type for a `MonoItem`.
@@ -263,6 +266,7 @@ pub fn new(name: Symbol) -> CodegenUnit<'tcx> {
             size_estimate: None,
             primary: false,
             is_code_coverage_dead_code_cgu: false,
+            merged_cgus: Vec::new(),
         }
     }
 
@@ -299,6 +303,19 @@ pub fn make_code_coverage_dead_code_cgu(&mut self) {
         self.is_code_coverage_dead_code_cgu = true;
     }
 
+    pub fn merged_cgus(&self) -> &[(Symbol, usize)] {
+        &self.merged_cgus
+    }
+
+    /// Moves the items of `other` into this CGU and records that it was merged into this one.
+    pub fn merge(&mut self, mut other: CodegenUnit<'tcx>) {
+        let other_size_estimate = other.size_estimate();
+        self.modify_size_estimate(other_size_estimate);
+        self.items.extend(other.items.drain());
+        self.merged_cgus.push((other.name, other_size_estimate));
+        self.merged_cgus.append(&mut other.merged_cgus);
+    }
+
     pub fn mangle_name(human_readable_name: &str) -> String {
         // We generate a 80 bit hash from the name. This should be enough to
         // avoid collisions and is still reasonably short for filenames.
@@ -398,6 +415,8 @@ fn hash_stable(&self, hcx: &mut StableHashingContext<'a>, hasher: &mut StableHas
             size_estimate: _,
             primary: _,
             is_code_coverage_dead_code_cgu,
+            // How the CGU came to be is not relevant to the hash either
+            merged_cgus: _,
         } = *self;
 
         name.hash_stable(hcx, hasher);
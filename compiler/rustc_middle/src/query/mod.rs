@@ -1370,6 +1370,19 @@
         desc { "query a crate is `#![profiler_runtime]`" }
         separate_provide_extern
     }
+    /// Whether the crate was built with `-Zgenerics-hub`, i.e. whether the generic
+    /// instantiations it exports should be linked against by downstream crates even
+    /// outside of share-generics mode.
+    query is_generics_hub(_: CrateNum) -> bool {
+        fatal_cycle
+        desc { "checking if the crate is a generics hub" }
+        separate_provide_extern
+    }
+    /// Whether any upstream crate is a generics hub. Without `-Zshare-generics`, upstream
+    /// monomorphizations are only looked up when this holds.
+    query has_upstream_generics_hub(_: ()) -> bool {
+        desc { "checking if an upstream crate is a generics hub" }
+    }
     query has_ffi_unwind_calls(key: LocalDefId) -> bool {
         desc { |tcx| "check if `{}` contains FFI-unwind calls", tcx.def_path_str(key.to_def_id()) }
         cache_on_disk_if { true }
@@ -1522,6 +1535,17 @@
         desc { "looking up the hash a crate" }
         separate_provide_extern
     }
+    /// A hash of everything about a crate that its dependents can observe: the
+    /// signatures of its exported items, its trait impls and the MIR it makes
+    /// available for inlining and const evaluation. Unlike `crate_hash`, it does
+    /// not change when only private implementation details change.
+    ///
+    /// It is only recorded in the metadata of crates built with `-Zpublic-api-hash`.
+    /// For other upstream crates, this falls back to their `crate_hash`.
+    query public_api_hash(_: CrateNum) -> Svh {
+        desc { "computing the public API hash of a crate" }
+        separate_provide_extern
+    }
     query crate_host_hash(_: CrateNum) -> Option<Svh> {
         eval_always
         desc { "looking up the hash of a host version of a crate" }
@@ -1723,6 +1723,10 @@ pub fn const_eval_limit(self) -> Limit {
         self.limits(()).const_eval_limit
     }
 
+    pub fn const_eval_memory_limit(self) -> Limit {
+        self.limits(()).const_eval_memory_limit
+    }
+
     pub fn all_traits(self) -> impl Iterator<Item = DefId> + 'tcx {
         iter::once(LOCAL_CRATE)
             .chain(self.crates(()).iter().copied())
@@ -3010,6 +3014,12 @@ pub fn provide(providers: &mut ty::query::Providers) {
         assert_eq!(cnum, LOCAL_CRATE);
         tcx.sess.contains_name(tcx.hir().krate_attrs(), sym::compiler_builtins)
     };
+    providers.is_generics_hub = |tcx, cnum| {
+        assert_eq!(cnum, LOCAL_CRATE);
+        tcx.sess.opts.unstable_opts.generics_hub.is_some()
+    };
+    providers.has_upstream_generics_hub =
+        |tcx, ()| tcx.crates(()).iter().any(|&cnum| tcx.is_generics_hub(cnum));
     providers.has_panic_handler = |tcx, cnum| {
         assert_eq!(cnum, LOCAL_CRATE);
         // We want to check if the panic handler was defined in this crate
@@ -112,12 +112,14 @@ pub fn ty(&self, tcx: TyCtxt<'tcx>, param_env: ty::ParamEnv<'tcx>) -> Ty<'tcx> {
     /// no upstream crate provides such an exported monomorphization.
     ///
     /// This method already takes into account the global `-Zshare-generics`
-    /// setting, always returning `None` if `share-generics` is off.
+    /// setting: if `share-generics` is off, only monomorphizations provided by
+    /// a crate built with `-Zgenerics-hub` are returned.
     pub fn upstream_monomorphization(&self, tcx: TyCtxt<'tcx>) -> Option<CrateNum> {
         // If we are not in share generics mode, we don't link to upstream
         // monomorphizations but always instantiate our own internal versions
-        // instead.
-        if !tcx.sess.opts.share_generics() {
+        // instead, unless a generics hub explicitly provides one.
+        let share_generics = tcx.sess.opts.share_generics();
+        if !share_generics && !tcx.has_upstream_generics_hub(()) {
             return None;
         }
 
@@ -130,13 +132,15 @@ pub fn upstream_monomorphization(&self, tcx: TyCtxt<'tcx>) -> Option<CrateNum> {
         // If this a non-generic instance, it cannot be a shared monomorphization.
         self.substs.non_erasable_generics().next()?;
 
-        match self.def {
+        let cnum = match self.def {
             InstanceDef::Item(def) => tcx
                 .upstream_monomorphizations_for(def.did)
                 .and_then(|monos| monos.get(&self.substs).cloned()),
             InstanceDef::DropGlue(_, Some(_)) => tcx.upstream_drop_glue_for(self.substs),
             _ => None,
-        }
+        }?;
+
+        if share_generics || tcx.is_generics_hub(cnum) { Some(cnum) } else { None }
     }
 }
 
@@ -244,6 +244,22 @@ struct MonoItems<'tcx> {
     // The collected mono items. The bool field in each element
     // indicates whether this element should be inlined.
     items: Vec<(Spanned<MonoItem<'tcx>>, bool /*inlined*/)>,
+
+    // The functions that are used but do not become mono items of this
+    // crate, like functions linked from upstream crates and intrinsics.
+    // They are only recorded when this is `Some`, for the `#[no_panic]`
+    // check.
+    non_mono_uses: Option<Vec<Spanned<NonMonoUse<'tcx>>>>,
+}
+
+/// A use of a function that does not become a mono item of this crate.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum NonMonoUse<'tcx> {
+    /// A function linked from an upstream crate, an intrinsic, or a method called through a
+    /// trait object.
+    Instance(Instance<'tcx>),
+    /// A call through a function pointer of the given type, whose callee is not known.
+    FnPtrCall(Ty<'tcx>),
 }
 
 impl<'tcx> MonoItems<'tcx> {
@@ -263,6 +279,13 @@ fn extend<T: IntoIterator<Item = Spanned<MonoItem<'tcx>>>>(&mut self, iter: T) {
             (mono_item, inlined)
         }))
     }
+
+    #[inline]
+    fn push_non_mono_use(&mut self, used: NonMonoUse<'tcx>, span: Span) {
+        if let Some(non_mono_uses) = &mut self.non_mono_uses {
+            non_mono_uses.push(respan(span, used));
+        }
+    }
 }
 
 impl<'tcx> InliningMap<'tcx> {
@@ -368,7 +391,8 @@ pub fn collect_crate_mono_items(
 #[instrument(skip(tcx, mode), level = "debug")]
 fn collect_roots(tcx: TyCtxt<'_>, mode: MonoItemCollectionMode) -> Vec<MonoItem<'_>> {
     debug!("collecting roots");
-    let mut roots = MonoItems { compute_inlining: false, tcx, items: Vec::new() };
+    let mut roots =
+        MonoItems { compute_inlining: false, tcx, items: Vec::new(), non_mono_uses: None };
 
     {
         let entry_fn = tcx.entry_fn(());
@@ -390,6 +414,13 @@ fn collect_roots(tcx: TyCtxt<'_>, mode: MonoItemCollectionMode) -> Vec<MonoItem<
         collector.push_extra_entry_roots();
     }
 
+    // A generics hub instantiates the generics listed in its profile for downstream crates.
+    if let Some(profile_path) = &tcx.sess.opts.unstable_opts.generics_hub {
+        for instance in crate::partitioning::generics_hub::profiled_instances(tcx, profile_path) {
+            roots.push(create_fn_mono_item(tcx, instance, DUMMY_SP));
+        }
+    }
+
     // We can only codegen items that are instantiable - items all of
     // whose predicates hold. Luckily, items that aren't instantiable
     // can't actually be used, so we can just skip codegenning them.
@@ -419,7 +450,8 @@ fn collect_items_rec<'tcx>(
     }
     debug!("BEGIN collect_items_rec({})", starting_point.node);
 
-    let mut neighbors = MonoItems { compute_inlining: true, tcx, items: Vec::new() };
+    let mut neighbors =
+        MonoItems { compute_inlining: true, tcx, items: Vec::new(), non_mono_uses: None };
     let recursion_depth_reset;
 
     //
@@ -450,21 +482,9 @@ fn collect_items_rec<'tcx>(
 
     match starting_point.node {
         MonoItem::Static(def_id) => {
-            let instance = Instance::mono(tcx, def_id);
-
-            // Sanity check whether this ended up being collected accidentally
-            debug_assert!(should_codegen_locally(tcx, &instance));
-
-            let ty = instance.ty(tcx, ty::ParamEnv::reveal_all());
-            visit_drop_use(tcx, ty, true, starting_point.span, &mut neighbors);
-
             recursion_depth_reset = None;
 
-            if let Ok(alloc) = tcx.eval_static_initializer(def_id) {
-                for &id in alloc.inner().relocations().values() {
-                    collect_miri(tcx, id, &mut neighbors);
-                }
-            }
+            collect_static_neighbours(tcx, def_id, starting_point.span, &mut neighbors);
         }
         MonoItem::Fn(instance) => {
             // Sanity check whether this ended up being collected accidentally
@@ -830,6 +850,9 @@ fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Loc
             mir::TerminatorKind::Call { ref func, .. } => {
                 let callee_ty = func.ty(self.body, tcx);
                 let callee_ty = self.monomorphize(callee_ty);
+                if let ty::FnPtr(_) = callee_ty.kind() {
+                    self.output.push_non_mono_use(NonMonoUse::FnPtrCall(callee_ty), source);
+                }
                 visit_fn_use(self.tcx, callee_ty, true, source, &mut self.output);
             }
             mir::TerminatorKind::Drop { ref place, .. }
@@ -862,18 +885,14 @@ fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Loc
                     _ => LangItem::Panic,
                 };
                 let instance = Instance::mono(tcx, tcx.require_lang_item(lang_item, Some(source)));
-                if should_codegen_locally(tcx, &instance) {
-                    self.output.push(create_fn_mono_item(tcx, instance, source));
-                }
+                visit_instance_use(tcx, instance, true, source, self.output);
             }
             mir::TerminatorKind::Abort { .. } => {
                 let instance = Instance::mono(
                     tcx,
                     tcx.require_lang_item(LangItem::PanicNoUnwind, Some(source)),
                 );
-                if should_codegen_locally(tcx, &instance) {
-                    self.output.push(create_fn_mono_item(tcx, instance, source));
-                }
+                visit_instance_use(tcx, instance, true, source, self.output);
             }
             mir::TerminatorKind::Goto { .. }
             | mir::TerminatorKind::SwitchInt { .. }
@@ -976,6 +995,7 @@ fn visit_instance_use<'tcx>(
 ) {
     debug!("visit_item_use({:?}, is_direct_call={:?})", instance, is_direct_call);
     if !should_codegen_locally(tcx, &instance) {
+        output.push_non_mono_use(NonMonoUse::Instance(instance), source);
         return;
     }
 
@@ -984,6 +1004,7 @@ fn visit_instance_use<'tcx>(
             if !is_direct_call {
                 bug!("{:?} being reified", instance);
             }
+            output.push_non_mono_use(NonMonoUse::Instance(instance), source);
         }
         ty::InstanceDef::DropGlue(_, None) => {
             // Don't need to emit noop drop glue if we are calling directly.
@@ -1178,23 +1199,24 @@ fn create_mono_items_for_vtable_methods<'tcx>(
 
             // Walk all methods of the trait, including those of its supertraits
             let entries = tcx.vtable_entries(poly_trait_ref);
-            let methods = entries
-                .iter()
-                .filter_map(|entry| match entry {
-                    VtblEntry::MetadataDropInPlace
-                    | VtblEntry::MetadataSize
-                    | VtblEntry::MetadataAlign
-                    | VtblEntry::Vacant => None,
-                    VtblEntry::TraitVPtr(_) => {
-                        // all super trait items already covered, so skip them.
-                        None
-                    }
-                    VtblEntry::Method(instance) => {
-                        Some(*instance).filter(|instance| should_codegen_locally(tcx, instance))
-                    }
-                })
-                .map(|item| create_fn_mono_item(tcx, item, source));
-            output.extend(methods);
+            let methods = entries.iter().filter_map(|entry| match entry {
+                VtblEntry::MetadataDropInPlace
+                | VtblEntry::MetadataSize
+                | VtblEntry::MetadataAlign
+                | VtblEntry::Vacant => None,
+                VtblEntry::TraitVPtr(_) => {
+                    // all super trait items already covered, so skip them.
+                    None
+                }
+                VtblEntry::Method(instance) => Some(*instance),
+            });
+            for instance in methods {
+                if should_codegen_locally(tcx, &instance) {
+                    output.push(create_fn_mono_item(tcx, instance, source));
+                } else {
+                    output.push_non_mono_use(NonMonoUse::Instance(instance), source);
+                }
+            }
         }
 
         // Also add the destructor.
@@ -1434,6 +1456,8 @@ fn collect_miri<'tcx>(tcx: TyCtxt<'tcx>, alloc_id: AllocId, output: &mut MonoIte
             if should_codegen_locally(tcx, &fn_instance) {
                 trace!("collecting {:?} with {:#?}", alloc_id, fn_instance);
                 output.push(create_fn_mono_item(tcx, fn_instance, DUMMY_SP));
+            } else {
+                output.push_non_mono_use(NonMonoUse::Instance(fn_instance), DUMMY_SP);
             }
         }
         GlobalAlloc::VTable(ty, trait_ref) => {
@@ -1454,6 +1478,49 @@ fn collect_neighbours<'tcx>(
     MirNeighborCollector { tcx, body: &body, output, instance }.visit_body(&body);
 }
 
+/// Finds the drop glue of a static and the items its initializer points to.
+fn collect_static_neighbours<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+    span: Span,
+    output: &mut MonoItems<'tcx>,
+) {
+    let instance = Instance::mono(tcx, def_id);
+
+    // Sanity check whether this ended up being collected accidentally
+    debug_assert!(should_codegen_locally(tcx, &instance));
+
+    let ty = instance.ty(tcx, ty::ParamEnv::reveal_all());
+    visit_drop_use(tcx, ty, true, span, output);
+
+    if let Ok(alloc) = tcx.eval_static_initializer(def_id) {
+        for &id in alloc.inner().relocations().values() {
+            collect_miri(tcx, id, output);
+        }
+    }
+}
+
+/// Returns the uses of `item`, which is either a mono item of this crate or an instance of an
+/// upstream function with MIR, for the `#[no_panic]` check. The first vector has the mono items
+/// it uses, like the collector finds them, and the second one the functions it uses that do not
+/// become mono items of this crate, including calls through trait objects and function pointers.
+pub(crate) fn collect_uses<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    item: MonoItem<'tcx>,
+) -> (Vec<Spanned<MonoItem<'tcx>>>, Vec<Spanned<NonMonoUse<'tcx>>>) {
+    let mut output =
+        MonoItems { compute_inlining: false, tcx, items: Vec::new(), non_mono_uses: Some(vec![]) };
+    match item {
+        MonoItem::Fn(instance) => collect_neighbours(tcx, instance, &mut output),
+        MonoItem::Static(def_id) => {
+            collect_static_neighbours(tcx, def_id, tcx.def_span(def_id), &mut output)
+        }
+        MonoItem::GlobalAsm(_) => {}
+    }
+    let items = output.items.into_iter().map(|(item, _)| item).collect();
+    (items, output.non_mono_uses.unwrap())
+}
+
 #[instrument(skip(tcx, output), level = "debug")]
 fn collect_const_value<'tcx>(
     tcx: TyCtxt<'tcx>,
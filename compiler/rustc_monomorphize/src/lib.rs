@@ -16,6 +16,7 @@
 use rustc_middle::ty::{self, Ty, TyCtxt};
 
 mod collector;
+mod no_panic;
 mod partitioning;
 mod polymorphize;
 mod util;
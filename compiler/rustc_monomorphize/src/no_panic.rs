@@ -0,0 +1,251 @@
+//! Checks the `#[no_panic]` attribute: no item reachable from an instance of a `#[no_panic]`
+//! function may call a panic entry point, like `core::panicking::panic_bounds_check`.
+//!
+//! The check runs after mono item collection, so that generic functions are checked for each of
+//! their instances. Non-generic functions are always checked, even if they are not collected
+//! because they are only instantiated by the crates using them. The check follows the same uses
+//! as the collector: calls, drop glue, function pointers and the methods of the vtables that are
+//! created. Functions of other crates that are not instantiated in this crate are followed too if
+//! their MIR is available, like for generic functions shared by an upstream crate. Otherwise,
+//! they cannot be checked, and calling them is an error, like calling a method of a trait object
+//! or a function pointer, whose callee is not known. Foreign functions are assumed not to panic.
+
+use crate::collector::{collect_uses, NonMonoUse};
+use rustc_data_structures::fx::{FxHashSet, FxIndexMap};
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::DefId;
+use rustc_hir::lang_items::LangItem;
+use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, Instance, Ty, TyCtxt};
+use rustc_span::source_map::Spanned;
+use rustc_span::symbol::sym;
+use rustc_span::Span;
+
+/// The lang items that start a panic. `panic_no_unwind` is called when a panic unwinds out of a
+/// function that cannot unwind.
+const PANIC_LANG_ITEMS: &[LangItem] = &[
+    LangItem::Panic,
+    LangItem::PanicFmt,
+    LangItem::PanicDisplay,
+    LangItem::ConstPanicFmt,
+    LangItem::PanicBoundsCheck,
+    LangItem::PanicNoUnwind,
+    LangItem::PanicImpl,
+    LangItem::BeginPanic,
+];
+
+/// Why an item may panic.
+enum PanicCause<'tcx> {
+    /// It calls a panic entry point.
+    EntryPoint(Instance<'tcx>),
+    /// It calls an intrinsic that panics for the type it is instantiated with.
+    Intrinsic(Instance<'tcx>),
+    /// It calls a function that cannot be checked.
+    Unchecked(UncheckedCall<'tcx>),
+}
+
+/// A call whose callee cannot be checked for panics.
+enum UncheckedCall<'tcx> {
+    /// A function of another crate whose MIR is not available.
+    NoMir(Instance<'tcx>),
+    /// A method of a trait object.
+    Virtual(Instance<'tcx>),
+    /// A function pointer of the given type.
+    FnPtr(Ty<'tcx>),
+}
+
+/// Reports the `#[no_panic]` functions of the local crate and the instances of generic
+/// `#[no_panic]` functions among the collected `items` from which a panic is reachable.
+pub(crate) fn check_no_panic<'tcx>(tcx: TyCtxt<'tcx>, items: &FxHashSet<MonoItem<'tcx>>) {
+    let is_no_panic =
+        |def_id| tcx.codegen_fn_attrs(def_id).flags.contains(CodegenFnAttrFlags::NO_PANIC);
+    let mut roots: FxHashSet<_> = items
+        .iter()
+        .filter(|item| match item {
+            MonoItem::Fn(Instance { def: ty::InstanceDef::Item(def), .. }) => is_no_panic(def.did),
+            _ => false,
+        })
+        .copied()
+        .collect();
+    // Non-generic functions are not necessarily collected, for example if they are `#[inline]`
+    // and not used in this crate, as they are then only instantiated by the crates using them.
+    roots.extend(
+        tcx.hir()
+            .body_owners()
+            .filter(|&def_id| {
+                matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+                    && is_no_panic(def_id.to_def_id())
+                    && !tcx.generics_of(def_id).requires_monomorphization(tcx)
+            })
+            .map(|def_id| MonoItem::Fn(Instance::mono(tcx, def_id.to_def_id()))),
+    );
+    if roots.is_empty() {
+        return;
+    }
+    let mut roots: Vec<_> = roots.into_iter().collect();
+    // Sort the roots, so that errors are reported in a deterministic order.
+    roots.sort_by_cached_key(|&item| item_name(tcx, item));
+
+    let panic_entry_points: FxHashSet<DefId> = PANIC_LANG_ITEMS
+        .iter()
+        .filter_map(|&lang_item| tcx.lang_items().require(lang_item).ok())
+        .collect();
+
+    // Items from which no panic is reachable, known from the roots checked so far.
+    let mut panic_free = FxHashSet::default();
+    for root in roots {
+        // Go through the reachable items breadth-first, so that the shortest chain of uses is
+        // reported. Each visited item is mapped to the item that uses it and the span of the use.
+        let mut visited: FxIndexMap<MonoItem<'tcx>, Option<(MonoItem<'tcx>, Span)>> =
+            FxIndexMap::default();
+        visited.insert(root, None);
+        let mut panic = None;
+        let mut next = 0;
+        'search: while let Some((&item, _)) = visited.get_index(next) {
+            next += 1;
+            if panic_free.contains(&item) {
+                continue;
+            }
+
+            let (mono_uses, non_mono_uses) = collect_uses(tcx, item);
+            for Spanned { node: used, span } in mono_uses {
+                if let MonoItem::Fn(instance) = used {
+                    if panic_entry_points.contains(&instance.def_id()) {
+                        panic = Some((item, span, PanicCause::EntryPoint(instance)));
+                        break 'search;
+                    }
+                }
+                visited.entry(used).or_insert(Some((item, span)));
+            }
+            for Spanned { node: used, span } in non_mono_uses {
+                let instance = match used {
+                    NonMonoUse::Instance(instance) => instance,
+                    NonMonoUse::FnPtrCall(ty) => {
+                        panic = Some((item, span, PanicCause::Unchecked(UncheckedCall::FnPtr(ty))));
+                        break 'search;
+                    }
+                };
+                let def_id = instance.def_id();
+                let cause = if panic_entry_points.contains(&def_id) {
+                    PanicCause::EntryPoint(instance)
+                } else if let ty::InstanceDef::Intrinsic(_) = instance.def {
+                    if !intrinsic_panics(tcx, instance) {
+                        continue;
+                    }
+                    PanicCause::Intrinsic(instance)
+                } else if let ty::InstanceDef::Virtual(..) = instance.def {
+                    PanicCause::Unchecked(UncheckedCall::Virtual(instance))
+                } else if tcx.is_foreign_item(def_id) {
+                    continue;
+                } else if !matches!(instance.def, ty::InstanceDef::Item(_))
+                    || tcx.is_mir_available(def_id)
+                {
+                    visited.entry(MonoItem::Fn(instance)).or_insert(Some((item, span)));
+                    continue;
+                } else {
+                    PanicCause::Unchecked(UncheckedCall::NoMir(instance))
+                };
+                panic = Some((item, span, cause));
+                break 'search;
+            }
+        }
+
+        match panic {
+            Some((item, span, cause)) => report_panic(tcx, &visited, root, item, span, cause),
+            None => panic_free.extend(visited.keys().copied()),
+        }
+    }
+}
+
+/// Returns whether a call to `instance`, an intrinsic, panics. Like in codegen, the intrinsics
+/// that check whether their type can be instantiated panic if it cannot.
+fn intrinsic_panics<'tcx>(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> bool {
+    let name = tcx.item_name(instance.def_id());
+    if ![sym::assert_inhabited, sym::assert_zero_valid, sym::assert_uninit_valid].contains(&name) {
+        return false;
+    }
+    let ty = instance.substs.type_at(0);
+    let Ok(layout) = tcx.layout_of(ty::ParamEnv::reveal_all().and(ty)) else {
+        return true;
+    };
+    match name {
+        sym::assert_inhabited => layout.abi.is_uninhabited(),
+        sym::assert_zero_valid => !tcx.permits_zero_init(layout),
+        _ => !tcx.permits_uninit_init(layout),
+    }
+}
+
+fn report_panic<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    visited: &FxIndexMap<MonoItem<'tcx>, Option<(MonoItem<'tcx>, Span)>>,
+    root: MonoItem<'tcx>,
+    item: MonoItem<'tcx>,
+    span: Span,
+    cause: PanicCause<'tcx>,
+) {
+    let MonoItem::Fn(root_instance) = root else { bug!("`#[no_panic]` on {:?}", root) };
+    let name = |item| item_name(tcx, item);
+
+    let mut err = tcx.sess.struct_span_err(
+        tcx.def_span(root_instance.def_id()),
+        &format!("`{}` is marked `#[no_panic]`, but may panic", name(root)),
+    );
+
+    // Walk the uses back to the root, to show them in the order they happen.
+    let mut chain = vec![];
+    let mut used = item;
+    while let Some(&Some((user, span))) = visited.get(&used) {
+        chain.push((user, span, used));
+        used = user;
+    }
+    let mut note = |span: Span, msg: &str| {
+        // Uses found in the values of constants and statics have no span.
+        if span.is_dummy() {
+            err.note(msg);
+        } else {
+            err.span_note(span, msg);
+        }
+    };
+    for (user, span, used) in chain.into_iter().rev() {
+        let verb = if let MonoItem::Static(_) = used { "refers to" } else { "calls" };
+        note(span, &format!("`{}` {} `{}`", name(user), verb, name(used)));
+    }
+
+    let msg = match cause {
+        PanicCause::EntryPoint(instance) => {
+            format!("`{}` may panic here by calling `{}`", name(item), name(MonoItem::Fn(instance)))
+        }
+        PanicCause::Intrinsic(instance) => format!(
+            "`{}` panics here by calling `{}`, which panics for this type",
+            name(item),
+            name(MonoItem::Fn(instance))
+        ),
+        PanicCause::Unchecked(UncheckedCall::NoMir(instance)) => format!(
+            "`{}` calls `{}`, which cannot be checked for panics because its MIR is not available",
+            name(item),
+            name(MonoItem::Fn(instance))
+        ),
+        PanicCause::Unchecked(UncheckedCall::Virtual(instance)) => format!(
+            "`{}` calls `{}` on a trait object, which cannot be checked for panics",
+            name(item),
+            with_no_trimmed_paths!(tcx.def_path_str(instance.def_id()))
+        ),
+        PanicCause::Unchecked(UncheckedCall::FnPtr(ty)) => format!(
+            "`{}` calls a function pointer of type `{}`, which cannot be checked for panics",
+            name(item),
+            ty
+        ),
+    };
+    note(span, &msg);
+    err.emit();
+}
+
+fn item_name<'tcx>(tcx: TyCtxt<'tcx>, item: MonoItem<'tcx>) -> String {
+    with_no_trimmed_paths!(match item {
+        MonoItem::Fn(instance) => instance.to_string(),
+        MonoItem::Static(def_id) => tcx.def_path_str(def_id),
+        MonoItem::GlobalAsm(_) => "global_asm".to_string(),
+    })
+}
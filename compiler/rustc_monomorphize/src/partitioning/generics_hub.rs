@@ -0,0 +1,469 @@
+//! Partitioning for crates built with `-Zgenerics-hub`.
+//!
+//! A generics hub is an rlib whose source code instantiates generic functions
+//! that are used throughout a workspace (`Vec<u8>`, `HashMap<String, _>` and so
+//! on). The profile passed to `-Zgenerics-hub` lists the mono items, one per
+//! line and named as `-Zprint-mono-items` prints them, that the hub makes
+//! available to downstream crates. Downstream crates then link against these
+//! instances instead of instantiating their own copies, whether or not they
+//! are compiled with `-Zshare-generics`.
+//!
+//! The profiled instances of generic functions and drop glue defined in the
+//! hub's dependencies are used as roots of the mono item collection, so the
+//! hub doesn't have to instantiate them in its source code. Apart from that and
+//! exporting the profiled instances, the hub is partitioned with the default
+//! strategy.
+
+use std::path::Path;
+
+use rustc_data_structures::fx::{FxHashSet, FxIndexSet};
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir::mono::{Linkage, MonoItem, Visibility};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::subst::{GenericArg, GenericArgKind, InternalSubsts, SubstsRef};
+use rustc_middle::ty::{self, GenericParamDefKind, Instance, InstanceDef, Ty, TyCtxt};
+use rustc_session::config::CrateType;
+
+use super::PartitioningCx;
+use crate::partitioning::default::DefaultPartitioning;
+use crate::partitioning::{Partitioner, PostInliningPartitioning, PreInliningPartitioning};
+
+pub struct GenericsHubPartitioning {
+    default: DefaultPartitioning,
+    /// The mono item names listed in the profile, in file order.
+    profile: FxIndexSet<String>,
+}
+
+impl GenericsHubPartitioning {
+    pub fn new(tcx: TyCtxt<'_>, profile_path: &Path) -> Self {
+        if tcx.sess.crate_types() != [CrateType::Rlib] {
+            tcx.sess.fatal("`-Zgenerics-hub` can only be used when building an rlib");
+        }
+
+        let profile = read_profile(tcx, profile_path);
+        GenericsHubPartitioning { default: DefaultPartitioning, profile }
+    }
+}
+
+fn read_profile(tcx: TyCtxt<'_>, profile_path: &Path) -> FxIndexSet<String> {
+    let contents = std::fs::read_to_string(profile_path).unwrap_or_else(|err| {
+        tcx.sess.fatal(&format!(
+            "failed to read generics hub profile `{}`: {}",
+            profile_path.display(),
+            err
+        ))
+    });
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Returns the instances named by the entries of the generics hub profile, for the collector to
+/// use them as roots. Entries naming items of the hub itself, or that can't be resolved, are
+/// skipped: they are reported by the partitioner if the hub doesn't instantiate them.
+pub(crate) fn profiled_instances<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    profile_path: &Path,
+) -> Vec<Instance<'tcx>> {
+    read_profile(tcx, profile_path)
+        .iter()
+        .filter_map(|entry| {
+            let instance = ProfileEntryParser { tcx, rest: entry }.instance();
+            debug!("generics hub profile entry `{}` resolved to {:?}", entry, instance);
+            instance
+        })
+        .collect()
+}
+
+/// Parses a mono item printed by `-Zprint-mono-items`, like
+/// `fn std::vec::Vec::<u8>::push` or
+/// `fn std::ptr::drop_in_place::<std::vec::Vec<u8>> - shim(Some(std::vec::Vec<u8>))`, and
+/// resolves the paths in it through the modules of the dependencies. Generic functions, inherent
+/// methods and drop glue are supported, trait methods (`<T as Trait>::method`) aren't.
+struct ProfileEntryParser<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    rest: &'a str,
+}
+
+impl<'a, 'tcx> ProfileEntryParser<'a, 'tcx> {
+    fn instance(&mut self) -> Option<Instance<'tcx>> {
+        if !self.eat("fn ") {
+            return None;
+        }
+        let (def_id, substs) = self.value_path()?;
+        let instance = if self.eat("- shim(Some(") {
+            let ty = self.ty()?;
+            if !self.eat("))") {
+                return None;
+            }
+            Instance::resolve_drop_in_place(self.tcx, ty)
+        } else {
+            Instance::resolve(self.tcx, ty::ParamEnv::reveal_all(), def_id, substs).ok()??
+        };
+        self.rest.trim().is_empty().then_some(instance)
+    }
+
+    /// Parses a path to a function, with turbofishes: `std::vec::Vec::<u8>::push`.
+    fn value_path(&mut self) -> Option<(DefId, SubstsRef<'tcx>)> {
+        let tcx = self.tcx;
+        let mut module = self.extern_crate()?;
+        let mut self_ty = None;
+        while self.eat("::") {
+            let name = self.ident()?;
+            let args = if self.eat("::<") { self.ty_list(">")? } else { vec![] };
+            if let Some(self_ty) = self_ty {
+                // An inherent method of the type named by the previous segments.
+                return tcx.inherent_impls(adt_def_id(self_ty)).iter().find_map(|&impl_def_id| {
+                    let method = tcx
+                        .associated_items(impl_def_id)
+                        .in_definition_order()
+                        .find(|item| item.kind == ty::AssocKind::Fn && item.name.as_str() == name)?;
+                    let impl_substs = impl_substs_for_self_ty(tcx, impl_def_id, self_ty)?;
+                    let substs = self.substs(method.def_id, Some(impl_substs), args.clone())?;
+                    Some((method.def_id, substs))
+                });
+            }
+            match self.module_child(module, name)? {
+                (DefKind::Mod, def_id) if args.is_empty() => module = def_id,
+                (DefKind::Struct | DefKind::Enum | DefKind::Union, def_id) => {
+                    self_ty = Some(self.adt(def_id, args)?);
+                }
+                (DefKind::Fn, def_id) => return Some((def_id, self.substs(def_id, None, args)?)),
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// Parses a type the way types are printed: `&mut [std::vec::Vec<u8>; 2]`.
+    fn ty(&mut self) -> Option<Ty<'tcx>> {
+        let tcx = self.tcx;
+        if self.eat("&") {
+            let mutbl = if self.eat("mut ") { ty::Mutability::Mut } else { ty::Mutability::Not };
+            let ty = self.ty()?;
+            return Some(tcx.mk_ref(tcx.lifetimes.re_erased, ty::TypeAndMut { ty, mutbl }));
+        }
+        if self.eat("*") {
+            let mutbl = if self.eat("const ") {
+                ty::Mutability::Not
+            } else if self.eat("mut ") {
+                ty::Mutability::Mut
+            } else {
+                return None;
+            };
+            let ty = self.ty()?;
+            return Some(tcx.mk_ptr(ty::TypeAndMut { ty, mutbl }));
+        }
+        if self.eat("[") {
+            let ty = self.ty()?;
+            if self.eat("]") {
+                return Some(tcx.mk_slice(ty));
+            }
+            if !self.eat(";") {
+                return None;
+            }
+            let len = self.ident()?.parse().ok()?;
+            return self.eat("]").then(|| tcx.mk_array(ty, len));
+        }
+        if self.eat("(") {
+            return Some(tcx.intern_tup(&self.ty_list(")")?));
+        }
+        if self.eat("!") {
+            return Some(tcx.types.never);
+        }
+
+        let name = self.ident()?;
+        let primitive = match name {
+            "bool" => tcx.types.bool,
+            "char" => tcx.types.char,
+            "str" => tcx.types.str_,
+            "i8" => tcx.types.i8,
+            "i16" => tcx.types.i16,
+            "i32" => tcx.types.i32,
+            "i64" => tcx.types.i64,
+            "i128" => tcx.types.i128,
+            "isize" => tcx.types.isize,
+            "u8" => tcx.types.u8,
+            "u16" => tcx.types.u16,
+            "u32" => tcx.types.u32,
+            "u64" => tcx.types.u64,
+            "u128" => tcx.types.u128,
+            "usize" => tcx.types.usize,
+            "f32" => tcx.types.f32,
+            "f64" => tcx.types.f64,
+            _ => {
+                // A path to an ADT, without turbofish: `std::vec::Vec<u8>`.
+                let mut module = self.crate_named(name)?;
+                while self.eat("::") {
+                    let name = self.ident()?;
+                    match self.module_child(module, name)? {
+                        (DefKind::Mod, def_id) => module = def_id,
+                        (DefKind::Struct | DefKind::Enum | DefKind::Union, def_id) => {
+                            let args = if self.eat("<") { self.ty_list(">")? } else { vec![] };
+                            return self.adt(def_id, args);
+                        }
+                        _ => return None,
+                    }
+                }
+                return None;
+            }
+        };
+        Some(primitive)
+    }
+
+    /// Parses the types of a list that was opened by the caller, up to the `close` token.
+    fn ty_list(&mut self, close: &str) -> Option<Vec<Ty<'tcx>>> {
+        let mut tys = vec![];
+        while !self.eat(close) {
+            tys.push(self.ty()?);
+            if !self.eat(",") {
+                return self.eat(close).then_some(tys);
+            }
+        }
+        Some(tys)
+    }
+
+    fn adt(&self, def_id: DefId, args: Vec<Ty<'tcx>>) -> Option<Ty<'tcx>> {
+        let substs = self.substs(def_id, None, args)?;
+        Some(self.tcx.mk_adt(self.tcx.adt_def(def_id), substs))
+    }
+
+    /// Creates the substs of `def_id` from the type arguments printed for it, in order. The
+    /// parameters of its parent are taken from `parent_substs`, lifetimes are erased and omitted
+    /// type parameters take their default.
+    fn substs(
+        &self,
+        def_id: DefId,
+        parent_substs: Option<SubstsRef<'tcx>>,
+        args: Vec<Ty<'tcx>>,
+    ) -> Option<SubstsRef<'tcx>> {
+        let tcx = self.tcx;
+        let parent_count = tcx.generics_of(def_id).parent_count;
+        let mut args = args.into_iter();
+        let mut complete = true;
+        let substs = InternalSubsts::for_item(tcx, def_id, |param, substs| {
+            let index = param.index as usize;
+            match (parent_substs, &param.kind) {
+                (Some(parent_substs), _) if index < parent_count => parent_substs[index],
+                (_, GenericParamDefKind::Lifetime) => tcx.lifetimes.re_erased.into(),
+                (_, GenericParamDefKind::Type { has_default, .. }) => match args.next() {
+                    Some(ty) => ty.into(),
+                    None if *has_default => {
+                        tcx.bound_type_of(param.def_id).subst(tcx, substs).into()
+                    }
+                    None => {
+                        complete = false;
+                        tcx.mk_param_from_def(param)
+                    }
+                },
+                (_, GenericParamDefKind::Const { .. }) => {
+                    complete = false;
+                    tcx.mk_param_from_def(param)
+                }
+            }
+        });
+        (complete && args.next().is_none()).then_some(substs)
+    }
+
+    fn extern_crate(&mut self) -> Option<DefId> {
+        let name = self.ident()?;
+        self.crate_named(name)
+    }
+
+    fn crate_named(&self, name: &str) -> Option<DefId> {
+        let tcx = self.tcx;
+        let cnum = tcx.crates(()).iter().find(|&&cnum| tcx.crate_name(cnum).as_str() == name)?;
+        Some(cnum.as_def_id())
+    }
+
+    /// Finds the item `name` in the module `module`, which is in a dependency, following
+    /// re-exports. Macros are ignored since they share their name with modules like `vec`.
+    fn module_child(&self, module: DefId, name: &str) -> Option<(DefKind, DefId)> {
+        self.tcx.module_children(module).iter().find_map(|child| match child.res {
+            Res::Def(kind, def_id)
+                if !matches!(kind, DefKind::Macro(_)) && child.ident.as_str() == name =>
+            {
+                Some((kind, def_id))
+            }
+            _ => None,
+        })
+    }
+
+    /// Skips whitespace, then consumes `token` if the input starts with it.
+    fn eat(&mut self, token: &str) -> bool {
+        match self.rest.trim_start().strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn ident(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim_start();
+        let len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+        if len == 0 {
+            return None;
+        }
+        let (ident, rest) = rest.split_at(len);
+        self.rest = rest;
+        Some(ident)
+    }
+}
+
+fn adt_def_id(ty: Ty<'_>) -> DefId {
+    match ty.kind() {
+        ty::Adt(adt_def, _) => adt_def.did(),
+        _ => bug!("expected an ADT, found `{}`", ty),
+    }
+}
+
+/// Matches the self type of an inherent impl, like `Vec<T, A>`, against `self_ty`, like
+/// `Vec<u8, Global>`, to find the substs of the impl.
+fn impl_substs_for_self_ty<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    impl_def_id: DefId,
+    self_ty: Ty<'tcx>,
+) -> Option<SubstsRef<'tcx>> {
+    let (ty::Adt(impl_adt, impl_args), ty::Adt(adt, args)) =
+        (tcx.type_of(impl_def_id).kind(), self_ty.kind())
+    else {
+        return None;
+    };
+    if impl_adt != adt {
+        return None;
+    }
+
+    let mut impl_substs: Vec<Option<GenericArg<'tcx>>> =
+        vec![None; tcx.generics_of(impl_def_id).count()];
+    for (impl_arg, arg) in impl_args.iter().zip(args.iter()) {
+        let index = match impl_arg.unpack() {
+            GenericArgKind::Lifetime(_) => continue,
+            GenericArgKind::Type(ty) => match ty.kind() {
+                ty::Param(param) => param.index,
+                _ if impl_arg == arg => continue,
+                // The impl is for specific types, e.g. `impl Vec<u8>`, and not these.
+                _ => return None,
+            },
+            GenericArgKind::Const(ct) => match ct.kind() {
+                ty::ConstKind::Param(param) => param.index,
+                _ if impl_arg == arg => continue,
+                _ => return None,
+            },
+        };
+        match impl_substs[index as usize] {
+            Some(prev) if prev != arg => return None,
+            _ => impl_substs[index as usize] = Some(arg),
+        }
+    }
+
+    let mut complete = true;
+    let substs = InternalSubsts::for_item(tcx, impl_def_id, |param, _| {
+        match (impl_substs[param.index as usize], &param.kind) {
+            (Some(arg), _) => arg,
+            (None, GenericParamDefKind::Lifetime) => tcx.lifetimes.re_erased.into(),
+            (None, _) => {
+                complete = false;
+                tcx.mk_param_from_def(param)
+            }
+        }
+    });
+    complete.then_some(substs)
+}
+
+impl<'tcx> Partitioner<'tcx> for GenericsHubPartitioning {
+    fn place_root_mono_items(
+        &mut self,
+        cx: &PartitioningCx<'_, 'tcx>,
+        mono_items: &mut dyn Iterator<Item = MonoItem<'tcx>>,
+    ) -> PreInliningPartitioning<'tcx> {
+        let mut partitioning = self.default.place_root_mono_items(cx, mono_items);
+        let mut shared = FxHashSet::default();
+
+        for cgu in &mut partitioning.codegen_units {
+            for (mono_item, (linkage, visibility)) in cgu.items_mut() {
+                if *linkage != Linkage::External || !is_shareable(cx.tcx, mono_item) {
+                    continue;
+                }
+
+                let name = with_no_trimmed_paths!(mono_item.to_string());
+                if self.profile.contains(&name) {
+                    // Downstream crates will link against this instance, so it
+                    // must neither be hidden nor internalized.
+                    *visibility = Visibility::Default;
+                    partitioning.internalization_candidates.remove(mono_item);
+                    shared.insert(name);
+                }
+            }
+        }
+
+        for entry in &self.profile {
+            if !shared.contains(entry) {
+                cx.tcx
+                    .sess
+                    .struct_warn(&format!(
+                        "generics hub profile entry `{}` is not instantiated as a shareable \
+                         generic in this crate",
+                        entry
+                    ))
+                    .note(
+                        "only generic functions and drop glue that are codegened once per crate \
+                         (not `#[inline]` ones) can be shared",
+                    )
+                    .emit();
+            }
+        }
+
+        partitioning
+    }
+
+    fn merge_codegen_units(
+        &mut self,
+        cx: &PartitioningCx<'_, 'tcx>,
+        initial_partitioning: &mut PreInliningPartitioning<'tcx>,
+    ) {
+        self.default.merge_codegen_units(cx, initial_partitioning);
+    }
+
+    fn place_inlined_mono_items(
+        &mut self,
+        cx: &PartitioningCx<'_, 'tcx>,
+        initial_partitioning: PreInliningPartitioning<'tcx>,
+    ) -> PostInliningPartitioning<'tcx> {
+        self.default.place_inlined_mono_items(cx, initial_partitioning)
+    }
+
+    fn internalize_symbols(
+        &mut self,
+        cx: &PartitioningCx<'_, 'tcx>,
+        partitioning: &mut PostInliningPartitioning<'tcx>,
+    ) {
+        self.default.internalize_symbols(cx, partitioning);
+    }
+}
+
+/// Whether `mono_item` is a monomorphization that downstream crates could pick
+/// up through `upstream_monomorphizations`.
+fn is_shareable<'tcx>(tcx: TyCtxt<'tcx>, mono_item: &MonoItem<'tcx>) -> bool {
+    let MonoItem::Fn(instance) = mono_item else { return false };
+
+    let def_id = match instance.def {
+        InstanceDef::Item(def) => def.did,
+        InstanceDef::DropGlue(def_id, Some(_)) => def_id,
+        _ => return false,
+    };
+
+    if instance.substs.non_erasable_generics().next().is_none() {
+        return false;
+    }
+
+    match def_id.as_local() {
+        Some(def_id) => !tcx.is_unreachable_local_definition(def_id),
+        None => true,
+    }
+}
@@ -34,15 +34,9 @@ pub fn merge_codegen_units<'tcx>(
     while codegen_units.len() > cx.target_cgu_count {
         // Sort small cgus to the back
         codegen_units.sort_by_cached_key(|cgu| cmp::Reverse(cgu.size_estimate()));
-        let mut smallest = codegen_units.pop().unwrap();
+        let smallest = codegen_units.pop().unwrap();
         let second_smallest = codegen_units.last_mut().unwrap();
 
-        // Move the mono-items from `smallest` to `second_smallest`
-        second_smallest.modify_size_estimate(smallest.size_estimate());
-        for (k, v) in smallest.items_mut().drain() {
-            second_smallest.items_mut().insert(k, v);
-        }
-
         // Record that `second_smallest` now contains all the stuff that was in
         // `smallest` before.
         let mut consumed_cgu_names = cgu_contents.remove(&smallest.name()).unwrap();
@@ -53,6 +47,9 @@ pub fn merge_codegen_units<'tcx>(
             smallest.name(),
             second_smallest.name()
         );
+
+        // Move the mono-items from `smallest` to `second_smallest`
+        second_smallest.merge(smallest);
     }
 
     let cgu_name_builder = &mut CodegenUnitNameBuilder::new(cx.tcx);
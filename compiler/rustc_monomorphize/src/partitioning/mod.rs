@@ -93,21 +93,30 @@
 //! inlining, even when they are not marked `#[inline]`.
 
 mod default;
+pub(crate) mod generics_hub;
 mod merging;
 
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::sync;
-use rustc_hir::def_id::DefIdSet;
+use rustc_hir::def_id::{DefIdSet, LOCAL_CRATE};
 use rustc_middle::mir;
 use rustc_middle::mir::mono::MonoItem;
 use rustc_middle::mir::mono::{CodegenUnit, Linkage};
 use rustc_middle::ty::print::with_no_trimmed_paths;
 use rustc_middle::ty::query::Providers;
 use rustc_middle::ty::TyCtxt;
+use rustc_session::config::MonoReportFormat;
 use rustc_span::symbol::Symbol;
+use serde::Serialize;
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
 
 use crate::collector::InliningMap;
 use crate::collector::{self, MonoItemCollectionMode};
+use crate::no_panic;
+use crate::polymorphize;
 
 pub struct PartitioningCx<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
@@ -148,7 +157,10 @@ fn get_partitioner<'tcx>(tcx: TyCtxt<'tcx>) -> Box<dyn Partitioner<'tcx>> {
     };
 
     match strategy {
-        "default" => Box::new(default::DefaultPartitioning),
+        "default" => match &tcx.sess.opts.unstable_opts.generics_hub {
+            Some(profile) => Box::new(generics_hub::GenericsHubPartitioning::new(tcx, profile)),
+            None => Box::new(default::DefaultPartitioning),
+        },
         _ => tcx.sess.fatal("unknown partitioning strategy"),
     }
 }
@@ -376,6 +388,8 @@ fn collect_and_partition_mono_items<'tcx>(
 
     let (items, inlining_map) = collector::collect_crate_mono_items(tcx, collection_mode);
 
+    tcx.sess.time("monomorphization_no_panic_check", || no_panic::check_no_panic(tcx, &items));
+
     tcx.sess.abort_if_errors();
 
     let (codegen_units, _) = tcx.sess.time("partition_and_assert_distinct_symbols", || {
@@ -436,22 +450,8 @@ fn collect_and_partition_mono_items<'tcx>(
                     output.push(' ');
                     output.push_str(cgu_name.as_str());
 
-                    let linkage_abbrev = match linkage {
-                        Linkage::External => "External",
-                        Linkage::AvailableExternally => "Available",
-                        Linkage::LinkOnceAny => "OnceAny",
-                        Linkage::LinkOnceODR => "OnceODR",
-                        Linkage::WeakAny => "WeakAny",
-                        Linkage::WeakODR => "WeakODR",
-                        Linkage::Appending => "Appending",
-                        Linkage::Internal => "Internal",
-                        Linkage::Private => "Private",
-                        Linkage::ExternalWeak => "ExternalWeak",
-                        Linkage::Common => "Common",
-                    };
-
                     output.push('[');
-                    output.push_str(linkage_abbrev);
+                    output.push_str(linkage_abbrev(linkage));
                     output.push(']');
                 }
                 output
@@ -465,9 +465,201 @@ fn collect_and_partition_mono_items<'tcx>(
         }
     }
 
+    if tcx.sess.opts.unstable_opts.print_polymorphization {
+        polymorphize::print_polymorphization(tcx, &items);
+    }
+
+    if let Some(MonoReportFormat::Json) = tcx.sess.opts.unstable_opts.mono_report {
+        dump_mono_report(tcx, &items, &inlining_map, codegen_units);
+    }
+
     (tcx.arena.alloc(mono_items), codegen_units)
 }
 
+fn linkage_abbrev(linkage: Linkage) -> &'static str {
+    match linkage {
+        Linkage::External => "External",
+        Linkage::AvailableExternally => "Available",
+        Linkage::LinkOnceAny => "OnceAny",
+        Linkage::LinkOnceODR => "OnceODR",
+        Linkage::WeakAny => "WeakAny",
+        Linkage::WeakODR => "WeakODR",
+        Linkage::Appending => "Appending",
+        Linkage::Internal => "Internal",
+        Linkage::Private => "Private",
+        Linkage::ExternalWeak => "ExternalWeak",
+        Linkage::Common => "Common",
+    }
+}
+
+#[derive(Serialize)]
+struct MonoReport {
+    crate_name: String,
+    /// Every mono item of the crate, sorted by name.
+    mono_items: Vec<MonoItemReport>,
+    /// The items with at least one instantiation, the biggest total size first.
+    generic_sources: Vec<GenericSourceReport>,
+}
+
+#[derive(Serialize)]
+struct MonoItemReport {
+    name: String,
+    kind: &'static str,
+    /// The path of the item this is an instantiation of.
+    source: String,
+    is_generic: bool,
+    /// The number of MIR statements and terminators, as used to estimate CGU sizes.
+    size_estimate: usize,
+    /// A shortest chain of mono items that leads from a collection root to this item, starting
+    /// with the item that uses this one. Empty for roots.
+    instantiated_by: Vec<String>,
+    codegen_units: Vec<CguPlacementReport>,
+}
+
+#[derive(Serialize)]
+struct CguPlacementReport {
+    name: String,
+    linkage: &'static str,
+}
+
+#[derive(Serialize)]
+struct GenericSourceReport {
+    source: String,
+    instantiations: usize,
+    total_size_estimate: usize,
+}
+
+/// Writes the `-Zmono-report=json` report, which explains where the mono items of the crate
+/// come from and where they end up.
+fn dump_mono_report<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    inlining_map: &InliningMap<'tcx>,
+    codegen_units: &[CodegenUnit<'tcx>],
+) {
+    let names: FxHashMap<MonoItem<'tcx>, String> =
+        items.iter().map(|item| (*item, with_no_trimmed_paths!(item.to_string()))).collect();
+
+    let mut item_to_cgus: FxHashMap<_, Vec<_>> = Default::default();
+    for cgu in codegen_units {
+        for (&mono_item, &(linkage, _)) in cgu.items() {
+            item_to_cgus.entry(mono_item).or_default().push(CguPlacementReport {
+                name: cgu.name().to_string(),
+                linkage: linkage_abbrev(linkage),
+            });
+        }
+    }
+
+    // The accesses recorded by the collector are what made it instantiate an item. Walk them
+    // breadth-first from the items nothing accesses, visiting items by name so that the chosen
+    // (shortest) chains don't depend on hashing.
+    let mut accesses: FxHashMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>> = Default::default();
+    let mut accessed = FxHashSet::default();
+    inlining_map.iter_accesses(|accessor, targets| {
+        let targets: Vec<_> =
+            targets.iter().copied().filter(|target| names.contains_key(target)).collect();
+        accessed.extend(targets.iter().copied());
+        accesses.insert(accessor, targets);
+    });
+    let by_name = |a: &MonoItem<'tcx>, b: &MonoItem<'tcx>| names[a].cmp(&names[b]);
+    let mut roots: Vec<_> = items.iter().copied().filter(|item| !accessed.contains(item)).collect();
+    roots.sort_by(by_name);
+
+    let mut instantiator: FxHashMap<MonoItem<'tcx>, MonoItem<'tcx>> = Default::default();
+    let mut visited: FxHashSet<_> = roots.iter().copied().collect();
+    let mut queue: VecDeque<_> = roots.into_iter().collect();
+    let mut visit = |queue: &mut VecDeque<_>, visited: &mut FxHashSet<_>| {
+        while let Some(item) = queue.pop_front() {
+            let mut targets = accesses.get(&item).cloned().unwrap_or_default();
+            targets.sort_by(by_name);
+            for target in targets {
+                if visited.insert(target) {
+                    instantiator.insert(target, item);
+                    queue.push_back(target);
+                }
+            }
+        }
+    };
+    visit(&mut queue, &mut visited);
+    // Items only reachable through cycles have no root, start from one of them.
+    let mut unvisited: Vec<_> =
+        items.iter().copied().filter(|item| !visited.contains(item)).collect();
+    unvisited.sort_by(by_name);
+    for item in unvisited {
+        if visited.insert(item) {
+            queue.push_back(item);
+            visit(&mut queue, &mut visited);
+        }
+    }
+
+    let mut generic_sources: FxHashMap<String, GenericSourceReport> = Default::default();
+    let mut mono_items: Vec<_> = items
+        .iter()
+        .map(|&item| {
+            let (kind, source) = match item {
+                MonoItem::Fn(instance) => ("fn", tcx.def_path_str(instance.def_id())),
+                MonoItem::Static(def_id) => ("static", tcx.def_path_str(def_id)),
+                MonoItem::GlobalAsm(item_id) => {
+                    ("global_asm", tcx.def_path_str(item_id.def_id.to_def_id()))
+                }
+            };
+            let is_generic = item.is_generic_fn();
+            let size_estimate = item.size_estimate(tcx);
+            if is_generic {
+                let generic_source =
+                    generic_sources.entry(source.clone()).or_insert_with(|| GenericSourceReport {
+                        source: source.clone(),
+                        instantiations: 0,
+                        total_size_estimate: 0,
+                    });
+                generic_source.instantiations += 1;
+                generic_source.total_size_estimate += size_estimate;
+            }
+
+            let mut instantiated_by = vec![];
+            let mut current = item;
+            while let Some(&caller) = instantiator.get(&current) {
+                instantiated_by.push(names[&caller].clone());
+                current = caller;
+            }
+
+            let mut codegen_units = item_to_cgus.remove(&item).unwrap_or_default();
+            codegen_units.sort_by(|a, b| a.name.cmp(&b.name));
+
+            MonoItemReport {
+                name: names[&item].clone(),
+                kind,
+                source,
+                is_generic,
+                size_estimate,
+                instantiated_by,
+                codegen_units,
+            }
+        })
+        .collect();
+    mono_items.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut generic_sources: Vec<_> = generic_sources.into_values().collect();
+    generic_sources.sort_by(|a, b| {
+        b.total_size_estimate.cmp(&a.total_size_estimate).then_with(|| a.source.cmp(&b.source))
+    });
+
+    let report = MonoReport {
+        crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+        mono_items,
+        generic_sources,
+    };
+    let path = tcx.output_filenames(()).with_extension("mono_report.json");
+    let result = File::create(&path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &report)?;
+        writer.flush()
+    });
+    if let Err(err) = result {
+        tcx.sess.err(&format!("failed to write mono report to `{}`: {}", path.display(), err));
+    }
+}
+
 fn codegened_and_inlined_items<'tcx>(tcx: TyCtxt<'tcx>, (): ()) -> &'tcx DefIdSet {
     let (items, cgus) = tcx.collect_and_partition_mono_items(());
     let mut visited = DefIdSet::default();
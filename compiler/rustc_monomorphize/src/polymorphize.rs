@@ -5,14 +5,17 @@
 //! generic parameters are unused (and eventually, in what ways generic parameters are used - only
 //! for their size, offset of a field, etc.).
 
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_hir::{def::DefKind, def_id::DefId, ConstContext};
 use rustc_index::bit_set::FiniteBitSet;
 use rustc_middle::mir::{
+    mono::MonoItem,
     visit::{TyContext, Visitor},
     Local, LocalDecl, Location,
 };
 use rustc_middle::ty::{
     self,
+    print::with_no_trimmed_paths,
     query::Providers,
     subst::SubstsRef,
     visit::{TypeSuperVisitable, TypeVisitable, TypeVisitor},
@@ -36,7 +39,10 @@ fn unused_generic_params<'tcx>(
     tcx: TyCtxt<'tcx>,
     instance: ty::InstanceDef<'tcx>,
 ) -> FiniteBitSet<u32> {
-    if !tcx.sess.opts.unstable_opts.polymorphize {
+    // `-Zprint-polymorphization` reports the results of the analysis without polymorphization
+    // being enabled: `Instance::polymorphize` only uses them with `-Zpolymorphize`.
+    let opts = &tcx.sess.opts.unstable_opts;
+    if !opts.polymorphize && !opts.print_polymorphization {
         // If polymorphization disabled, then all parameters are used.
         return FiniteBitSet::new_empty();
     }
@@ -128,8 +134,8 @@ fn should_polymorphize<'tcx>(
 }
 
 /// Some parameters are considered used-by-default, such as non-generic parameters and the dummy
-/// generic parameters from closures, this function marks them as used. `leaf_is_closure` should
-/// be `true` if the item that `unused_generic_params` was invoked on is a closure.
+/// generic parameters from closures, generators and inline constants, this function marks them as
+/// used.
 #[instrument(level = "debug", skip(tcx, def_id, generics, unused_parameters))]
 fn mark_used_by_default_parameters<'tcx>(
     tcx: TyCtxt<'tcx>,
@@ -138,9 +144,14 @@ fn mark_used_by_default_parameters<'tcx>(
     unused_parameters: &mut FiniteBitSet<u32>,
 ) {
     match tcx.def_kind(def_id) {
-        DefKind::Closure | DefKind::Generator => {
+        // The own parameters of closures and generators are synthetic: they stand for the closure
+        // kind, its signature, the types of its upvars and, for generators, the resume, yield,
+        // return and witness types. Likewise, the own parameter of an inline constant is its type.
+        // These are always needed to codegen the body, so only the parameters inherited from the
+        // parent can be unused.
+        DefKind::Closure | DefKind::Generator | DefKind::InlineConst => {
             for param in &generics.params {
-                debug!(?param, "(closure/gen)");
+                debug!(?param, "(closure/gen/inline const)");
                 unused_parameters.clear(param.index);
             }
         }
@@ -167,7 +178,6 @@ fn mark_used_by_default_parameters<'tcx>(
         | DefKind::Use
         | DefKind::ForeignMod
         | DefKind::AnonConst
-        | DefKind::InlineConst
         | DefKind::OpaqueTy
         | DefKind::Field
         | DefKind::LifetimeParam
@@ -224,6 +234,55 @@ fn emit_unused_generic_params_error<'tcx>(
     err.emit();
 }
 
+/// Print the result of the analysis for every local generic function, closure and generator among
+/// the collected mono items, for `-Zprint-polymorphization`. Each line also states how many
+/// instances of the item were collected: without `-Zpolymorphize`, that's one per instantiation,
+/// and with it, what remains after polymorphization, which is what determines the code size.
+pub(crate) fn print_polymorphization<'tcx>(tcx: TyCtxt<'tcx>, items: &FxHashSet<MonoItem<'tcx>>) {
+    let mut instances: FxHashMap<DefId, usize> = FxHashMap::default();
+    for item in items {
+        let MonoItem::Fn(ty::Instance { def: ty::InstanceDef::Item(def), .. }) = *item else {
+            continue;
+        };
+        if def.did.is_local() && tcx.generics_of(def.did).count() > 0 {
+            *instances.entry(def.did).or_default() += 1;
+        }
+    }
+
+    let mut lines: Vec<_> = instances
+        .into_iter()
+        .map(|(def_id, count)| {
+            let instance = ty::InstanceDef::Item(ty::WithOptConstParam::unknown(def_id));
+            let unused_parameters = tcx.unused_generic_params(instance);
+
+            let mut unused = Vec::new();
+            let mut next_generics = Some(tcx.generics_of(def_id));
+            while let Some(generics) = next_generics {
+                for param in &generics.params {
+                    if unused_parameters.contains(param.index).unwrap_or(false) {
+                        unused.push((param.index, param.name));
+                    }
+                }
+                next_generics = generics.parent.map(|did| tcx.generics_of(did));
+            }
+            unused.sort_by_key(|&(index, _)| index);
+
+            let unused: Vec<_> = unused.into_iter().map(|(_, name)| name.to_string()).collect();
+            format!(
+                "{} unused: [{}] instances: {}",
+                with_no_trimmed_paths!(tcx.def_path_str(def_id)),
+                unused.join(", "),
+                count
+            )
+        })
+        .collect();
+    lines.sort();
+
+    for line in lines {
+        println!("POLYMORPHIZATION {}", line);
+    }
+}
+
 /// Visitor used to aggregate generic parameter uses.
 struct MarkUsedGenericParams<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
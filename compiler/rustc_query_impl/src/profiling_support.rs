@@ -244,7 +244,7 @@ fn alloc_self_profile_query_strings_for_query_cache<'tcx, C>(
         if profiler.query_key_recording_enabled() {
             let mut query_string_builder = QueryKeyStringBuilder::new(profiler, tcx, string_cache);
 
-            let query_name = profiler.get_or_alloc_cached_string(query_name);
+            let query_name_id = profiler.get_or_alloc_cached_string(query_name);
 
             // Since building the string representation of query keys might
             // need to invoke queries itself, we cannot keep the query caches
@@ -252,6 +252,10 @@ fn alloc_self_profile_query_strings_for_query_cache<'tcx, C>(
             // `(query_key, dep_node_index)` pairs and release the lock again.
             let mut query_keys_and_indices = Vec::new();
             query_cache.iter(&mut |k, _, i| query_keys_and_indices.push((k.clone(), i)));
+            profiler.record_query_names(
+                query_name,
+                query_keys_and_indices.iter().map(|&(_, i)| i.into()),
+            );
 
             // Now actually allocate the strings. If allocating the strings
             // generates new entries in the query cache, we'll miss them but
@@ -262,7 +266,7 @@ fn alloc_self_profile_query_strings_for_query_cache<'tcx, C>(
 
                 // Create the string version of the query-key
                 let query_key = query_key.to_self_profile_string(&mut query_string_builder);
-                let event_id = event_id_builder.from_label_and_arg(query_name, query_key);
+                let event_id = event_id_builder.from_label_and_arg(query_name_id, query_key);
 
                 // Doing this in bulk might be a good idea:
                 profiler.map_query_invocation_id_to_string(
@@ -272,8 +276,8 @@ fn alloc_self_profile_query_strings_for_query_cache<'tcx, C>(
             }
         } else {
             // In this branch we don't allocate query keys
-            let query_name = profiler.get_or_alloc_cached_string(query_name);
-            let event_id = event_id_builder.from_label(query_name).to_string_id();
+            let query_name_id = profiler.get_or_alloc_cached_string(query_name);
+            let event_id = event_id_builder.from_label(query_name_id).to_string_id();
 
             // FIXME(eddyb) make this O(1) by using a pre-cached query name `EventId`,
             // instead of passing the `DepNodeIndex` to `finish_with_query_invocation_id`,
@@ -282,6 +286,7 @@ fn alloc_self_profile_query_strings_for_query_cache<'tcx, C>(
             query_cache.iter(&mut |_, _, i| {
                 query_invocation_ids.push(i.into());
             });
+            profiler.record_query_names(query_name, query_invocation_ids.iter().copied());
 
             profiler.bulk_map_query_invocation_id_to_single_string(
                 query_invocation_ids.into_iter(),
@@ -96,6 +96,10 @@ struct DepGraphData<K: DepKind> {
     /// a particular query result was decoded from disk
     /// (not just marked green)
     debug_loaded_from_disk: Lock<FxHashSet<DepNode<K>>>,
+
+    /// For `-Zincremental-explain`: the number of queries that were re-executed
+    /// because of each changed dep-node.
+    reexecution_causes: Lock<FxHashMap<DepNode<K>, usize>>,
 }
 
 pub fn hash_result<R>(hcx: &mut StableHashingContext<'_>, result: &R) -> Fingerprint
@@ -158,6 +162,7 @@ pub fn new(
                 previous: prev_graph,
                 colors,
                 debug_loaded_from_disk: Default::default(),
+                reexecution_causes: Default::default(),
             })),
             virtual_dep_node_index: Lrc::new(AtomicU32::new(0)),
         }
@@ -322,6 +327,18 @@ fn with_task_impl<Ctxt: HasDepContext<DepKind = K>, A: Debug, R>(
             key
         );
 
+        // For `-Zincremental-explain`, determine why a node that existed in the previous
+        // session is re-executed while the colors of its dependencies are still the ones
+        // that prevented marking it green.
+        let reexecution_chain = if cx.dep_context().sess().opts.unstable_opts.incremental_explain
+            && !cx.dep_context().is_eval_always(key.kind)
+            && data.previous.node_to_index_opt(&key).is_some()
+        {
+            Some(self.red_dependency_chain(*cx.dep_context(), &key))
+        } else {
+            None
+        };
+
         let task_deps = if cx.dep_context().is_eval_always(key.kind) {
             None
         } else {
@@ -361,6 +378,11 @@ fn with_task_impl<Ctxt: HasDepContext<DepKind = K>, A: Debug, R>(
 
         hashing_timer.finish_with_query_invocation_id(dep_node_index.into());
 
+        if let Some(chain) = reexecution_chain {
+            let result_changed = matches!(prev_and_color, Some((_, DepNodeColor::Red)));
+            self.explain_reexecution(data, key, chain, result_changed);
+        }
+
         if let Some((prev_index, color)) = prev_and_color {
             debug_assert!(
                 data.colors.get(prev_index).is_none(),
@@ -816,6 +838,112 @@ pub fn is_green(&self, dep_node: &DepNode<K>) -> bool {
         self.node_color(dep_node).map_or(false, |c| c.is_green())
     }
 
+    /// Explains why `dep_node` could not be marked green. Starting at `dep_node`, this follows
+    /// the edges of the previous dependency graph to dependencies that were marked red in the
+    /// current session, until reaching the red node whose own result changed. Returns the nodes
+    /// along the way, starting with the red dependency of `dep_node` and ending with the node that
+    /// changed.
+    ///
+    /// Eval-always nodes such as `hir_crate` are red whenever anything in the crate changed, so
+    /// the chain prefers other red dependencies. It only ends at an eval-always node if that is
+    /// the only red dependency of `dep_node` itself.
+    ///
+    /// The chain is empty if `dep_node` did not exist in the previous session or if none of its
+    /// dependencies are known to be red.
+    pub fn red_dependency_chain<Ctxt: DepContext<DepKind = K>>(
+        &self,
+        tcx: Ctxt,
+        dep_node: &DepNode<K>,
+    ) -> Vec<DepNode<K>> {
+        let Some(data) = &self.data else { return Vec::new() };
+        let Some(mut prev_index) = data.previous.node_to_index_opt(dep_node) else {
+            return Vec::new();
+        };
+
+        let mut chain = Vec::new();
+        let mut visited = FxHashSet::default();
+        loop {
+            let mut red_input = None;
+            let mut red_dep = None;
+            for &dep in data.previous.edge_targets_from(prev_index) {
+                if data.colors.get(dep) != Some(DepNodeColor::Red) || !visited.insert(dep) {
+                    continue;
+                }
+                if tcx.is_eval_always(data.previous.index_to_node(dep).kind) {
+                    red_input = red_input.or(Some(dep));
+                } else {
+                    red_dep = Some(dep);
+                    break;
+                }
+            }
+
+            match (red_dep, red_input) {
+                (Some(dep), _) => {
+                    chain.push(data.previous.index_to_node(dep));
+                    prev_index = dep;
+                }
+                (None, Some(input)) if chain.is_empty() => {
+                    chain.push(data.previous.index_to_node(input));
+                    return chain;
+                }
+                (None, _) => return chain,
+            }
+        }
+    }
+
+    /// Prints why `key`, which existed in the previous session, was re-executed, for
+    /// `-Zincremental-explain`. `chain` is the red dependency chain of `key` from before it
+    /// was re-executed.
+    fn explain_reexecution(
+        &self,
+        data: &DepGraphData<K>,
+        key: DepNode<K>,
+        chain: Vec<DepNode<K>>,
+        result_changed: bool,
+    ) {
+        let outcome = if result_changed { "result changed" } else { "result unchanged" };
+        let Some(&changed) = chain.last() else {
+            eprintln!(
+                "[incremental] re-executed {:?} ({}) because a dependency could not be \
+                 recomputed from its dep-node",
+                key, outcome
+            );
+            return;
+        };
+
+        *data.reexecution_causes.lock().entry(changed).or_default() += 1;
+        let path: Vec<_> = chain
+            .iter()
+            .rev()
+            .chain(std::iter::once(&key))
+            .map(|dep_node| format!("{:?}", dep_node))
+            .collect();
+        eprintln!(
+            "[incremental] re-executed {:?} ({}) because {:?} changed: {}",
+            key,
+            outcome,
+            changed,
+            path.join(" -> ")
+        );
+    }
+
+    /// Prints, for `-Zincremental-explain`, how many queries each changed dep-node caused to be
+    /// re-executed, the most expensive changes first.
+    pub fn print_incremental_explanation(&self) {
+        let Some(data) = &self.data else { return };
+        let causes = data.reexecution_causes.lock();
+        let mut causes: Vec<_> =
+            causes.iter().map(|(dep_node, &count)| (format!("{:?}", dep_node), count)).collect();
+        causes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        eprintln!("[incremental]");
+        eprintln!("[incremental] Re-executed queries by changed dep-node");
+        for (dep_node, count) in causes {
+            eprintln!("[incremental]  {:>6} {}", count, dep_node);
+        }
+        eprintln!("[incremental]");
+    }
+
     // This method loads all on-disk cacheable query results into memory, so
     // they can be written out to the new cache file again. Most query results
     // will already be in memory but in the case where we marked something as
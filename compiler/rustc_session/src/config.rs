@@ -3,6 +3,7 @@
 
 pub use crate::options::*;
 
+use crate::lint_config::LintConfig;
 use crate::search_paths::SearchPath;
 use crate::utils::{CanonicalizedPath, NativeLib, NativeLibKind};
 use crate::{early_error, early_warn, Session};
@@ -140,6 +141,13 @@ pub enum MirSpanview {
     Block,
 }
 
+/// The output formats of `-Z mono-report`.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum MonoReportFormat {
+    /// `-Z mono-report=json`
+    Json,
+}
+
 /// The different settings that the `-C instrument-coverage` flag can have.
 ///
 /// Coverage instrumentation now supports combining `-C instrument-coverage`
@@ -715,6 +723,7 @@ fn default() -> Options {
             debuginfo: DebugInfo::None,
             lint_opts: Vec::new(),
             lint_cap: None,
+            lint_config: None,
             describe_lints: false,
             output_types: OutputTypes(BTreeMap::new()),
             search_paths: vec![],
@@ -1380,6 +1389,13 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
              level",
             "LEVEL",
         ),
+        opt::opt(
+            "",
+            "lint-config",
+            "Set lint levels from a configuration file, which take precedence \
+             over lint flags but not over lint attributes",
+            "PATH",
+        ),
         opt::multi_s("C", "codegen", "Set a codegen option", "OPT[=VALUE]"),
         opt::flag_s("V", "version", "Print version info and exit"),
         opt::flag_s("v", "verbose", "Use verbose output"),
@@ -2223,6 +2239,9 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
 
     let mut unstable_opts = UnstableOptions::build(matches, error_format);
     let (lint_opts, describe_lints, lint_cap) = get_cmd_lint_options(matches, error_format);
+    let lint_config = matches
+        .opt_str("lint-config")
+        .map(|path| LintConfig::from_file(Path::new(&path), error_format));
 
     check_error_format_stability(&unstable_opts, error_format, json_rendered);
 
@@ -2477,6 +2496,7 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         debuginfo,
         lint_opts,
         lint_cap,
+        lint_config,
         describe_lints,
         output_types,
         search_paths,
@@ -2751,6 +2771,7 @@ pub(crate) mod dep_tracking {
         SwitchWithOptPath, SymbolManglingVersion, TrimmedDefPaths,
     };
     use crate::lint;
+    use crate::lint_config::LintConfig;
     use crate::options::WasiExecModel;
     use crate::utils::{NativeLib, NativeLibKind};
     use rustc_errors::LanguageIdentifier;
@@ -2848,6 +2869,7 @@ fn hash(
         BranchProtection,
         OomStrategy,
         LanguageIdentifier,
+        LintConfig,
     );
 
     impl<T1, T2> DepTrackingHash for (T1, T2)
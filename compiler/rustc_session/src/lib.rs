@@ -23,6 +23,7 @@
 pub mod config;
 pub mod cstore;
 pub mod filesearch;
+pub mod lint_config;
 mod options;
 pub mod search_paths;
 
@@ -0,0 +1,187 @@
+//! The lint configuration file given with `--lint-config`, which sets lint levels for all crates
+//! built with it, instead of repeating the same lint attributes at the root of each of them.
+//!
+//! ```toml
+//! [lints]
+//! unsafe_code = "forbid"
+//! missing_docs = { level = "deny", reason = "public APIs must be documented" }
+//! "clippy::pedantic" = "warn"
+//! "clippy::module_name_repetitions" = { level = "allow", priority = 1 }
+//!
+//! [[overrides]]
+//! paths = ["crates/generated", "crates/legacy/src/ffi.rs"]
+//! lints = { missing_docs = "allow" }
+//! ```
+//!
+//! The levels of `[lints]` apply to the whole crate, and the levels of an override to the modules
+//! loaded from the files and directories it lists, which are relative to the configuration file.
+//! Within a table, lints are applied in the order of their `priority`, which defaults to 0, so
+//! that a lint can be configured differently from a group it belongs to.
+
+use crate::config::ErrorOutputType;
+use crate::early_error;
+use crate::lint::Level;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Hash)]
+pub struct LintConfig {
+    /// The path of the configuration file, as given on the command line.
+    pub path: PathBuf,
+    pub lints: Vec<LintConfigEntry>,
+    pub overrides: Vec<LintConfigOverride>,
+}
+
+/// The level of a lint or lint group in the configuration file.
+#[derive(Clone, Debug, Hash)]
+pub struct LintConfigEntry {
+    /// The name of the lint, with `-` replaced by `_`, like on the command line.
+    pub name: String,
+    pub level: Level,
+    /// The reason given for the level, which is shown with the lint like the one of a lint
+    /// attribute.
+    pub reason: Option<String>,
+}
+
+/// Lint levels that only apply to the modules loaded from some files and directories.
+#[derive(Clone, Debug, Hash)]
+pub struct LintConfigOverride {
+    /// Canonical paths of the files and directories the override applies to.
+    pub paths: Vec<PathBuf>,
+    pub lints: Vec<LintConfigEntry>,
+}
+
+impl LintConfig {
+    pub fn from_file(path: &Path, error_format: ErrorOutputType) -> LintConfig {
+        let error = |msg: String| -> ! {
+            early_error(
+                error_format,
+                &format!("failed to load lint configuration `{}`: {}", path.display(), msg),
+            )
+        };
+
+        let contents = fs::read_to_string(path).unwrap_or_else(|e| error(e.to_string()));
+        let toml: toml::Value =
+            contents.parse().unwrap_or_else(|e: toml::de::Error| error(e.to_string()));
+        let Some(table) = toml.as_table() else { error("expected a table".to_string()) };
+
+        // Override paths are relative to the configuration file. They are made absolute so that
+        // they can be compared with the paths of source files, whatever the working directory.
+        let base = match path.parent() {
+            Some(dir) if dir.is_absolute() => dir.to_path_buf(),
+            dir => env::current_dir()
+                .unwrap_or_else(|e| error(e.to_string()))
+                .join(dir.unwrap_or(Path::new(""))),
+        };
+
+        let mut lints = Vec::new();
+        let mut overrides = Vec::new();
+        for (key, value) in table {
+            match &key[..] {
+                "lints" => lints = parse_lints(value).unwrap_or_else(|e| error(e)),
+                "overrides" => {
+                    let Some(values) = value.as_array() else {
+                        error("`overrides` must be an array of tables".to_string())
+                    };
+                    for value in values {
+                        let o = parse_override(value, &base).unwrap_or_else(|e| error(e));
+                        overrides.push(o);
+                    }
+                }
+                _ => error(format!("unknown key `{}`", key)),
+            }
+        }
+
+        LintConfig { path: path.to_path_buf(), lints, overrides }
+    }
+
+    /// Returns the lints of the overrides that apply to the source file at `file`, which is
+    /// either absolute or relative to the working directory.
+    pub fn overrides_for(&self, file: &Path) -> impl Iterator<Item = &LintConfigEntry> {
+        let file = canonicalize(env::current_dir().map(|dir| dir.join(file)).unwrap_or_default());
+        self.overrides
+            .iter()
+            .filter(move |o| o.paths.iter().any(|path| file.starts_with(path)))
+            .flat_map(|o| &o.lints)
+    }
+}
+
+fn parse_lints(value: &toml::Value) -> Result<Vec<LintConfigEntry>, String> {
+    let Some(table) = value.as_table() else {
+        return Err("`lints` must be a table".to_string());
+    };
+
+    let mut lints = Vec::with_capacity(table.len());
+    for (name, value) in table {
+        let (level, priority, reason) = match value {
+            toml::Value::String(level) => (level, 0, None),
+            toml::Value::Table(table) => {
+                let mut level = None;
+                let mut priority = 0;
+                let mut reason = None;
+                for (key, value) in table {
+                    match (&key[..], value) {
+                        ("level", toml::Value::String(s)) => level = Some(s),
+                        ("priority", toml::Value::Integer(i)) => priority = *i,
+                        ("reason", toml::Value::String(s)) => reason = Some(s.clone()),
+                        ("level" | "priority" | "reason", _) => {
+                            return Err(format!("invalid `{}` for lint `{}`", key, name));
+                        }
+                        _ => return Err(format!("unknown key `{}` for lint `{}`", key, name)),
+                    }
+                }
+                let Some(level) = level else {
+                    return Err(format!("missing `level` for lint `{}`", name));
+                };
+                (level, priority, reason)
+            }
+            _ => return Err(format!("invalid configuration for lint `{}`", name)),
+        };
+        let Some(level) = Level::from_str(level) else {
+            return Err(format!(
+                "unknown lint level `{}` for lint `{}`, expected one of `allow`, `warn`, `deny` \
+                 or `forbid`",
+                level, name
+            ));
+        };
+        lints.push((priority, LintConfigEntry { name: name.replace('-', "_"), level, reason }));
+    }
+
+    // The sort is stable, lints of the same priority are applied in the order of their names.
+    lints.sort_by_key(|&(priority, _)| priority);
+    Ok(lints.into_iter().map(|(_, lint)| lint).collect())
+}
+
+fn parse_override(value: &toml::Value, base: &Path) -> Result<LintConfigOverride, String> {
+    let Some(table) = value.as_table() else {
+        return Err("`overrides` must be an array of tables".to_string());
+    };
+
+    let mut paths = Vec::new();
+    let mut lints = Vec::new();
+    for (key, value) in table {
+        match &key[..] {
+            "paths" => {
+                let paths_err = || "`paths` of an override must be an array of strings".to_string();
+                for path in value.as_array().ok_or_else(paths_err)? {
+                    paths.push(canonicalize(base.join(path.as_str().ok_or_else(paths_err)?)));
+                }
+            }
+            "lints" => lints = parse_lints(value)?,
+            _ => return Err(format!("unknown key `{}` in override", key)),
+        }
+    }
+    if paths.is_empty() {
+        return Err("missing `paths` in override".to_string());
+    }
+
+    Ok(LintConfigOverride { paths, lints })
+}
+
+/// Resolves symlinks and `..` components, so that paths of the same file compare equal whichever
+/// way they were written. Paths that don't exist are kept as they are.
+fn canonicalize(path: PathBuf) -> PathBuf {
+    fs::canonicalize(&path).unwrap_or(path)
+}
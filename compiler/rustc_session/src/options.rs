@@ -2,8 +2,10 @@
 
 use crate::early_error;
 use crate::lint;
+use crate::lint_config::LintConfig;
 use crate::search_paths::SearchPath;
 use crate::utils::NativeLib;
+use rustc_data_structures::profiling::SelfProfileFormat;
 use rustc_errors::LanguageIdentifier;
 use rustc_target::spec::{CodeModel, LinkerFlavor, MergeFunctions, PanicStrategy, SanitizerSet};
 use rustc_target::spec::{
@@ -140,6 +142,7 @@ pub struct Options {
         debuginfo: DebugInfo [TRACKED],
         lint_opts: Vec<(String, lint::Level)> [TRACKED_NO_CRATE_HASH],
         lint_cap: Option<lint::Level> [TRACKED_NO_CRATE_HASH],
+        lint_config: Option<LintConfig> [TRACKED_NO_CRATE_HASH],
         describe_lints: bool [UNTRACKED],
         output_types: OutputTypes [TRACKED],
         search_paths: Vec<SearchPath> [UNTRACKED],
@@ -385,6 +388,7 @@ mod desc {
     pub const parse_linker_flavor: &str = ::rustc_target::spec::LinkerFlavor::one_of();
     pub const parse_optimization_fuel: &str = "crate=integer";
     pub const parse_mir_spanview: &str = "`statement` (default), `terminator`, or `block`";
+    pub const parse_mono_report: &str = "`json`";
     pub const parse_instrument_coverage: &str =
         "`all` (default), `except-unused-generics`, `except-unused-functions`, or `off`";
     pub const parse_unpretty: &str = "`string` or `string=string`";
@@ -416,6 +420,7 @@ mod desc {
         "a `,` separated combination of `bti`, `b-key`, `pac-ret`, or `leaf`";
     pub const parse_proc_macro_execution_strategy: &str =
         "one of supported execution strategies (`same-thread`, or `cross-thread`)";
+    pub const parse_self_profile_format: &str = "one of: `raw`, `chrome`, or `summary`";
 }
 
 mod parse {
@@ -827,6 +832,14 @@ pub(crate) fn parse_mir_spanview(slot: &mut Option<MirSpanview>, v: Option<&str>
         true
     }
 
+    pub(crate) fn parse_mono_report(slot: &mut Option<MonoReportFormat>, v: Option<&str>) -> bool {
+        *slot = Some(match v {
+            Some("json") => MonoReportFormat::Json,
+            _ => return false,
+        });
+        true
+    }
+
     pub(crate) fn parse_instrument_coverage(
         slot: &mut Option<InstrumentCoverage>,
         v: Option<&str>,
@@ -1078,6 +1091,16 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         };
         true
     }
+
+    pub(crate) fn parse_self_profile_format(slot: &mut SelfProfileFormat, v: Option<&str>) -> bool {
+        *slot = match v {
+            Some("raw") => SelfProfileFormat::Raw,
+            Some("chrome") => SelfProfileFormat::Chrome,
+            Some("summary") => SelfProfileFormat::Summary,
+            _ => return false,
+        };
+        true
+    }
 }
 
 options! {
@@ -1244,12 +1267,19 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         "instrument control-flow architecture protection"),
     cgu_partitioning_strategy: Option<String> = (None, parse_opt_string, [TRACKED],
         "the codegen unit partitioning strategy to use"),
+    cgu_report: bool = (false, parse_bool, [UNTRACKED],
+        "write a JSON report of the codegen units, how they were formed and whether they were \
+        reused from the incremental cache to `<crate>.cgu_report.json` (default: no)"),
     chalk: bool = (false, parse_bool, [TRACKED],
         "enable the experimental Chalk-based trait solving engine"),
     codegen_backend: Option<String> = (None, parse_opt_string, [TRACKED],
         "the backend to use"),
     combine_cgu: bool = (false, parse_bool, [TRACKED],
         "combine CGUs into a single one"),
+    const_eval_trace: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "print a trace of the executed basic blocks, stack frames and allocations, followed by \
+        the number of steps taken in each function, while evaluating the given const item or \
+        const fn at compile time"),
     crate_attr: Vec<String> = (Vec::new(), parse_string_push, [TRACKED],
         "inject the given attribute in the crate"),
     debug_info_for_profiling: bool = (false, parse_bool, [TRACKED],
@@ -1308,6 +1338,10 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         "emit a section containing stack size metadata (default: no)"),
     emit_thin_lto: bool = (true, parse_bool, [TRACKED],
         "emit the bc module with thin LTO info (default: yes)"),
+    explain_borrowck: Option<String> = (None, parse_opt_string, [TRACKED],
+        "explain the lifetime errors of the functions whose path contains this string (`all` \
+        for every function): note the chain of constraints behind each error, and write the \
+        constraints relevant to it to a graphviz file in `-Z dump-mir-dir`"),
     export_executable_symbols: bool = (false, parse_bool, [TRACKED],
         "export symbols from executables, as if they were dynamic libraries"),
     extra_const_ub_checks: bool = (false, parse_bool, [TRACKED],
@@ -1325,6 +1359,9 @@ pub(crate) fn parse_proc_macro_execution_strategy(
     future_incompat_test: bool = (false, parse_bool, [UNTRACKED],
         "forces all lints to be future incompatible, used for internal testing (default: no)"),
     gcc_ld: Option<LdImpl> = (None, parse_gcc_ld, [TRACKED], "implementation of ld used by cc"),
+    generics_hub: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED],
+        "build this rlib as a generics hub that exports the generic instantiations listed in \
+        the given profile file to downstream crates"),
     graphviz_dark_mode: bool = (false, parse_bool, [UNTRACKED],
         "use dark-themed colors in graphviz output (default: no)"),
     graphviz_font: String = ("Courier, monospace".to_string(), parse_string, [UNTRACKED],
@@ -1336,6 +1373,9 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         "generate human-readable, predictable names for codegen units (default: no)"),
     identify_regions: bool = (false, parse_bool, [UNTRACKED],
         "display unnamed regions as `'<id>`, using a non-ident unique id (default: no)"),
+    incremental_explain: bool = (false, parse_bool, [UNTRACKED],
+        "print, for each query that is re-executed in an incremental session, the chain of \
+        dependencies leading to the input that changed (default: no)"),
     incremental_ignore_spans: bool = (false, parse_bool, [UNTRACKED],
         "ignore spans during ICH computation -- used for testing (default: no)"),
     incremental_info: bool = (false, parse_bool, [UNTRACKED],
@@ -1343,6 +1383,9 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         (default: no)"),
     incremental_relative_spans: bool = (false, parse_bool, [TRACKED],
         "hash spans relative to their parent item for incr. comp. (default: no)"),
+    incremental_shared_cache: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "share incremental compilation sessions with other build directories through a \
+        content-addressed cache in the given directory"),
     incremental_verify_ich: bool = (false, parse_bool, [UNTRACKED],
         "verify incr. comp. hashes of green query instances (default: no)"),
     inline_mir: Option<bool> = (None, parse_opt_bool, [TRACKED],
@@ -1403,6 +1446,9 @@ pub(crate) fn parse_proc_macro_execution_strategy(
     #[rustc_lint_opt_deny_field_access("use `Session::mir_opt_level` instead of this field")]
     mir_opt_level: Option<usize> = (None, parse_opt_number, [TRACKED],
         "MIR optimization level (0-4; default: 1 in non optimized builds and 2 in optimized builds)"),
+    mono_report: Option<MonoReportFormat> = (None, parse_mono_report, [UNTRACKED],
+        "write a report of the monomorphized items, the generic items they instantiate, what \
+        instantiates them, their size and their codegen units to `<crate>.mono_report.json`"),
     move_size_limit: Option<usize> = (None, parse_opt_number, [TRACKED],
         "the size at which the `large_assignments` lint starts to be emitted"),
     mutable_noalias: Option<bool> = (None, parse_opt_bool, [TRACKED],
@@ -1472,6 +1518,9 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         "print the LLVM optimization passes being run (default: no)"),
     print_mono_items: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "print the result of the monomorphization collection pass"),
+    print_polymorphization: bool = (false, parse_bool, [TRACKED],
+        "print the generic parameters that the polymorphization analysis finds unused for \
+        each function, without enabling polymorphization (default: no)"),
     print_type_sizes: bool = (false, parse_bool, [UNTRACKED],
         "print layout information for each type encountered (default: no)"),
     proc_macro_backtrace: bool = (false, parse_bool, [UNTRACKED],
@@ -1490,6 +1539,10 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         "name of the profiler runtime crate to automatically inject (default: `profiler_builtins`)"),
     profile_sample_use: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED],
         "use the given `.prof` file for sampled profile-guided optimization (also known as AutoFDO)"),
+    public_api_hash: bool = (false, parse_bool, [UNTRACKED],
+        "write the hash of the crate's public API to a `.public-api-hash` file next to its \
+        metadata, for build systems to decide whether dependents need to be rebuilt \
+        (default: no)"),
     query_dep_graph: bool = (false, parse_bool, [UNTRACKED],
         "enable queries of the dependency graph for regression testing (default: no)"),
     randomize_layout: bool = (false, parse_bool, [TRACKED],
@@ -1534,6 +1587,11 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         `instructions:u` (retired instructions, userspace-only)
         `instructions-minus-irqs:u` (subtracting hardware interrupt counts for extra accuracy)"
     ),
+    self_profile_format: SelfProfileFormat = (SelfProfileFormat::Raw,
+        parse_self_profile_format, [UNTRACKED],
+        "the format in which the self profiler outputs the event data, in addition to the raw \
+        event data: `chrome` (a Chrome trace-event file), or `summary` (a table of the items \
+        that took the most time, printed to stderr) (default: `raw`)"),
     share_generics: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "make the current crate share its generic instantiations"),
     show_span: Option<String> = (None, parse_opt_string, [TRACKED],
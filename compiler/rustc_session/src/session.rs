@@ -122,6 +122,8 @@ pub struct Limits {
     pub type_length_limit: Limit,
     /// The maximum blocks a const expression can evaluate.
     pub const_eval_limit: Limit,
+    /// The maximum number of bytes a const expression can have allocated at the same time.
+    pub const_eval_memory_limit: Limit,
 }
 
 /// Represents the data associated with a compilation
@@ -1099,6 +1101,20 @@ pub fn threads(&self) -> usize {
         self.opts.unstable_opts.threads
     }
 
+    /// Runs `f`, which may emit diagnostics from several threads of the parallel
+    /// front-end. If more than one thread is requested, the diagnostics are emitted in an
+    /// order that does not depend on thread scheduling, see
+    /// [`rustc_errors::Handler::emit_in_deterministic_order`]. This is done whether or not
+    /// the compiler was built with the parallel front-end, so that the output for a given
+    /// `-Zthreads` is the same for both builds.
+    pub fn with_deterministic_diagnostics<R>(&self, f: impl FnOnce() -> R) -> R {
+        if self.threads() > 1 {
+            self.diagnostic().emit_in_deterministic_order(f)
+        } else {
+            f()
+        }
+    }
+
     /// Returns the number of codegen units that should be used for this
     /// compilation
     pub fn codegen_units(&self) -> usize {
@@ -1348,6 +1364,7 @@ pub fn build_session(
             sopts.crate_name.as_deref(),
             sopts.unstable_opts.self_profile_events.as_ref().map(|xs| &xs[..]),
             &sopts.unstable_opts.self_profile_counter,
+            sopts.unstable_opts.self_profile_format,
         );
         match profiler {
             Ok(profiler) => Some(Arc::new(profiler)),
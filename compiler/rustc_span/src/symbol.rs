@@ -504,6 +504,7 @@
         const_constructor,
         const_deallocate,
         const_eval_limit,
+        const_eval_memory_limit,
         const_eval_select,
         const_eval_select_ct,
         const_evaluatable_checked,
@@ -996,6 +997,7 @@
         no_link,
         no_main,
         no_mangle,
+        no_panic,
         no_sanitize,
         no_stack_check,
         no_start,
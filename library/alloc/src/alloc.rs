@@ -166,16 +166,103 @@ pub unsafe fn alloc_zeroed(layout: Layout) -> *mut u8 {
     unsafe { __rust_alloc_zeroed(layout.size(), layout.align()) }
 }
 
+/// Allocates through the global allocator at runtime and with `const_allocate` during const
+/// evaluation.
+///
+/// # Safety
+///
+/// `layout` must be non-zero in size.
+#[cfg(not(test))]
+#[inline]
+const unsafe fn alloc_raw(layout: Layout, zeroed: bool) -> *mut u8 {
+    const fn ct_alloc(layout: Layout, zeroed: bool) -> *mut u8 {
+        // SAFETY: `const_allocate` returns a fresh allocation of `layout.size()` bytes.
+        unsafe {
+            let ptr = intrinsics::const_allocate(layout.size(), layout.align());
+            if zeroed {
+                ptr.write_bytes(0, layout.size());
+            }
+            ptr
+        }
+    }
+
+    fn rt_alloc(layout: Layout, zeroed: bool) -> *mut u8 {
+        // SAFETY: `layout` is non-zero in size, as required by the caller.
+        unsafe { if zeroed { alloc_zeroed(layout) } else { alloc(layout) } }
+    }
+
+    // SAFETY: both functions allocate a block of memory fitting `layout`.
+    unsafe { intrinsics::const_eval_select((layout, zeroed), ct_alloc, rt_alloc) }
+}
+
+/// Reallocates through the global allocator at runtime. During const evaluation a new block is
+/// allocated with `const_allocate`, the contents are copied over and the old block is freed.
+///
+/// # Safety
+///
+/// Same as [`realloc`].
+#[cfg(not(test))]
+#[inline]
+const unsafe fn realloc_raw(ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    const fn ct_realloc(ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let old_size = layout.size();
+        let copied = if old_size < new_size { old_size } else { new_size };
+        // SAFETY: the old block is valid for reads of `old_size` bytes and the new one for writes
+        // of `new_size` bytes. The new block was just allocated, so the two cannot overlap.
+        unsafe {
+            let new_ptr = intrinsics::const_allocate(new_size, layout.align());
+            ptr::copy_nonoverlapping(ptr, new_ptr, copied);
+            intrinsics::const_deallocate(ptr, old_size, layout.align());
+            new_ptr
+        }
+    }
+
+    fn rt_realloc(ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // SAFETY: the safety contract must be upheld by the caller.
+        unsafe { realloc(ptr, layout, new_size) }
+    }
+
+    // SAFETY: both functions move the block at `ptr` to one of `new_size` bytes.
+    unsafe { intrinsics::const_eval_select((ptr, layout, new_size), ct_realloc, rt_realloc) }
+}
+
+/// Deallocates through the global allocator at runtime and with `const_deallocate` during const
+/// evaluation.
+///
+/// # Safety
+///
+/// Same as [`dealloc`].
+#[cfg(not(test))]
+#[inline]
+const unsafe fn dealloc_raw(ptr: *mut u8, layout: Layout) {
+    const fn ct_dealloc(ptr: *mut u8, layout: Layout) {
+        // SAFETY: the safety contract must be upheld by the caller.
+        unsafe { intrinsics::const_deallocate(ptr, layout.size(), layout.align()) }
+    }
+
+    fn rt_dealloc(ptr: *mut u8, layout: Layout) {
+        // SAFETY: the safety contract must be upheld by the caller.
+        unsafe { dealloc(ptr, layout) }
+    }
+
+    // SAFETY: both functions free the block at `ptr`.
+    unsafe { intrinsics::const_eval_select((ptr, layout), ct_dealloc, rt_dealloc) }
+}
+
 #[cfg(not(test))]
 impl Global {
     #[inline]
-    fn alloc_impl(&self, layout: Layout, zeroed: bool) -> Result<NonNull<[u8]>, AllocError> {
+    const fn alloc_impl(
+        &self,
+        layout: Layout,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
         match layout.size() {
             0 => Ok(NonNull::slice_from_raw_parts(layout.dangling(), 0)),
             // SAFETY: `layout` is non-zero in size,
             size => unsafe {
-                let raw_ptr = if zeroed { alloc_zeroed(layout) } else { alloc(layout) };
-                let ptr = NonNull::new(raw_ptr).ok_or(AllocError)?;
+                let raw_ptr = alloc_raw(layout, zeroed);
+                let Some(ptr) = NonNull::new(raw_ptr) else { return Err(AllocError) };
                 Ok(NonNull::slice_from_raw_parts(ptr, size))
             },
         }
@@ -183,7 +270,7 @@ fn alloc_impl(&self, layout: Layout, zeroed: bool) -> Result<NonNull<[u8]>, Allo
 
     // SAFETY: Same as `Allocator::grow`
     #[inline]
-    unsafe fn grow_impl(
+    const unsafe fn grow_impl(
         &self,
         ptr: NonNull<u8>,
         old_layout: Layout,
@@ -206,8 +293,8 @@ unsafe fn grow_impl(
                 // `realloc` probably checks for `new_size >= old_layout.size()` or something similar.
                 intrinsics::assume(new_size >= old_layout.size());
 
-                let raw_ptr = realloc(ptr.as_ptr(), old_layout, new_size);
-                let ptr = NonNull::new(raw_ptr).ok_or(AllocError)?;
+                let raw_ptr = realloc_raw(ptr.as_ptr(), old_layout, new_size);
+                let Some(ptr) = NonNull::new(raw_ptr) else { return Err(AllocError) };
                 if zeroed {
                     raw_ptr.add(old_size).write_bytes(0, new_size - old_size);
                 }
@@ -231,7 +318,8 @@ unsafe fn grow_impl(
 
 #[unstable(feature = "allocator_api", issue = "32838")]
 #[cfg(not(test))]
-unsafe impl Allocator for Global {
+#[rustc_const_unstable(feature = "const_heap", issue = "79597")]
+unsafe impl const Allocator for Global {
     #[inline]
     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
         self.alloc_impl(layout, false)
@@ -247,7 +335,7 @@ unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
         if layout.size() != 0 {
             // SAFETY: `layout` is non-zero in size,
             // other conditions must be upheld by the caller
-            unsafe { dealloc(ptr.as_ptr(), layout) }
+            unsafe { dealloc_raw(ptr.as_ptr(), layout) }
         }
     }
 
@@ -297,8 +385,8 @@ unsafe fn shrink(
                 // `realloc` probably checks for `new_size <= old_layout.size()` or something similar.
                 intrinsics::assume(new_size <= old_layout.size());
 
-                let raw_ptr = realloc(ptr.as_ptr(), old_layout, new_size);
-                let ptr = NonNull::new(raw_ptr).ok_or(AllocError)?;
+                let raw_ptr = realloc_raw(ptr.as_ptr(), old_layout, new_size);
+                let Some(ptr) = NonNull::new(raw_ptr) else { return Err(AllocError) };
                 Ok(NonNull::slice_from_raw_parts(ptr, new_size))
             },
 
@@ -315,6 +403,11 @@ unsafe fn shrink(
             },
         }
     }
+
+    #[inline(always)]
+    fn by_ref(&self) -> &Self {
+        self
+    }
 }
 
 /// The allocator for unique pointers.
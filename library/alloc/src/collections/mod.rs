@@ -111,7 +111,8 @@ pub enum TryReserveErrorKind {
     reason = "Uncertain how much info should be exposed",
     issue = "48043"
 )]
-impl From<TryReserveErrorKind> for TryReserveError {
+#[rustc_const_unstable(feature = "const_convert", issue = "88674")]
+impl const From<TryReserveErrorKind> for TryReserveError {
     #[inline]
     fn from(kind: TryReserveErrorKind) -> Self {
         Self { kind }
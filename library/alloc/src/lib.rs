@@ -96,7 +96,10 @@
 #![feature(async_iterator)]
 #![feature(coerce_unsized)]
 #![cfg_attr(not(no_global_oom_handling), feature(const_alloc_error))]
+#![feature(const_alloc_layout)]
+#![feature(const_assume)]
 #![feature(const_box)]
+#![feature(const_cmp)]
 #![cfg_attr(not(no_global_oom_handling), feature(const_btree_new))]
 #![feature(const_cow_is_borrowed)]
 #![feature(const_convert)]
@@ -108,6 +111,10 @@
 #![feature(const_refs_to_cell)]
 #![feature(core_intrinsics)]
 #![feature(const_eval_select)]
+#![feature(const_heap)]
+#![feature(const_nonnull_new)]
+#![feature(const_nonnull_slice_from_raw_parts)]
+#![feature(const_ptr_is_null)]
 #![feature(const_pin)]
 #![feature(cstr_from_bytes_until_nul)]
 #![feature(dispatch_from_dyn)]
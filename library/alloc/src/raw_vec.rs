@@ -131,6 +131,17 @@ pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
         Self::allocate_in(capacity, AllocInit::Uninitialized, alloc)
     }
 
+    /// Like `with_capacity_in`, but callable in a `const fn` with an allocator that can be used
+    /// during const evaluation. This keeps the `~const` bound off the public constructors.
+    #[cfg(not(no_global_oom_handling))]
+    #[inline]
+    pub(crate) const fn with_capacity_in_const(capacity: usize, alloc: A) -> Self
+    where
+        A: ~const Allocator,
+    {
+        Self::allocate_in(capacity, AllocInit::Uninitialized, alloc)
+    }
+
     /// Like `with_capacity_zeroed`, but parameterized over the choice
     /// of allocator for the returned `RawVec`.
     #[cfg(not(no_global_oom_handling))]
@@ -166,7 +177,10 @@ pub unsafe fn into_box(self, len: usize) -> Box<[MaybeUninit<T>], A> {
     }
 
     #[cfg(not(no_global_oom_handling))]
-    fn allocate_in(capacity: usize, init: AllocInit, alloc: A) -> Self {
+    const fn allocate_in(capacity: usize, init: AllocInit, alloc: A) -> Self
+    where
+        A: ~const Allocator,
+    {
         // Don't allocate here because `Drop` will not deallocate when `capacity` is 0.
         if mem::size_of::<T>() == 0 || capacity == 0 {
             Self::new_in(alloc)
@@ -220,7 +234,7 @@ pub unsafe fn from_raw_parts_in(ptr: *mut T, capacity: usize, alloc: A) -> Self
     /// `Unique::dangling()` if `capacity == 0` or `T` is zero-sized. In the former case, you must
     /// be careful.
     #[inline]
-    pub fn ptr(&self) -> *mut T {
+    pub const fn ptr(&self) -> *mut T {
         self.ptr.as_ptr()
     }
 
@@ -228,7 +242,7 @@ pub fn ptr(&self) -> *mut T {
     ///
     /// This will always be `usize::MAX` if `T` is zero-sized.
     #[inline(always)]
-    pub fn capacity(&self) -> usize {
+    pub const fn capacity(&self) -> usize {
         if mem::size_of::<T>() == 0 { usize::MAX } else { self.cap }
     }
 
@@ -502,7 +516,7 @@ fn handle_reserve(result: Result<(), TryReserveError>) {
 // all 4GB in user-space, e.g., PAE or x32.
 
 #[inline]
-fn alloc_guard(alloc_size: usize) -> Result<(), TryReserveError> {
+const fn alloc_guard(alloc_size: usize) -> Result<(), TryReserveError> {
     if usize::BITS < 64 && alloc_size > isize::MAX as usize {
         Err(CapacityOverflow.into())
     } else {
@@ -514,6 +528,6 @@ fn alloc_guard(alloc_size: usize) -> Result<(), TryReserveError> {
 // ensure that the code generation related to these panics is minimal as there's
 // only one location which panics rather than a bunch throughout the module.
 #[cfg(not(no_global_oom_handling))]
-fn capacity_overflow() -> ! {
+const fn capacity_overflow() -> ! {
     panic!("capacity overflow");
 }
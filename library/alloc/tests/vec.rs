@@ -2417,3 +2417,20 @@ fn test_into_flattened_size_overflow() {
     let v = vec![[(); usize::MAX]; 2];
     let _ = v.into_flattened();
 }
+
+#[test]
+fn const_vec() {
+    const V: &Vec<u8> = &{
+        let mut v = Vec::with_capacity(3);
+        unsafe {
+            v.as_mut_ptr().write(1);
+            v.as_mut_ptr().add(1).write(2);
+            v.as_mut_ptr().add(2).write(3);
+            v.set_len(3);
+        }
+        v
+    };
+
+    assert_eq!(**V, [1, 2, 3]);
+    assert_eq!(V.capacity(), 3);
+}
@@ -56,7 +56,7 @@ pub(crate) fn log2(self) -> u32 {
 
     /// Returns the alignment for a type.
     #[inline]
-    pub(crate) fn of<T>() -> Self {
+    pub(crate) const fn of<T>() -> Self {
         // SAFETY: rustc ensures that type alignment is always a power of two.
         unsafe { ValidAlign::new_unchecked(mem::align_of::<T>()) }
     }
@@ -273,6 +273,11 @@ pub(crate) struct RenderOptions {
     pub(crate) generate_link_to_definition: bool,
     /// Set of function-call locations to include as examples
     pub(crate) call_locations: AllCallLocations,
+    /// Maximum number of scraped examples to include for a single item, if any.
+    pub(crate) scrape_examples_limit: Option<usize>,
+    /// External commands checking the syntax of code blocks, by code block language. Each
+    /// command is given the contents of a code block on its standard input.
+    pub(crate) code_block_checkers: FxHashMap<String, Vec<String>>,
     /// If `true`, Context::init will not emit shared files.
     pub(crate) no_emit_shared: bool,
 }
@@ -697,6 +702,28 @@ fn println_condition(condition: Condition) {
         let scrape_examples_options = ScrapeExamplesOptions::new(matches, &diag)?;
         let with_examples = matches.opt_strs("with-examples");
         let call_locations = crate::scrape_examples::load_call_locations(with_examples, &diag)?;
+        let mut code_block_checkers = FxHashMap::default();
+        for checker in matches.opt_strs("check-code-block") {
+            let command = checker.split_once('=').and_then(|(lang, command)| {
+                // Split the command like a shell would, so that arguments can be quoted.
+                let command = shlex::split(command)?;
+                if lang.is_empty() || command.is_empty() { None } else { Some((lang, command)) }
+            });
+            let Some((lang, command)) = command else {
+                diag.struct_err("--check-code-block must be of the form `lang=command`")
+                    .note("arguments of the command can be quoted, as in a shell")
+                    .emit();
+                return Err(1);
+            };
+            code_block_checkers.insert(lang.to_owned(), command);
+        }
+        let scrape_examples_limit = match matches.opt_get("scrape-examples-limit") {
+            Ok(limit) => limit,
+            Err(_) => {
+                diag.err("--scrape-examples-limit must be a non-negative integer");
+                return Err(1);
+            }
+        };
 
         Ok(Options {
             input,
@@ -761,6 +788,8 @@ fn println_condition(condition: Condition) {
                 emit,
                 generate_link_to_definition,
                 call_locations,
+                scrape_examples_limit,
+                code_block_checkers,
                 no_emit_shared: false,
             },
             crate_name,
@@ -1424,6 +1424,68 @@ pub(crate) fn rust_code_blocks(md: &str, extra_info: &ExtraInfo<'_>) -> Vec<Rust
     code_blocks
 }
 
+/// A fenced code block, whatever its language.
+pub(crate) struct FencedCodeBlock {
+    /// The range in the markdown that the code block occupies, including the fences.
+    pub(crate) range: Range<usize>,
+    /// The range in the markdown that the code within the code block occupies.
+    pub(crate) code: Range<usize>,
+    /// The first token of the info string, e.g. `json` for a block starting with ```` ```json ````.
+    pub(crate) lang: Option<String>,
+    /// Whether the code block is treated as Rust code, see [`rust_code_blocks`].
+    pub(crate) is_rust: bool,
+    /// Whether a closing fence was found. An unclosed code block extends to the end of the
+    /// documentation (or of its container).
+    pub(crate) is_closed: bool,
+}
+
+/// Returns every fenced code block in the markdown.
+pub(crate) fn fenced_code_blocks(md: &str) -> Vec<FencedCodeBlock> {
+    let mut code_blocks = vec![];
+
+    if md.is_empty() {
+        return code_blocks;
+    }
+
+    let mut p = Parser::new_ext(md, main_body_opts()).into_offset_iter();
+
+    while let Some((event, range)) = p.next() {
+        let Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(syntax))) = event else { continue };
+        let is_rust =
+            syntax.is_empty() || LangString::parse(&syntax, ErrorCodes::No, false, None).rust;
+        let lang = LangString::tokens(&syntax).next().map(str::to_owned);
+
+        let mut code = Range { start: range.end, end: range.end };
+        if let Some((Event::Text(_), offset)) = p.next() {
+            code = offset;
+            while let Some((Event::Text(_), offset)) = p.next() {
+                code.end = offset.end;
+            }
+        }
+        let is_closed = is_closed_fence(&md[range.clone()]);
+
+        code_blocks.push(FencedCodeBlock { range, code, lang, is_rust, is_closed });
+    }
+
+    code_blocks
+}
+
+/// Checks that the last line of a fenced code block is a fence matching the opening one.
+fn is_closed_fence(block: &str) -> bool {
+    // Code blocks can be nested in block quotes and lists, in which case each line is prefixed.
+    let strip_container = |line: &str| line.trim_start_matches(|c| matches!(c, ' ' | '\t' | '>'));
+
+    let mut lines = block.trim_end().lines();
+    let Some(opening) = lines.next().map(strip_container) else { return false };
+    let Some(closing) = lines.last().map(|line| strip_container(line).trim_end()) else {
+        return false;
+    };
+    let Some(fence_char) = opening.chars().next() else { return false };
+    let fence_len = opening.chars().take_while(|&c| c == fence_char).count();
+
+    closing.len() >= fence_len && closing.chars().all(|c| c == fence_char)
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct IdMap {
     map: FxHashMap<Cow<'static, str>, usize>,
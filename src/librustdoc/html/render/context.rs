@@ -124,6 +124,8 @@ pub(crate) struct SharedContext<'tcx> {
     pub(crate) cache: Cache,
 
     pub(crate) call_locations: AllCallLocations,
+    /// Maximum number of scraped examples to include for a single item, if any.
+    pub(crate) scrape_examples_limit: Option<usize>,
 }
 
 impl SharedContext<'_> {
@@ -405,6 +407,7 @@ fn init(
             show_type_layout,
             generate_link_to_definition,
             call_locations,
+            scrape_examples_limit,
             no_emit_shared,
             ..
         } = options;
@@ -490,6 +493,7 @@ fn init(
             span_correspondance_map: matches,
             cache,
             call_locations,
+            scrape_examples_limit,
         };
 
         // Add the default themes to the `Vec` of stylepaths
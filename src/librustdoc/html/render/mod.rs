@@ -48,7 +48,7 @@
 
 use rustc_ast_pretty::pprust;
 use rustc_attr::{ConstStability, Deprecation, StabilityLevel};
-use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap};
 use rustc_hir::def::CtorKind;
 use rustc_hir::def_id::DefId;
 use rustc_hir::Mutability;
@@ -77,7 +77,7 @@
 use crate::html::markdown::{HeadingOffset, IdMap, Markdown, MarkdownHtml, MarkdownSummaryLine};
 use crate::html::sources;
 use crate::html::static_files::SCRAPE_EXAMPLES_HELP_MD;
-use crate::scrape_examples::{CallData, CallLocation};
+use crate::scrape_examples::{CallData, CallLocation, FnCallLocations};
 use crate::try_none;
 use crate::DOC_RUST_LANG_ORG_CHANNEL;
 
@@ -2645,6 +2645,38 @@ fn collect_paths_for_type(first_ty: clean::Type, cache: &Cache) -> Vec<String> {
 const MAX_FULL_EXAMPLES: usize = 5;
 const NUM_VISIBLE_LINES: usize = 10;
 
+/// Determines the sequence in which the examples of an item are output.
+///
+/// Ideally the most "relevant" examples would be shown first, but there's no general algorithm
+/// for determining relevance. Instead, we prefer the smallest examples being likely the easiest to
+/// understand at a glance. Examples can come from several sources (examples, tests, benchmarks
+/// and dependent crates), so to keep a single source from crowding out all the others, the
+/// examples are taken in turns from each crate and kind of target, each source sorted by size.
+fn order_call_locations(call_locations: &FnCallLocations) -> Vec<(&PathBuf, &CallData)> {
+    let sort_criterion = |(_, call_data): &(_, &CallData)| {
+        // Use the first location because that's what the user will see initially
+        let (lo, hi) = call_data.locations[0].enclosing_item.byte_span;
+        hi - lo
+    };
+
+    let mut locs = call_locations.iter().collect::<Vec<_>>();
+    locs.sort_by_key(sort_criterion);
+
+    // Sources are ordered by their smallest example, since that is the first one taken from them.
+    let mut sources: FxIndexMap<_, VecDeque<_>> = FxIndexMap::default();
+    for loc @ (_, call_data) in locs {
+        sources.entry((call_data.kind, &call_data.crate_name)).or_default().push_back(loc);
+    }
+
+    let mut ordered = Vec::with_capacity(call_locations.len());
+    while ordered.len() < call_locations.len() {
+        for source in sources.values_mut() {
+            ordered.extend(source.pop_front());
+        }
+    }
+    ordered
+}
+
 /// Generates the HTML for example call locations generated via the --scrape-examples flag.
 fn render_call_locations(w: &mut Buffer, cx: &mut Context<'_>, item: &clean::Item) {
     let tcx = cx.tcx();
@@ -2652,6 +2684,14 @@ fn render_call_locations(w: &mut Buffer, cx: &mut Context<'_>, item: &clean::Ite
     let key = tcx.def_path_hash(def_id);
     let Some(call_locations) = cx.shared.call_locations.get(&key) else { return };
 
+    let mut ordered_locations = order_call_locations(call_locations);
+    if let Some(limit) = cx.shared.scrape_examples_limit {
+        ordered_locations.truncate(limit);
+    }
+    if ordered_locations.is_empty() {
+        return;
+    }
+
     // Generate a unique ID so users can link to this section for a given method
     let id = cx.id_map.derive("scraped-examples");
     write!(
@@ -2793,22 +2833,6 @@ fn render_call_locations(w: &mut Buffer, cx: &mut Context<'_>, item: &clean::Ite
         true
     };
 
-    // The call locations are output in sequence, so that sequence needs to be determined.
-    // Ideally the most "relevant" examples would be shown first, but there's no general algorithm
-    // for determining relevance. Instead, we prefer the smallest examples being likely the easiest to
-    // understand at a glance.
-    let ordered_locations = {
-        let sort_criterion = |(_, call_data): &(_, &CallData)| {
-            // Use the first location because that's what the user will see initially
-            let (lo, hi) = call_data.locations[0].enclosing_item.byte_span;
-            hi - lo
-        };
-
-        let mut locs = call_locations.iter().collect::<Vec<_>>();
-        locs.sort_by_key(sort_criterion);
-        locs
-    };
-
     let mut it = ordered_locations.into_iter().peekable();
 
     // An example may fail to write if its source can't be read for some reason, so this method
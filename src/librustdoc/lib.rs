@@ -603,6 +603,30 @@ fn opts() -> Vec<RustcOptGroup> {
         unstable("scrape-tests", |o| {
             o.optflag("", "scrape-tests", "Include test code when scraping examples")
         }),
+        unstable("scrape-examples-kind", |o| {
+            o.optopt(
+                "",
+                "scrape-examples-kind",
+                "kind of target the examples are scraped from",
+                "example|test|bench|dependent",
+            )
+        }),
+        unstable("scrape-examples-limit", |o| {
+            o.optopt(
+                "",
+                "scrape-examples-limit",
+                "maximum number of scraped examples included for a single item",
+                "N",
+            )
+        }),
+        unstable("check-code-block", |o| {
+            o.optmulti(
+                "",
+                "check-code-block",
+                "check the syntax of code blocks in a language by piping them to a command",
+                "LANG=COMMAND",
+            )
+        }),
         unstable("with-examples", |o| {
             o.optmulti(
                 "",
@@ -169,6 +169,51 @@ macro_rules! declare_rustdoc_lint {
    "codeblock could not be parsed as valid Rust or is empty"
 }
 
+declare_rustdoc_lint! {
+    /// The `invalid_codeblock_syntax` lint detects code blocks in documentation
+    /// which are never closed, and JSON, TOML or `--check-code-block` code
+    /// blocks with invalid syntax. This is a `rustdoc` only lint, see the
+    /// documentation in the [rustdoc book].
+    ///
+    /// [rustdoc book]: ../../../rustdoc/lints.html#invalid_codeblock_syntax
+    INVALID_CODEBLOCK_SYNTAX,
+    Warn,
+    "codeblock is never closed or could not be parsed in its language"
+}
+
+declare_rustdoc_lint! {
+    /// The `missing_safety_doc` lint detects `unsafe` functions and traits
+    /// whose documentation has no `# Safety` section. This is a `rustdoc`
+    /// only lint, see the documentation in the [rustdoc book].
+    ///
+    /// [rustdoc book]: ../../../rustdoc/lints.html#missing_safety_doc
+    MISSING_SAFETY_DOC,
+    Allow,
+    "detects unsafe functions and traits without a `# Safety` section in their documentation"
+}
+
+declare_rustdoc_lint! {
+    /// The `missing_errors_doc` lint detects public functions returning
+    /// `Result` whose documentation has no `# Errors` section. This is a
+    /// `rustdoc` only lint, see the documentation in the [rustdoc book].
+    ///
+    /// [rustdoc book]: ../../../rustdoc/lints.html#missing_errors_doc
+    MISSING_ERRORS_DOC,
+    Allow,
+    "detects functions returning `Result` without an `# Errors` section in their documentation"
+}
+
+declare_rustdoc_lint! {
+    /// The `missing_panics_doc` lint detects functions which may panic in
+    /// their own body and whose documentation has no `# Panics` section. This
+    /// is a `rustdoc` only lint, see the documentation in the [rustdoc book].
+    ///
+    /// [rustdoc book]: ../../../rustdoc/lints.html#missing_panics_doc
+    MISSING_PANICS_DOC,
+    Allow,
+    "detects functions which may panic without a `# Panics` section in their documentation"
+}
+
 pub(crate) static RUSTDOC_LINTS: Lazy<Vec<&'static Lint>> = Lazy::new(|| {
     vec![
         BROKEN_INTRA_DOC_LINKS,
@@ -177,9 +222,13 @@ macro_rules! declare_rustdoc_lint {
         PRIVATE_DOC_TESTS,
         INVALID_CODEBLOCK_ATTRIBUTES,
         INVALID_RUST_CODEBLOCKS,
+        INVALID_CODEBLOCK_SYNTAX,
         INVALID_HTML_TAGS,
         BARE_URLS,
         MISSING_CRATE_LEVEL_DOCS,
+        MISSING_SAFETY_DOC,
+        MISSING_ERRORS_DOC,
+        MISSING_PANICS_DOC,
     ]
 });
 
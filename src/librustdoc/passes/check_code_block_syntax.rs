@@ -1,4 +1,7 @@
-//! Validates syntax inside Rust code blocks (\`\`\`rust).
+//! Validates syntax inside code blocks: Rust code blocks (\`\`\`rust) are parsed with
+//! `rustc_parse`, JSON and TOML ones with their respective parsers, and code blocks in other
+//! languages can be piped to the commands given with `--check-code-block`.
+use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::sync::{Lock, Lrc};
 use rustc_errors::{
     emitter::Emitter, translation::Translate, Applicability, Diagnostic, Handler,
@@ -10,28 +13,42 @@
 use rustc_span::source_map::{FilePathMapping, SourceMap};
 use rustc_span::{FileName, InnerSpan, DUMMY_SP};
 
+use std::io::Write;
+use std::ops::Range;
+use std::process::{Command, Stdio};
+
 use crate::clean;
 use crate::core::DocContext;
-use crate::html::markdown::{self, RustCodeBlock};
+use crate::html::markdown::{self, FencedCodeBlock, RustCodeBlock};
 use crate::passes::Pass;
 use crate::visit::DocVisitor;
 
 pub(crate) const CHECK_CODE_BLOCK_SYNTAX: Pass = Pass {
     name: "check-code-block-syntax",
     run: check_code_block_syntax,
-    description: "validates syntax inside Rust code blocks",
+    description: "validates syntax inside code blocks",
 };
 
 pub(crate) fn check_code_block_syntax(
     krate: clean::Crate,
     cx: &mut DocContext<'_>,
 ) -> clean::Crate {
-    SyntaxChecker { cx }.visit_crate(&krate);
+    SyntaxChecker { cx, failed_checkers: FxHashSet::default() }.visit_crate(&krate);
     krate
 }
 
 struct SyntaxChecker<'a, 'tcx> {
     cx: &'a DocContext<'tcx>,
+    /// Languages whose `--check-code-block` command couldn't be run, so that the error is only
+    /// reported once.
+    failed_checkers: FxHashSet<String>,
+}
+
+/// A syntax error found in a code block that isn't Rust code.
+struct SyntaxError {
+    message: String,
+    /// The byte offset of the error in the code block, if known.
+    offset: Option<usize>,
 }
 
 impl<'a, 'tcx> SyntaxChecker<'a, 'tcx> {
@@ -151,6 +168,139 @@ fn check_rust_syntax(&self, item: &clean::Item, dox: &str, code_block: RustCodeB
             diag_builder,
         );
     }
+
+    fn check_fenced_code_block(
+        &mut self,
+        item: &clean::Item,
+        dox: &str,
+        code_block: FencedCodeBlock,
+    ) {
+        let Some(local_id) = item.item_id.as_def_id().and_then(|x| x.as_local())
+        else {
+            // We don't need to check the syntax for other crates so returning
+            // without doing anything should not be a problem.
+            return;
+        };
+        let tcx = self.cx.tcx;
+        let hir_id = tcx.hir().local_def_id_to_hir_id(local_id);
+        let span_for = |range: Range<usize>| {
+            super::source_span_for_markdown_range(tcx, dox, &range, &item.attrs)
+                .unwrap_or_else(|| item.attr_span(tcx))
+        };
+
+        if !code_block.is_closed {
+            let block = &dox[code_block.range.clone()];
+            let fence_end = code_block.range.start + block.find('\n').unwrap_or(block.len());
+            let sp = span_for(code_block.range.start..fence_end);
+            tcx.struct_span_lint_hir(crate::lint::INVALID_CODEBLOCK_SYNTAX, hir_id, sp, |lint| {
+                lint.build("this code block is never closed")
+                    .note("the rest of the documentation is displayed as part of the code block")
+                    .help("add a closing fence at the end of the code block")
+                    .emit();
+            });
+            // Without a closing fence, the contents of the code block aren't what was intended.
+            return;
+        }
+        if code_block.is_rust {
+            // Rust code blocks are checked by `check_rust_syntax`.
+            return;
+        }
+        let Some(lang) = code_block.lang else { return };
+
+        let code = &dox[code_block.code.clone()];
+        let error = match self.cx.render_options.code_block_checkers.get(&lang) {
+            Some(command) => self.run_checker(&lang, command, code),
+            None if lang == "json" => check_json_syntax(code),
+            None if lang == "toml" => check_toml_syntax(code),
+            None => None,
+        };
+        let Some(error) = error else { return };
+
+        let range = match error.offset {
+            Some(offset) if offset < code.len() => {
+                let len = code[offset..].chars().next().map_or(0, char::len_utf8);
+                let start = code_block.code.start + offset;
+                start..start + len
+            }
+            _ => code_block.range,
+        };
+        tcx.struct_span_lint_hir(
+            crate::lint::INVALID_CODEBLOCK_SYNTAX,
+            hir_id,
+            span_for(range),
+            |lint| {
+                lint.build(&format!("could not parse code block as `{}`", lang))
+                    .note(&error.message)
+                    .emit();
+            },
+        );
+    }
+
+    /// Pipes `code` to the `--check-code-block` command given for `lang`, which is expected to
+    /// exit unsuccessfully if the code is invalid.
+    fn run_checker(&mut self, lang: &str, command: &[String], code: &str) -> Option<SyntaxError> {
+        if self.failed_checkers.contains(lang) {
+            return None;
+        }
+        let output = Command::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                // The checker may exit without reading all of its input, so ignore write errors.
+                let _ = child.stdin.take().unwrap().write_all(code.as_bytes());
+                child.wait_with_output()
+            });
+        match output {
+            Ok(output) if output.status.success() => None,
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let message = match (stderr.trim(), stdout.trim()) {
+                    ("", "") => format!("`{}` exited with {}", command.join(" "), output.status),
+                    ("", stdout) => stdout.to_owned(),
+                    (stderr, _) => stderr.to_owned(),
+                };
+                Some(SyntaxError { message, offset: None })
+            }
+            Err(err) => {
+                self.cx.tcx.sess.err(&format!(
+                    "failed to run `{}` to check `{}` code blocks: {}",
+                    command.join(" "),
+                    lang,
+                    err
+                ));
+                self.failed_checkers.insert(lang.to_owned());
+                None
+            }
+        }
+    }
+}
+
+fn check_json_syntax(code: &str) -> Option<SyntaxError> {
+    let err = serde_json::from_str::<serde_json::Value>(code).err()?;
+    // Lines and columns are 1-based.
+    let (line, col) = (err.line().saturating_sub(1), err.column().saturating_sub(1));
+    let offset = offset_of_line_col(code, line, col);
+    Some(SyntaxError { message: err.to_string(), offset: Some(offset) })
+}
+
+fn check_toml_syntax(code: &str) -> Option<SyntaxError> {
+    let err = code.parse::<toml::Value>().err()?;
+    let offset = err.line_col().map(|(line, col)| offset_of_line_col(code, line, col));
+    Some(SyntaxError { message: err.to_string(), offset })
+}
+
+/// Converts a 0-based line and byte column into a byte offset in `code`.
+fn offset_of_line_col(code: &str, line: usize, col: usize) -> usize {
+    let line_start: usize = code.split_inclusive('\n').take(line).map(str::len).sum();
+    let mut offset = (line_start + col).min(code.len());
+    while !code.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
 }
 
 impl<'a, 'tcx> DocVisitor for SyntaxChecker<'a, 'tcx> {
@@ -162,9 +312,23 @@ fn visit_item(&mut self, item: &clean::Item) {
                 item.item_id.expect_def_id(),
                 sp,
             );
+            let fenced_code_blocks = markdown::fenced_code_blocks(dox);
+            // Unclosed code blocks are reported by `check_fenced_code_block`, don't also report
+            // that the rest of the documentation can't be parsed as Rust.
+            let unclosed = fenced_code_blocks
+                .iter()
+                .filter(|code_block| !code_block.is_closed)
+                .map(|code_block| code_block.range.start)
+                .collect::<FxHashSet<_>>();
             for code_block in markdown::rust_code_blocks(dox, &extra) {
+                if code_block.is_fenced && unclosed.contains(&code_block.range.start) {
+                    continue;
+                }
                 self.check_rust_syntax(item, dox, code_block);
             }
+            for code_block in fenced_code_blocks {
+                self.check_fenced_code_block(item, dox, code_block);
+            }
         }
 
         self.visit_item_recur(item)
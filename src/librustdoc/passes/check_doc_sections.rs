@@ -0,0 +1,246 @@
+//! Detects functions and traits whose documentation is missing a section describing how they can
+//! be misused or fail:
+//!
+//! - MISSING_SAFETY_DOC: `unsafe fn` and `unsafe trait` without a `# Safety` section.
+//! - MISSING_ERRORS_DOC: public functions returning `Result` without an `# Errors` section.
+//! - MISSING_PANICS_DOC: functions that can panic in their own body without a `# Panics` section.
+use super::Pass;
+use crate::clean::*;
+use crate::core::DocContext;
+use crate::html::markdown::main_body_opts;
+use crate::visit::DocVisitor;
+use pulldown_cmark::{Event, Parser, Tag};
+use rustc_hir as hir;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::DefId;
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_middle::hir::nested_filter;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_session::lint::{Level, Lint};
+use rustc_span::symbol::sym;
+use rustc_span::Span;
+
+pub(crate) const CHECK_DOC_SECTIONS: Pass = Pass {
+    name: "check-doc-sections",
+    run: check_doc_sections,
+    description: "detects missing `Safety`, `Errors` and `Panics` sections in documentation",
+};
+
+struct DocSectionsLinter<'a, 'tcx> {
+    cx: &'a mut DocContext<'tcx>,
+}
+
+pub(crate) fn check_doc_sections(krate: Crate, cx: &mut DocContext<'_>) -> Crate {
+    DocSectionsLinter { cx }.visit_crate(&krate);
+    krate
+}
+
+/// The sections (level-agnostic headings) found in a documentation string.
+struct Sections {
+    safety: bool,
+    errors: bool,
+    panics: bool,
+}
+
+impl Sections {
+    fn find(dox: &str) -> Self {
+        let mut sections = Sections { safety: false, errors: false, panics: false };
+        let mut in_heading = false;
+        for event in Parser::new_ext(dox, main_body_opts()) {
+            match event {
+                Event::Start(Tag::Heading(..)) => in_heading = true,
+                Event::End(Tag::Heading(..)) => in_heading = false,
+                Event::Text(text) if in_heading => {
+                    let text = text.trim();
+                    sections.safety |= text.eq_ignore_ascii_case("safety");
+                    sections.errors |= text.eq_ignore_ascii_case("errors");
+                    sections.panics |= text.eq_ignore_ascii_case("panics");
+                }
+                _ => {}
+            }
+        }
+        sections
+    }
+}
+
+impl<'a, 'tcx> DocVisitor for DocSectionsLinter<'a, 'tcx> {
+    fn visit_item(&mut self, item: &Item) {
+        let Some(hir_id) = DocContext::as_local_hir_id(self.cx.tcx, item.item_id)
+        else {
+            // If non-local, no need to check anything.
+            return;
+        };
+        let tcx = self.cx.tcx;
+        let report = |lint: &'static Lint, sp: Span, msg: &str, help: &str| {
+            tcx.struct_span_lint_hir(lint, hir_id, sp, |lint| {
+                lint.build(msg).help(help).emit();
+            });
+        };
+
+        match *item.kind {
+            FunctionItem(_) | MethodItem(..) | TyMethodItem(_) => {
+                let def_id = item.item_id.expect_def_id();
+                let dox = item.attrs.collapsed_doc_value().unwrap_or_default();
+                let sections = Sections::find(&dox);
+                let sp = item.attr_span(tcx);
+
+                if !sections.safety && tcx.fn_sig(def_id).unsafety() == hir::Unsafety::Unsafe {
+                    report(
+                        crate::lint::MISSING_SAFETY_DOC,
+                        sp,
+                        "unsafe function's docs are missing a `# Safety` section",
+                        "describe the conditions callers must uphold in a `# Safety` section",
+                    );
+                }
+                if !sections.errors
+                    && self.cx.cache.access_levels.is_public(def_id)
+                    && returns_result(tcx, def_id)
+                {
+                    report(
+                        crate::lint::MISSING_ERRORS_DOC,
+                        sp,
+                        "docs for function returning `Result` are missing an `# Errors` section",
+                        "describe when an error is returned in an `# Errors` section",
+                    );
+                }
+                // Looking for panics means walking the whole body, so only do it when needed.
+                let (panics_level, _) =
+                    tcx.lint_level_at_node(crate::lint::MISSING_PANICS_DOC, hir_id);
+                if !sections.panics && panics_level != Level::Allow {
+                    if let Some(panic_span) = find_panic(tcx, def_id) {
+                        let msg =
+                            "docs for function which may panic are missing a `# Panics` section";
+                        let help = "describe when the function panics in a `# Panics` section";
+                        tcx.struct_span_lint_hir(
+                            crate::lint::MISSING_PANICS_DOC,
+                            hir_id,
+                            sp,
+                            |lint| {
+                                lint.build(msg)
+                                    .span_note(panic_span, "this call may panic")
+                                    .help(help)
+                                    .emit();
+                            },
+                        );
+                    }
+                }
+            }
+            TraitItem(ref t) => {
+                let dox = item.attrs.collapsed_doc_value().unwrap_or_default();
+                if t.unsafety(tcx) == hir::Unsafety::Unsafe && !Sections::find(&dox).safety {
+                    report(
+                        crate::lint::MISSING_SAFETY_DOC,
+                        item.attr_span(tcx),
+                        "unsafe trait's docs are missing a `# Safety` section",
+                        "describe the conditions implementors must uphold in a `# Safety` section",
+                    );
+                }
+            }
+            // Methods of trait implementations are documented by the trait.
+            ImplItem(box Impl { trait_: Some(_), .. }) => return,
+            _ => {}
+        }
+
+        self.visit_item_recur(item)
+    }
+}
+
+fn returns_result(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    let output = tcx.fn_sig(def_id).output().skip_binder();
+    match output.kind() {
+        ty::Adt(adt, _) => tcx.is_diagnostic_item(sym::Result, adt.did()),
+        _ => false,
+    }
+}
+
+/// Returns the span of the first call in the body of `def_id` that may panic, if any.
+///
+/// Only the body of the function itself (including its closures) is looked at, not the functions
+/// it calls. Since rustdoc doesn't typecheck function bodies, method calls are matched by name.
+fn find_panic(tcx: TyCtxt<'_>, def_id: DefId) -> Option<Span> {
+    let body_id = tcx.hir().maybe_body_owned_by(def_id.as_local()?)?;
+    let mut finder = PanicFinder { tcx, panic_span: None };
+    finder.visit_expr(&tcx.hir().body(body_id).value);
+    finder.panic_span
+}
+
+struct PanicFinder<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    panic_span: Option<Span>,
+}
+
+impl<'tcx> PanicFinder<'tcx> {
+    fn is_panic_fn(&self, def_id: DefId) -> bool {
+        let lang_items = self.tcx.lang_items();
+        [
+            lang_items.panic_fn(),
+            lang_items.panic_fmt(),
+            lang_items.panic_display(),
+            lang_items.begin_panic_fn(),
+        ]
+        .contains(&Some(def_id))
+            || matches!(
+                self.tcx.get_diagnostic_name(def_id),
+                Some(sym::panic_str | sym::unreachable_display)
+            )
+    }
+
+    /// Whether the panic comes from a macro asserting that it can't happen or that is only
+    /// enabled in debug builds.
+    fn is_expected_unreachable(&self, span: Span) -> bool {
+        span.macro_backtrace().any(|expn| {
+            expn.macro_def_id.map_or(false, |def_id| {
+                matches!(
+                    self.tcx.get_diagnostic_name(def_id),
+                    Some(
+                        sym::unreachable_macro
+                            | sym::unreachable_2015_macro
+                            | sym::unreachable_2021_macro
+                            | sym::debug_assert_macro
+                            | sym::debug_assert_eq_macro
+                            | sym::debug_assert_ne_macro
+                    )
+                )
+            })
+        })
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for PanicFinder<'tcx> {
+    type NestedFilter = nested_filter::OnlyBodies;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) {
+        if self.panic_span.is_some() {
+            return;
+        }
+        match expr.kind {
+            hir::ExprKind::Call(
+                hir::Expr {
+                    kind:
+                        hir::ExprKind::Path(hir::QPath::Resolved(
+                            _,
+                            hir::Path { res: Res::Def(DefKind::Fn, def_id), .. },
+                        )),
+                    ..
+                },
+                _,
+            ) if self.is_panic_fn(*def_id) && !self.is_expected_unreachable(expr.span) => {
+                self.panic_span = Some(expr.span.source_callsite());
+                return;
+            }
+            hir::ExprKind::MethodCall(segment, ..)
+                if matches!(segment.ident.name, sym::unwrap | sym::expect)
+                    && !expr.span.from_expansion() =>
+            {
+                self.panic_span = Some(expr.span);
+                return;
+            }
+            _ => {}
+        }
+        intravisit::walk_expr(self, expr);
+    }
+}
@@ -45,6 +45,9 @@
 mod html_tags;
 pub(crate) use self::html_tags::CHECK_INVALID_HTML_TAGS;
 
+mod check_doc_sections;
+pub(crate) use self::check_doc_sections::CHECK_DOC_SECTIONS;
+
 /// A single pass over the cleaned documentation.
 ///
 /// Runs in the compiler context, so it has access to types and traits and the like.
@@ -87,6 +90,7 @@ pub(crate) enum Condition {
     CALCULATE_DOC_COVERAGE,
     CHECK_INVALID_HTML_TAGS,
     CHECK_BARE_URLS,
+    CHECK_DOC_SECTIONS,
 ];
 
 /// The list of passes run by default.
@@ -101,6 +105,7 @@ pub(crate) enum Condition {
     ConditionalPass::always(CHECK_INVALID_HTML_TAGS),
     ConditionalPass::always(PROPAGATE_DOC_CFG),
     ConditionalPass::always(CHECK_BARE_URLS),
+    ConditionalPass::always(CHECK_DOC_SECTIONS),
 ];
 
 /// The list of default passes run when `--doc-coverage` is passed to rustdoc.
@@ -29,11 +29,41 @@
 
 use std::fs;
 use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The kind of target that a set of call locations was scraped from.
+///
+/// Besides the `examples/` directory, examples can be scraped from integration tests, benchmarks
+/// and from other crates of the workspace that depend on the documented crate. The kind is
+/// recorded alongside the calls so that the documentation can mix examples from different
+/// sources instead of only showing the ones from the most prolific target.
+#[derive(Encodable, Decodable, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ExampleKind {
+    Example,
+    Test,
+    Bench,
+    Dependent,
+}
+
+impl FromStr for ExampleKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "example" => Ok(ExampleKind::Example),
+            "test" => Ok(ExampleKind::Test),
+            "bench" => Ok(ExampleKind::Bench),
+            "dependent" => Ok(ExampleKind::Dependent),
+            _ => Err(()),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub(crate) struct ScrapeExamplesOptions {
     output_path: PathBuf,
     target_crates: Vec<String>,
+    kind: ExampleKind,
     pub(crate) scrape_tests: bool,
 }
 
@@ -45,10 +75,24 @@ pub(crate) fn new(
         let output_path = matches.opt_str("scrape-examples-output-path");
         let target_crates = matches.opt_strs("scrape-examples-target-crate");
         let scrape_tests = matches.opt_present("scrape-tests");
+        let kind = match matches.opt_str("scrape-examples-kind") {
+            Some(kind) => match kind.parse() {
+                Ok(kind) => kind,
+                Err(()) => {
+                    diag.err(&format!("unrecognized scrape examples kind: {}", kind));
+                    return Err(1);
+                }
+            },
+            None if scrape_tests => ExampleKind::Test,
+            None => ExampleKind::Example,
+        };
+        // Tests and benchmarks only exist under `cfg(test)`, so they can't be scraped without it.
+        let scrape_tests = scrape_tests || matches!(kind, ExampleKind::Test | ExampleKind::Bench);
         match (output_path, !target_crates.is_empty(), scrape_tests) {
             (Some(output_path), true, _) => Ok(Some(ScrapeExamplesOptions {
                 output_path: PathBuf::from(output_path),
                 target_crates,
+                kind,
                 scrape_tests,
             })),
             (Some(_), false, _) | (None, true, _) => {
@@ -110,6 +154,9 @@ pub(crate) struct CallData {
     pub(crate) url: String,
     pub(crate) display_name: String,
     pub(crate) edition: Edition,
+    /// The name of the crate containing the calls.
+    pub(crate) crate_name: String,
+    pub(crate) kind: ExampleKind,
 }
 
 pub(crate) type FnCallLocations = FxHashMap<PathBuf, CallData>;
@@ -121,6 +168,7 @@ struct FindCalls<'a, 'tcx> {
     map: Map<'tcx>,
     cx: Context<'tcx>,
     target_crates: Vec<CrateNum>,
+    kind: ExampleKind,
     calls: &'a mut AllCallLocations,
 }
 
@@ -246,7 +294,15 @@ fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
                 let mk_call_data = || {
                     let display_name = file_path.display().to_string();
                     let edition = call_span.edition();
-                    CallData { locations: Vec::new(), url, display_name, edition }
+                    let crate_name = tcx.crate_name(LOCAL_CRATE).to_string();
+                    CallData {
+                        locations: Vec::new(),
+                        url,
+                        display_name,
+                        edition,
+                        crate_name,
+                        kind: self.kind,
+                    }
                 };
 
                 let fn_key = tcx.def_path_hash(*def_id);
@@ -301,7 +357,14 @@ pub(crate) fn run(
 
         // Run call-finder on all items
         let mut calls = FxHashMap::default();
-        let mut finder = FindCalls { calls: &mut calls, tcx, map: tcx.hir(), cx, target_crates };
+        let mut finder = FindCalls {
+            calls: &mut calls,
+            tcx,
+            map: tcx.hir(),
+            cx,
+            target_crates,
+            kind: options.kind,
+        };
         tcx.hir().visit_all_item_likes_in_crate(&mut finder);
 
         // The visitor might have found a type error, which we need to
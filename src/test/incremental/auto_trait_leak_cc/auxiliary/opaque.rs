@@ -0,0 +1,16 @@
+// compile-flags: -Zpublic-api-hash
+
+#![crate_type = "rlib"]
+
+#[cfg(rpass1)]
+pub fn make() -> impl Fn() -> u32 {
+    let value = std::sync::Arc::new(1);
+    move || *value
+}
+
+// Only the hidden type changed, and it is no longer `Send`.
+#[cfg(cfail2)]
+pub fn make() -> impl Fn() -> u32 {
+    let value = std::rc::Rc::new(1);
+    move || *value
+}
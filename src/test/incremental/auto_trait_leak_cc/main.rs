@@ -0,0 +1,14 @@
+// Checks that dependents notice when the hidden type of an `impl Trait` returned by another crate
+// stops implementing `Send`, even though the signature returning it did not change.
+
+// revisions: rpass1 cfail2
+// aux-build: opaque.rs
+
+extern crate opaque;
+
+fn assert_send<T: Send>(_: T) {}
+
+fn main() {
+    assert_send(opaque::make());
+    //[cfail2]~^ ERROR `Rc<u32>` cannot be sent between threads safely
+}
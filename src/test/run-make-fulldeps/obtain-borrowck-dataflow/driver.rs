@@ -0,0 +1,136 @@
+#![feature(rustc_private)]
+
+//! This program implements a rustc driver that retrieves MIR bodies with their
+//! borrows, region inference context and the results of the `Borrows` dataflow
+//! analysis, and checks the inferred regions and the borrows in scope. Unlike
+//! `obtain-borrowck`, the driver doesn't override the `mir_borrowck` query: the
+//! `after_expansion` callback requests every body with
+//! `get_body_with_borrows_dataflow` before the analysis borrow checks (and
+//! steals) them.
+
+extern crate rustc_borrowck;
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+
+use rustc_borrowck::consumers::{
+    get_body_with_borrows_dataflow, BodyWithBorrowckFacts, BodyWithBorrowsDataflow, BorrowIndex,
+    TwoPhaseActivation,
+};
+use rustc_driver::Compilation;
+use rustc_hir::def::DefKind;
+use rustc_interface::interface::Compiler;
+use rustc_interface::Queries;
+use rustc_middle::ty;
+
+fn main() {
+    let exit_code = rustc_driver::catch_with_exit_code(move || {
+        let mut rustc_args: Vec<_> = std::env::args().collect();
+        // We must pass -Zpolonius so that the borrowck information is computed.
+        rustc_args.push("-Zpolonius".to_owned());
+        let mut callbacks = CompilerCalls::default();
+        // Call the Rust compiler with our callbacks.
+        rustc_driver::RunCompiler::new(&rustc_args, &mut callbacks).run()
+    });
+    std::process::exit(exit_code);
+}
+
+#[derive(Default)]
+pub struct CompilerCalls;
+
+impl rustc_driver::Callbacks for CompilerCalls {
+    // In this callback we retrieve all function bodies before they are borrow
+    // checked by the analysis.
+    fn after_expansion<'tcx>(
+        &mut self,
+        compiler: &Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        compiler.session().abort_if_errors();
+        queries.global_ctxt().unwrap().peek_mut().enter(|tcx| {
+            // Collect definition ids of MIR bodies.
+            let hir = tcx.hir();
+            let mut bodies = Vec::new();
+
+            let crate_items = tcx.hir_crate_items(());
+            for id in crate_items.items() {
+                if matches!(tcx.def_kind(id.def_id), DefKind::Fn) {
+                    bodies.push(id.def_id);
+                }
+            }
+
+            for id in crate_items.trait_items() {
+                if matches!(tcx.def_kind(id.def_id), DefKind::AssocFn) {
+                    let trait_item = hir.trait_item(id);
+                    if let rustc_hir::TraitItemKind::Fn(_, trait_fn) = &trait_item.kind {
+                        if let rustc_hir::TraitFn::Provided(_) = trait_fn {
+                            bodies.push(trait_item.def_id);
+                        }
+                    }
+                }
+            }
+
+            for id in crate_items.impl_items() {
+                if matches!(tcx.def_kind(id.def_id), DefKind::AssocFn) {
+                    bodies.push(id.def_id);
+                }
+            }
+
+            let mut bodies: Vec<_> = bodies
+                .into_iter()
+                .map(|def_id| {
+                    let def_path = tcx.def_path(def_id.to_def_id()).to_string_no_crate_verbose();
+                    (def_path, get_body_with_borrows_dataflow(tcx, def_id))
+                })
+                .collect();
+            bodies.sort_by(|(def_path1, _), (def_path2, _)| def_path1.cmp(def_path2));
+            println!("Bodies retrieved for:");
+            for (def_path, body_with_dataflow) in bodies {
+                let BodyWithBorrowsDataflow {
+                    body_with_facts,
+                    borrows_out_of_scope_at_location,
+                    borrows_in_scope_at_location,
+                } = &body_with_dataflow;
+                let BodyWithBorrowckFacts { borrow_set, region_inference_context, .. } =
+                    body_with_facts;
+
+                let mut activated = 0;
+                for (i, borrow) in borrow_set.location_map.values().enumerate() {
+                    let index = BorrowIndex::from_usize(i);
+                    // A borrow is in scope right after it is created...
+                    assert!(borrows_in_scope_at_location[&borrow.reserve_location].contains(index));
+                    // ...and goes out of scope once its region ends.
+                    assert!(borrows_out_of_scope_at_location
+                        .values()
+                        .any(|borrows| borrows.contains(&index)));
+                    // The region of the borrow contains the point where it is created, and it
+                    // is an existential region of the body, so it lives in the root universe.
+                    assert!(region_inference_context
+                        .region_contains(borrow.region, borrow.reserve_location));
+                    assert_eq!(
+                        region_inference_context.region_universe(borrow.region),
+                        ty::UniverseIndex::ROOT
+                    );
+                    assert!(region_inference_context
+                        .region_value_str(borrow.region)
+                        .starts_with("{bb"));
+                    if let TwoPhaseActivation::ActivatedAt(location) = borrow.activation_location {
+                        assert_eq!(borrow_set.activation_map[&location], [index]);
+                        // The borrow must still be live when it is activated.
+                        assert!(region_inference_context.region_contains(borrow.region, location));
+                        activated += 1;
+                    }
+                }
+                println!(
+                    "{}: {} borrows, {} two-phase borrows activated",
+                    def_path,
+                    borrow_set.location_map.len(),
+                    activated,
+                );
+            }
+        });
+
+        Compilation::Continue
+    }
+}
@@ -0,0 +1,11 @@
+fn shared() -> u32 {
+    let x = 1;
+    let r = &x;
+    *r
+}
+
+fn two_phase(v: &mut Vec<usize>) {
+    v.push(v.len());
+}
+
+fn main() {}
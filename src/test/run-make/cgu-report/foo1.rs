@@ -0,0 +1,11 @@
+pub mod a {
+    pub fn a() -> u32 {
+        1
+    }
+}
+
+pub mod b {
+    pub fn b() -> u32 {
+        2
+    }
+}
@@ -0,0 +1,19 @@
+#![crate_type = "lib"]
+
+const fn square(x: u32) -> u32 {
+    x * x
+}
+
+const fn make_table() -> [u32; 4] {
+    let mut table = [0; 4];
+    let mut i = 0;
+    while i < 4 {
+        table[i] = square(i as u32);
+        i += 1;
+    }
+    table
+}
+
+pub const TABLE: [u32; 4] = make_table();
+
+pub const OTHER: u32 = square(3);
@@ -0,0 +1,10 @@
+#![crate_type = "lib"]
+
+pub fn foo<'a, 'b>(_x: &'a u32, y: &'b u32) -> &'a u32 {
+    let z = y;
+    z
+}
+
+pub fn bar<'a, 'b>(_x: &'a u32, y: &'b u32) -> &'a u32 {
+    y
+}
@@ -0,0 +1,5 @@
+#![crate_type = "rlib"]
+
+pub fn process<T: Copy>(x: T) -> T {
+    x
+}
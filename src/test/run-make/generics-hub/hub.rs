@@ -0,0 +1,7 @@
+#![crate_type = "rlib"]
+
+extern crate common;
+
+pub fn instantiate() {
+    common::process(0u32);
+}
@@ -0,0 +1,6 @@
+extern crate common;
+extern crate hub;
+
+fn main() {
+    println!("{} {} {}", common::process(42u32), common::process(7u8), common::process(43u64));
+}
@@ -0,0 +1,9 @@
+pub fn foo() -> u32 {
+    1
+}
+
+pub mod bar {
+    pub fn bar() -> u32 {
+        2
+    }
+}
@@ -0,0 +1,9 @@
+#![crate_type = "lib"]
+
+pub fn generic<T: Default>() -> T {
+    T::default()
+}
+
+pub fn caller() -> (u32, u64) {
+    (generic(), generic())
+}
@@ -0,0 +1,18 @@
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    pub fn new() -> Counter {
+        Counter { count: start() }
+    }
+
+    pub fn bump(&mut self) -> u32 {
+        self.count += 1;
+        self.count
+    }
+}
+
+fn start() -> u32 {
+    0
+}
@@ -0,0 +1,23 @@
+// Only private code and the body of a non-inlinable function changed.
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    pub fn new() -> Counter {
+        Counter { count: start() + offset() }
+    }
+
+    pub fn bump(&mut self) -> u32 {
+        self.count += 2;
+        self.count
+    }
+}
+
+fn start() -> u32 {
+    1
+}
+
+fn offset() -> u32 {
+    2
+}
@@ -0,0 +1,19 @@
+// The signature of a public function changed.
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    pub fn new() -> Counter {
+        Counter { count: start() }
+    }
+
+    pub fn bump(&mut self) -> u64 {
+        self.count += 1;
+        self.count as u64
+    }
+}
+
+fn start() -> u32 {
+    0
+}
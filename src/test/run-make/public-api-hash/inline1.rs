@@ -0,0 +1,4 @@
+#[inline]
+pub fn check(x: u32) {
+    assert!(x < 10);
+}
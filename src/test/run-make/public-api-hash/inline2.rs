@@ -0,0 +1,6 @@
+// The body of an inlinable function moved, which changes the panic location it embeds in
+// dependents.
+#[inline]
+pub fn check(x: u32) {
+    assert!(x < 10);
+}
@@ -0,0 +1,4 @@
+pub fn make() -> impl Fn() -> u32 {
+    let value = std::sync::Arc::new(1);
+    move || *value
+}
@@ -0,0 +1,5 @@
+// The hidden type of the returned `impl Trait` is no longer `Send`.
+pub fn make() -> impl Fn() -> u32 {
+    let value = std::rc::Rc::new(1);
+    move || *value
+}
@@ -0,0 +1,15 @@
+fn main() {}
+
+#[test]
+fn a_bench() {
+    foobar::ok();
+
+    // The benchmark is the biggest example, so it would be left out by the limit if it
+    // was not recorded as a different kind of source than the examples of the same crate.
+
+    //  ..
+
+    // BIG
+
+    // item
+}
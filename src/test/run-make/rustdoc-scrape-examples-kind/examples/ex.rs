@@ -0,0 +1,5 @@
+mod helper;
+
+fn main() {
+    foobar::ok();
+}
@@ -0,0 +1,6 @@
+// Examples are taken in turns from each kind of source, so the benchmark is shown along with
+// one of the two examples despite all of them coming from the `ex` crate.
+// @count foobar/fn.ok.html '//*[@class="scraped-example-title"]' 2
+// @has foobar/fn.ok.html '//*[@class="scraped-example-title"]' 'bench.rs'
+
+pub fn ok() {}
@@ -0,0 +1,3 @@
+// @count foobar/fn.ok.html '//*[@class="docblock scraped-example-list"]' 0
+
+pub fn ok() {}
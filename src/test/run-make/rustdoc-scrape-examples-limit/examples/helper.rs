@@ -0,0 +1,3 @@
+pub fn f() {
+    foobar::ok(1);
+}
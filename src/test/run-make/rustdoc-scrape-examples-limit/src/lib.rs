@@ -0,0 +1,6 @@
+// Examples are taken in turns from each crate, so whatever their sizes, the limit keeps one of
+// the two examples from `ex1` and the one from `ex2`.
+// @count foobar/fn.ok.html '//*[@class="scraped-example-title"]' 2
+// @count foobar/fn.ok.html '//*[@class="scraped-example-title"]' 'ex2.rs' 1
+
+pub fn ok(_x: i32) {}
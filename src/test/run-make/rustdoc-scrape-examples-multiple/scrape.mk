@@ -16,6 +16,7 @@ $(TMPDIR)/lib%.rmeta: src/lib.rs
 scrape: $(foreach d,$(deps),$(TMPDIR)/$(d).calls)
 	$(RUSTDOC) src/lib.rs --crate-name foobar --crate-type lib --output $(OUTPUT_DIR) \
 		-Z unstable-options \
-		$(foreach d,$(deps),--with-examples $(TMPDIR)/$(d).calls)
+		$(foreach d,$(deps),--with-examples $(TMPDIR)/$(d).calls) \
+		$(extra_render_flags)
 
 	$(HTMLDOCCK) $(OUTPUT_DIR) src/lib.rs
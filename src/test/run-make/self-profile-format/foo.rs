@@ -0,0 +1,7 @@
+fn square(x: u32) -> u32 {
+    x * x
+}
+
+fn main() {
+    println!("{}", square(3));
+}
@@ -0,0 +1,20 @@
+#!/usr/bin/env python
+
+import json
+import sys
+
+# Checks that the given file is a Chrome trace with complete events for queries.
+with open(sys.argv[1]) as f:
+    trace = json.load(f)
+
+events = trace["traceEvents"]
+assert events[0]["ph"] == "M", events[0]
+assert events[0]["args"]["name"] == "rustc foo", events[0]
+
+queries = [event["name"] for event in events[1:] if event["cat"] == "Query"]
+assert "typeck" in queries, "no `typeck` query in the trace"
+for event in events[1:]:
+    assert event["ph"] in ("X", "i"), event
+    assert event["ts"] >= 0, event
+    if event["ph"] == "X":
+        assert event["dur"] >= 0, event
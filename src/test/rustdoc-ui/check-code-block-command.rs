@@ -0,0 +1,9 @@
+// check-pass
+// ignore-windows
+// compile-flags: -Z unstable-options --check-code-block=sh=false
+
+/// ```sh
+/// echo foo
+/// ```
+pub fn foo() {}
+//~^^^^ WARNING could not parse code block as `sh`
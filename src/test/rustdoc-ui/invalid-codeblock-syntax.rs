@@ -0,0 +1,34 @@
+// check-pass
+
+/// ```json
+/// { "name": "foo", }
+/// ```
+pub fn invalid_json() {}
+//~^^^ WARNING could not parse code block as `json`
+
+/// ```toml
+/// [package]
+/// name "foo"
+/// ```
+pub fn invalid_toml() {}
+//~^^^ WARNING could not parse code block as `toml`
+
+/// ```json
+/// { "name": "foo" }
+/// ```
+///
+/// ```toml
+/// [package]
+/// name = "foo"
+/// ```
+pub fn valid() {}
+
+/// ```sh
+/// echo foo
+pub fn unclosed() {}
+//~^^^ WARNING this code block is never closed
+
+/// ```
+/// fn foo() {
+pub fn unclosed_rust() {}
+//~^^^ WARNING this code block is never closed
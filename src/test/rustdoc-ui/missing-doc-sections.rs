@@ -0,0 +1,79 @@
+#![deny(rustdoc::missing_safety_doc, rustdoc::missing_errors_doc, rustdoc::missing_panics_doc)]
+
+/// Reads the byte behind `ptr`.
+//~^ ERROR unsafe function's docs are missing a `# Safety` section
+pub unsafe fn read(ptr: *const u8) -> u8 {
+    *ptr
+}
+
+/// Reads the byte behind `ptr`.
+///
+/// ## SAFETY
+///
+/// `ptr` must be valid for reads.
+pub unsafe fn read_documented(ptr: *const u8) -> u8 {
+    *ptr
+}
+
+/// A marker.
+//~^ ERROR unsafe trait's docs are missing a `# Safety` section
+pub unsafe trait Marker {}
+
+/// Parses a port number.
+//~^ ERROR docs for function returning `Result` are missing an `# Errors` section
+pub fn parse_port(s: &str) -> Result<u16, std::num::ParseIntError> {
+    s.parse()
+}
+
+/// Parses a port number.
+///
+/// # Errors
+///
+/// Fails if `s` isn't a number.
+pub fn parse_port_documented(s: &str) -> Result<u16, std::num::ParseIntError> {
+    s.parse()
+}
+
+/// Returns the first byte.
+//~^ ERROR docs for function which may panic are missing a `# Panics` section
+pub fn first(bytes: &[u8]) -> u8 {
+    *bytes.first().unwrap()
+}
+
+/// Fails.
+//~^ ERROR docs for function which may panic are missing a `# Panics` section
+pub fn fail(reason: &str) {
+    panic!("failed: {}", reason);
+}
+
+/// Returns the first byte.
+///
+/// # Panics
+///
+/// Panics if `bytes` is empty.
+pub fn first_documented(bytes: &[u8]) -> u8 {
+    *bytes.first().unwrap()
+}
+
+/// Never fails.
+pub fn never_fails(x: u8) -> u8 {
+    debug_assert!(x > 0);
+    match x {
+        0 => unreachable!(),
+        x => x,
+    }
+}
+
+pub struct Port;
+
+unsafe impl Marker for Port {}
+
+// Trait implementations are documented by their trait.
+impl std::str::FromStr for Port {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        assert!(!s.is_empty());
+        Ok(Port)
+    }
+}
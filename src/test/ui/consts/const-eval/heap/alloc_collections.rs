@@ -0,0 +1,54 @@
+// run-pass
+#![feature(allocator_api)]
+#![feature(const_box)]
+#![feature(const_heap)]
+#![feature(const_mut_refs)]
+#![feature(const_ptr_write)]
+#![feature(const_trait_impl)]
+use std::alloc::Global;
+
+// `Vec`, `String` and `Box` allocate through `Global` during const evaluation. Their heap memory
+// is interned as immutable static memory when the collection ends up behind a shared reference.
+// `push` is not a `const fn`, so the elements are written into the buffer directly.
+const fn squares(len: usize) -> Vec<u32> {
+    let mut v = Vec::with_capacity(len);
+    let mut i = 0;
+    while i < len {
+        unsafe { v.as_mut_ptr().add(i).write((i * i) as u32) };
+        i += 1;
+    }
+    unsafe { v.set_len(len) };
+    v
+}
+
+const SQUARES: &Vec<u32> = &squares(5);
+
+const GREETING: &String = &{
+    let mut bytes = Vec::with_capacity(2);
+    unsafe {
+        bytes.as_mut_ptr().write(b'h');
+        bytes.as_mut_ptr().add(1).write(b'i');
+        bytes.set_len(2);
+        String::from_utf8_unchecked(bytes)
+    }
+};
+
+const BOXED: &Box<u32, Global> = &Box::new_in(5, Global);
+
+const NESTED: &Vec<Box<u32, Global>> = &{
+    let mut v = Vec::with_capacity(2);
+    unsafe {
+        v.as_mut_ptr().write(Box::new_in(1, Global));
+        v.as_mut_ptr().add(1).write(Box::new_in(2, Global));
+        v.set_len(2);
+    }
+    v
+};
+
+fn main() {
+    assert_eq!(**SQUARES, [0, 1, 4, 9, 16]);
+    assert_eq!(SQUARES.capacity(), 5);
+    assert_eq!(GREETING.as_str(), "hi");
+    assert_eq!(**BOXED, 5);
+    assert_eq!(*NESTED[0] + *NESTED[1], 3);
+}
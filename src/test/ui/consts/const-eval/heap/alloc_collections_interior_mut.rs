@@ -0,0 +1,36 @@
+#![feature(allocator_api)]
+#![feature(const_box)]
+#![feature(const_heap)]
+#![feature(const_mut_refs)]
+#![feature(const_ptr_write)]
+#![feature(const_trait_impl)]
+use std::alloc::Global;
+use std::cell::Cell;
+
+// `Vec` and `Box` are `Freeze` even if their contents are not, but their heap memory could still
+// be mutated through a shared reference. Such allocations can't be promoted to immutable memory.
+const CELLS: &Vec<Cell<u32>> = &{
+//~^ error: untyped pointers are not allowed in constant
+    let mut v = Vec::with_capacity(1);
+    unsafe {
+        v.as_mut_ptr().write(Cell::new(1));
+        v.set_len(1);
+    }
+    v
+};
+
+// The `Cell` is only reachable through a second heap allocation.
+const BOXED_CELLS: &Vec<Box<Cell<u32>, Global>> = &{
+//~^ error: untyped pointers are not allowed in constant
+    let mut v = Vec::with_capacity(1);
+    unsafe {
+        v.as_mut_ptr().write(Box::new_in(Cell::new(1), Global));
+        v.set_len(1);
+    }
+    v
+};
+
+fn main() {
+    CELLS[0].set(2);
+    BOXED_CELLS[0].set(2);
+}
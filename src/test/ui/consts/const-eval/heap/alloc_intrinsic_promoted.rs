@@ -0,0 +1,48 @@
+// run-pass
+#![feature(core_intrinsics)]
+#![feature(const_heap)]
+#![feature(const_mut_refs)]
+use std::intrinsics;
+
+// A heap allocation that is only reachable through a shared reference is interned as immutable
+// static memory, even when it is pointed to by a raw pointer.
+struct Table {
+    ptr: *const u32,
+    len: usize,
+}
+
+impl Table {
+    const fn squares(len: usize) -> Table {
+        unsafe {
+            let ptr = intrinsics::const_allocate(len * 4, 4) as *mut u32;
+            let mut i = 0;
+            while i < len {
+                *ptr.add(i) = (i * i) as u32;
+                i += 1;
+            }
+            Table { ptr, len }
+        }
+    }
+
+    fn get(&self, i: usize) -> u32 {
+        assert!(i < self.len);
+        unsafe { *self.ptr.add(i) }
+    }
+}
+
+const TABLE: &Table = &Table::squares(5);
+
+// Heap allocations may also point to other heap allocations.
+const NESTED: &*const *const u32 = &unsafe {
+    let inner = intrinsics::const_allocate(4, 4) as *mut u32;
+    *inner = 42;
+    let outer = intrinsics::const_allocate(8, 8) as *mut *const u32;
+    *outer = inner;
+    outer as *const *const u32
+};
+
+fn main() {
+    assert_eq!(TABLE.get(0), 0);
+    assert_eq!(TABLE.get(4), 16);
+    assert_eq!(unsafe { **(*NESTED) }, 42);
+}
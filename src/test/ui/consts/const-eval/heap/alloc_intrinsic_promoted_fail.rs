@@ -0,0 +1,29 @@
+#![feature(core_intrinsics)]
+#![feature(const_heap)]
+#![feature(const_mut_refs)]
+use std::intrinsics;
+
+// Heap allocations that are part of the value of a constant itself are still rejected, as every
+// use of the constant would get a pointer to the same memory.
+struct Table {
+    ptr: *const u32,
+    len: usize,
+}
+
+const TABLE: Table = unsafe {
+//~^ error: untyped pointers are not allowed in constant
+    let ptr = intrinsics::const_allocate(4, 4) as *mut u32;
+    *ptr = 1;
+    Table { ptr, len: 1 }
+};
+
+// Heap allocations pointed to by `*mut` pointers could be mutated through them, so they can't be
+// promoted to immutable memory either.
+const MUT_PTR: &*mut u32 = &unsafe {
+//~^ error: untyped pointers are not allowed in constant
+    let ptr = intrinsics::const_allocate(4, 4) as *mut u32;
+    *ptr = 1;
+    ptr
+};
+
+fn main() {}
@@ -0,0 +1,22 @@
+// normalize-stderr-test "\d+ steps" -> "N steps"
+#![feature(const_eval_limit)]
+#![feature(const_heap)]
+#![feature(const_mut_refs)]
+#![feature(core_intrinsics)]
+#![const_eval_memory_limit = "1000"]
+
+use std::intrinsics;
+
+const fn leak_chunks(n: usize) -> u8 {
+    let mut i = 0;
+    while i < n {
+        unsafe { intrinsics::const_allocate(100, 1) };
+        //~^ ERROR could not evaluate static initializer
+        i += 1;
+    }
+    n as u8
+}
+
+static X: u8 = leak_chunks(20);
+
+fn main() {}
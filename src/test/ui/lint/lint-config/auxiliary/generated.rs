@@ -0,0 +1,6 @@
+// Loaded as a module of `lint-config.rs`, whose lint configuration allows `unused_variables` in
+// this file.
+
+pub fn generated() {
+    let y = 2;
+}
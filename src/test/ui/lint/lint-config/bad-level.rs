@@ -0,0 +1,3 @@
+// compile-flags: -Z unstable-options --lint-config {{src-base}}/lint/lint-config/bad-level.toml
+
+fn main() {}
@@ -0,0 +1,8 @@
+// Checks that the `--lint-config` file can't lower the level of a forbidden lint, like lint
+// attributes can't.
+
+// compile-flags: -F unsafe_code
+// compile-flags: -Z unstable-options --lint-config {{src-base}}/lint/lint-config/forbid.toml
+// error-pattern: allow(unsafe_code) incompatible with previous forbid
+
+fn main() {}
@@ -0,0 +1,16 @@
+// Checks that lint levels are set from the `--lint-config` file, and that lint attributes and
+// the overrides of the file take precedence over its `[lints]` table.
+
+// compile-flags: -Z unstable-options --lint-config {{src-base}}/lint/lint-config/lints.toml
+
+#[path = "auxiliary/generated.rs"]
+mod generated;
+
+use std::fmt; //~ WARNING unused import
+
+fn main() {
+    let x = 1; //~ ERROR unused variable
+    #[allow(unused_variables)]
+    let z = 3;
+    generated::generated();
+}
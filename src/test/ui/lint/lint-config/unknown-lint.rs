@@ -0,0 +1,6 @@
+// Checks that unknown lints in the `--lint-config` file are reported with the file they come from.
+//
+// compile-flags: -Z unstable-options --lint-config {{src-base}}/lint/lint-config/unknown-lint.toml
+// error-pattern: unknown lint: `unused_variable`
+
+fn main() {}
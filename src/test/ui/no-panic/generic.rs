@@ -0,0 +1,38 @@
+// Checks that `#[no_panic]` is checked for each instance of a generic function.
+
+// build-fail
+
+#![crate_type = "lib"]
+#![feature(no_panic)]
+
+pub trait Parse {
+    fn parse(b: u8) -> Self;
+}
+
+impl Parse for bool {
+    fn parse(b: u8) -> bool {
+        b != 0
+    }
+}
+
+const DIGITS: [char; 10] = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+
+impl Parse for char {
+    fn parse(b: u8) -> char {
+        DIGITS[b as usize]
+    }
+}
+
+#[no_panic]
+pub fn parse_pair<T: Parse>(a: u8, b: u8) -> (T, T) {
+    //~^ ERROR `parse_pair::<char>` is marked `#[no_panic]`, but may panic
+    (T::parse(a), T::parse(b))
+}
+
+pub fn parse_bools(a: u8, b: u8) -> (bool, bool) {
+    parse_pair(a, b)
+}
+
+pub fn parse_digits(a: u8, b: u8) -> (char, char) {
+    parse_pair(a, b)
+}
@@ -0,0 +1,25 @@
+// Checks that `#[no_panic]` reports calls through trait objects and function pointers, whose
+// callee is not known, and finds `panic!` through `panic_fmt`.
+
+// build-fail
+// edition:2021
+
+#![crate_type = "lib"]
+#![feature(no_panic)]
+
+#[no_panic]
+pub fn call_dyn(f: &dyn Fn()) { //~ ERROR `call_dyn` is marked `#[no_panic]`, but may panic
+    f()
+}
+
+#[no_panic]
+pub fn call_ptr(f: fn()) { //~ ERROR `call_ptr` is marked `#[no_panic]`, but may panic
+    f()
+}
+
+#[no_panic]
+pub fn check(x: u32) { //~ ERROR `check` is marked `#[no_panic]`, but may panic
+    if x > 10 {
+        panic!("{} is too large", x);
+    }
+}
@@ -0,0 +1,24 @@
+// Checks that `#[inline]` functions are checked even if they are not used in their crate, which
+// then leaves instantiating them to the crates using them.
+
+// build-fail
+
+#![crate_type = "lib"]
+#![feature(no_panic)]
+
+#[inline]
+#[no_panic]
+pub fn first(v: &[u8]) -> u8 { //~ ERROR `first` is marked `#[no_panic]`, but may panic
+    v[0]
+}
+
+pub struct Digits([u8; 4]);
+
+impl Digits {
+    #[inline]
+    #[no_panic]
+    pub fn get(&self, i: usize) -> u8 {
+        //~^ ERROR `Digits::get` is marked `#[no_panic]`, but may panic
+        self.0[i]
+    }
+}
@@ -0,0 +1,29 @@
+// Checks that `#[no_panic]` functions from which a panic is reachable are reported with the
+// chain of calls that leads to the panic.
+
+// build-fail
+
+#![crate_type = "lib"]
+#![feature(no_panic)]
+
+#[no_panic]
+pub fn first(v: &[u8]) -> u8 { //~ ERROR `first` is marked `#[no_panic]`, but may panic
+    get(v, 0)
+}
+
+fn get(v: &[u8], i: usize) -> u8 {
+    v[i]
+}
+
+#[no_panic]
+pub fn first_checked(v: &[u8]) -> Option<u8> {
+    match v {
+        [x, ..] => Some(*x),
+        [] => None,
+    }
+}
+
+#[no_panic]
+pub fn stop() -> ! { //~ ERROR `stop` is marked `#[no_panic]`, but may panic
+    std::process::abort()
+}
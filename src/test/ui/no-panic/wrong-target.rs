@@ -0,0 +1,10 @@
+#![crate_type = "lib"]
+#![feature(no_panic)]
+
+#[no_panic] //~ ERROR attribute should be applied to a function definition
+pub struct S;
+
+pub trait T {
+    #[no_panic] //~ ERROR attribute should be applied to a function definition
+    fn f();
+}
@@ -0,0 +1,31 @@
+// Checks that type errors found by several threads of the parallel front-end are reported in
+// source order, no matter which thread finds them first. The errors are sorted whenever more
+// than one thread is requested, even if the compiler is built without the parallel front-end.
+//
+// compile-flags: -Zthreads=4
+
+fn a() -> u32 {
+    "a" //~ ERROR mismatched types
+}
+
+fn b() -> u32 {
+    'b' //~ ERROR mismatched types
+}
+
+fn c() -> u32 {
+    true //~ ERROR mismatched types
+}
+
+fn d() -> u32 {
+    1.0 //~ ERROR mismatched types
+}
+
+// Casts are checked at the end of the body, so without sorting, the cast error would be
+// reported after the mismatched types one.
+fn e() -> char {
+    let c = 1u16 as char; //~ ERROR only `u8` can be cast as `char`, not `u16`
+    let _: u32 = "e"; //~ ERROR mismatched types
+    c
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+// build-pass
+// compile-flags: -Zprint-polymorphization -Zinline-mir=off
+
+// This test checks that `-Zprint-polymorphization` reports the unused generic parameters without
+// enabling polymorphization: every instantiation is still codegened separately.
+
+fn unused_type<T>() -> u32 {
+    1
+}
+
+fn closure_unused<T>() -> u32 {
+    let add_one = |x: u32| x + 1;
+    add_one(1)
+}
+
+fn main() {
+    unused_type::<u8>();
+    unused_type::<u16>();
+    closure_unused::<u8>();
+    closure_unused::<u16>();
+}
@@ -0,0 +1,51 @@
+// build-pass
+// compile-flags: -Zpolymorphize=on -Zprint-polymorphization -Zinline-mir=off
+#![feature(generators, generator_trait)]
+
+// This test checks that `-Zprint-polymorphization` reports the unused generic parameters of
+// functions, closures and generators, and how many instances of each are left.
+
+use std::ops::Generator;
+use std::pin::Pin;
+
+fn unused_type<T>() -> u32 {
+    1
+}
+
+fn used_type<T: Default>() -> T {
+    T::default()
+}
+
+fn unused_const<const N: usize>() -> u32 {
+    2
+}
+
+fn used_const<const N: usize>() -> usize {
+    N
+}
+
+fn closure_unused<T>() -> u32 {
+    let add_one = |x: u32| x + 1;
+    add_one(1)
+}
+
+fn generator_unused<T>() -> impl Generator<(), Yield = u32, Return = u32> + Unpin {
+    || {
+        yield 1;
+        2
+    }
+}
+
+fn main() {
+    unused_type::<u8>();
+    unused_type::<u16>();
+    used_type::<u8>();
+    used_type::<u16>();
+    unused_const::<1>();
+    unused_const::<2>();
+    used_const::<1>();
+    closure_unused::<u8>();
+    closure_unused::<u16>();
+    let mut generator = generator_unused::<u8>();
+    let _ = Pin::new(&mut generator).resume(());
+}